@@ -0,0 +1,285 @@
+//! Optimizing many files in parallel.
+//!
+//! Plugins keep state for the document they're working on and aren't
+//! `Sync`, so every file gets its own [`SVGProcessor`] built from a shared
+//! [`PluginConfig`]. Files are spread over a rayon thread pool, and a file
+//! that fails is reported without stopping the others.
+
+use rayon::prelude::*;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::thread;
+
+use crate::cli::{PluginConfig, SvgFormat};
+use crate::error::SVGError;
+use crate::pretty::Indent;
+use crate::processor::{add_plugins, percent_saved, size_change, FileReport, SVGProcessor};
+
+/// Optimizes a list of files concurrently with the same plugin settings.
+///
+/// ```no_run
+/// use std::path::PathBuf;
+/// use svgo_rs::{BatchProcessor, PluginConfig, Preset};
+///
+/// let files = vec![(PathBuf::from("a.svg"), PathBuf::from("out/a.svg"))];
+/// let mut batch = BatchProcessor::new(PluginConfig::from_preset(Preset::Default), 8 * 1024);
+/// let result = batch.jobs(4).process(&files)?;
+/// println!("{} optimized, {} failed", result.reports.len(), result.failures.len());
+/// # Ok::<(), svgo_rs::SVGError>(())
+/// ```
+pub struct BatchProcessor {
+    config: PluginConfig,
+    chunk_size: usize,
+    jobs: usize,
+    input_format: Option<SvgFormat>,
+    output_format: Option<SvgFormat>,
+    indent: Option<Indent>,
+    dry_run: bool,
+}
+
+/// The outcome of a batch, in the order the files were given.
+pub struct BatchResult {
+    /// Statistics of each file that was optimized.
+    pub reports: Vec<FileReport>,
+    /// Each file that failed, with its error.
+    pub failures: Vec<(PathBuf, SVGError)>,
+    /// Sizes and numeric plugin statistics summed over `reports`.
+    pub totals: StatisticsTotals,
+}
+
+impl BatchProcessor {
+    /// A batch processor using one thread per CPU core.
+    pub fn new(config: PluginConfig, chunk_size: usize) -> Self {
+        Self {
+            config,
+            chunk_size,
+            jobs: thread::available_parallelism().map_or(1, usize::from),
+            input_format: None,
+            output_format: None,
+            indent: None,
+            dry_run: false,
+        }
+    }
+
+    /// Number of files to process concurrently.
+    pub fn jobs(&mut self, jobs: usize) -> &mut Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Force the input and output formats; `None` detects them per file
+    /// from the extension.
+    pub fn formats(&mut self, input: Option<SvgFormat>, output: Option<SvgFormat>) -> &mut Self {
+        self.input_format = input;
+        self.output_format = output;
+        self
+    }
+
+    /// Pretty-print the output with `indent`.
+    pub fn indent(&mut self, indent: Option<Indent>) -> &mut Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Run the plugins without writing any output.
+    pub fn dry_run(&mut self, dry_run: bool) -> &mut Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Optimize each `(source, target)` pair, in place when both are the
+    /// same path, creating the target's directory as needed.
+    ///
+    /// Only failing to set up the thread pool is an error; the errors of
+    /// individual files end up in [`BatchResult::failures`].
+    pub fn process(&self, files: &[(PathBuf, PathBuf)]) -> Result<BatchResult, SVGError> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.jobs)
+            .build()
+            .map_err(io::Error::other)?;
+
+        // Results come back in input order, keeping the report deterministic
+        let results: Vec<Result<FileReport, SVGError>> = pool.install(|| {
+            files
+                .par_iter()
+                .map(|(source, target)| self.process_file(source, target))
+                .collect()
+        });
+
+        let mut result = BatchResult {
+            reports: Vec::new(),
+            failures: Vec::new(),
+            totals: StatisticsTotals::default(),
+        };
+        for ((source, _), file_result) in files.iter().zip(results) {
+            match file_result {
+                Ok(report) => {
+                    result.totals.add(&report);
+                    result.reports.push(report);
+                }
+                Err(e) => result.failures.push((source.clone(), e)),
+            }
+        }
+        Ok(result)
+    }
+
+    fn process_file(&self, source: &PathBuf, target: &PathBuf) -> Result<FileReport, SVGError> {
+        let mut processor = SVGProcessor::new(self.chunk_size);
+        processor.set_input_format(self.input_format);
+        processor.set_output_format(self.output_format);
+        processor.set_indent(self.indent);
+        processor.set_dry_run(self.dry_run);
+        add_plugins(&mut processor, &self.config, false);
+
+        if source == target {
+            processor.process_file_in_place(source)?;
+        } else {
+            if let Some(parent) = target.parent().filter(|_| !self.dry_run) {
+                fs::create_dir_all(parent)?;
+            }
+            processor.process_file(source, target)?;
+        }
+        Ok(FileReport::new(source, target, &processor))
+    }
+}
+
+/// Numeric plugin statistics summed across the files of a batch.
+#[derive(Default)]
+pub struct StatisticsTotals {
+    /// `(plugin, statistic, total)` in first-seen order.
+    totals: Vec<(String, String, u64)>,
+    original_bytes: u64,
+    optimized_bytes: u64,
+}
+
+impl StatisticsTotals {
+    fn add(&mut self, report: &FileReport) {
+        self.original_bytes += report.statistics.original_bytes;
+        self.optimized_bytes += report.statistics.optimized_bytes;
+        for (plugin, stats) in &report.plugins {
+            for (name, value) in stats {
+                let Ok(value) = value.parse::<u64>() else { continue };
+
+                match self
+                    .totals
+                    .iter_mut()
+                    .find(|(p, n, _)| p == plugin && n == name)
+                {
+                    Some((_, _, total)) => *total += value,
+                    None => self.totals.push((plugin.clone(), name.clone(), value)),
+                }
+            }
+        }
+    }
+
+    /// The summed value of a plugin's statistic, if it was numeric.
+    pub fn get(&self, plugin: &str, statistic: &str) -> Option<u64> {
+        self.totals
+            .iter()
+            .find(|(p, n, _)| p == plugin && n == statistic)
+            .map(|(_, _, total)| *total)
+    }
+
+    /// The combined size change of all files, as in
+    /// [`ProcessingStatistics::size_summary`](crate::processor::ProcessingStatistics::size_summary).
+    pub fn size_summary(&self) -> String {
+        size_change(self.original_bytes, self.optimized_bytes)
+    }
+
+    /// How much smaller all files are together, in percent.
+    pub fn percent_saved(&self) -> f64 {
+        percent_saved(self.original_bytes, self.optimized_bytes)
+    }
+
+    pub(crate) fn print(&self) {
+        let mut current_plugin = None;
+        for (plugin, name, total) in &self.totals {
+            if current_plugin != Some(plugin) {
+                if current_plugin.is_some() {
+                    eprintln!("--------------------");
+                }
+                eprintln!("\n{} Statistics (all files):", plugin);
+                eprintln!("--------------------");
+                current_plugin = Some(plugin);
+            }
+            eprintln!("{}: {}", name, total);
+        }
+        if current_plugin.is_some() {
+            eprintln!("--------------------");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Preset;
+    use crate::processor::ProcessingStatistics;
+
+    #[test]
+    fn test_statistics_totals() {
+        let report = FileReport {
+            input: PathBuf::from("a.svg"),
+            output: PathBuf::from("b.svg"),
+            statistics: ProcessingStatistics {
+                processing_time: None,
+                total_time: None,
+                throughput_mb_per_sec: None,
+                compressed_size: None,
+                original_bytes: 2048,
+                optimized_bytes: 1024,
+                uncompressed_bytes: 1024,
+                elements_seen: 0,
+                elements_removed: 0,
+                attributes_seen: 0,
+                attributes_removed: 0,
+            },
+            percent_saved: 50.0,
+            plugins: vec![(
+                "PathOptimizer".to_string(),
+                vec![
+                    ("Paths optimized".to_string(), "2".to_string()),
+                    ("Mode".to_string(), "fast".to_string()),
+                ],
+            )],
+            diagnostics: Vec::new(),
+        };
+
+        let mut totals = StatisticsTotals::default();
+        totals.add(&report);
+        totals.add(&report);
+
+        assert_eq!(
+            totals.totals,
+            vec![("PathOptimizer".to_string(), "Paths optimized".to_string(), 4)]
+        );
+        assert_eq!(totals.get("PathOptimizer", "Paths optimized"), Some(4));
+        assert_eq!(totals.get("PathOptimizer", "Mode"), None);
+        assert_eq!(totals.size_summary(), "4.0 KB → 2.0 KB (-50.0%)");
+    }
+
+    #[test]
+    fn test_failures_do_not_stop_the_batch() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let dir = temp_dir.path();
+        for name in ["a.svg", "c.svg"] {
+            fs::write(dir.join(name), "<svg><!-- comment --></svg>")?;
+        }
+        let files: Vec<_> = ["a.svg", "b.svg", "c.svg"]
+            .iter()
+            .map(|name| (dir.join(name), dir.join("out").join(name)))
+            .collect();
+
+        let mut batch = BatchProcessor::new(PluginConfig::from_preset(Preset::Safe), 1024);
+        let result = batch.jobs(2).process(&files)?;
+
+        let optimized: Vec<_> = result.reports.iter().map(|report| report.input.clone()).collect();
+        assert_eq!(optimized, [dir.join("a.svg"), dir.join("c.svg")]);
+        assert_eq!(result.failures.len(), 1);
+        assert_eq!(result.failures[0].0, dir.join("b.svg"));
+        assert_eq!(result.totals.get("RemoveComments", "Comments removed"), Some(2));
+        assert_eq!(fs::read_to_string(dir.join("out/c.svg"))?, "<svg></svg>");
+        Ok(())
+    }
+}
@@ -1,4 +1,15 @@
-use clap::{Parser, Subcommand, Args};
+use crate::error::SVGError;
+use crate::plugins::Shape;
+use crate::pretty::Indent;
+use crate::registry::PluginRegistry;
+use clap::{Parser, Subcommand, Args, ValueEnum};
+use regex::Regex;
+use serde::de::{self, Deserializer, MapAccess, Visitor};
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::Path;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -9,9 +20,9 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
 
-    /// Buffer size in KB for reading/writing files
-    #[arg(short, long, default_value = "8")]
-    pub buffer_size: usize,
+    /// Buffer size in KB for reading/writing files [default: 8]
+    #[arg(short, long)]
+    pub buffer_size: Option<usize>,
 
     /// Enable verbose output
     #[arg(short, long)]
@@ -19,10 +30,14 @@ pub struct Cli {
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)] // Parsed once, boxing buys nothing
 pub enum Commands {
     /// Optimize SVG files
     Optimize(OptimizeArgs),
 
+    /// Optimize every SVG file in a directory into another directory
+    Batch(BatchArgs),
+
     /// List available plugins
     ListPlugins,
 
@@ -32,21 +47,97 @@ pub enum Commands {
 
 #[derive(Args)]
 pub struct OptimizeArgs {
-    /// Input SVG file
-    #[arg(required = true)]
-    pub input: PathBuf,
+    /// Input SVG file, directory or glob pattern, or '-' for stdin
+    #[arg(required_unless_present = "stdin")]
+    pub input: Option<PathBuf>,
 
-    /// Output SVG file
-    #[arg(required = true)]
-    pub output: PathBuf,
+    /// Output SVG file (or directory for batch input), or '-' for stdout
+    #[arg(required_unless_present_any = ["in_place", "stdin", "stdout", "dry_run"])]
+    pub output: Option<PathBuf>,
+
+    /// Overwrite the input file(s) with the optimized result
+    #[arg(short, long, conflicts_with_all = ["output", "stdin", "stdout"])]
+    pub in_place: bool,
+
+    /// Read the SVG from stdin; the only path given is then the output
+    #[arg(long)]
+    pub stdin: bool,
+
+    /// Write the optimized SVG to stdout
+    #[arg(long, conflicts_with = "output")]
+    pub stdout: bool,
+
+    /// Descend into subdirectories when the input is a directory
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// Report the savings and what each plugin would change, without writing output
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Fail unless the output is at least PCT percent smaller than the input
+    #[arg(long, value_name = "PCT", requires = "dry_run")]
+    pub min_savings_pct: Option<f64>,
+
+    /// Keep running and optimize the input again whenever it changes
+    #[arg(short, long, conflicts_with_all = ["stdin", "stdout", "dry_run"])]
+    pub watch: bool,
+
+    /// With --watch, wait until files stop changing for MS milliseconds
+    #[arg(long, value_name = "MS", default_value_t = 300, requires = "watch")]
+    pub debounce: u64,
+
+    /// JSON or TOML config file with plugin settings; flags given here override it
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Start from a predefined set of plugins instead of a config file
+    #[arg(long, value_enum, value_name = "NAME", conflicts_with = "config")]
+    pub preset: Option<Preset>,
+
+    /// Process batch input with N parallel jobs; a bare --jobs uses every CPU core (default: 1)
+    #[arg(short, long, value_name = "N", num_args = 0..=1, default_missing_value = "0")]
+    pub jobs: Option<usize>,
+
+    /// Enable plugins by name (comma-separated, see list-plugins)
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub enable: Vec<String>,
+
+    /// Disable plugins by name, even if enabled elsewhere (comma-separated)
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub disable: Vec<String>,
+
+    /// Run these plugins first, in this order (comma-separated names)
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub order: Option<Vec<String>>,
+
+    /// Write statistics as JSON to FILE, or to stdout without one
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+    pub stats_json: Option<PathBuf>,
+
+    /// Format of the statistics; json writes them to stdout like a bare --stats-json
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t, conflicts_with = "stats_json")]
+    pub output_format: OutputFormat,
 
     /// Enable path optimization
     #[arg(long)]
     pub optimize_paths: bool,
 
     /// Decimal places for path optimization (default: 2)
-    #[arg(long, default_value = "2")]
-    pub path_decimals: usize,
+    #[arg(long)]
+    pub path_decimals: Option<usize>,
+
+    /// Rewrite absolute path commands as relative ones
+    #[arg(long)]
+    pub path_relative: bool,
+
+    /// Write each path segment in whichever of its absolute and relative forms is shorter
+    #[arg(long)]
+    pub path_shortest: bool,
+
+    /// Drop path command letters that repeat the previous command
+    #[arg(long)]
+    pub path_collapse_commands: bool,
 
     /// Enable gradient deduplication
     #[arg(long)]
@@ -63,6 +154,313 @@ pub struct OptimizeArgs {
     /// Preserve specified IDs (comma-separated)
     #[arg(long, value_delimiter = ',')]
     pub preserve_ids: Option<Vec<String>>,
+
+    /// Enable comment removal
+    #[arg(long)]
+    pub remove_comments: bool,
+
+    /// Keep comments matching this regex (repeatable, replaces the default '^!')
+    #[arg(long, value_name = "REGEX", value_parser = Regex::new)]
+    pub preserve_comments: Vec<Regex>,
+
+    /// Remove empty groups and unwrap redundant ones
+    #[arg(long)]
+    pub collapse_groups: bool,
+
+    /// Remove elements hidden with display:none or visibility:hidden
+    #[arg(long)]
+    pub remove_invisible: bool,
+
+    /// Remove attributes set to their SVG default value
+    #[arg(long)]
+    pub remove_default_attrs: bool,
+
+    /// Remove <metadata> elements
+    #[arg(long)]
+    pub remove_metadata: bool,
+
+    /// Keep <metadata> elements, even if the config file removes them
+    #[arg(long, conflicts_with = "remove_metadata")]
+    pub preserve_metadata: bool,
+
+    /// Convert basic shapes to paths
+    #[arg(long)]
+    pub shapes_to_paths: bool,
+
+    /// Shapes to convert to paths (comma-separated, default: all)
+    #[arg(long, value_delimiter = ',', value_name = "SHAPES")]
+    pub shapes: Option<Vec<Shape>>,
+
+    /// Remove the DOCTYPE declaration
+    #[arg(long)]
+    pub remove_doctype: bool,
+
+    /// Remove unreferenced <defs> children
+    #[arg(long)]
+    pub remove_unused_defs: bool,
+
+    /// Remove processing instructions like <?xml-stylesheet?>
+    #[arg(long)]
+    pub remove_xml_proc_inst: bool,
+
+    /// Remove editor namespaces (Inkscape, Sodipodi, Illustrator, ...)
+    #[arg(long)]
+    pub remove_editor_ns: bool,
+
+    /// Editor namespace prefixes to keep (comma-separated)
+    #[arg(long, value_delimiter = ',', value_name = "PREFIXES")]
+    pub preserve_ns: Option<Vec<String>>,
+
+    /// Rewrite colors to their shortest form (rgb(255,0,0) -> red, #ffffff -> #fff)
+    #[arg(long)]
+    pub convert_colors: bool,
+
+    /// Keep colors as #rrggbb instead of using #rgb or color keywords
+    #[arg(long)]
+    pub color_longhand: bool,
+
+    /// Keep the case of hex colors instead of lowercasing them
+    #[arg(long)]
+    pub color_keep_case: bool,
+
+    /// Remove <title> and <desc> elements
+    #[arg(long)]
+    pub remove_title_desc: bool,
+
+    /// Keep <title> elements when removing titles and descriptions
+    #[arg(long)]
+    pub preserve_title: bool,
+
+    /// Keep <desc> elements when removing titles and descriptions
+    #[arg(long)]
+    pub preserve_desc: bool,
+
+    /// Remove presentation attributes with an empty value, like fill=""
+    #[arg(long)]
+    pub remove_empty_attrs: bool,
+
+    /// Also remove these attributes when empty (comma-separated)
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub empty_attrs_allow: Option<Vec<String>>,
+
+    /// Never remove these attributes when empty (comma-separated)
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub empty_attrs_deny: Option<Vec<String>>,
+
+    /// Also remove empty transform and d attributes
+    #[arg(long)]
+    pub empty_attrs_geometry: bool,
+
+    /// Normalize fill, stroke and stop-color values, including in style, to lowercase shortest form
+    #[arg(long)]
+    pub normalize_colors: bool,
+
+    /// Drop redundant SVG namespace prefixes from attributes (svg:fill -> fill)
+    #[arg(long)]
+    pub clean_namespaces: bool,
+
+    /// Also rewrite xlink:href to the SVG 2 href when cleaning namespaces
+    #[arg(long)]
+    pub xlink_to_href: bool,
+
+    /// Sort attributes into a consistent order for better gzip compression
+    #[arg(long)]
+    pub sort_attrs: bool,
+
+    /// Attributes to put first when sorting (comma-separated) [default: id,width,height,x,y,d,fill,stroke]
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub attrs_order: Option<Vec<String>>,
+
+    /// Round numbers in attributes like width, x, r and opacity
+    #[arg(long)]
+    pub cleanup_numeric_values: bool,
+
+    /// Decimal places for numeric attribute values (default: 2)
+    #[arg(long)]
+    pub numeric_decimals: Option<usize>,
+
+    /// Also clean number lists in viewBox, points and stroke-dasharray
+    #[arg(long)]
+    pub numeric_lists: bool,
+
+    /// Remove identity transforms and collapse transform chains
+    #[arg(long)]
+    pub optimize_transforms: bool,
+
+    /// Move transforms of otherwise bare groups onto their children
+    #[arg(long)]
+    pub propagate_transforms: bool,
+
+    /// Remove paint properties that can't show, like stroke-width with stroke="none"
+    #[arg(long)]
+    pub remove_useless_paint: bool,
+
+    /// Report paths drawing the same shape as an earlier path
+    #[arg(long)]
+    pub dedupe_paths: bool,
+
+    /// Remove paths that exactly repeat the element before them
+    #[arg(long)]
+    pub remove_duplicate_paths: bool,
+
+    /// Merge neighbouring paths with the same attributes that don't overlap
+    #[arg(long)]
+    pub merge_paths: bool,
+
+    /// Move style declarations into presentation attributes
+    #[arg(long)]
+    pub inline_styles: bool,
+
+    /// Move simple <style> rules into presentation attributes
+    #[arg(long)]
+    pub inline_stylesheet: bool,
+
+    /// Move presentation attributes into the style attribute
+    #[arg(long)]
+    pub presentation_to_style: bool,
+
+    /// Minify the CSS of <style> elements and style attributes
+    #[arg(long, alias = "minify-styles")]
+    pub minify_css: bool,
+
+    /// Remove <style> rules whose selectors match no element
+    #[arg(long)]
+    pub remove_unused_css: bool,
+
+    /// Collapse and trim whitespace in <text> content
+    #[arg(long)]
+    pub normalize_text_whitespace: bool,
+
+    /// Remove <script> elements, on* event handler attributes and javascript: links
+    #[arg(long)]
+    pub remove_scripts: bool,
+
+    /// Keep scripts and event handlers, even if the config file removes them
+    #[arg(long, conflicts_with_all = ["remove_scripts", "sanitize"])]
+    pub allow_scripts: bool,
+
+    /// Remove <foreignObject>, <use> of other documents and links to remote URLs
+    #[arg(long)]
+    pub remove_external_refs: bool,
+
+    /// Remove scripts and external references from untrusted input (see the sanitize preset)
+    #[arg(long)]
+    pub sanitize: bool,
+
+    /// Remove whitespace-only text between elements outside text content
+    #[arg(long)]
+    pub collapse_whitespace: bool,
+
+    /// Remove empty <g>, <defs>, <symbol> and other containers
+    #[arg(long)]
+    pub remove_empty_containers: bool,
+
+    /// Remove hidden, fully transparent and zero-size elements
+    #[arg(long)]
+    pub remove_hidden_elems: bool,
+
+    /// Remove width and height from the root <svg>, adding a viewBox if needed
+    #[arg(long)]
+    pub remove_dimensions: bool,
+
+    /// Remove attributes matching an element:attribute:value pattern (repeatable)
+    #[arg(long, value_name = "PATTERN")]
+    pub remove_attrs: Vec<String>,
+
+    /// Remove unused ids and shorten the others, rewriting every reference
+    #[arg(long)]
+    pub cleanup_ids: bool,
+
+    /// With --cleanup-ids, keep ids starting with these prefixes (comma-separated)
+    #[arg(long, value_delimiter = ',', value_name = "PREFIXES")]
+    pub preserve_id_prefixes: Option<Vec<String>>,
+
+    /// Prefix ids and class names with PREFIX, or with the file name without one
+    #[arg(long, value_name = "PREFIX", num_args = 0..=1)]
+    pub prefix_ids: Option<Option<String>>,
+
+    /// With --prefix-ids, put DELIM between the prefix and the name [default: __]
+    #[arg(long, value_name = "DELIM", requires = "prefix_ids")]
+    pub prefix_delimiter: Option<String>,
+
+    /// Input file format (default: detected from the file extension)
+    #[arg(long, value_enum)]
+    pub input_format: Option<SvgFormat>,
+
+    /// Write gzip-compressed SVGZ output regardless of the output extension
+    #[arg(long, alias = "gzip")]
+    pub svgz: bool,
+
+    /// Indent the output for reading and diffing instead of minifying it
+    #[arg(long)]
+    pub pretty: bool,
+
+    /// Indentation width for --pretty [default: 2]
+    #[arg(long, value_name = "N", requires = "pretty")]
+    pub indent: Option<usize>,
+
+    /// Indent --pretty output with tabs instead of spaces
+    #[arg(long, requires = "pretty")]
+    pub indent_tabs: bool,
+}
+
+#[derive(Args)]
+pub struct BatchArgs {
+    /// Directory to read SVG and SVGZ files from
+    #[arg(long, value_name = "DIR")]
+    pub input_dir: PathBuf,
+
+    /// Directory to write the optimized files to, mirroring the input layout
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: PathBuf,
+
+    /// Only process files whose path below the input directory matches this glob
+    #[arg(long, value_name = "GLOB")]
+    pub pattern: Option<String>,
+
+    /// Descend into subdirectories
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// JSON or TOML config file with plugin settings
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Start from a predefined set of plugins instead of a config file
+    #[arg(long, value_enum, value_name = "NAME", conflicts_with = "config")]
+    pub preset: Option<Preset>,
+
+    /// Enable plugins by name (comma-separated, see list-plugins)
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub enable: Vec<String>,
+
+    /// Disable plugins by name, even if enabled elsewhere (comma-separated)
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub disable: Vec<String>,
+
+    /// Process N files in parallel; a bare --jobs uses every CPU core (default: 1)
+    #[arg(short, long, value_name = "N", num_args = 0..=1, default_missing_value = "0")]
+    pub jobs: Option<usize>,
+
+    /// Write statistics as JSON to FILE, or to stdout without one
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = "-")]
+    pub stats_json: Option<PathBuf>,
+
+    /// Format of the statistics; json writes them to stdout like a bare --stats-json
+    #[arg(long, value_enum, value_name = "FORMAT", default_value_t, conflicts_with = "stats_json")]
+    pub output_format: OutputFormat,
+}
+
+impl BatchArgs {
+    /// Where to write JSON statistics, as for [`OptimizeArgs::stats_json_path`].
+    pub fn stats_json_path(&self) -> Option<PathBuf> {
+        stats_json_path(&self.stats_json, self.output_format)
+    }
+
+    /// Number of parallel jobs, as for [`OptimizeArgs::job_count`].
+    pub fn job_count(&self, configured: Option<usize>) -> usize {
+        job_count(self.jobs.or(configured))
+    }
 }
 
 #[derive(Args)]
@@ -70,51 +468,1566 @@ pub struct AnalyzeArgs {
     /// Input SVG file
     #[arg(required = true)]
     pub input: PathBuf,
+
+    /// Analyze with the plugins of this config file instead of all of them
+    #[arg(short, long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+}
+
+/// Whether a path argument refers to stdin/stdout (`-`).
+pub fn is_stdio(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+/// On-disk format of an SVG file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SvgFormat {
+    /// Plain SVG text
+    Svg,
+    /// Gzip-compressed SVG
+    Svgz,
+}
+
+impl SvgFormat {
+    /// Detect the format from a file extension, defaulting to plain SVG.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("svgz") => SvgFormat::Svgz,
+            _ => SvgFormat::Svg,
+        }
+    }
+}
+
+/// A predefined plugin configuration, for `--preset`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Preset {
+    /// Only remove content that is never rendered
+    Safe,
+    /// Also round numbers and remove unused or redundant elements
+    Default,
+    /// Also rewrite shapes, transforms and styles, and drop ids and titles
+    Aggressive,
+    /// Safe, plus removing scripts and external references from untrusted input
+    Sanitize,
+}
+
+/// How statistics are reported, for `--output-format`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// A summary for people, on stderr
+    #[default]
+    Text,
+    /// The `--stats-json` report, on stdout
+    Json,
+}
+
+/// Where to write JSON statistics: the `--stats-json` path, or stdout for
+/// `--output-format json`.
+fn stats_json_path(stats_json: &Option<PathBuf>, format: OutputFormat) -> Option<PathBuf> {
+    match format {
+        OutputFormat::Json => Some(stats_json.clone().unwrap_or_else(|| PathBuf::from("-"))),
+        OutputFormat::Text => stats_json.clone(),
+    }
+}
+
+/// How the processor hands the document to plugins.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Events are passed to plugins one at a time as the file is read.
+    #[default]
+    Streaming,
+    /// The document is also parsed into a tree for plugins that need
+    /// parent/child context.
+    Tree,
+}
+
+impl OptimizeArgs {
+    /// Where to write JSON statistics, if anywhere: the `--stats-json`
+    /// file, or `-` for stdout with `--output-format json`.
+    pub fn stats_json_path(&self) -> Option<PathBuf> {
+        stats_json_path(&self.stats_json, self.output_format)
+    }
+
+    /// The input and output paths, with `-` standing for stdin and stdout.
+    ///
+    /// Positional arguments are assigned in order, so with `--stdin` the
+    /// only path given is the output. The output is `None` for `--in-place`,
+    /// and for `--dry-run` without an output path.
+    pub fn paths(&self) -> Result<(PathBuf, Option<PathBuf>), SVGError> {
+        let (input, output) = if self.stdin {
+            if self.output.is_some() || (self.stdout && self.input.is_some()) {
+                return Err(SVGError::InvalidPath("with --stdin, give only the output path".to_string()));
+            }
+            (PathBuf::from("-"), self.input.clone())
+        } else {
+            let input = self.input.clone().expect("clap requires an input without --stdin");
+            (input, self.output.clone())
+        };
+
+        let output = if self.stdout { Some(PathBuf::from("-")) } else { output };
+        if output.is_none() && !self.in_place && !self.dry_run {
+            return Err(SVGError::InvalidPath(
+                "an output path, --stdout or --in-place is required".to_string(),
+            ));
+        }
+        Ok((input, output))
+    }
+
+    /// Number of parallel jobs: 1 without `--jobs`, all cores for a bare `--jobs`.
+    /// `configured` is used when the flag isn't given, 0 also meaning all cores.
+    pub fn job_count(&self, configured: Option<usize>) -> usize {
+        job_count(self.jobs.or(configured))
+    }
+
+    /// Indentation for `--pretty` output: two spaces, or one tab with
+    /// `--indent-tabs`, unless `--indent` sets the width.
+    pub fn indent(&self) -> Option<Indent> {
+        if !self.pretty {
+            return None;
+        }
+        if self.indent_tabs {
+            Some(Indent::tabs(self.indent.unwrap_or(1)))
+        } else {
+            Some(Indent::spaces(self.indent.unwrap_or(2)))
+        }
+    }
+}
+
+/// Resolve a job count where 0 means all cores and none means one job.
+fn job_count(jobs: Option<usize>) -> usize {
+    match jobs {
+        None => 1,
+        Some(0) => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        Some(jobs) => jobs,
+    }
 }
 
 // Plugin configuration structures
-#[derive(Default)]
+#[derive(Clone, Debug, Default)]
 pub struct PluginConfig {
     pub path_optimizer: Option<PathOptimizerConfig>,
     pub gradient_deduplicator: bool,
     pub id_remover: IdRemoverConfig,
     pub data_attr_remover: bool,
+    pub comment_remover: CommentRemoverConfig,
+    pub group_collapser: bool,
+    pub invisible_remover: bool,
+    pub default_attr_remover: bool,
+    pub metadata_remover: bool,
+    pub shape_converter: ShapeToPathConfig,
+    pub doctype_remover: bool,
+    pub unused_defs_remover: bool,
+    pub proc_inst_remover: bool,
+    pub editor_ns_remover: EditorNsRemoverConfig,
+    pub color_converter: Option<ColorConverterConfig>,
+    pub title_desc_remover: TitleDescRemoverConfig,
+    pub empty_attr_remover: EmptyAttrRemoverConfig,
+    pub color_normalizer: bool,
+    pub namespace_cleaner: NamespaceCleanerConfig,
+    pub attr_sorter: AttrSorterConfig,
+    pub numeric_cleaner: Option<NumericCleanerConfig>,
+    pub transform_optimizer: TransformOptimizerConfig,
+    pub useless_paint_remover: bool,
+    pub path_deduplicator: PathDeduplicatorConfig,
+    pub path_merger: bool,
+    pub style_inliner: bool,
+    pub stylesheet_inliner: bool,
+    pub style_converter: bool,
+    pub css_minifier: bool,
+    pub unused_css_remover: bool,
+    pub text_whitespace_normalizer: bool,
+    pub script_remover: bool,
+    pub external_refs_remover: bool,
+    pub whitespace_collapser: bool,
+    pub empty_container_remover: bool,
+    pub hidden_elems_remover: Option<HiddenElemsConfig>,
+    pub dimensions_remover: bool,
+    /// `element:attribute:value` patterns of attributes to remove; the
+    /// plugin runs when there are any.
+    pub attrs_remover: Vec<String>,
+    pub id_cleaner: Option<IdCleanerConfig>,
+    pub id_prefixer: Option<IdPrefixerConfig>,
+    /// Plugins to run regardless of the settings above, by registry name.
+    pub enable: Vec<String>,
+    /// Plugins never to run, by registry name.
+    pub disable: Vec<String>,
+    /// Plugins to run before all others, in this order, by registry name.
+    /// The rest follow in the registry's order.
+    pub order: Vec<String>,
 }
 
+#[derive(Clone, Debug)]
 pub struct PathOptimizerConfig {
     pub decimal_places: usize,
+    /// Rewrite absolute commands as relative ones.
+    pub use_relative: bool,
+    /// Pick the shorter of the absolute and relative form per segment.
+    pub use_shortest: bool,
+    /// Drop command letters repeating the previous command.
+    pub collapse_repeated: bool,
 }
 
+impl Default for PathOptimizerConfig {
+    fn default() -> Self {
+        Self {
+            decimal_places: DEFAULT_DECIMAL_PLACES,
+            use_relative: false,
+            use_shortest: false,
+            collapse_repeated: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
 pub struct IdRemoverConfig {
     pub enabled: bool,
     pub preserve: Vec<String>,
 }
 
-impl Default for IdRemoverConfig {
+#[derive(Clone, Debug, Default)]
+pub struct CommentRemoverConfig {
+    pub enabled: bool,
+    /// Patterns of comments to keep; empty keeps the plugin's default.
+    pub preserve: Vec<Regex>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TitleDescRemoverConfig {
+    pub enabled: bool,
+    pub preserve_title: bool,
+    pub preserve_desc: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NamespaceCleanerConfig {
+    pub enabled: bool,
+    /// Rewrite `xlink:href` to `href`.
+    pub xlink_to_href: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct NumericCleanerConfig {
+    pub decimal_places: usize,
+    /// Also clean `viewBox`, `points` and `stroke-dasharray`.
+    pub lists: bool,
+}
+
+impl Default for NumericCleanerConfig {
     fn default() -> Self {
         Self {
-            enabled: false,
-            preserve: Vec::new(),
+            decimal_places: DEFAULT_DECIMAL_PLACES,
+            lists: false,
         }
     }
 }
 
-impl From<&OptimizeArgs> for PluginConfig {
-    fn from(args: &OptimizeArgs) -> Self {
+#[derive(Clone, Debug, Default)]
+pub struct TransformOptimizerConfig {
+    pub enabled: bool,
+    /// Move group transforms onto the group's children.
+    pub propagate: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PathDeduplicatorConfig {
+    pub enabled: bool,
+    /// Remove paths repeating their previous sibling, not just report them.
+    pub remove: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct AttrSorterConfig {
+    pub enabled: bool,
+    /// Attributes to put first; empty uses the plugin's default order.
+    pub order: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EmptyAttrRemoverConfig {
+    pub enabled: bool,
+    /// Extra attribute names to remove when empty.
+    pub allow: Vec<String>,
+    /// Attribute names never to remove.
+    pub deny: Vec<String>,
+    /// Also remove empty `transform` and `d` attributes.
+    pub geometry: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct ColorConverterConfig {
+    /// Use `#rgb` and color keywords when they are shorter.
+    pub shorthand: bool,
+    /// Lowercase hex digits.
+    pub lowercase: bool,
+}
+
+impl Default for ColorConverterConfig {
+    fn default() -> Self {
         Self {
-            path_optimizer: if args.optimize_paths {
-                Some(PathOptimizerConfig {
-                    decimal_places: args.path_decimals,
-                })
-            } else {
-                None
-            },
-            gradient_deduplicator: args.dedupe_gradients,
-            id_remover: IdRemoverConfig {
-                enabled: args.remove_ids,
-                preserve: args.preserve_ids.clone().unwrap_or_default(),
-            },
-            data_attr_remover: args.remove_data_attrs,
+            shorthand: true,
+            lowercase: true,
+        }
+    }
+}
+
+/// Which kinds of hidden elements `RemoveHiddenElemsPlugin` removes.
+#[derive(Clone, Debug)]
+pub struct HiddenElemsConfig {
+    /// Elements with `display="none"`.
+    pub display_none: bool,
+    /// Elements with `visibility="hidden"` and no visible descendant.
+    pub visibility_hidden: bool,
+    /// Elements with `opacity="0"`.
+    pub opacity_zero: bool,
+    /// Shapes with a zero width, height or radius.
+    pub zero_size: bool,
+}
+
+impl Default for HiddenElemsConfig {
+    fn default() -> Self {
+        Self {
+            display_none: true,
+            visibility_hidden: true,
+            opacity_zero: true,
+            zero_size: true,
+        }
+    }
+}
+
+/// What `CleanupIdsPlugin` does with ids.
+#[derive(Clone, Debug)]
+pub struct IdCleanerConfig {
+    /// Remove ids nothing refers to.
+    pub remove: bool,
+    /// Shorten the ids that are referenced.
+    pub minify: bool,
+    /// Ids starting with these are kept as they are.
+    pub preserve_prefixes: Vec<String>,
+}
+
+impl Default for IdCleanerConfig {
+    fn default() -> Self {
+        Self {
+            remove: true,
+            minify: true,
+            preserve_prefixes: Vec::new(),
+        }
+    }
+}
+
+/// How `PrefixIdsPlugin` builds the new names.
+#[derive(Clone, Debug)]
+pub struct IdPrefixerConfig {
+    /// The prefix; `None` derives it from the input's file name.
+    pub prefix: Option<String>,
+    /// Put between the prefix and the original name.
+    pub delimiter: String,
+}
+
+impl Default for IdPrefixerConfig {
+    fn default() -> Self {
+        Self {
+            prefix: None,
+            delimiter: DEFAULT_PREFIX_DELIMITER.to_string(),
         }
     }
 }
+
+/// Delimiter between an id prefix and the original name.
+pub const DEFAULT_PREFIX_DELIMITER: &str = "__";
+
+/// Decimal places used when path optimization is enabled without a value.
+const DEFAULT_DECIMAL_PLACES: usize = 2;
+
+/// Buffer size in KB used when neither `--buffer-size` nor the config file
+/// sets one.
+pub const DEFAULT_BUFFER_SIZE: usize = 8;
+
+/// The contents of a config file: plugin settings plus processor options.
+///
+/// TOML files use the same keys as JSON ones, with plugin options as
+/// tables:
+///
+/// ```toml
+/// bufferSize = 64
+/// jobs = 4
+/// removeComments = true
+///
+/// [pathOptimizer]
+/// decimalPlaces = 1
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(try_from = "ConfigFile")]
+pub struct Config {
+    pub plugins: PluginConfig,
+    /// Buffer size in KB for reading and writing files.
+    pub buffer_size: Option<usize>,
+    /// Number of files processed in parallel in batch mode, 0 for all cores.
+    pub jobs: Option<usize>,
+}
+
+impl Config {
+    /// Load a config file, as TOML if its extension is `.toml` and as JSON
+    /// otherwise (`.json` or any other extension).
+    pub fn from_file(path: &Path) -> Result<Self, SVGError> {
+        if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("toml")) {
+            Self::from_toml_file(path)
+        } else {
+            Self::from_json_file(path)
+        }
+    }
+
+    /// Load a JSON config file.
+    pub fn from_json_file(path: &Path) -> Result<Self, SVGError> {
+        let json = fs::read_to_string(path)?;
+        Self::from_json_str(&json).map_err(|e| invalid_config_file(path, e))
+    }
+
+    /// Parse a JSON config. Both formats deserialize into the same
+    /// structures, so equivalent files give identical configs.
+    pub fn from_json_str(json: &str) -> Result<Self, SVGError> {
+        serde_json::from_str(json).map_err(|e| SVGError::InvalidConfig(e.to_string()))
+    }
+
+    /// Load a TOML config file.
+    pub fn from_toml_file(path: &Path) -> Result<Self, SVGError> {
+        let toml = fs::read_to_string(path)?;
+        Self::from_toml_str(&toml).map_err(|e| invalid_config_file(path, e))
+    }
+
+    /// Parse a TOML config. Unknown keys are rejected, as in JSON files.
+    pub fn from_toml_str(toml: &str) -> Result<Self, SVGError> {
+        toml::from_str(toml).map_err(|e| SVGError::InvalidConfig(e.to_string()))
+    }
+}
+
+impl TryFrom<ConfigFile> for Config {
+    type Error = SVGError;
+
+    fn try_from(mut file: ConfigFile) -> Result<Self, SVGError> {
+        Ok(Self {
+            buffer_size: file.buffer_size.take(),
+            jobs: file.jobs.take(),
+            plugins: PluginConfig::from_config_file(file)?,
+        })
+    }
+}
+
+fn invalid_config_file(path: &Path, e: SVGError) -> SVGError {
+    SVGError::InvalidConfig(format!("invalid config file {}: {}", path.display(), e))
+}
+
+impl PluginConfig {
+    /// Load plugin settings from a JSON config file such as
+    /// `{"pathOptimizer": {"decimalPlaces": 1}, "dedupeGradients": true}`.
+    ///
+    /// Each key names a plugin and is either `true`/`false` or an object of
+    /// options, which enables the plugin. Unknown keys are rejected.
+    ///
+    /// Processor options in the file are ignored; [`Config::from_json_file`]
+    /// returns them as well.
+    pub fn from_json_file(path: &Path) -> Result<Self, SVGError> {
+        Config::from_json_file(path).map(|config| config.plugins)
+    }
+
+    /// Parse the plugin settings of a JSON config.
+    pub fn from_json_str(json: &str) -> Result<Self, SVGError> {
+        Config::from_json_str(json).map(|config| config.plugins)
+    }
+
+    fn from_config_file(file: ConfigFile) -> Result<Self, SVGError> {
+        let comment_options = file.remove_comments.and_then(PluginEntry::options);
+        let preserve_comments = match &comment_options {
+            Some(options) => options
+                .preserve
+                .iter()
+                .map(|pattern| Regex::new(pattern))
+                .collect::<Result<_, _>>()
+                .map_err(|e| SVGError::InvalidConfig(e.to_string()))?,
+            None => Vec::new(),
+        };
+        let id_options = file.remove_ids.and_then(PluginEntry::options);
+        let shape_options = file.shapes_to_paths.and_then(PluginEntry::options);
+        let editor_ns_options = file.remove_editor_namespaces.and_then(PluginEntry::options);
+        let title_desc_options = file.remove_title_desc.and_then(PluginEntry::options);
+        let empty_attr_options = file.remove_empty_attrs.and_then(PluginEntry::options);
+        let namespace_options = file.clean_namespaces.and_then(PluginEntry::options);
+        let sort_options = file.sort_attrs.and_then(PluginEntry::options);
+        let transform_options = file.optimize_transforms.and_then(PluginEntry::options);
+        let path_dedupe_options = file.dedupe_paths.and_then(PluginEntry::options);
+
+        Ok(Self {
+            path_optimizer: file
+                .path_optimizer
+                .and_then(PluginEntry::options)
+                .map(|options| PathOptimizerConfig {
+                    decimal_places: options.decimal_places,
+                    use_relative: options.use_relative,
+                    use_shortest: options.use_shortest,
+                    collapse_repeated: options.collapse_repeated,
+                }),
+            gradient_deduplicator: file.dedupe_gradients.unwrap_or_default(),
+            id_remover: IdRemoverConfig {
+                enabled: id_options.is_some(),
+                preserve: id_options.map(|options| options.preserve).unwrap_or_default(),
+            },
+            data_attr_remover: file.remove_data_attrs.unwrap_or_default(),
+            comment_remover: CommentRemoverConfig {
+                enabled: comment_options.is_some(),
+                preserve: preserve_comments,
+            },
+            group_collapser: file.collapse_groups.unwrap_or_default(),
+            invisible_remover: file.remove_invisible.unwrap_or_default(),
+            default_attr_remover: file.remove_default_attrs.unwrap_or_default(),
+            metadata_remover: file.remove_metadata.unwrap_or_default(),
+            shape_converter: ShapeToPathConfig {
+                enabled: shape_options.is_some(),
+                shapes: shape_options.map(|options| options.shapes).unwrap_or_default(),
+            },
+            doctype_remover: file.remove_doctype.unwrap_or_default(),
+            unused_defs_remover: file.remove_unused_defs.unwrap_or_default(),
+            proc_inst_remover: file.remove_xml_proc_inst.unwrap_or_default(),
+            editor_ns_remover: EditorNsRemoverConfig {
+                enabled: editor_ns_options.is_some(),
+                preserve: editor_ns_options.map(|options| options.preserve).unwrap_or_default(),
+            },
+            color_converter: file
+                .convert_colors
+                .and_then(PluginEntry::options)
+                .map(|options| ColorConverterConfig {
+                    shorthand: options.shorthand,
+                    lowercase: options.lowercase,
+                }),
+            title_desc_remover: TitleDescRemoverConfig {
+                enabled: title_desc_options.is_some(),
+                preserve_title: title_desc_options.as_ref().is_some_and(|options| options.preserve_title),
+                preserve_desc: title_desc_options.as_ref().is_some_and(|options| options.preserve_desc),
+            },
+            empty_attr_remover: match empty_attr_options {
+                Some(options) => EmptyAttrRemoverConfig {
+                    enabled: true,
+                    allow: options.allow,
+                    deny: options.deny,
+                    geometry: options.geometry,
+                },
+                None => EmptyAttrRemoverConfig::default(),
+            },
+            color_normalizer: file.normalize_colors.unwrap_or_default(),
+            namespace_cleaner: NamespaceCleanerConfig {
+                enabled: namespace_options.is_some(),
+                xlink_to_href: namespace_options.is_some_and(|options| options.xlink_to_href),
+            },
+            attr_sorter: AttrSorterConfig {
+                enabled: sort_options.is_some(),
+                order: sort_options.map(|options| options.order).unwrap_or_default(),
+            },
+            numeric_cleaner: file
+                .cleanup_numeric_values
+                .and_then(PluginEntry::options)
+                .map(|options| NumericCleanerConfig {
+                    decimal_places: options.decimal_places,
+                    lists: options.lists,
+                }),
+            transform_optimizer: TransformOptimizerConfig {
+                enabled: transform_options.is_some(),
+                propagate: transform_options.is_some_and(|options| options.propagate),
+            },
+            useless_paint_remover: file.remove_useless_paint.unwrap_or_default(),
+            path_deduplicator: PathDeduplicatorConfig {
+                enabled: path_dedupe_options.is_some(),
+                remove: path_dedupe_options.is_some_and(|options| options.remove),
+            },
+            path_merger: file.merge_paths.unwrap_or_default(),
+            style_inliner: file.inline_styles.unwrap_or_default(),
+            stylesheet_inliner: file.inline_stylesheet.unwrap_or_default(),
+            style_converter: file.presentation_to_style.unwrap_or_default(),
+            css_minifier: file.minify_css.unwrap_or_default(),
+            unused_css_remover: file.remove_unused_css.unwrap_or_default(),
+            text_whitespace_normalizer: file.normalize_text_whitespace.unwrap_or_default(),
+            script_remover: file.remove_scripts.unwrap_or_default(),
+            external_refs_remover: file.remove_external_refs.unwrap_or_default(),
+            whitespace_collapser: file.collapse_whitespace.unwrap_or_default(),
+            empty_container_remover: file.remove_empty_containers.unwrap_or_default(),
+            hidden_elems_remover: file
+                .remove_hidden_elems
+                .and_then(PluginEntry::options)
+                .map(|options| HiddenElemsConfig {
+                    display_none: options.display_none,
+                    visibility_hidden: options.visibility_hidden,
+                    opacity_zero: options.opacity_zero,
+                    zero_size: options.zero_size,
+                }),
+            dimensions_remover: file.remove_dimensions.unwrap_or_default(),
+            attrs_remover: file
+                .remove_attrs
+                .and_then(PluginEntry::options)
+                .map(|options| options.attrs)
+                .unwrap_or_default(),
+            id_cleaner: file
+                .cleanup_ids
+                .and_then(PluginEntry::options)
+                .map(|options| IdCleanerConfig {
+                    remove: options.remove,
+                    minify: options.minify,
+                    preserve_prefixes: options.preserve_prefixes,
+                }),
+            id_prefixer: file
+                .prefix_ids
+                .and_then(PluginEntry::options)
+                .map(|options| IdPrefixerConfig {
+                    prefix: options.prefix,
+                    delimiter: options.delimiter,
+                }),
+            enable: Vec::new(),
+            disable: Vec::new(),
+            order: file.order,
+        })
+    }
+
+    /// The plugins of a preset. Safe, default and aggressive each include
+    /// the ones before it.
+    ///
+    /// - [`Preset::Safe`] removes comments, metadata, the DOCTYPE, processing
+    ///   instructions, editor namespaces, default or empty attributes and
+    ///   whitespace between elements, and shortens colors. None of this can
+    ///   change how the file renders.
+    /// - [`Preset::Default`] adds path optimization and numeric cleanup with
+    ///   two decimal places, merges identical gradients, removes unused
+    ///   definitions, invisible and hidden elements, useless paint, empty
+    ///   containers, redundant groups and redundant `svg:` prefixes, and
+    ///   minifies CSS.
+    /// - [`Preset::Aggressive`] also writes path data with the shortest of
+    ///   relative and absolute commands without repeated command letters,
+    ///   cleans numbers in lists like `viewBox`, and adds shape to path
+    ///   conversion, transform propagation, style inlining, path merging,
+    ///   removal of repeated paths, the rewrite of `xlink:href` to `href`,
+    ///   and the removal of unreferenced ids, with the others shortened,
+    ///   data attributes, titles, descriptions, empty `transform` and `d`
+    ///   attributes, unused CSS and the root element's dimensions.
+    /// - [`Preset::Sanitize`] is the safe preset plus the script and external
+    ///   reference removers, for SVGs from untrusted sources. It is a
+    ///   safeguard, not a full sanitizer: CSS `url()` and `@import`,
+    ///   relative file references and animations changing `href` are left
+    ///   alone.
+    pub fn from_preset(preset: Preset) -> Self {
+        let mut config = Self {
+            comment_remover: CommentRemoverConfig {
+                enabled: true,
+                preserve: Vec::new(),
+            },
+            metadata_remover: true,
+            doctype_remover: true,
+            proc_inst_remover: true,
+            editor_ns_remover: EditorNsRemoverConfig {
+                enabled: true,
+                preserve: Vec::new(),
+            },
+            default_attr_remover: true,
+            empty_attr_remover: EmptyAttrRemoverConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            color_converter: Some(ColorConverterConfig::default()),
+            whitespace_collapser: true,
+            ..Default::default()
+        };
+        match preset {
+            Preset::Safe => return config,
+            Preset::Sanitize => {
+                config.script_remover = true;
+                config.external_refs_remover = true;
+                return config;
+            }
+            Preset::Default | Preset::Aggressive => {}
+        }
+
+        config.path_optimizer = Some(PathOptimizerConfig::default());
+        config.numeric_cleaner = Some(NumericCleanerConfig::default());
+        config.gradient_deduplicator = true;
+        config.unused_defs_remover = true;
+        config.invisible_remover = true;
+        config.useless_paint_remover = true;
+        config.group_collapser = true;
+        config.empty_container_remover = true;
+        config.hidden_elems_remover = Some(HiddenElemsConfig::default());
+        config.namespace_cleaner.enabled = true;
+        config.css_minifier = true;
+        if preset == Preset::Default {
+            return config;
+        }
+
+        config.path_optimizer = Some(PathOptimizerConfig {
+            use_relative: true,
+            use_shortest: true,
+            collapse_repeated: true,
+            ..Default::default()
+        });
+        config.numeric_cleaner = Some(NumericCleanerConfig {
+            lists: true,
+            ..Default::default()
+        });
+        config.shape_converter.enabled = true;
+        config.transform_optimizer = TransformOptimizerConfig {
+            enabled: true,
+            propagate: true,
+        };
+        config.id_remover.enabled = true;
+        config.data_attr_remover = true;
+        config.title_desc_remover.enabled = true;
+        config.empty_attr_remover.geometry = true;
+        config.namespace_cleaner.xlink_to_href = true;
+        config.path_deduplicator = PathDeduplicatorConfig {
+            enabled: true,
+            remove: true,
+        };
+        config.path_merger = true;
+        config.style_inliner = true;
+        config.unused_css_remover = true;
+        config.dimensions_remover = true;
+        config.id_cleaner = Some(IdCleanerConfig::default());
+        config
+    }
+
+    /// The engine the enabled plugins need: the tree engine as soon as one
+    /// of them works on the document tree, streaming otherwise.
+    pub fn engine(&self) -> Engine {
+        PluginRegistry::default().engine(self)
+    }
+
+    /// Apply command-line flags on top of this configuration. Flags only
+    /// ever enable plugins or replace their options, except
+    /// `--preserve-metadata`, which turns the metadata remover off.
+    pub fn apply_args(&mut self, args: &OptimizeArgs) {
+        if args.optimize_paths && self.path_optimizer.is_none() {
+            self.path_optimizer = Some(PathOptimizerConfig::default());
+        }
+        if let Some(path_config) = &mut self.path_optimizer {
+            if let Some(decimal_places) = args.path_decimals {
+                path_config.decimal_places = decimal_places;
+            }
+            path_config.use_relative |= args.path_relative;
+            path_config.use_shortest |= args.path_shortest;
+            path_config.collapse_repeated |= args.path_collapse_commands;
+        }
+
+        self.gradient_deduplicator |= args.dedupe_gradients;
+
+        self.id_remover.enabled |= args.remove_ids;
+        if let Some(preserve) = &args.preserve_ids {
+            self.id_remover.preserve = preserve.clone();
+        }
+
+        self.data_attr_remover |= args.remove_data_attrs;
+
+        self.comment_remover.enabled |= args.remove_comments;
+        if !args.preserve_comments.is_empty() {
+            self.comment_remover.preserve = args.preserve_comments.clone();
+        }
+
+        self.group_collapser |= args.collapse_groups;
+        self.invisible_remover |= args.remove_invisible;
+        self.default_attr_remover |= args.remove_default_attrs;
+        self.metadata_remover |= args.remove_metadata;
+        self.metadata_remover &= !args.preserve_metadata;
+
+        self.shape_converter.enabled |= args.shapes_to_paths;
+        if let Some(shapes) = &args.shapes {
+            self.shape_converter.shapes = shapes.clone();
+        }
+        self.doctype_remover |= args.remove_doctype;
+        self.unused_defs_remover |= args.remove_unused_defs;
+        self.proc_inst_remover |= args.remove_xml_proc_inst;
+
+        self.editor_ns_remover.enabled |= args.remove_editor_ns;
+        if let Some(preserve) = &args.preserve_ns {
+            self.editor_ns_remover.preserve = preserve.clone();
+        }
+
+        if args.convert_colors && self.color_converter.is_none() {
+            self.color_converter = Some(ColorConverterConfig::default());
+        }
+        if let Some(color_config) = &mut self.color_converter {
+            color_config.shorthand &= !args.color_longhand;
+            color_config.lowercase &= !args.color_keep_case;
+        }
+
+        self.title_desc_remover.enabled |= args.remove_title_desc;
+        self.title_desc_remover.preserve_title |= args.preserve_title;
+        self.title_desc_remover.preserve_desc |= args.preserve_desc;
+
+        self.empty_attr_remover.enabled |= args.remove_empty_attrs;
+        if let Some(allow) = &args.empty_attrs_allow {
+            self.empty_attr_remover.allow = allow.clone();
+        }
+        if let Some(deny) = &args.empty_attrs_deny {
+            self.empty_attr_remover.deny = deny.clone();
+        }
+        self.empty_attr_remover.geometry |= args.empty_attrs_geometry;
+        self.color_normalizer |= args.normalize_colors;
+
+        self.namespace_cleaner.enabled |= args.clean_namespaces;
+        self.namespace_cleaner.xlink_to_href |= args.xlink_to_href;
+
+        self.attr_sorter.enabled |= args.sort_attrs;
+        if let Some(order) = &args.attrs_order {
+            self.attr_sorter.order = order.clone();
+        }
+
+        if args.cleanup_numeric_values && self.numeric_cleaner.is_none() {
+            self.numeric_cleaner = Some(NumericCleanerConfig::default());
+        }
+        if let Some(numeric_config) = &mut self.numeric_cleaner {
+            if let Some(decimal_places) = args.numeric_decimals {
+                numeric_config.decimal_places = decimal_places;
+            }
+            numeric_config.lists |= args.numeric_lists;
+        }
+
+        self.transform_optimizer.enabled |= args.optimize_transforms;
+        self.transform_optimizer.propagate |= args.propagate_transforms;
+        self.useless_paint_remover |= args.remove_useless_paint;
+        self.path_deduplicator.enabled |= args.dedupe_paths || args.remove_duplicate_paths;
+        self.path_deduplicator.remove |= args.remove_duplicate_paths;
+        self.path_merger |= args.merge_paths;
+        self.style_inliner |= args.inline_styles;
+        self.stylesheet_inliner |= args.inline_stylesheet;
+        self.style_converter |= args.presentation_to_style;
+        self.css_minifier |= args.minify_css;
+        self.unused_css_remover |= args.remove_unused_css;
+        self.text_whitespace_normalizer |= args.normalize_text_whitespace;
+        self.script_remover |= args.remove_scripts || args.sanitize;
+        self.script_remover &= !args.allow_scripts;
+        self.external_refs_remover |= args.remove_external_refs || args.sanitize;
+        self.whitespace_collapser |= args.collapse_whitespace;
+        self.empty_container_remover |= args.remove_empty_containers;
+        if args.remove_hidden_elems && self.hidden_elems_remover.is_none() {
+            self.hidden_elems_remover = Some(HiddenElemsConfig::default());
+        }
+        self.dimensions_remover |= args.remove_dimensions;
+        self.attrs_remover.extend(args.remove_attrs.iter().cloned());
+        if args.cleanup_ids && self.id_cleaner.is_none() {
+            self.id_cleaner = Some(IdCleanerConfig::default());
+        }
+        if let (Some(cleaner), Some(prefixes)) = (&mut self.id_cleaner, &args.preserve_id_prefixes) {
+            cleaner.preserve_prefixes = prefixes.clone();
+        }
+        if let Some(prefix) = &args.prefix_ids {
+            let prefixer = self.id_prefixer.get_or_insert_with(IdPrefixerConfig::default);
+            if prefix.is_some() {
+                prefixer.prefix = prefix.clone();
+            }
+            if let Some(delimiter) = &args.prefix_delimiter {
+                prefixer.delimiter = delimiter.clone();
+            }
+        }
+        self.enable.extend(args.enable.iter().cloned());
+        self.disable.extend(args.disable.iter().cloned());
+        if let Some(order) = &args.order {
+            self.order = order.clone();
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ShapeToPathConfig {
+    pub enabled: bool,
+    /// Shapes to convert; empty converts all of them.
+    pub shapes: Vec<Shape>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct EditorNsRemoverConfig {
+    pub enabled: bool,
+    /// Namespace prefixes to keep.
+    pub preserve: Vec<String>,
+}
+
+impl From<&OptimizeArgs> for PluginConfig {
+    fn from(args: &OptimizeArgs) -> Self {
+        let mut config = Self::default();
+        config.apply_args(args);
+        config
+    }
+}
+
+/// Layout of a JSON config file.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ConfigFile {
+    /// Processor options rather than plugins.
+    buffer_size: Option<usize>,
+    jobs: Option<usize>,
+    /// Plugins to run first, by name; not a plugin itself.
+    #[serde(default)]
+    order: Vec<String>,
+    path_optimizer: Option<PluginEntry<PathOptimizerOptions>>,
+    dedupe_gradients: Option<bool>,
+    remove_ids: Option<PluginEntry<PreserveOptions>>,
+    remove_data_attrs: Option<bool>,
+    remove_comments: Option<PluginEntry<PreserveOptions>>,
+    collapse_groups: Option<bool>,
+    remove_invisible: Option<bool>,
+    remove_default_attrs: Option<bool>,
+    remove_metadata: Option<bool>,
+    shapes_to_paths: Option<PluginEntry<ShapeOptions>>,
+    remove_doctype: Option<bool>,
+    remove_unused_defs: Option<bool>,
+    remove_xml_proc_inst: Option<bool>,
+    remove_editor_namespaces: Option<PluginEntry<PreserveOptions>>,
+    convert_colors: Option<PluginEntry<ColorOptions>>,
+    remove_title_desc: Option<PluginEntry<TitleDescOptions>>,
+    remove_empty_attrs: Option<PluginEntry<EmptyAttrsOptions>>,
+    normalize_colors: Option<bool>,
+    clean_namespaces: Option<PluginEntry<NamespaceOptions>>,
+    sort_attrs: Option<PluginEntry<SortAttrsOptions>>,
+    cleanup_numeric_values: Option<PluginEntry<NumericOptions>>,
+    optimize_transforms: Option<PluginEntry<TransformOptions>>,
+    remove_useless_paint: Option<bool>,
+    dedupe_paths: Option<PluginEntry<PathDedupeOptions>>,
+    merge_paths: Option<bool>,
+    inline_styles: Option<bool>,
+    inline_stylesheet: Option<bool>,
+    presentation_to_style: Option<bool>,
+    #[serde(alias = "minifyStyles")]
+    minify_css: Option<bool>,
+    remove_unused_css: Option<bool>,
+    normalize_text_whitespace: Option<bool>,
+    remove_scripts: Option<bool>,
+    remove_external_refs: Option<bool>,
+    collapse_whitespace: Option<bool>,
+    remove_empty_containers: Option<bool>,
+    remove_hidden_elems: Option<PluginEntry<HiddenElemsOptions>>,
+    remove_dimensions: Option<bool>,
+    remove_attrs: Option<PluginEntry<RemoveAttrsOptions>>,
+    cleanup_ids: Option<PluginEntry<CleanupIdsOptions>>,
+    prefix_ids: Option<PluginEntry<PrefixIdsOptions>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct PathOptimizerOptions {
+    #[serde(default = "default_decimal_places")]
+    decimal_places: usize,
+    #[serde(default)]
+    use_relative: bool,
+    #[serde(default)]
+    use_shortest: bool,
+    #[serde(default)]
+    collapse_repeated: bool,
+}
+
+impl Default for PathOptimizerOptions {
+    fn default() -> Self {
+        Self {
+            decimal_places: DEFAULT_DECIMAL_PLACES,
+            use_relative: false,
+            use_shortest: false,
+            collapse_repeated: false,
+        }
+    }
+}
+
+fn default_decimal_places() -> usize {
+    DEFAULT_DECIMAL_PLACES
+}
+
+/// Options of plugins that take a list of things to keep.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PreserveOptions {
+    #[serde(default)]
+    preserve: Vec<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ShapeOptions {
+    #[serde(default)]
+    shapes: Vec<Shape>,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ColorOptions {
+    shorthand: bool,
+    lowercase: bool,
+}
+
+impl Default for ColorOptions {
+    fn default() -> Self {
+        Self {
+            shorthand: true,
+            lowercase: true,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+struct HiddenElemsOptions {
+    display_none: bool,
+    visibility_hidden: bool,
+    opacity_zero: bool,
+    zero_size: bool,
+}
+
+impl Default for HiddenElemsOptions {
+    fn default() -> Self {
+        Self {
+            display_none: true,
+            visibility_hidden: true,
+            opacity_zero: true,
+            zero_size: true,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+struct CleanupIdsOptions {
+    remove: bool,
+    minify: bool,
+    preserve_prefixes: Vec<String>,
+}
+
+impl Default for CleanupIdsOptions {
+    fn default() -> Self {
+        Self {
+            remove: true,
+            minify: true,
+            preserve_prefixes: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PrefixIdsOptions {
+    prefix: Option<String>,
+    delimiter: String,
+}
+
+impl Default for PrefixIdsOptions {
+    fn default() -> Self {
+        Self {
+            prefix: None,
+            delimiter: DEFAULT_PREFIX_DELIMITER.to_string(),
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct TransformOptions {
+    propagate: bool,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PathDedupeOptions {
+    remove: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+struct NumericOptions {
+    decimal_places: usize,
+    lists: bool,
+}
+
+impl Default for NumericOptions {
+    fn default() -> Self {
+        Self {
+            decimal_places: DEFAULT_DECIMAL_PLACES,
+            lists: false,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+struct TitleDescOptions {
+    preserve_title: bool,
+    preserve_desc: bool,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase", deny_unknown_fields)]
+struct NamespaceOptions {
+    xlink_to_href: bool,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct SortAttrsOptions {
+    order: Vec<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RemoveAttrsOptions {
+    attrs: Vec<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct EmptyAttrsOptions {
+    allow: Vec<String>,
+    deny: Vec<String>,
+    geometry: bool,
+}
+
+/// A plugin in a config file: either `true`/`false`, or an object of
+/// options that enables it.
+enum PluginEntry<T> {
+    Enabled(bool),
+    Options(T),
+}
+
+impl<T: Default> PluginEntry<T> {
+    /// The plugin's options if it is enabled, using defaults for `true`.
+    fn options(self) -> Option<T> {
+        match self {
+            PluginEntry::Enabled(true) => Some(T::default()),
+            PluginEntry::Enabled(false) => None,
+            PluginEntry::Options(options) => Some(options),
+        }
+    }
+}
+
+// Hand-written so errors inside an options object are reported as-is,
+// rather than as a failure to match either variant.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for PluginEntry<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntryVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for EntryVisitor<T> {
+            type Value = PluginEntry<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a boolean or an object of plugin options")
+            }
+
+            fn visit_bool<E: de::Error>(self, enabled: bool) -> Result<Self::Value, E> {
+                Ok(PluginEntry::Enabled(enabled))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                T::deserialize(de::value::MapAccessDeserializer::new(map)).map(PluginEntry::Options)
+            }
+        }
+
+        deserializer.deserialize_any(EntryVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_config_from_json() -> io::Result<()> {
+        let config = PluginConfig::from_json_str(
+            r#"{"pathOptimizer": {"decimalPlaces": 1}, "removeIds": {"preserve": ["logo"]}, "dedupeGradients": true, "removeComments": true}"#,
+        )?;
+
+        assert_eq!(config.path_optimizer.unwrap().decimal_places, 1);
+        assert!(config.id_remover.enabled);
+        assert_eq!(config.id_remover.preserve, vec!["logo".to_string()]);
+        assert!(config.gradient_deduplicator);
+        assert!(config.comment_remover.enabled);
+        assert!(config.comment_remover.preserve.is_empty());
+        assert!(!config.data_attr_remover);
+        assert!(!config.shape_converter.enabled);
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_defaults_for_enabled_plugins() -> io::Result<()> {
+        let config = PluginConfig::from_json_str(
+            r#"{"pathOptimizer": true, "removeIds": false, "shapesToPaths": {"shapes": ["rect"]}}"#,
+        )?;
+
+        assert_eq!(config.path_optimizer.unwrap().decimal_places, 2);
+        assert!(!config.id_remover.enabled);
+        assert!(config.shape_converter.enabled);
+        assert_eq!(config.shape_converter.shapes, vec![Shape::Rect]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hidden_elems_options() -> io::Result<()> {
+        let config = PluginConfig::from_json_str(r#"{"removeHiddenElems": {"opacityZero": false}}"#)?;
+        let hidden = config.hidden_elems_remover.unwrap();
+
+        assert!(hidden.display_none && hidden.visibility_hidden && hidden.zero_size);
+        assert!(!hidden.opacity_zero);
+        assert!(PluginConfig::from_json_str(r#"{"removeHiddenElems": false}"#)?.hidden_elems_remover.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cleanup_ids_options() -> io::Result<()> {
+        let mut config = PluginConfig::from_json_str(r#"{"cleanupIds": {"minify": false, "preservePrefixes": ["icon-"]}}"#)?;
+        let cleaner = config.id_cleaner.clone().unwrap();
+        assert!(cleaner.remove && !cleaner.minify);
+        assert_eq!(cleaner.preserve_prefixes, ["icon-"]);
+
+        let cli = Cli::parse_from(["svgo-rs", "optimize", "a.svg", "b.svg", "--preserve-id-prefixes", "logo-,brand-"]);
+        let Commands::Optimize(args) = cli.command else { unreachable!() };
+        config.apply_args(&args);
+        assert_eq!(config.id_cleaner.unwrap().preserve_prefixes, ["logo-", "brand-"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefix_ids_options() -> io::Result<()> {
+        let prefixer = |json: &str, flags: &[&str]| -> io::Result<Option<IdPrefixerConfig>> {
+            let mut config = PluginConfig::from_json_str(json)?;
+            let cli = Cli::parse_from(["svgo-rs", "optimize", "a.svg", "b.svg"].iter().chain(flags));
+            let Commands::Optimize(args) = cli.command else { unreachable!() };
+            config.apply_args(&args);
+            Ok(config.id_prefixer)
+        };
+
+        let file = prefixer(r#"{"prefixIds": {"prefix": "logo", "delimiter": "-"}}"#, &[])?.unwrap();
+        assert_eq!((file.prefix.as_deref(), file.delimiter.as_str()), (Some("logo"), "-"));
+
+        let from_name = prefixer("{}", &["--prefix-ids"])?.unwrap();
+        assert_eq!((from_name.prefix, from_name.delimiter.as_str()), (None, "__"));
+
+        let flags = prefixer(r#"{"prefixIds": {"prefix": "logo"}}"#, &["--prefix-ids", "icon", "--prefix-delimiter", "_"])?.unwrap();
+        assert_eq!((flags.prefix.as_deref(), flags.delimiter.as_str()), (Some("icon"), "_"));
+        assert!(prefixer("{}", &[])?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_attrs_patterns() -> io::Result<()> {
+        let mut config = PluginConfig::from_json_str(r#"{"removeAttrs": {"attrs": ["*:inkscape:*:*"]}}"#)?;
+        let cli = Cli::parse_from(["svgo-rs", "optimize", "a.svg", "b.svg", "--remove-attrs", "*:fill:#000000"]);
+        let Commands::Optimize(args) = cli.command else { unreachable!() };
+        config.apply_args(&args);
+
+        assert_eq!(config.attrs_remover, ["*:inkscape:*:*", "*:fill:#000000"]);
+        assert!(PluginConfig::from_json_str(r#"{"removeAttrs": true}"#)?.attrs_remover.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_minify_styles_alias() -> io::Result<()> {
+        let mut config = PluginConfig::default();
+        let cli = Cli::parse_from(["svgo-rs", "optimize", "a.svg", "b.svg", "--minify-styles"]);
+        let Commands::Optimize(args) = cli.command else { unreachable!() };
+        config.apply_args(&args);
+
+        assert!(config.css_minifier);
+        assert!(PluginConfig::from_json_str(r#"{"minifyStyles": true}"#)?.css_minifier);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sanitize() -> io::Result<()> {
+        let sanitize = PluginConfig::from_preset(Preset::Sanitize);
+        let safe = PluginConfig::from_preset(Preset::Safe);
+        assert!(sanitize.script_remover && sanitize.external_refs_remover);
+        assert!(!safe.script_remover && !safe.external_refs_remover);
+        assert!(sanitize.path_optimizer.is_none());
+
+        let mut config = PluginConfig::from_preset(Preset::Aggressive);
+        let cli = Cli::parse_from(["svgo-rs", "optimize", "a.svg", "b.svg", "--preset", "aggressive", "--sanitize"]);
+        let Commands::Optimize(args) = cli.command else { unreachable!() };
+        config.apply_args(&args);
+        assert!(config.script_remover && config.external_refs_remover && config.shape_converter.enabled);
+
+        assert!(PluginConfig::from_json_str(r#"{"removeExternalRefs": true}"#)?.external_refs_remover);
+        assert!(Cli::try_parse_from(["svgo-rs", "optimize", "a.svg", "b.svg", "--sanitize", "--allow-scripts"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected() {
+        let unknown = PluginConfig::from_json_str(r#"{"removeEverything": true}"#).unwrap_err();
+        assert!(unknown.to_string().contains("unknown field `removeEverything`"));
+
+        let option = PluginConfig::from_json_str(r#"{"pathOptimizer": {"decimals": 1}}"#).unwrap_err();
+        assert!(option.to_string().contains("unknown field `decimals`"));
+
+        assert!(PluginConfig::from_json_str("{").is_err());
+        assert!(PluginConfig::from_json_str(r#"{"removeComments": {"preserve": ["("]}}"#).is_err());
+    }
+
+    #[test]
+    fn test_flags_override_config() -> io::Result<()> {
+        let mut config = PluginConfig::from_json_str(
+            r#"{"pathOptimizer": {"decimalPlaces": 1}, "removeIds": {"preserve": ["logo"]}, "removeMetadata": true}"#,
+        )?;
+        let cli = Cli::parse_from([
+            "svgo-rs",
+            "optimize",
+            "in.svg",
+            "out.svg",
+            "--path-decimals",
+            "3",
+            "--preserve-ids",
+            "icon",
+            "--remove-comments",
+            "--preserve-metadata",
+        ]);
+        let Commands::Optimize(args) = cli.command else { unreachable!() };
+
+        config.apply_args(&args);
+
+        assert_eq!(config.path_optimizer.unwrap().decimal_places, 3);
+        assert_eq!(config.id_remover.preserve, vec!["icon".to_string()]);
+        assert!(config.comment_remover.enabled);
+        assert!(!config.metadata_remover);
+        Ok(())
+    }
+
+    #[test]
+    fn test_presets_build_on_each_other() {
+        let registry = PluginRegistry::default();
+        let names = |preset| -> Vec<String> {
+            let config = PluginConfig::from_preset(preset);
+            registry.ordered(&config).iter().map(|plugin| plugin.name().to_string()).collect()
+        };
+        let (safe, default, aggressive) = (names(Preset::Safe), names(Preset::Default), names(Preset::Aggressive));
+
+        assert!(safe.contains(&"removeComments".to_string()));
+        assert!(!safe.contains(&"pathOptimizer".to_string()));
+        assert!(default.contains(&"pathOptimizer".to_string()));
+        assert!(!default.contains(&"shapesToPaths".to_string()));
+        assert!(aggressive.contains(&"shapesToPaths".to_string()));
+        assert!(safe.iter().all(|name| default.contains(name)));
+        assert!(default.iter().all(|name| aggressive.contains(name)));
+    }
+
+    #[test]
+    fn test_output_format() {
+        let stats = |flags: &[&str]| {
+            let cli = Cli::parse_from(["svgo-rs", "optimize", "in.svg", "out.svg"].iter().chain(flags));
+            let Commands::Optimize(args) = cli.command else { unreachable!() };
+            args.stats_json_path()
+        };
+
+        assert_eq!(stats(&[]), None);
+        assert_eq!(stats(&["--output-format", "text"]), None);
+        assert_eq!(stats(&["--output-format", "json"]), Some(PathBuf::from("-")));
+        assert_eq!(stats(&["--stats-json", "s.json"]), Some(PathBuf::from("s.json")));
+        assert!(Cli::try_parse_from(["svgo-rs", "optimize", "a", "b", "--output-format", "json", "--stats-json"]).is_err());
+        assert!(Cli::try_parse_from(["svgo-rs", "optimize", "a", "b", "--output-format", "xml"]).is_err());
+    }
+
+    #[test]
+    fn test_stdin_and_stdout_flags() -> io::Result<()> {
+        let paths = |args: &[&str]| -> io::Result<(PathBuf, Option<PathBuf>)> {
+            let cli = Cli::try_parse_from(["svgo-rs", "optimize"].iter().chain(args))
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let Commands::Optimize(args) = cli.command else { unreachable!() };
+            Ok(args.paths()?)
+        };
+        let stdio = || Some(PathBuf::from("-"));
+
+        assert_eq!(paths(&["--stdin", "--stdout"])?, (PathBuf::from("-"), stdio()));
+        assert_eq!(paths(&["--stdin", "out.svg"])?, (PathBuf::from("-"), Some(PathBuf::from("out.svg"))));
+        assert_eq!(paths(&["in.svg", "--stdout"])?, (PathBuf::from("in.svg"), stdio()));
+        assert_eq!(paths(&["-", "-"])?, (PathBuf::from("-"), stdio()));
+        assert!(paths(&["--stdin"]).is_err());
+        assert!(paths(&["--stdin", "a.svg", "b.svg"]).is_err());
+        assert!(paths(&["--stdin", "--stdout", "a.svg"]).is_err());
+        assert!(paths(&["in.svg", "out.svg", "--stdout"]).is_err());
+        assert!(paths(&["in.svg", "-i", "--stdout"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run_flags() -> io::Result<()> {
+        let cli = Cli::parse_from(["svgo-rs", "optimize", "icon.svg", "--dry-run", "--min-savings-pct", "5"]);
+        let Commands::Optimize(args) = cli.command else { unreachable!() };
+
+        assert!(args.dry_run);
+        assert_eq!(args.min_savings_pct, Some(5.0));
+        assert_eq!(args.paths()?, (PathBuf::from("icon.svg"), None));
+        assert!(Cli::try_parse_from(["svgo-rs", "optimize", "a.svg", "b.svg", "--min-savings-pct", "5"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_watch_flags() {
+        let cli = Cli::parse_from(["svgo-rs", "optimize", "icons", "out", "-w", "--debounce", "50"]);
+        let Commands::Optimize(args) = cli.command else { unreachable!() };
+
+        assert!(args.watch);
+        assert_eq!(args.debounce, 50);
+        assert!(Cli::try_parse_from(["svgo-rs", "optimize", "a.svg", "b.svg", "--debounce", "50"]).is_err());
+        assert!(Cli::try_parse_from(["svgo-rs", "optimize", "a.svg", "--stdout", "--watch"]).is_err());
+    }
+
+    #[test]
+    fn test_in_place_short_flag() {
+        let cli = Cli::parse_from(["svgo-rs", "optimize", "-i", "icon.svg"]);
+        let Commands::Optimize(args) = cli.command else { unreachable!() };
+
+        assert!(args.in_place);
+        assert_eq!(args.output, None);
+        assert!(Cli::try_parse_from(["svgo-rs", "optimize", "icon.svg"]).is_err());
+        assert!(Cli::try_parse_from(["svgo-rs", "optimize", "-i", "icon.svg", "out.svg"]).is_err());
+    }
+
+    #[test]
+    fn test_batch_args() {
+        let cli = Cli::parse_from([
+            "svgo-rs", "batch", "--input-dir", "icons", "--output-dir", "out", "--pattern", "*.svg", "-r", "-j",
+        ]);
+        let Commands::Batch(args) = cli.command else { unreachable!() };
+
+        assert_eq!(args.input_dir, PathBuf::from("icons"));
+        assert_eq!(args.output_dir, PathBuf::from("out"));
+        assert_eq!(args.pattern.as_deref(), Some("*.svg"));
+        assert!(args.recursive);
+        assert!(args.job_count(None) >= 1);
+        assert!(Cli::try_parse_from(["svgo-rs", "batch", "--input-dir", "icons"]).is_err());
+    }
+
+    #[test]
+    fn test_preset_flag() {
+        let cli = Cli::parse_from(["svgo-rs", "optimize", "in.svg", "out.svg", "--preset", "aggressive"]);
+        let Commands::Optimize(args) = cli.command else { unreachable!() };
+
+        assert_eq!(args.preset, Some(Preset::Aggressive));
+        assert!(Cli::try_parse_from(["svgo-rs", "optimize", "in.svg", "out.svg", "--preset", "unsafe"]).is_err());
+        assert!(Cli::try_parse_from(["svgo-rs", "optimize", "a.svg", "b.svg", "--preset", "safe", "-c", "c.json"]).is_err());
+    }
+
+    #[test]
+    fn test_pretty_indent() {
+        let indent = |flags: &[&str]| {
+            let cli = Cli::parse_from(["svgo-rs", "optimize", "in.svg", "out.svg"].iter().chain(flags));
+            let Commands::Optimize(args) = cli.command else { unreachable!() };
+            args.indent()
+        };
+
+        assert_eq!(indent(&[]), None);
+        assert_eq!(indent(&["--pretty"]), Some(Indent::spaces(2)));
+        assert_eq!(indent(&["--pretty", "--indent", "4"]), Some(Indent::spaces(4)));
+        assert_eq!(indent(&["--pretty", "--indent-tabs"]), Some(Indent::tabs(1)));
+        assert!(Cli::try_parse_from(["svgo-rs", "optimize", "in.svg", "out.svg", "--indent", "4"]).is_err());
+    }
+
+    #[test]
+    fn test_config_from_toml() -> io::Result<()> {
+        let config = Config::from_toml_str(
+            r#"
+bufferSize = 64
+jobs = 0
+dedupeGradients = true
+removeComments = true
+
+[pathOptimizer]
+decimalPlaces = 1
+
+[removeIds]
+preserve = ["logo"]
+"#,
+        )?;
+        let json = PluginConfig::from_json_str(
+            r#"{"pathOptimizer": {"decimalPlaces": 1}, "removeIds": {"preserve": ["logo"]}, "dedupeGradients": true, "removeComments": true}"#,
+        )?;
+
+        assert_eq!((config.buffer_size, config.jobs), (Some(64), Some(0)));
+        assert_eq!(format!("{:?}", config.plugins), format!("{:?}", json));
+
+        let unknown = Config::from_toml_str("removeEverything = true").unwrap_err();
+        assert!(unknown.to_string().contains("unknown field `removeEverything`"));
+        assert!(Config::from_toml_str("[pathOptimizer]\ndecimalPlaces = \"one\"").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_toml_syntax() -> io::Result<()> {
+        let config = Config::from_toml_str(
+            r#"
+pathOptimizer.decimalPlaces = 2
+removeComments = { preserve = ['''
+(?i)copyright''', "^\u0021"] }
+"#,
+        )?;
+        let json = PluginConfig::from_json_str(
+            r#"{"pathOptimizer": {"decimalPlaces": 2}, "removeComments": {"preserve": ["(?i)copyright", "^!"]}}"#,
+        )?;
+        assert_eq!(format!("{:?}", config.plugins), format!("{:?}", json));
+
+        let error = Config::from_toml_str("bufferSize = 16
+jobs = = 2").unwrap_err();
+        assert!(error.to_string().contains("line 2"), "{}", error);
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_and_toml_configs_match() -> io::Result<()> {
+        let json = Config::from_json_str(
+            r#"{
+                "bufferSize": 32,
+                "order": ["minifyCss"],
+                "pathOptimizer": {"decimalPlaces": 3, "useRelative": true},
+                "removeComments": {"preserve": ["^!"]},
+                "convertColors": {"shorthand": false},
+                "shapesToPaths": {"shapes": ["rect", "circle"]},
+                "optimizeTransforms": true,
+                "minifyCss": true,
+                "removeScripts": false
+            }"#,
+        )?;
+        let toml = Config::from_toml_str(
+            r#"
+bufferSize = 32
+order = ["minifyCss"]
+removeComments = { preserve = ['^!'] }
+optimizeTransforms = true
+minifyCss = true
+removeScripts = false
+
+[pathOptimizer]
+decimalPlaces = 3
+useRelative = true
+
+[convertColors]
+shorthand = false
+
+[shapesToPaths]
+shapes = ["rect", "circle"]
+"#,
+        )?;
+
+        assert_eq!(format!("{:?}", json), format!("{:?}", toml));
+        assert!(json.plugins.transform_optimizer.enabled);
+        assert_eq!(json.plugins.order, vec!["minifyCss".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_file_format_follows_extension() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let toml = temp_dir.path().join("svgo.toml");
+        let json = temp_dir.path().join("svgo.json");
+        fs::write(&toml, "bufferSize = 16\nminifyCss = true\n")?;
+        fs::write(&json, r#"{"bufferSize": 16, "minifyCss": true}"#)?;
+
+        for path in [&toml, &json] {
+            let config = Config::from_file(path)?;
+            assert_eq!(config.buffer_size, Some(16));
+            assert!(config.plugins.css_minifier);
+        }
+
+        let other = temp_dir.path().join(".svgorc");
+        fs::write(&other, r#"{"minifyCss": true}"#)?;
+        assert!(Config::from_file(&other)?.plugins.css_minifier);
+
+        fs::write(&toml, r#"{"minifyCss": true}"#)?;
+        let error = Config::from_file(&toml).unwrap_err();
+        assert!(error.to_string().starts_with(&format!("invalid config file {}", toml.display())));
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+//! Non-fatal problems plugins notice in a document.
+//!
+//! A plugin that finds something suspicious but can carry on, such as a
+//! reference to an id that doesn't exist, records a [`Diagnostic`] in its
+//! [`DiagnosticsCollector`] and hands them out through
+//! [`PluginStatistics::diagnostics`](crate::plugins::PluginStatistics::diagnostics).
+//! The processor gathers them after each run, like plugin statistics.
+
+use serde::Serialize;
+use std::fmt;
+
+/// How serious a diagnostic is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticLevel {
+    /// Worth knowing, but the output is as intended.
+    Info,
+    /// The input probably doesn't render the way its author meant.
+    Warning,
+}
+
+impl fmt::Display for DiagnosticLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagnosticLevel::Info => write!(f, "info"),
+            DiagnosticLevel::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem found in the document that didn't stop processing.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub level: DiagnosticLevel,
+    pub message: String,
+    /// Name of the element the problem was found on, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.level, self.message)?;
+        if let Some(element) = &self.element {
+            write!(f, " (<{}>)", element)?;
+        }
+        Ok(())
+    }
+}
+
+/// The diagnostics a plugin has found in the current document.
+///
+/// Plugins keep one as a field, clear it in `init` and return
+/// [`as_slice`](Self::as_slice) from `PluginStatistics::diagnostics`.
+#[derive(Clone, Debug, Default)]
+pub struct DiagnosticsCollector {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a diagnostic, optionally on the element named `element`.
+    pub fn push(&mut self, level: DiagnosticLevel, message: impl Into<String>, element: Option<&str>) {
+        self.diagnostics.push(Diagnostic {
+            level,
+            message: message.into(),
+            element: element.map(str::to_string),
+        });
+    }
+
+    /// Record a warning, optionally on the element named `element`.
+    pub fn warn(&mut self, message: impl Into<String>, element: Option<&str>) {
+        self.push(DiagnosticLevel::Warning, message, element);
+    }
+
+    /// Record an informational note, optionally on the element named `element`.
+    pub fn info(&mut self, message: impl Into<String>, element: Option<&str>) {
+        self.push(DiagnosticLevel::Info, message, element);
+    }
+
+    /// Forget the diagnostics of the previous document.
+    pub fn clear(&mut self) {
+        self.diagnostics.clear();
+    }
+
+    pub fn as_slice(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collector() {
+        let mut diagnostics = DiagnosticsCollector::new();
+        diagnostics.warn("reference to missing id 'a'", Some("use"));
+        diagnostics.info("nothing to do", None);
+
+        assert_eq!(diagnostics.as_slice().len(), 2);
+        assert_eq!(diagnostics.as_slice()[0].to_string(), "warning: reference to missing id 'a' (<use>)");
+        assert_eq!(diagnostics.as_slice()[1].to_string(), "info: nothing to do");
+
+        diagnostics.clear();
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let diagnostic = Diagnostic {
+            level: DiagnosticLevel::Warning,
+            message: "malformed color '#12'".to_string(),
+            element: Some("rect".to_string()),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&diagnostic).unwrap(),
+            r##"{"level":"warning","message":"malformed color '#12'","element":"rect"}"##
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! The error type returned by the processor.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// Why optimizing a document failed.
+#[derive(Debug)]
+pub enum SVGError {
+    /// Reading the input or writing the output failed.
+    Io(io::Error),
+    /// Reading or writing XML failed outside of parsing the input, for
+    /// example on a malformed attribute.
+    Xml(quick_xml::Error),
+    /// The input is not well-formed XML.
+    Parse {
+        /// Byte offset in the (decompressed) input where the error was found.
+        position: u64,
+        source: quick_xml::Error,
+    },
+    /// The input contained no XML at all.
+    EmptyInput,
+    /// A path can't be used the way it was given, like stdin for an
+    /// in-place run.
+    InvalidPath(String),
+    /// A config file or string can't be parsed or has invalid settings.
+    InvalidConfig(String),
+    /// A plugin hook returned an error.
+    Plugin {
+        plugin: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+    /// No plugin is registered under this name.
+    UnknownPlugin(String),
+}
+
+/// The name this type had before it was renamed to [`SVGError`].
+pub type SvgoError = SVGError;
+
+impl SVGError {
+    pub(crate) fn plugin(plugin: &str, source: impl Into<Box<dyn Error + Send + Sync>>) -> Self {
+        SVGError::Plugin {
+            plugin: plugin.to_string(),
+            source: source.into(),
+        }
+    }
+}
+
+impl fmt::Display for SVGError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SVGError::Io(e) => write!(f, "{}", e),
+            SVGError::Xml(e) => write!(f, "XML error: {}", e),
+            SVGError::Parse { position, source } => {
+                write!(f, "invalid XML at byte {}: {}", position, source)
+            }
+            SVGError::EmptyInput => write!(f, "No SVG content was processed"),
+            SVGError::InvalidPath(message) | SVGError::InvalidConfig(message) => write!(f, "{}", message),
+            SVGError::Plugin { plugin, source } => write!(f, "plugin {} failed: {}", plugin, source),
+            SVGError::UnknownPlugin(name) => {
+                write!(f, "unknown plugin '{}' (see `svgo-rs list-plugins`)", name)
+            }
+        }
+    }
+}
+
+impl Error for SVGError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SVGError::Io(e) => Some(e),
+            SVGError::Xml(e) | SVGError::Parse { source: e, .. } => Some(e),
+            SVGError::EmptyInput
+            | SVGError::InvalidPath(_)
+            | SVGError::InvalidConfig(_)
+            | SVGError::UnknownPlugin(_) => None,
+            SVGError::Plugin { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
+
+impl From<io::Error> for SVGError {
+    fn from(e: io::Error) -> Self {
+        SVGError::Io(e)
+    }
+}
+
+impl From<quick_xml::Error> for SVGError {
+    fn from(e: quick_xml::Error) -> Self {
+        SVGError::Xml(e)
+    }
+}
+
+/// For callers that only deal in `io::Result`. I/O errors come back
+/// unchanged, invalid paths as `InvalidInput` and everything else as
+/// `InvalidData`.
+impl From<SVGError> for io::Error {
+    fn from(e: SVGError) -> Self {
+        match e {
+            SVGError::Io(e) => e,
+            e @ SVGError::InvalidPath(_) => io::Error::new(io::ErrorKind::InvalidInput, e),
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = SVGError::plugin("RemoveComments", io::Error::other("boom"));
+        assert_eq!(error.to_string(), "plugin RemoveComments failed: boom");
+        assert!(error.source().is_some());
+        assert_eq!(SVGError::EmptyInput.to_string(), "No SVG content was processed");
+
+        let error = SVGError::plugin("RemoveAttrs", "bad pattern");
+        assert!(matches!(&error, SVGError::Plugin { source, .. } if source.to_string() == "bad pattern"));
+    }
+
+    #[test]
+    fn test_conversion_to_io_error() {
+        let error: io::Error = SVGError::Io(io::Error::from(io::ErrorKind::NotFound)).into();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+        let error: io::Error = SVGError::EmptyInput.into();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        let error: io::Error = SVGError::InvalidPath("no".to_string()).into();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    }
+}
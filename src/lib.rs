@@ -0,0 +1,46 @@
+//! SVG optimization as a library.
+//!
+//! The command-line tool is a thin wrapper around this crate; the same
+//! processor and plugins can be driven from a build script or any other
+//! Rust program:
+//!
+//! ```no_run
+//! use svgo_rs::{PathOptimizerPlugin, SVGProcessor};
+//!
+//! let mut processor = SVGProcessor::new(8 * 1024);
+//! processor.add_plugin(PathOptimizerPlugin::new(2));
+//! processor.process_file("input.svg", "output.svg")?;
+//!
+//! // Or without touching the filesystem
+//! let optimized = processor.optimize_str(r#"<svg><path d="M 10.000 20.000"/></svg>"#)?;
+//! assert_eq!(optimized, r#"<svg><path d="M10 20"/></svg>"#);
+//! # Ok::<(), svgo_rs::SVGError>(())
+//! ```
+//!
+//! # Stability
+//!
+//! The items re-exported at the crate root and the plugins in [`plugins`]
+//! are the public API and follow semantic versioning. The [`cli`] module
+//! and [`processor::SVGProcessorCLI`] exist for the binary: apart from
+//! [`PluginConfig`] and the configuration structs it holds, they may change
+//! in any release.
+
+pub mod batch;
+pub mod cli;
+pub mod diagnostics;
+pub mod error;
+pub mod plugins;
+pub mod pretty;
+pub mod processor;
+pub mod registry;
+pub mod tree;
+mod watch;
+
+pub use batch::{BatchProcessor, BatchResult, StatisticsTotals};
+pub use cli::{Config, Engine, OutputFormat, PluginConfig, Preset, SvgFormat};
+pub use diagnostics::{Diagnostic, DiagnosticLevel, DiagnosticsCollector};
+pub use error::{SVGError, SvgoError};
+pub use plugins::{ElementAction, PathOptimizerPlugin, PluginStatistics, SVGPlugin, TreePlugin};
+pub use pretty::Indent;
+pub use processor::{ProcessorBuilder, SVGProcessor, SVGProcessorBuilder};
+pub use registry::PluginRegistry;
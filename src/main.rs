@@ -1,13 +1,12 @@
-mod cli;
-mod processor;
-mod plugins;
-
+use std::path::Path;
 use std::process;
+use std::time::Duration;
 use clap::Parser;
-use cli::{Cli, Commands, OptimizeArgs, PluginConfig};
-use processor::SVGProcessorCLI;
+use svgo_rs::cli::{self, Cli, Commands, Config, PluginConfig, SvgFormat, DEFAULT_BUFFER_SIZE};
+use svgo_rs::processor::{self, SVGProcessorCLI};
+use svgo_rs::{PluginRegistry, SVGError};
 
-fn run(cli: Cli) -> std::io::Result<()> {
+fn run(cli: Cli) -> Result<(), SVGError> {
     match cli.command {
         Commands::ListPlugins => {
             SVGProcessorCLI::list_plugins();
@@ -15,51 +14,180 @@ fn run(cli: Cli) -> std::io::Result<()> {
         },
 
         Commands::Optimize(args) => {
+            // Start from the config file, if any; command-line flags win
+            let file = load_config(args.config.as_deref())?;
             let mut processor = SVGProcessorCLI::new(
-                cli.buffer_size,
+                cli.buffer_size.or(file.buffer_size).unwrap_or(DEFAULT_BUFFER_SIZE),
                 cli.verbose
             );
 
-            // Convert OptimizeArgs into PluginConfig
-            let config = PluginConfig::from(&args);
+            // A preset takes the place of the config file's plugins
+            let mut config = match args.preset {
+                Some(preset) => PluginConfig::from_preset(preset),
+                None => file.plugins,
+            };
+            config.apply_args(&args);
+            let names = config.enable.iter().chain(&config.disable).chain(&config.order);
+            PluginRegistry::default().check(names)?;
 
             // Configure and run the processor
             processor
                 .configure(config)
-                .process(&args.input, &args.output)
-        },
+                .formats(args.input_format, args.svgz.then_some(SvgFormat::Svgz))
+                .indent(args.indent())
+                .jobs(args.job_count(file.jobs))
+                .stats_json(args.stats_json_path())
+                .dry_run(args.dry_run)
+                .min_savings_pct(args.min_savings_pct);
 
-        Commands::Analyze(args) => {
-            if cli.verbose {
-                println!("Analyzing SVG file: {}", args.input.display());
+            // Without an output path --in-place or --dry-run was given
+            let (input, output) = args.paths()?;
+            let stdio = |path: &Path| (!cli::is_stdio(path)).then(|| path.to_path_buf());
+            if args.watch {
+                let debounce = Duration::from_millis(args.debounce);
+                processor.watch(&input, output.as_deref(), args.recursive, debounce)
+            } else if input.is_dir() || processor::is_glob_pattern(&input) {
+                processor.process_many(&input, output.as_deref(), args.recursive)
+            } else if let Some(output) = &output {
+                processor.process(stdio(&input), stdio(output))
+            } else if args.dry_run {
+                // Nothing is written, so the output doesn't matter
+                processor.process(stdio(&input), None)
+            } else {
+                processor.process_in_place(&input)
             }
+        },
 
-            // Create processor with all plugins enabled for analysis
-            let mut processor = SVGProcessorCLI::new(cli.buffer_size, true);
-            let config = PluginConfig {
-                path_optimizer: Some(cli::PathOptimizerConfig {
-                    decimal_places: 2,
-                }),
-                gradient_deduplicator: true,
-                id_remover: cli::IdRemoverConfig::default(),
-                data_attr_remover: true,
-            };
+        Commands::Batch(args) => {
+            let file = load_config(args.config.as_deref())?;
+            let mut processor = SVGProcessorCLI::new(
+                cli.buffer_size.or(file.buffer_size).unwrap_or(DEFAULT_BUFFER_SIZE),
+                cli.verbose
+            );
 
-            // Create temporary output path for analysis
-            let temp_output = args.input.with_extension("analysis.svg");
+            let mut config = match args.preset {
+                Some(preset) => PluginConfig::from_preset(preset),
+                None => file.plugins,
+            };
+            config.enable.extend(args.enable.iter().cloned());
+            config.disable.extend(args.disable.iter().cloned());
+            PluginRegistry::default().check(config.enable.iter().chain(&config.disable).chain(&config.order))?;
 
             processor
                 .configure(config)
-                .process(&args.input, &temp_output)?;
+                .jobs(args.job_count(file.jobs))
+                .stats_json(args.stats_json_path())
+                .process_dir(&args.input_dir, &args.output_dir, args.pattern.as_deref(), args.recursive)
+        },
+
+        Commands::Analyze(args) => {
+            if cli.verbose {
+                eprintln!("Analyzing SVG file: {}", args.input.display());
+            }
 
-            // Clean up temporary file
-            std::fs::remove_file(temp_output)?;
+            let file = load_config(args.config.as_deref())?;
+            let buffer_size = cli.buffer_size.or(file.buffer_size).unwrap_or(DEFAULT_BUFFER_SIZE);
+            let mut processor = SVGProcessorCLI::new(buffer_size, cli.verbose);
 
-            Ok(())
+            // Without a config file, analyze with all plugins enabled
+            let config = match &args.config {
+                Some(_) => file.plugins,
+                None => analysis_config(),
+            };
+
+            processor.configure(config).analyze(&args.input)
         }
     }
 }
 
+/// Every plugin enabled, for analyzing a file without a config file.
+fn analysis_config() -> PluginConfig {
+    PluginConfig {
+        path_optimizer: Some(cli::PathOptimizerConfig {
+            decimal_places: 2,
+            use_relative: false,
+            use_shortest: false,
+            collapse_repeated: false,
+        }),
+        gradient_deduplicator: true,
+        id_remover: cli::IdRemoverConfig::default(),
+        data_attr_remover: true,
+        comment_remover: cli::CommentRemoverConfig {
+            enabled: true,
+            preserve: Vec::new(),
+        },
+        group_collapser: true,
+        invisible_remover: true,
+        default_attr_remover: true,
+        metadata_remover: true,
+        shape_converter: cli::ShapeToPathConfig {
+            enabled: true,
+            shapes: Vec::new(),
+        },
+        doctype_remover: true,
+        unused_defs_remover: true,
+        proc_inst_remover: true,
+        editor_ns_remover: cli::EditorNsRemoverConfig {
+            enabled: true,
+            preserve: Vec::new(),
+        },
+        color_converter: Some(cli::ColorConverterConfig::default()),
+        title_desc_remover: cli::TitleDescRemoverConfig {
+            enabled: true,
+            ..Default::default()
+        },
+        empty_attr_remover: cli::EmptyAttrRemoverConfig {
+            enabled: true,
+            ..Default::default()
+        },
+        color_normalizer: true,
+        namespace_cleaner: cli::NamespaceCleanerConfig {
+            enabled: true,
+            ..Default::default()
+        },
+        attr_sorter: cli::AttrSorterConfig {
+            enabled: true,
+            ..Default::default()
+        },
+        numeric_cleaner: Some(cli::NumericCleanerConfig::default()),
+        transform_optimizer: cli::TransformOptimizerConfig {
+            enabled: true,
+            ..Default::default()
+        },
+        useless_paint_remover: true,
+        path_deduplicator: cli::PathDeduplicatorConfig {
+            enabled: true,
+            ..Default::default()
+        },
+        path_merger: true,
+        style_inliner: true,
+        stylesheet_inliner: true,
+        style_converter: false,
+        css_minifier: true,
+        unused_css_remover: true,
+        text_whitespace_normalizer: true,
+        script_remover: true,
+        external_refs_remover: true,
+        whitespace_collapser: true,
+        empty_container_remover: true,
+        hidden_elems_remover: Some(cli::HiddenElemsConfig::default()),
+        dimensions_remover: true,
+        attrs_remover: Vec::new(),
+        id_cleaner: Some(cli::IdCleanerConfig::default()),
+        id_prefixer: None,
+        enable: Vec::new(),
+        disable: Vec::new(),
+        order: Vec::new(),
+    }
+}
+
+fn load_config(path: Option<&Path>) -> Result<Config, SVGError> {
+    match path {
+        Some(path) => Ok(Config::from_file(path)?),
+        None => Ok(Config::default()),
+    }
+}
+
 fn main() {
     // Parse command line arguments
     let cli = Cli::parse();
@@ -74,19 +202,101 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cli::OptimizeArgs;
     use std::path::PathBuf;
 
     #[test]
     fn test_optimize_args_to_config() {
         let args = OptimizeArgs {
-            input: PathBuf::from("input.svg"),
-            output: PathBuf::from("output.svg"),
+            input: Some(PathBuf::from("input.svg")),
+            output: Some(PathBuf::from("output.svg")),
+            in_place: false,
+            stdin: false,
+            stdout: false,
+            recursive: false,
+            dry_run: false,
+            min_savings_pct: None,
+            watch: false,
+            debounce: 300,
+            config: None,
+            preset: None,
+            jobs: None,
+            enable: Vec::new(),
+            disable: Vec::new(),
+            order: None,
+            stats_json: None,
+            output_format: cli::OutputFormat::Text,
             optimize_paths: true,
-            path_decimals: 3,
+            path_decimals: Some(3),
+            path_relative: false,
+            path_shortest: false,
+            path_collapse_commands: false,
             dedupe_gradients: true,
             remove_ids: true,
             remove_data_attrs: false,
             preserve_ids: Some(vec!["id1".to_string(), "id2".to_string()]),
+            remove_comments: true,
+            preserve_comments: Vec::new(),
+            collapse_groups: true,
+            remove_invisible: false,
+            remove_default_attrs: false,
+            remove_metadata: false,
+            preserve_metadata: false,
+            shapes_to_paths: true,
+            shapes: None,
+            remove_doctype: false,
+            remove_unused_defs: false,
+            remove_xml_proc_inst: false,
+            remove_editor_ns: false,
+            preserve_ns: None,
+            convert_colors: false,
+            color_longhand: false,
+            color_keep_case: false,
+            remove_title_desc: false,
+            preserve_title: false,
+            preserve_desc: false,
+            remove_empty_attrs: false,
+            empty_attrs_allow: None,
+            empty_attrs_deny: None,
+            empty_attrs_geometry: false,
+            normalize_colors: false,
+            clean_namespaces: false,
+            xlink_to_href: false,
+            sort_attrs: false,
+            attrs_order: None,
+            cleanup_numeric_values: false,
+            numeric_decimals: None,
+            numeric_lists: false,
+            optimize_transforms: false,
+            propagate_transforms: false,
+            remove_useless_paint: false,
+            dedupe_paths: false,
+            remove_duplicate_paths: false,
+            merge_paths: false,
+            inline_styles: false,
+            inline_stylesheet: false,
+            presentation_to_style: false,
+            minify_css: false,
+            remove_unused_css: false,
+            normalize_text_whitespace: false,
+            remove_scripts: true,
+            allow_scripts: false,
+            remove_external_refs: true,
+            sanitize: false,
+            collapse_whitespace: false,
+            remove_empty_containers: false,
+            remove_hidden_elems: false,
+            remove_dimensions: false,
+            remove_attrs: Vec::new(),
+            cleanup_ids: false,
+            preserve_id_prefixes: None,
+            prefix_ids: None,
+            prefix_delimiter: None,
+            input_format: None,
+            svgz: false,
+            pretty: false,
+            indent: None,
+            indent_tabs: false,
         };
 
         let config = PluginConfig::from(&args);
@@ -96,6 +306,42 @@ mod tests {
         assert!(config.gradient_deduplicator);
         assert!(config.id_remover.enabled);
         assert!(!config.data_attr_remover);
+        assert!(config.comment_remover.enabled);
+        assert!(config.group_collapser);
+        assert!(!config.invisible_remover);
+        assert!(!config.default_attr_remover);
+        assert!(!config.metadata_remover);
+        assert!(config.shape_converter.enabled);
+        assert!(!config.doctype_remover);
+        assert!(!config.unused_defs_remover);
+        assert!(!config.proc_inst_remover);
+        assert!(!config.editor_ns_remover.enabled);
+        assert!(config.color_converter.is_none());
+        assert!(!config.title_desc_remover.enabled);
+        assert!(!config.empty_attr_remover.enabled);
+        assert!(!config.color_normalizer);
+        assert!(!config.namespace_cleaner.enabled);
+        assert!(!config.attr_sorter.enabled);
+        assert!(config.numeric_cleaner.is_none());
+        assert!(!config.transform_optimizer.enabled);
+        assert!(!config.useless_paint_remover);
+        assert!(!config.path_deduplicator.enabled);
+        assert!(!config.path_merger);
+        assert!(!config.style_inliner);
+        assert!(!config.stylesheet_inliner);
+        assert!(!config.style_converter);
+        assert!(!config.css_minifier);
+        assert!(!config.unused_css_remover);
+        assert!(!config.text_whitespace_normalizer);
+        assert!(config.script_remover);
+        assert!(config.external_refs_remover);
+        assert!(!config.whitespace_collapser);
+        assert!(!config.empty_container_remover);
+        assert!(config.hidden_elems_remover.is_none());
+        assert!(!config.dimensions_remover);
+        assert!(config.attrs_remover.is_empty());
+        assert!(config.id_cleaner.is_none());
+        assert!(config.id_prefixer.is_none());
         assert_eq!(config.id_remover.preserve.len(), 2);
     }
 }
@@ -0,0 +1,219 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes};
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashSet;
+use std::io;
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+
+/// Rewrites attributes with a redundant namespace prefix to their plain
+/// name and drops the declarations that are no longer used.
+///
+/// An attribute such as `svg:fill`, whose prefix is bound to the SVG
+/// namespace, becomes `fill`. With `migrate_xlink`, `xlink:href` becomes
+/// the SVG 2 `href` as well. An attribute is only renamed when the element
+/// doesn't already have the plain name. Afterwards, an `xmlns:` declaration
+/// of the SVG or XLink namespace whose prefix no element or attribute uses
+/// anymore is removed.
+///
+/// Prefixes are collected document-wide in the analysis pass, so a prefix
+/// rebound to another namespace further down the tree isn't supported.
+pub struct CleanNamespacesPlugin {
+    migrate_xlink: bool,
+    /// Prefixes bound to the SVG namespace.
+    svg_prefixes: HashSet<String>,
+    /// Prefixes bound to the XLink namespace.
+    xlink_prefixes: HashSet<String>,
+    /// Prefixes still used once attributes are renamed.
+    used_prefixes: HashSet<String>,
+    attributes_renamed: usize,
+    namespaces_removed: usize,
+}
+
+impl CleanNamespacesPlugin {
+    pub fn new() -> Self {
+        Self {
+            migrate_xlink: false,
+            svg_prefixes: HashSet::new(),
+            xlink_prefixes: HashSet::new(),
+            used_prefixes: HashSet::new(),
+            attributes_renamed: 0,
+            namespaces_removed: 0,
+        }
+    }
+
+    /// Whether to rewrite `xlink:href` to `href`.
+    pub fn with_migrate_xlink(mut self, migrate_xlink: bool) -> Self {
+        self.migrate_xlink = migrate_xlink;
+        self
+    }
+
+    /// The plain name an attribute is renamed to, if any.
+    fn renamed<'a>(&self, key: &'a str, attrs: &[(String, String)]) -> Option<&'a str> {
+        let (prefix, local) = key.split_once(':')?;
+        let redundant = self.svg_prefixes.contains(prefix)
+            || (self.migrate_xlink && local == "href" && self.xlink_prefixes.contains(prefix));
+        (redundant && !attrs.iter().any(|(other, _)| other == local)).then_some(local)
+    }
+
+    /// Whether a declaration binds a cleaned prefix nothing uses anymore.
+    fn is_unused_declaration(&self, key: &str) -> bool {
+        key.strip_prefix("xmlns:").is_some_and(|prefix| {
+            (self.svg_prefixes.contains(prefix) || self.xlink_prefixes.contains(prefix))
+                && !self.used_prefixes.contains(prefix)
+        })
+    }
+
+    fn visit(&mut self, element: &BytesStart) {
+        let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+        if let Some((prefix, _)) = name.split_once(':') {
+            self.used_prefixes.insert(prefix.to_string());
+        }
+
+        let attrs = owned_attributes(element);
+        for (key, _) in &attrs {
+            if key == "xmlns" || key.starts_with("xmlns:") || self.renamed(key, &attrs).is_some() {
+                continue;
+            }
+            if let Some((prefix, _)) = key.split_once(':') {
+                self.used_prefixes.insert(prefix.to_string());
+            }
+        }
+    }
+}
+
+impl Default for CleanNamespacesPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for CleanNamespacesPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.svg_prefixes.clear();
+        self.xlink_prefixes.clear();
+        self.used_prefixes.clear();
+        self.attributes_renamed = 0;
+        self.namespaces_removed = 0;
+        Ok(())
+    }
+
+    fn requires_analysis(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, events: &[Event<'static>]) -> io::Result<()> {
+        let elements = || {
+            events.iter().filter_map(|event| match event {
+                Event::Start(elem) | Event::Empty(elem) => Some(elem),
+                _ => None,
+            })
+        };
+
+        // Bindings first: the attributes that use them may come earlier
+        for element in elements() {
+            for (key, value) in owned_attributes(element) {
+                let Some(prefix) = key.strip_prefix("xmlns:") else { continue };
+                match value.as_str() {
+                    SVG_NAMESPACE => self.svg_prefixes.insert(prefix.to_string()),
+                    XLINK_NAMESPACE => self.xlink_prefixes.insert(prefix.to_string()),
+                    _ => false,
+                };
+            }
+        }
+        for element in elements() {
+            self.visit(element);
+        }
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let attrs = owned_attributes(element);
+        let mut changed = false;
+        let mut cleaned = Vec::with_capacity(attrs.len());
+
+        for (key, value) in &attrs {
+            if self.is_unused_declaration(key) {
+                self.namespaces_removed += 1;
+                changed = true;
+            } else if let Some(local) = self.renamed(key, &attrs) {
+                cleaned.push((local.to_string(), value.clone()));
+                self.attributes_renamed += 1;
+                changed = true;
+            } else {
+                cleaned.push((key.clone(), value.clone()));
+            }
+        }
+
+        if changed {
+            set_attributes(element, &cleaned);
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn name(&self) -> &str {
+        "CleanNamespaces"
+    }
+}
+
+impl PluginStatistics for CleanNamespacesPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Attributes renamed", self.attributes_renamed.to_string()),
+            ("Namespaces removed", self.namespaces_removed.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_svg_prefixes_are_removed() -> io::Result<()> {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:svg="http://www.w3.org/2000/svg"><rect svg:fill="red" svg:width="10"/><circle fill="blue" svg:fill="red"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(CleanNamespacesPlugin::new(), svg)?,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:svg="http://www.w3.org/2000/svg"><rect fill="red" width="10"/><circle fill="blue" svg:fill="red"/></svg>"#
+        );
+
+        let unused = r#"<svg xmlns:svg="http://www.w3.org/2000/svg"><rect svg:fill="red"/></svg>"#;
+        assert_eq!(
+            optimize_with(CleanNamespacesPlugin::new(), unused)?,
+            r#"<svg><rect fill="red"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefixed_elements_keep_the_declaration() -> io::Result<()> {
+        let svg = r#"<svg xmlns:s="http://www.w3.org/2000/svg"><s:rect s:fill="red"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(CleanNamespacesPlugin::new(), svg)?,
+            r#"<svg xmlns:s="http://www.w3.org/2000/svg"><s:rect fill="red"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_xlink_migration_is_optional() -> io::Result<()> {
+        let svg = r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink"><use xlink:href="#a"/></svg>"##;
+        assert_eq!(optimize_with(CleanNamespacesPlugin::new(), svg)?, svg);
+
+        assert_eq!(
+            optimize_with(CleanNamespacesPlugin::new().with_migrate_xlink(true), svg)?,
+            r##"<svg><use href="#a"/></svg>"##
+        );
+
+        let titled = r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink"><a xlink:href="#a" xlink:title="A"/></svg>"##;
+        assert_eq!(
+            optimize_with(CleanNamespacesPlugin::new().with_migrate_xlink(true), titled)?,
+            r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink"><a href="#a" xlink:title="A"/></svg>"##
+        );
+        Ok(())
+    }
+}
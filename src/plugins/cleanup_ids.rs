@@ -0,0 +1,437 @@
+//! Removal and minification of ids.
+//!
+//! `RemoveIDPlugin` drops unreferenced ids while streaming. This plugin
+//! additionally shortens the ids that are kept, which means rewriting every
+//! reference to them. References can come before or after the element they
+//! point to, so it runs on the document tree: one pass collects ids and
+//! references, a second renames and rewrites.
+
+use crate::diagnostics::{Diagnostic, DiagnosticsCollector};
+use crate::plugins::traits::{PluginStatistics, TreePlugin};
+use crate::plugins::util::{rewrite_url_references, url_references, XLinkPrefixes};
+use crate::tree::{Document, Element, Node};
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+/// Attributes holding SMIL timing values such as `begin="fade.end+1s"`.
+pub(crate) const TIMING_ATTRIBUTES: &[&str] = &["begin", "end"];
+
+/// Attributes holding a space-separated list of ids.
+pub(crate) const ID_LIST_ATTRIBUTES: &[&str] = &["aria-labelledby", "aria-describedby"];
+
+/// Characters of minified ids, in the order they are handed out.
+const ID_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Removes unused ids and renames the others to the shortest free names.
+///
+/// References are `#id` in `href` and in `xlink:href` under any prefix
+/// bound to the XLink namespace, `url(#id)` in any attribute, `id.event` in
+/// animation `begin` and `end` times, and the id lists of `aria-labelledby`
+/// and `aria-describedby`. Used ids become `a`, `b`, ..., `Z`, `aa`, ... in
+/// document order, and every reference is rewritten to match.
+///
+/// Ids starting with one of the preserved prefixes are neither removed nor
+/// renamed. References to ids that don't exist are reported as warnings.
+/// Documents with a `<style>` or `<script>` element are left
+/// alone, since selectors and code can name ids in ways that can't be
+/// rewritten safely.
+pub struct CleanupIdsPlugin {
+    remove: bool,
+    minify: bool,
+    preserve_prefixes: Vec<String>,
+    /// Ids referenced anywhere in the document.
+    references: HashSet<String>,
+    xlink: XLinkPrefixes,
+    /// Each referenced id with the element that first refers to it, in
+    /// document order.
+    first_references: Vec<(String, String)>,
+    /// New names of the minified ids.
+    renames: HashMap<String, String>,
+    ids_removed: usize,
+    ids_minified: usize,
+    diagnostics: DiagnosticsCollector,
+}
+
+impl CleanupIdsPlugin {
+    pub fn new() -> Self {
+        Self {
+            remove: true,
+            minify: true,
+            preserve_prefixes: Vec::new(),
+            references: HashSet::new(),
+            xlink: XLinkPrefixes::default(),
+            first_references: Vec::new(),
+            renames: HashMap::new(),
+            ids_removed: 0,
+            ids_minified: 0,
+            diagnostics: DiagnosticsCollector::new(),
+        }
+    }
+
+    /// Whether to remove ids nothing refers to.
+    pub fn with_remove(mut self, enabled: bool) -> Self {
+        self.remove = enabled;
+        self
+    }
+
+    /// Whether to rename referenced ids to shorter ones.
+    pub fn with_minify(mut self, enabled: bool) -> Self {
+        self.minify = enabled;
+        self
+    }
+
+    /// Keep ids starting with any of `prefixes` exactly as they are.
+    pub fn with_preserve_prefixes(mut self, prefixes: Vec<String>) -> Self {
+        self.preserve_prefixes = prefixes;
+        self
+    }
+
+    fn is_preserved(&self, id: &str) -> bool {
+        self.preserve_prefixes.iter().any(|prefix| id.starts_with(prefix.as_str()))
+    }
+
+    fn collect_references(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            let Node::Element(element) = node else { continue };
+            let mut ids = Vec::new();
+            for (key, value) in &element.attrs {
+                if self.xlink.is_link(key) {
+                    ids.extend(value.strip_prefix('#'));
+                } else if TIMING_ATTRIBUTES.contains(&key.as_str()) {
+                    ids.extend(timing_references(value).map(|(_, id)| id));
+                } else if ID_LIST_ATTRIBUTES.contains(&key.as_str()) {
+                    ids.extend(value.split_whitespace());
+                } else {
+                    ids.extend(url_references(value));
+                }
+            }
+            for id in ids {
+                if self.references.insert(id.to_string()) {
+                    self.first_references.push((id.to_string(), element.name.clone()));
+                }
+            }
+            self.collect_references(&element.children);
+        }
+    }
+
+    /// Pick new names for the referenced ids, in document order, skipping
+    /// names taken by ids that keep theirs.
+    fn assign_names(&mut self, nodes: &[Node]) {
+        let mut ids = Vec::new();
+        collect_ids(nodes, &mut ids);
+
+        let mut taken: HashSet<&str> = HashSet::new();
+        let mut renamed = Vec::new();
+        for id in ids {
+            if !self.references.contains(id) || self.is_preserved(id) {
+                // Kept ids are either preserved or removed below
+                if self.is_preserved(id) || !self.remove {
+                    taken.insert(id);
+                }
+            } else if !renamed.contains(&id) {
+                renamed.push(id);
+            }
+        }
+
+        let mut counter = 0;
+        for id in renamed {
+            let name = loop {
+                let name = short_name(counter);
+                counter += 1;
+                if !taken.contains(name.as_str()) {
+                    break name;
+                }
+            };
+            if name != id {
+                self.renames.insert(id.to_string(), name);
+            }
+        }
+    }
+
+    fn rewrite(&mut self, nodes: &mut [Node]) {
+        for node in nodes {
+            let Node::Element(element) = node else { continue };
+            self.rewrite_element(element);
+            self.rewrite(&mut element.children);
+        }
+    }
+
+    fn rewrite_element(&mut self, element: &mut Element) {
+        let renames = &self.renames;
+        let rename = |id: &str| renames.get(id).cloned();
+
+        let mut removed = false;
+        for (key, value) in &mut element.attrs {
+            let rewritten = if key == "id" {
+                if self.remove && !self.references.contains(value.as_str()) && !self.is_preserved(value) {
+                    removed = true;
+                    None
+                } else {
+                    let new = rename(value);
+                    self.ids_minified += new.is_some() as usize;
+                    new
+                }
+            } else if self.xlink.is_link(key) {
+                value.strip_prefix('#').and_then(rename).map(|id| format!("#{}", id))
+            } else if TIMING_ATTRIBUTES.contains(&key.as_str()) {
+                rewrite_timing(value, rename)
+            } else if ID_LIST_ATTRIBUTES.contains(&key.as_str()) {
+                let ids: Vec<String> = value
+                    .split_whitespace()
+                    .map(|id| rename(id).unwrap_or_else(|| id.to_string()))
+                    .collect();
+                Some(ids.join(" ")).filter(|ids| ids != value)
+            } else {
+                rewrite_url_references(value, rename)
+            };
+            if let Some(rewritten) = rewritten {
+                *value = rewritten;
+            }
+        }
+
+        if removed {
+            element.attrs.retain(|(key, _)| key != "id");
+            self.ids_removed += 1;
+        }
+    }
+}
+
+/// The ids of `nodes` and their descendants, in document order.
+fn collect_ids<'a>(nodes: &'a [Node], ids: &mut Vec<&'a str>) {
+    for node in nodes {
+        let Node::Element(element) = node else { continue };
+        if let Some(id) = element.attr("id") {
+            ids.push(id);
+        }
+        collect_ids(&element.children, ids);
+    }
+}
+
+/// The `n`th shortest id: `a` to `Z`, then `aa`, `ab`, ...
+fn short_name(mut n: usize) -> String {
+    let base = ID_CHARS.len();
+    let mut name = Vec::new();
+    loop {
+        name.push(ID_CHARS[n % base]);
+        if n < base {
+            break;
+        }
+        n = n / base - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap()
+}
+
+/// The ids referenced by a `begin` or `end` value, as `(offset, id)`.
+///
+/// Each `;`-separated entry may start with `id.` followed by an event or
+/// `begin`/`end`. Offsets like `1.5s` aren't references, since an id can't
+/// start with a digit.
+pub(crate) fn timing_references(value: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    value.split(';').filter_map(move |entry| {
+        let start = offset + (entry.len() - entry.trim_start().len());
+        offset += entry.len() + 1;
+        let (id, _) = entry.trim_start().split_once('.')?;
+        let first = id.chars().next()?;
+        let valid = !first.is_ascii_digit()
+            && !matches!(first, '+' | '-')
+            && !id.contains(|c: char| c.is_whitespace() || c == '(');
+        valid.then_some((start, id))
+    })
+}
+
+/// Rewrite the ids referenced by a timing value; `None` if none changed.
+pub(crate) fn rewrite_timing<F>(value: &str, rename: F) -> Option<String>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut result = String::with_capacity(value.len());
+    let mut last = 0;
+    for (start, id) in timing_references(value) {
+        if let Some(new_id) = rename(id) {
+            result.push_str(&value[last..start]);
+            result.push_str(&new_id);
+            last = start + id.len();
+        }
+    }
+    if last == 0 {
+        return None;
+    }
+    result.push_str(&value[last..]);
+    Some(result)
+}
+
+fn has_style_or_script(nodes: &[Node]) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Element(element) => {
+            element.name == "style" || element.name == "script" || has_style_or_script(&element.children)
+        }
+        Node::ProcessingInstruction(pi) => pi.starts_with("xml-stylesheet"),
+        _ => false,
+    })
+}
+
+impl Default for CleanupIdsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreePlugin for CleanupIdsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.references.clear();
+        self.xlink.clear();
+        self.first_references.clear();
+        self.renames.clear();
+        self.ids_removed = 0;
+        self.ids_minified = 0;
+        self.diagnostics.clear();
+        Ok(())
+    }
+
+    fn process_tree(&mut self, document: &mut Document) -> io::Result<()> {
+        if has_style_or_script(&document.children) {
+            return Ok(());
+        }
+        self.xlink = XLinkPrefixes::from_nodes(&document.children);
+        self.collect_references(&document.children);
+
+        let mut ids = Vec::new();
+        collect_ids(&document.children, &mut ids);
+        let ids: HashSet<&str> = ids.into_iter().collect();
+        for (id, element) in &self.first_references {
+            if !ids.contains(id.as_str()) {
+                self.diagnostics
+                    .warn(format!("reference to missing id '{}'", id), Some(element));
+            }
+        }
+
+        if self.minify {
+            self.assign_names(&document.children);
+        }
+        self.rewrite(&mut document.children);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "CleanupIds"
+    }
+}
+
+impl PluginStatistics for CleanupIdsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Ids removed", self.ids_removed.to_string()),
+            ("Ids minified", self.ids_minified.to_string()),
+        ]
+    }
+
+    fn diagnostics(&self) -> &[Diagnostic] {
+        self.diagnostics.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_tree_with;
+    use crate::processor::SVGProcessor;
+
+    #[test]
+    fn test_gradient_and_filter_references_are_rewritten() -> io::Result<()> {
+        let svg = r##"<svg><defs><linearGradient id="brandGradient"/><filter id="softShadow"/></defs><rect id="unused" fill="url(#brandGradient)" filter="url(#softShadow)"/><use xlink:href="#brandGradient"/></svg>"##;
+
+        assert_eq!(
+            optimize_tree_with(CleanupIdsPlugin::new(), svg)?,
+            r##"<svg><defs><linearGradient id="a"/><filter id="b"/></defs><rect fill="url(#a)" filter="url(#b)"/><use xlink:href="#a"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_aliased_xlink_references_are_rewritten() -> io::Result<()> {
+        let svg = r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><path id="shape"/><use x:href="#shape"/></svg>"##;
+
+        assert_eq!(
+            optimize_tree_with(CleanupIdsPlugin::new(), svg)?,
+            r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><path id="a"/><use x:href="#a"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_only_remove_or_only_minify() -> io::Result<()> {
+        let svg = r##"<svg><path id="shape"/><g id="layer1"/><use href="#shape"/></svg>"##;
+
+        assert_eq!(
+            optimize_tree_with(CleanupIdsPlugin::new().with_minify(false), svg)?,
+            r##"<svg><path id="shape"/><g/><use href="#shape"/></svg>"##
+        );
+        assert_eq!(
+            optimize_tree_with(CleanupIdsPlugin::new().with_remove(false), svg)?,
+            r##"<svg><path id="a"/><g id="layer1"/><use href="#a"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_preserved_prefixes_keep_their_names() -> io::Result<()> {
+        let svg = r##"<svg><g id="a"/><path id="icon-star"/><path id="p"/><use href="#p"/><use href="#a"/></svg>"##;
+
+        assert_eq!(
+            optimize_tree_with(CleanupIdsPlugin::new().with_preserve_prefixes(vec!["icon-".to_string(), "a".to_string()]), svg)?,
+            r##"<svg><g id="a"/><path id="icon-star"/><path id="b"/><use href="#b"/><use href="#a"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_animation_and_aria_references() -> io::Result<()> {
+        let svg = r##"<svg aria-labelledby="title"><title id="title">Logo</title><animate id="fadeIn" begin="0s"/><animate begin="fadeIn.end+0.5s; 2s" end="fadeIn.begin"/></svg>"##;
+
+        assert_eq!(
+            optimize_tree_with(CleanupIdsPlugin::new(), svg)?,
+            r##"<svg aria-labelledby="a"><title id="a">Logo</title><animate id="b" begin="0s"/><animate begin="b.end+0.5s; 2s" end="b.begin"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_documents_with_style_are_kept() -> io::Result<()> {
+        let svg = r##"<svg><style>#logo{fill:red}</style><path id="logo"/></svg>"##;
+
+        assert_eq!(optimize_tree_with(CleanupIdsPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_references_are_reported() -> io::Result<()> {
+        let svg = r##"<svg><rect id="shape" fill="url(#gone)"/><use href="#shape"/><use href="#gone"/><animate begin="lost.end"/></svg>"##;
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_tree_plugin(CleanupIdsPlugin::new());
+
+        assert_eq!(
+            processor.optimize_str(svg)?,
+            r##"<svg><rect id="a" fill="url(#gone)"/><use href="#a"/><use href="#gone"/><animate begin="lost.end"/></svg>"##
+        );
+        let diagnostics: Vec<_> = processor.diagnostics().into_iter().map(|(plugin, d)| format!("{} {}", plugin, d)).collect();
+        assert_eq!(
+            diagnostics,
+            [
+                "CleanupIds warning: reference to missing id 'gone' (<rect>)",
+                "CleanupIds warning: reference to missing id 'lost' (<animate>)",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_short_names() {
+        assert_eq!(short_name(0), "a");
+        assert_eq!(short_name(51), "Z");
+        assert_eq!(short_name(52), "aa");
+        assert_eq!(short_name(53), "ab");
+        assert_eq!(short_name(52 + 52 * 52), "aaa");
+    }
+}
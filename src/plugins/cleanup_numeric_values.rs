@@ -0,0 +1,150 @@
+use crate::plugins::path::{format_number, strip_leading_zero};
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes};
+use quick_xml::events::BytesStart;
+use std::io;
+
+/// Attributes holding a single number, optionally with a unit.
+const NUMERIC_ATTRIBUTES: &[&str] = &[
+    "x", "y", "x1", "y1", "x2", "y2", "cx", "cy", "fx", "fy", "r", "fr", "rx", "ry",
+    "width", "height", "offset", "opacity", "fill-opacity", "stroke-opacity",
+    "stop-opacity", "stroke-width", "stroke-miterlimit", "stroke-dashoffset",
+    "font-size", "pathLength",
+];
+
+/// Attributes holding a list of numbers, only cleaned when lists are enabled.
+const LIST_ATTRIBUTES: &[&str] = &["viewBox", "points", "stroke-dasharray"];
+
+/// Rounds and shortens the numbers of non-path attributes.
+///
+/// `width="100.0000"` becomes `width="100"`, `opacity="0.50"` becomes
+/// `opacity=".5"`. A `px` unit is dropped, since user units are pixels;
+/// other units are kept. Values that aren't plain numbers, such as `auto`
+/// or `inherit`, are left alone. Lists like `viewBox` and `points` are only
+/// touched when lists are enabled.
+pub struct CleanupNumericValuesPlugin {
+    decimal_places: usize,
+    lists: bool,
+    values_cleaned: usize,
+    chars_saved: usize,
+}
+
+impl CleanupNumericValuesPlugin {
+    pub fn new(decimal_places: usize) -> Self {
+        Self {
+            decimal_places,
+            lists: false,
+            values_cleaned: 0,
+            chars_saved: 0,
+        }
+    }
+
+    /// Whether to clean number lists such as `viewBox` and `points`.
+    pub fn with_lists(mut self, lists: bool) -> Self {
+        self.lists = lists;
+        self
+    }
+
+    /// The shortened form of a single number with an optional unit.
+    fn clean_number(&self, value: &str) -> Option<String> {
+        let value = value.trim();
+        let split = value
+            .rfind(|c: char| !(c.is_ascii_alphabetic() || c == '%'))
+            .map_or(0, |index| index + 1);
+        let (number, unit) = value.split_at(split);
+        let number = number.parse::<f64>().ok().filter(|number| number.is_finite())?;
+        let unit = if unit == "px" { "" } else { unit };
+
+        Some(format!("{}{}", strip_leading_zero(format_number(number, self.decimal_places)), unit))
+    }
+
+    /// The shortened form of a list of numbers, separated by single spaces.
+    fn clean_list(&self, value: &str) -> Option<String> {
+        let cleaned: Option<Vec<String>> = value
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|part| !part.is_empty())
+            .map(|part| self.clean_number(part))
+            .collect();
+        Some(cleaned?.join(" "))
+    }
+}
+
+impl SVGPlugin for CleanupNumericValuesPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.values_cleaned = 0;
+        self.chars_saved = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let mut attrs = owned_attributes(element);
+        let mut changed = false;
+
+        for (key, value) in &mut attrs {
+            let cleaned = if NUMERIC_ATTRIBUTES.contains(&key.as_str()) {
+                self.clean_number(value)
+            } else if self.lists && LIST_ATTRIBUTES.contains(&key.as_str()) {
+                self.clean_list(value)
+            } else {
+                None
+            };
+
+            if let Some(cleaned) = cleaned.filter(|cleaned| cleaned.len() < value.len()) {
+                self.values_cleaned += 1;
+                self.chars_saved += value.len() - cleaned.len();
+                *value = cleaned;
+                changed = true;
+            }
+        }
+
+        if changed {
+            set_attributes(element, &attrs);
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn name(&self) -> &str {
+        "CleanupNumericValues"
+    }
+}
+
+impl PluginStatistics for CleanupNumericValuesPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Values cleaned", self.values_cleaned.to_string()),
+            ("Characters saved", self.chars_saved.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+    use crate::processor::SVGProcessor;
+
+    #[test]
+    fn test_numbers_are_shortened() -> io::Result<()> {
+        let svg = r#"<svg width="100.0000px" height="50%"><rect x="0.5000" y="-0.25" rx="1e1" opacity="1.00" stroke-width="1.23456"/><circle r="2.0em" cx="auto"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(CleanupNumericValuesPlugin::new(3), svg)?,
+            r#"<svg width="100" height="50%"><rect x=".5" y="-.25" rx="10" opacity="1" stroke-width="1.235"/><circle r="2em" cx="auto"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lists_need_to_be_enabled() -> io::Result<()> {
+        let svg = r#"<svg viewBox="0.0, 0.0, 24.000, 24.000"><polygon points="0.5,1.0 2.26,3"/></svg>"#;
+        assert_eq!(optimize_with(CleanupNumericValuesPlugin::new(3), svg)?, svg);
+
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(CleanupNumericValuesPlugin::new(1).with_lists(true));
+        let output = processor.optimize_str(svg)?;
+
+        assert_eq!(output, r#"<svg viewBox="0 0 24 24"><polygon points=".5 1 2.3 3"/></svg>"#);
+        assert_eq!(processor.plugin_statistics()[0].1[0].1, "2");
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+//! Removes empty `<g>` elements and merges redundant ones into their child.
+//!
+//! Whether a group is redundant depends on its content and on the rest of
+//! the document (an `id` may be referenced anywhere), so the plugin runs on
+//! the document tree. Groups are handled innermost first: a group whose
+//! only child was itself a collapsed group can be collapsed in turn.
+
+use crate::plugins::remove_default_attrs::INHERITED_PROPERTIES;
+use crate::plugins::traits::{PluginStatistics, TreePlugin};
+use crate::plugins::util::{referenced_ids, style_declarations};
+use crate::tree::{Document, Element, Node};
+use std::collections::HashSet;
+use std::io;
+
+/// Group attributes that apply to the group as a whole, in its coordinate
+/// system, and can't be moved onto a child that may be transformed.
+const GROUP_EFFECTS: &[&str] = &["clip-path", "mask", "filter"];
+
+/// Children that describe or animate their parent and must stay inside
+/// the group.
+const PARENT_BOUND_CHILDREN: &[&str] = &[
+    "animate",
+    "animateColor",
+    "animateMotion",
+    "animateTransform",
+    "desc",
+    "set",
+    "title",
+];
+
+/// Collapses `<g>` elements that add nothing to the document.
+///
+/// - A group without child elements or text is removed, unless it has an
+///   `id` that other content may refer to.
+/// - A group with a single child element and no text is replaced by the
+///   child, which takes over the group's attributes:
+///   - transforms are composed, the group's applying first;
+///   - inherited properties the child sets itself are dropped, since the
+///     child's value wins;
+///   - an `id` is moved if nothing refers to it and the child has none.
+///
+///   Groups with a `class` or `style`, a clip path, mask or filter, a
+///   referenced `id`, or any other attribute the child also sets, as an
+///   attribute or in its `style`, are kept.
+pub struct CollapseGroupsPlugin {
+    /// Ids referenced anywhere in the document.
+    references: HashSet<String>,
+    groups_removed: usize,
+    groups_collapsed: usize,
+}
+
+impl CollapseGroupsPlugin {
+    pub fn new() -> Self {
+        Self {
+            references: HashSet::new(),
+            groups_removed: 0,
+            groups_collapsed: 0,
+        }
+    }
+
+    /// Collapse the groups among `nodes` and their descendants.
+    fn collapse(&mut self, nodes: &mut Vec<Node>) {
+        let mut result = Vec::with_capacity(nodes.len());
+
+        for mut node in nodes.drain(..) {
+            if let Node::Element(element) = &mut node {
+                self.collapse(&mut element.children);
+
+                if element.name == "g" {
+                    if is_empty(element) && element.attr("id").is_none() {
+                        self.groups_removed += 1;
+                        continue;
+                    }
+                    if let Some(child) = self.merge_into_child(element) {
+                        self.groups_collapsed += 1;
+                        result.push(Node::Element(child));
+                        continue;
+                    }
+                }
+            }
+            result.push(node);
+        }
+
+        *nodes = result;
+    }
+
+    /// The group's only child with the group's attributes moved onto it, or
+    /// `None` if the group can't be collapsed.
+    fn merge_into_child(&self, group: &Element) -> Option<Element> {
+        let mut children = group.children.iter().filter(|node| !is_blank(node));
+        let (Some(Node::Element(child)), None) = (children.next(), children.next()) else {
+            return None;
+        };
+        if PARENT_BOUND_CHILDREN.contains(&child.name.as_str()) {
+            return None;
+        }
+
+        let mut merged = child.clone();
+        let mut moved = Vec::new();
+        for (key, value) in &group.attrs {
+            let child_value = child.attr(key).or_else(|| {
+                child
+                    .attr("style")
+                    .into_iter()
+                    .flat_map(style_declarations)
+                    .find(|(property, _)| property == key)
+                    .map(|(_, value)| value)
+            });
+            match key.as_str() {
+                // A moved style would override the child's own attributes
+                "class" | "style" => return None,
+                key if GROUP_EFFECTS.contains(&key) => return None,
+                "transform" => match child_value {
+                    Some(child_transform) => {
+                        merged.set_attr("transform", format!("{} {}", value, child_transform));
+                    }
+                    None => moved.push((key.clone(), value.clone())),
+                },
+                "id" if self.references.contains(value) || child_value.is_some() => return None,
+                key if INHERITED_PROPERTIES.contains(&key) && child_value.is_some() => {}
+                _ if child_value.is_some() => return None,
+                _ => moved.push((key.clone(), value.clone())),
+            }
+        }
+
+        moved.append(&mut merged.attrs);
+        merged.attrs = moved;
+        Some(merged)
+    }
+}
+
+/// Whether a node is whitespace-only text.
+fn is_blank(node: &Node) -> bool {
+    matches!(node, Node::Text(text) if text.trim().is_empty())
+}
+
+/// Whether an element has no content besides whitespace and comments.
+fn is_empty(element: &Element) -> bool {
+    element
+        .children
+        .iter()
+        .all(|node| is_blank(node) || matches!(node, Node::Comment(_)))
+}
+
+impl Default for CollapseGroupsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreePlugin for CollapseGroupsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.references.clear();
+        self.groups_removed = 0;
+        self.groups_collapsed = 0;
+        Ok(())
+    }
+
+    fn process_tree(&mut self, document: &mut Document) -> io::Result<()> {
+        self.references = referenced_ids(&document.children);
+        self.collapse(&mut document.children);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "CollapseGroups"
+    }
+}
+
+impl PluginStatistics for CollapseGroupsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Empty groups removed", self.groups_removed.to_string()),
+            ("Groups collapsed", self.groups_collapsed.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_tree_with;
+
+    #[test]
+    fn test_empty_groups_are_removed() -> io::Result<()> {
+        let output = optimize_tree_with(CollapseGroupsPlugin::new(), r#"<svg><g></g><g fill="red"/><g id="target"/><rect/></svg>"#)?;
+
+        assert_eq!(output, r#"<svg><g id="target"/><rect/></svg>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_child_groups_are_collapsed() -> io::Result<()> {
+        let output = optimize_tree_with(CollapseGroupsPlugin::new(), 
+            r#"<svg><g><g fill="red"><path fill="red" d="M0 0"/></g></g><g fill="blue" stroke="red">
+  <path fill="green" d="M1 1"/>
+</g></svg>"#,
+        )?;
+
+        assert_eq!(
+            output,
+            r#"<svg><path fill="red" d="M0 0"/><path stroke="red" fill="green" d="M1 1"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_groups_with_content_are_kept() -> io::Result<()> {
+        let svg = r#"<svg><g><rect/><circle/></g><g><text>Hi</text></g><a><g>label</g></a></svg>"#;
+        let output = optimize_tree_with(CollapseGroupsPlugin::new(), svg)?;
+
+        assert_eq!(output, r#"<svg><g><rect/><circle/></g><text>Hi</text><a><g>label</g></a></svg>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_emptied_by_removal_is_collapsed() -> io::Result<()> {
+        let output = optimize_tree_with(CollapseGroupsPlugin::new(), "<svg><g><g></g><g><rect/></g></g></svg>")?;
+
+        assert_eq!(output, "<svg><rect/></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_transforms_are_composed() -> io::Result<()> {
+        let output = optimize_tree_with(CollapseGroupsPlugin::new(), 
+            r#"<svg><g transform="translate(10 20)"><g transform="rotate(45)"><rect transform="scale(2)"/></g></g></svg>"#,
+        )?;
+
+        assert_eq!(
+            output,
+            r#"<svg><rect transform="translate(10 20) rotate(45) scale(2)"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_conflicting_groups_are_kept() -> io::Result<()> {
+        let svg = r##"<svg><g class="a"><rect/></g><g clip-path="url(#c)"><rect transform="scale(2)"/></g><g id="ref"><rect/></g><use href="#ref"/><g opacity=".5"><rect opacity=".5"/></g><g id="own"><rect id="child"/></g><g><title>Icon</title></g></svg>"##;
+
+        assert_eq!(optimize_tree_with(CollapseGroupsPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_styled_groups_are_kept() -> io::Result<()> {
+        let svg = r#"<svg><g style="fill:red"><rect fill="blue"/></g><g style="opacity:.5"><rect opacity=".5"/></g><g style="filter:url(#f)"><rect/></g><g opacity=".5"><rect style="opacity:.5"/></g></svg>"#;
+
+        assert_eq!(optimize_tree_with(CollapseGroupsPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unreferenced_id_moves_to_child() -> io::Result<()> {
+        let output = optimize_tree_with(CollapseGroupsPlugin::new(), r#"<svg><g id="layer1"><rect/></g></svg>"#)?;
+
+        assert_eq!(output, r#"<svg><rect id="layer1"/></svg>"#);
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use quick_xml::events::{BytesEnd, BytesStart, BytesText};
+use std::io;
+
+/// Elements whose text content is significant, whitespace included.
+const TEXT_ELEMENTS: &[&[u8]] = &[b"text", b"tspan", b"textPath", b"style", b"script", b"pre"];
+
+/// Removes whitespace-only text between elements.
+///
+/// Indentation and line breaks between tags are never rendered, but editors
+/// write plenty of them. Inside `<text>`, `<tspan>`, `<textPath>`, `<style>`,
+/// `<script>` and `<pre>`, and below `xml:space="preserve"`, whitespace can
+/// matter and is kept.
+pub struct CollapseWhitespacePlugin {
+    /// For each open element, whether whitespace inside it is kept.
+    preserve: Vec<bool>,
+    text_nodes_removed: usize,
+    bytes_removed: usize,
+}
+
+impl CollapseWhitespacePlugin {
+    pub fn new() -> Self {
+        Self {
+            preserve: Vec::new(),
+            text_nodes_removed: 0,
+            bytes_removed: 0,
+        }
+    }
+
+    fn preserves_whitespace(element: &BytesStart) -> bool {
+        TEXT_ELEMENTS.contains(&element.local_name().as_ref())
+            || element
+                .try_get_attribute("xml:space")
+                .ok()
+                .flatten()
+                .is_some_and(|attr| attr.value.as_ref() == b"preserve")
+    }
+}
+
+impl Default for CollapseWhitespacePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for CollapseWhitespacePlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.preserve.clear();
+        self.text_nodes_removed = 0;
+        self.bytes_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let inherited = self.preserve.last().copied().unwrap_or(false);
+        self.preserve.push(inherited || Self::preserves_whitespace(element));
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_end<'a>(&mut self, end: BytesEnd<'a>) -> io::Result<Option<BytesEnd<'a>>> {
+        self.preserve.pop();
+        Ok(Some(end))
+    }
+
+    fn process_text<'a>(&mut self, text: BytesText<'a>) -> io::Result<Option<BytesText<'a>>> {
+        let preserve = self.preserve.last().copied().unwrap_or(false);
+        if preserve || !text.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Some(text));
+        }
+
+        self.text_nodes_removed += 1;
+        self.bytes_removed += text.len();
+        Ok(None)
+    }
+
+    fn name(&self) -> &str {
+        "CollapseWhitespace"
+    }
+}
+
+impl PluginStatistics for CollapseWhitespacePlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Whitespace nodes removed", self.text_nodes_removed.to_string()),
+            ("Whitespace bytes removed", self.bytes_removed.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_whitespace_between_elements_is_removed() -> io::Result<()> {
+        let svg = "<svg>\n  <g>\n    <rect/>\n\t<circle r=\"1\"/>\n  </g>\n</svg>\n";
+
+        assert_eq!(optimize_with(CollapseWhitespacePlugin::new(), svg)?, "<svg><g><rect/><circle r=\"1\"/></g></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_significant_text_is_preserved() -> io::Result<()> {
+        let svg = "<svg>\n  <text> a <tspan>b</tspan> <tspan> </tspan></text>\n  \
+                   <style>\n  .a { fill: red }\n</style>\n  \
+                   <g xml:space=\"preserve\">\n    <rect/>\n  </g>\n  <desc>  note  </desc>\n</svg>";
+
+        assert_eq!(
+            optimize_with(CollapseWhitespacePlugin::new(), svg)?,
+            "<svg><text> a <tspan>b</tspan> <tspan> </tspan></text>\
+             <style>\n  .a { fill: red }\n</style>\
+             <g xml:space=\"preserve\">\n    <rect/>\n  </g><desc>  note  </desc></svg>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_statistics() -> io::Result<()> {
+        let mut plugin = CollapseWhitespacePlugin::new();
+        plugin.process_element(&mut BytesStart::new("svg"))?;
+
+        assert!(plugin.process_text(BytesText::new("\n  "))?.is_none());
+        assert!(plugin.process_text(BytesText::new(" x "))?.is_some());
+        assert_eq!(plugin.get_statistics(), [
+            ("Whitespace nodes removed", "1".to_string()),
+            ("Whitespace bytes removed", "3".to_string()),
+        ]);
+        Ok(())
+    }
+}
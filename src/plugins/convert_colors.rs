@@ -0,0 +1,489 @@
+use crate::diagnostics::{Diagnostic, DiagnosticsCollector};
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes};
+use quick_xml::events::BytesStart;
+use std::io;
+
+/// Attributes whose value is a color.
+const COLOR_ATTRIBUTES: &[&str] = &["fill", "stroke", "stop-color", "color"];
+
+/// The SVG 1.1 color keywords.
+const NAMED_COLORS: &[(&str, [u8; 3])] = &[
+    ("aliceblue", [240, 248, 255]),
+    ("antiquewhite", [250, 235, 215]),
+    ("aqua", [0, 255, 255]),
+    ("aquamarine", [127, 255, 212]),
+    ("azure", [240, 255, 255]),
+    ("beige", [245, 245, 220]),
+    ("bisque", [255, 228, 196]),
+    ("black", [0, 0, 0]),
+    ("blanchedalmond", [255, 235, 205]),
+    ("blue", [0, 0, 255]),
+    ("blueviolet", [138, 43, 226]),
+    ("brown", [165, 42, 42]),
+    ("burlywood", [222, 184, 135]),
+    ("cadetblue", [95, 158, 160]),
+    ("chartreuse", [127, 255, 0]),
+    ("chocolate", [210, 105, 30]),
+    ("coral", [255, 127, 80]),
+    ("cornflowerblue", [100, 149, 237]),
+    ("cornsilk", [255, 248, 220]),
+    ("crimson", [220, 20, 60]),
+    ("cyan", [0, 255, 255]),
+    ("darkblue", [0, 0, 139]),
+    ("darkcyan", [0, 139, 139]),
+    ("darkgoldenrod", [184, 134, 11]),
+    ("darkgray", [169, 169, 169]),
+    ("darkgreen", [0, 100, 0]),
+    ("darkgrey", [169, 169, 169]),
+    ("darkkhaki", [189, 183, 107]),
+    ("darkmagenta", [139, 0, 139]),
+    ("darkolivegreen", [85, 107, 47]),
+    ("darkorange", [255, 140, 0]),
+    ("darkorchid", [153, 50, 204]),
+    ("darkred", [139, 0, 0]),
+    ("darksalmon", [233, 150, 122]),
+    ("darkseagreen", [143, 188, 143]),
+    ("darkslateblue", [72, 61, 139]),
+    ("darkslategray", [47, 79, 79]),
+    ("darkslategrey", [47, 79, 79]),
+    ("darkturquoise", [0, 206, 209]),
+    ("darkviolet", [148, 0, 211]),
+    ("deeppink", [255, 20, 147]),
+    ("deepskyblue", [0, 191, 255]),
+    ("dimgray", [105, 105, 105]),
+    ("dimgrey", [105, 105, 105]),
+    ("dodgerblue", [30, 144, 255]),
+    ("firebrick", [178, 34, 34]),
+    ("floralwhite", [255, 250, 240]),
+    ("forestgreen", [34, 139, 34]),
+    ("fuchsia", [255, 0, 255]),
+    ("gainsboro", [220, 220, 220]),
+    ("ghostwhite", [248, 248, 255]),
+    ("gold", [255, 215, 0]),
+    ("goldenrod", [218, 165, 32]),
+    ("gray", [128, 128, 128]),
+    ("grey", [128, 128, 128]),
+    ("green", [0, 128, 0]),
+    ("greenyellow", [173, 255, 47]),
+    ("honeydew", [240, 255, 240]),
+    ("hotpink", [255, 105, 180]),
+    ("indianred", [205, 92, 92]),
+    ("indigo", [75, 0, 130]),
+    ("ivory", [255, 255, 240]),
+    ("khaki", [240, 230, 140]),
+    ("lavender", [230, 230, 250]),
+    ("lavenderblush", [255, 240, 245]),
+    ("lawngreen", [124, 252, 0]),
+    ("lemonchiffon", [255, 250, 205]),
+    ("lightblue", [173, 216, 230]),
+    ("lightcoral", [240, 128, 128]),
+    ("lightcyan", [224, 255, 255]),
+    ("lightgoldenrodyellow", [250, 250, 210]),
+    ("lightgray", [211, 211, 211]),
+    ("lightgreen", [144, 238, 144]),
+    ("lightgrey", [211, 211, 211]),
+    ("lightpink", [255, 182, 193]),
+    ("lightsalmon", [255, 160, 122]),
+    ("lightseagreen", [32, 178, 170]),
+    ("lightskyblue", [135, 206, 250]),
+    ("lightslategray", [119, 136, 153]),
+    ("lightslategrey", [119, 136, 153]),
+    ("lightsteelblue", [176, 196, 222]),
+    ("lightyellow", [255, 255, 224]),
+    ("lime", [0, 255, 0]),
+    ("limegreen", [50, 205, 50]),
+    ("linen", [250, 240, 230]),
+    ("magenta", [255, 0, 255]),
+    ("maroon", [128, 0, 0]),
+    ("mediumaquamarine", [102, 205, 170]),
+    ("mediumblue", [0, 0, 205]),
+    ("mediumorchid", [186, 85, 211]),
+    ("mediumpurple", [147, 112, 219]),
+    ("mediumseagreen", [60, 179, 113]),
+    ("mediumslateblue", [123, 104, 238]),
+    ("mediumspringgreen", [0, 250, 154]),
+    ("mediumturquoise", [72, 209, 204]),
+    ("mediumvioletred", [199, 21, 133]),
+    ("midnightblue", [25, 25, 112]),
+    ("mintcream", [245, 255, 250]),
+    ("mistyrose", [255, 228, 225]),
+    ("moccasin", [255, 228, 181]),
+    ("navajowhite", [255, 222, 173]),
+    ("navy", [0, 0, 128]),
+    ("oldlace", [253, 245, 230]),
+    ("olive", [128, 128, 0]),
+    ("olivedrab", [107, 142, 35]),
+    ("orange", [255, 165, 0]),
+    ("orangered", [255, 69, 0]),
+    ("orchid", [218, 112, 214]),
+    ("palegoldenrod", [238, 232, 170]),
+    ("palegreen", [152, 251, 152]),
+    ("paleturquoise", [175, 238, 238]),
+    ("palevioletred", [219, 112, 147]),
+    ("papayawhip", [255, 239, 213]),
+    ("peachpuff", [255, 218, 185]),
+    ("peru", [205, 133, 63]),
+    ("pink", [255, 192, 203]),
+    ("plum", [221, 160, 221]),
+    ("powderblue", [176, 224, 230]),
+    ("purple", [128, 0, 128]),
+    ("red", [255, 0, 0]),
+    ("rosybrown", [188, 143, 143]),
+    ("royalblue", [65, 105, 225]),
+    ("saddlebrown", [139, 69, 19]),
+    ("salmon", [250, 128, 114]),
+    ("sandybrown", [244, 164, 96]),
+    ("seagreen", [46, 139, 87]),
+    ("seashell", [255, 245, 238]),
+    ("sienna", [160, 82, 45]),
+    ("silver", [192, 192, 192]),
+    ("skyblue", [135, 206, 235]),
+    ("slateblue", [106, 90, 205]),
+    ("slategray", [112, 128, 144]),
+    ("slategrey", [112, 128, 144]),
+    ("snow", [255, 250, 250]),
+    ("springgreen", [0, 255, 127]),
+    ("steelblue", [70, 130, 180]),
+    ("tan", [210, 180, 140]),
+    ("teal", [0, 128, 128]),
+    ("thistle", [216, 191, 216]),
+    ("tomato", [255, 99, 71]),
+    ("turquoise", [64, 224, 208]),
+    ("violet", [238, 130, 238]),
+    ("wheat", [245, 222, 179]),
+    ("white", [255, 255, 255]),
+    ("whitesmoke", [245, 245, 245]),
+    ("yellow", [255, 255, 0]),
+    ("yellowgreen", [154, 205, 50]),
+];
+
+/// Rewrites color values to their shortest equivalent.
+///
+/// `rgb()`, `hsl()` and their fully opaque `rgba()`/`hsla()` forms, color
+/// keywords and `#rrggbb` values of
+/// `fill`, `stroke`, `stop-color` and `color` are rewritten as `#rgb`,
+/// `#rrggbb` or a keyword, whichever is shortest. Anything else, such as
+/// `none`, `currentColor` or `url(#paint)`, is left untouched.
+pub struct ConvertColorsPlugin {
+    /// Use `#rgb` and keywords when they are shorter than `#rrggbb`.
+    shorthand: bool,
+    /// Lowercase hex digits; otherwise hex values keep their case.
+    lowercase: bool,
+    colors_converted: usize,
+    diagnostics: DiagnosticsCollector,
+}
+
+impl ConvertColorsPlugin {
+    pub fn new() -> Self {
+        Self {
+            shorthand: true,
+            lowercase: true,
+            colors_converted: 0,
+            diagnostics: DiagnosticsCollector::new(),
+        }
+    }
+
+    /// Whether to emit `#rgb` and keywords when they are shorter.
+    pub fn with_shorthand(mut self, shorthand: bool) -> Self {
+        self.shorthand = shorthand;
+        self
+    }
+
+    /// Whether to lowercase hex digits.
+    pub fn with_lowercase(mut self, lowercase: bool) -> Self {
+        self.lowercase = lowercase;
+        self
+    }
+}
+
+impl Default for ConvertColorsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The shortest form of a color value, or `None` if it isn't a color this
+/// module understands or can't be shortened.
+///
+/// Without `shorthand` colors are written as `#rrggbb`; without `lowercase`
+/// hex values keep the case they were written in.
+pub(crate) fn shorten_color(value: &str, shorthand: bool, lowercase: bool) -> Option<String> {
+    let value = value.trim();
+    let (hex, keep_case) = match value.strip_prefix('#') {
+        Some(digits) if digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+            (digits.to_string(), !lowercase)
+        }
+        Some(digits) if digits.len() == 3 && digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+            let expanded = digits.chars().flat_map(|c| [c, c]).collect();
+            (expanded, !lowercase)
+        }
+        Some(_) => return None,
+        None => {
+            let [r, g, b] = parse_rgb(value)
+                .or_else(|| parse_hsl(value))
+                .or_else(|| named_color(value))?;
+            (format!("{:02x}{:02x}{:02x}", r, g, b), false)
+        }
+    };
+    let hex = if keep_case { hex } else { hex.to_ascii_lowercase() };
+
+    let mut best = format!("#{}", hex);
+    if shorthand {
+        let bytes = hex.as_bytes();
+        if bytes[0] == bytes[1] && bytes[2] == bytes[3] && bytes[4] == bytes[5] {
+            best = format!("#{}{}{}", bytes[0] as char, bytes[2] as char, bytes[4] as char);
+        }
+        let rgb = hex_to_rgb(&hex)?;
+        if let Some((name, _)) = NAMED_COLORS
+            .iter()
+            .filter(|(name, color)| *color == rgb && name.len() < best.len())
+            .min_by_key(|(name, _)| name.len())
+        {
+            best = name.to_string();
+        }
+    }
+
+    (best != value && best.len() <= value.len()).then_some(best)
+}
+
+/// Whether `value` looks like a hex color but isn't one, like `#12` or
+/// `#ggg`. Renderers ignore such values.
+fn is_malformed_hex(value: &str) -> bool {
+    match value.trim().strip_prefix('#') {
+        Some(digits) => {
+            !matches!(digits.len(), 3 | 4 | 6 | 8) || !digits.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => false,
+    }
+}
+
+fn named_color(value: &str) -> Option<[u8; 3]> {
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+        .map(|(_, rgb)| *rgb)
+}
+
+fn hex_to_rgb(hex: &str) -> Option<[u8; 3]> {
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Parse `rgb(r, g, b)` or an opaque `rgba(r, g, b, 1)`, with numbers or
+/// percentages separated by commas or spaces.
+fn parse_rgb(value: &str) -> Option<[u8; 3]> {
+    let lower = value.to_ascii_lowercase();
+    let args = lower
+        .strip_prefix("rgba(")
+        .or_else(|| lower.strip_prefix("rgb("))?
+        .strip_suffix(')')?;
+
+    match function_args(args).as_slice() {
+        [r, g, b] => Some([channel(r)?, channel(g)?, channel(b)?]),
+        [r, g, b, alpha] if is_opaque(alpha) => Some([channel(r)?, channel(g)?, channel(b)?]),
+        _ => None,
+    }
+}
+
+/// Parse `hsl(h, s%, l%)` or an opaque `hsla(h, s%, l%, 1)`. The hue may
+/// carry a `deg` unit.
+fn parse_hsl(value: &str) -> Option<[u8; 3]> {
+    let lower = value.to_ascii_lowercase();
+    let args = lower
+        .strip_prefix("hsla(")
+        .or_else(|| lower.strip_prefix("hsl("))?
+        .strip_suffix(')')?;
+
+    let (h, s, l) = match function_args(args).as_slice() {
+        [h, s, l] => (*h, *s, *l),
+        [h, s, l, alpha] if is_opaque(alpha) => (*h, *s, *l),
+        _ => return None,
+    };
+    let hue = h.strip_suffix("deg").unwrap_or(h).parse::<f64>().ok()?.rem_euclid(360.0) / 360.0;
+    let saturation = s.strip_suffix('%')?.parse::<f64>().ok()?.clamp(0.0, 100.0) / 100.0;
+    let lightness = l.strip_suffix('%')?.parse::<f64>().ok()?.clamp(0.0, 100.0) / 100.0;
+
+    let q = if lightness < 0.5 {
+        lightness * (1.0 + saturation)
+    } else {
+        lightness + saturation - lightness * saturation
+    };
+    let p = 2.0 * lightness - q;
+    let to_channel = |t: f64| (hue_to_rgb(p, q, t) * 255.0).round().clamp(0.0, 255.0) as u8;
+    Some([to_channel(hue + 1.0 / 3.0), to_channel(hue), to_channel(hue - 1.0 / 3.0)])
+}
+
+fn hue_to_rgb(p: f64, q: f64, t: f64) -> f64 {
+    let t = t.rem_euclid(1.0);
+    if t < 1.0 / 6.0 {
+        p + (q - p) * 6.0 * t
+    } else if t < 0.5 {
+        q
+    } else if t < 2.0 / 3.0 {
+        p + (q - p) * (2.0 / 3.0 - t) * 6.0
+    } else {
+        p
+    }
+}
+
+/// The arguments of a color function, separated by commas, slashes or
+/// whitespace.
+fn function_args(args: &str) -> Vec<&str> {
+    args.split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+fn channel(part: &str) -> Option<u8> {
+    let value = match part.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().ok()? * 255.0 / 100.0,
+        None => part.parse::<f64>().ok()?,
+    };
+    Some(value.round().clamp(0.0, 255.0) as u8)
+}
+
+fn is_opaque(alpha: &str) -> bool {
+    match alpha.strip_suffix('%') {
+        Some(percent) => percent.parse::<f64>().is_ok_and(|value| value >= 100.0),
+        None => alpha.parse::<f64>().is_ok_and(|value| value >= 1.0),
+    }
+}
+
+/// Whether a color has an alpha below one: a `#rgba` or `#rrggbbaa` hex
+/// color, or a color function with a fourth argument, like `rgba()` and
+/// `hsla()`.
+pub(crate) fn is_translucent(value: &str) -> bool {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#').filter(|hex| hex.is_ascii()) {
+        return match hex.len() {
+            4 => !hex[3..].eq_ignore_ascii_case("f"),
+            8 => !hex[6..].eq_ignore_ascii_case("ff"),
+            _ => false,
+        };
+    }
+    let Some((function, args)) = value.split_once('(') else {
+        return false;
+    };
+    if !["rgb", "rgba", "hsl", "hsla"].contains(&function.trim().to_ascii_lowercase().as_str()) {
+        return false;
+    }
+    match function_args(args.trim_end().trim_end_matches(')')).as_slice() {
+        [_, _, _, alpha] => !is_opaque(alpha),
+        _ => false,
+    }
+}
+
+impl SVGPlugin for ConvertColorsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.colors_converted = 0;
+        self.diagnostics.clear();
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let mut attrs = owned_attributes(element);
+        let mut changed = false;
+
+        for (key, value) in &mut attrs {
+            if !COLOR_ATTRIBUTES.contains(&key.as_str()) {
+                continue;
+            }
+            if is_malformed_hex(value) {
+                let element_name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+                self.diagnostics
+                    .warn(format!("malformed color '{}' in {}", value, key), Some(&element_name));
+                continue;
+            }
+            if let Some(converted) = shorten_color(value, self.shorthand, self.lowercase) {
+                *value = converted;
+                self.colors_converted += 1;
+                changed = true;
+            }
+        }
+
+        if changed {
+            set_attributes(element, &attrs);
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn name(&self) -> &str {
+        "ConvertColors"
+    }
+}
+
+impl PluginStatistics for ConvertColorsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Colors converted", self.colors_converted.to_string())]
+    }
+
+    fn diagnostics(&self) -> &[Diagnostic] {
+        self.diagnostics.as_slice()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+    use crate::processor::SVGProcessor;
+
+    #[test]
+    fn test_colors_are_shortened() -> io::Result<()> {
+        let svg = r##"<svg><rect fill="rgb(255, 0, 0)" stroke="white"/><stop stop-color="#AABBCC"/><path color="rgba(0%, 0%, 50%, 1)" fill="rgb(0 128 128)" stroke="#123456"/><rect fill="#F00" stroke="#BCD"/><rect fill="hsl(120, 100%, 25%)" stroke="hsla(240deg 100% 50% / 1)"/></svg>"##;
+
+        let output = optimize_with(ConvertColorsPlugin::new(), svg)?;
+
+        assert_eq!(
+            output,
+            r##"<svg><rect fill="red" stroke="#fff"/><stop stop-color="#abc"/><path color="navy" fill="teal" stroke="#123456"/><rect fill="red" stroke="#bcd"/><rect fill="green" stroke="#00f"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_colors_are_untouched() -> io::Result<()> {
+        let svg = r##"<svg><rect fill="none" stroke="currentColor"/><rect fill="url(#paint)" stroke="rgba(0, 0, 0, 0.5)"/><rect fill="#abc" stroke="transparent" opacity="red"/></svg>"##;
+
+        assert_eq!(optimize_with(ConvertColorsPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_hex_colors_are_reported() -> io::Result<()> {
+        let svg = r##"<svg><rect fill="#12" stroke="#abcd"/><circle stroke="#ggg" fill="#1234567"/></svg>"##;
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(ConvertColorsPlugin::new());
+
+        assert_eq!(processor.optimize_str(svg)?, svg);
+        let diagnostics: Vec<_> = processor.diagnostics().into_iter().map(|(_, d)| d.to_string()).collect();
+        assert_eq!(
+            diagnostics,
+            [
+                "warning: malformed color '#12' in fill (<rect>)",
+                "warning: malformed color '#ggg' in stroke (<circle>)",
+                "warning: malformed color '#1234567' in fill (<circle>)",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_shorthand_and_case_options() -> io::Result<()> {
+        let svg = r##"<svg><rect fill="rgb(255,255,255)" stroke="#FFAA00"/><rect fill="black"/></svg>"##;
+
+        let longhand = optimize_with(ConvertColorsPlugin::new().with_shorthand(false), svg)?;
+        assert_eq!(
+            longhand,
+            r##"<svg><rect fill="#ffffff" stroke="#ffaa00"/><rect fill="black"/></svg>"##
+        );
+
+        let keep_case = optimize_with(ConvertColorsPlugin::new().with_lowercase(false), svg)?;
+        assert_eq!(
+            keep_case,
+            r##"<svg><rect fill="#fff" stroke="#FA0"/><rect fill="#000"/></svg>"##
+        );
+        Ok(())
+    }
+}
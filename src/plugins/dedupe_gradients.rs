@@ -0,0 +1,365 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, rewrite_url_references, set_attributes, XLinkPrefixes};
+use quick_xml::events::{BytesCData, BytesEnd, BytesStart, BytesText, Event};
+use std::collections::HashMap;
+use std::io;
+
+/// Collapses identical `<linearGradient>` and `<radialGradient>` definitions.
+///
+/// Two gradients are identical when their attributes (other than `id`) and
+/// their child elements (usually `<stop>`s) match exactly. The first
+/// gradient is kept; later duplicates are removed and every `url(#id)` and
+/// `href="#id"` reference to them is rewritten to the kept one, including
+/// `url(#id)` references in `<style>` elements.
+///
+/// Duplicates are found in `analyze`, since a reference can come before the
+/// gradient it points to and the output is written as the document streams.
+/// `process_element` tracks what it drops and rewrites, and `finalize` adds
+/// that to the statistics.
+pub struct DeduplicateGradientsPlugin {
+    /// Maps the id of a removed duplicate to the id of the kept gradient.
+    replacements: HashMap<String, String>,
+    xlink: XLinkPrefixes,
+    /// Ids of the duplicates dropped from the current document.
+    removed: Vec<String>,
+    /// References rewritten in the current document.
+    rewritten: usize,
+    /// Open elements from the current `<style>` down; 0 outside of one.
+    style_depth: usize,
+    gradients_removed: usize,
+    references_updated: usize,
+}
+
+/// A gradient being collected during analysis.
+struct PendingGradient {
+    id: Option<String>,
+    signature: String,
+    depth: usize,
+}
+
+impl DeduplicateGradientsPlugin {
+    pub fn new() -> Self {
+        Self {
+            replacements: HashMap::new(),
+            xlink: XLinkPrefixes::default(),
+            removed: Vec::new(),
+            rewritten: 0,
+            style_depth: 0,
+            gradients_removed: 0,
+            references_updated: 0,
+        }
+    }
+
+    fn is_gradient(element: &BytesStart) -> bool {
+        matches!(
+            element.name().as_ref(),
+            b"linearGradient" | b"radialGradient"
+        )
+    }
+
+    /// Describe an element by its name and id-less attributes in sorted order.
+    fn element_signature(element: &BytesStart) -> (Option<String>, String) {
+        let mut id = None;
+        let mut attrs: Vec<(String, String)> = owned_attributes(element)
+            .into_iter()
+            .filter(|(key, value)| {
+                if key == "id" {
+                    id = Some(value.clone());
+                    false
+                } else {
+                    true
+                }
+            })
+            .collect();
+        attrs.sort();
+
+        let mut signature = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+        for (key, value) in attrs {
+            signature.push_str(&format!(" {}={:?}", key, value));
+        }
+        (id, signature)
+    }
+
+    /// Record a fully collected gradient, marking it as a duplicate if an
+    /// identical one was seen before.
+    fn record(&mut self, gradient: PendingGradient, seen: &mut HashMap<String, String>) {
+        let Some(id) = gradient.id else { return };
+
+        match seen.get(&gradient.signature) {
+            Some(kept) => {
+                self.replacements.insert(id, kept.clone());
+            }
+            None => {
+                seen.insert(gradient.signature, id);
+            }
+        }
+    }
+
+    /// Point a reference at the kept gradient if it targets a duplicate.
+    fn rewrite_reference(&self, key: &str, value: &str) -> Option<String> {
+        if self.xlink.is_link(key) {
+            let id = value.strip_prefix('#')?;
+            return self.replacements.get(id).map(|kept| format!("#{}", kept));
+        }
+
+        rewrite_url_references(value, |id| self.replacements.get(id).cloned())
+    }
+
+    /// Point the `url(#id)` references in style sheet text at the kept
+    /// gradients, counting each rewritten reference.
+    fn rewrite_style_sheet(&mut self, css: &str) -> Option<String> {
+        let mut rewritten = 0;
+        let result = rewrite_url_references(css, |id| {
+            let kept = self.replacements.get(id).cloned();
+            rewritten += kept.is_some() as usize;
+            kept
+        });
+        self.rewritten += rewritten;
+        result
+    }
+}
+
+impl Default for DeduplicateGradientsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for DeduplicateGradientsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.replacements.clear();
+        self.xlink.clear();
+        self.removed.clear();
+        self.rewritten = 0;
+        self.style_depth = 0;
+        self.gradients_removed = 0;
+        self.references_updated = 0;
+        Ok(())
+    }
+
+    fn requires_analysis(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, events: &[Event<'static>]) -> io::Result<()> {
+        self.xlink = XLinkPrefixes::from_events(events);
+        let mut seen = HashMap::new();
+        let mut current: Option<PendingGradient> = None;
+
+        for event in events {
+            match (event, current.as_mut()) {
+                (Event::Start(elem), None) if Self::is_gradient(elem) => {
+                    let (id, signature) = Self::element_signature(elem);
+                    current = Some(PendingGradient { id, signature, depth: 1 });
+                }
+                (Event::Empty(elem), None) if Self::is_gradient(elem) => {
+                    let (id, signature) = Self::element_signature(elem);
+                    self.record(PendingGradient { id, signature, depth: 0 }, &mut seen);
+                }
+                (Event::Start(elem), Some(gradient)) => {
+                    let (_, child) = Self::element_signature(elem);
+                    gradient.signature.push_str(&format!(" <{}>", child));
+                    gradient.depth += 1;
+                }
+                (Event::Empty(elem), Some(gradient)) => {
+                    let (_, child) = Self::element_signature(elem);
+                    gradient.signature.push_str(&format!(" <{}/>", child));
+                }
+                (Event::End(_), Some(gradient)) => {
+                    gradient.depth -= 1;
+                    if gradient.depth == 0 {
+                        let gradient = current.take().unwrap();
+                        self.record(gradient, &mut seen);
+                    } else {
+                        gradient.signature.push_str(" </>");
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        if self.replacements.is_empty() {
+            return Ok(ElementAction::Keep);
+        }
+
+        let mut attrs = owned_attributes(element);
+
+        if Self::is_gradient(element) {
+            let duplicate = attrs
+                .iter()
+                .find(|(key, value)| key == "id" && self.replacements.contains_key(value));
+            if let Some((_, id)) = duplicate {
+                self.removed.push(id.clone());
+                return Ok(ElementAction::Remove);
+            }
+        }
+
+        let mut changed = false;
+        for (key, value) in attrs.iter_mut() {
+            if let Some(rewritten) = self.rewrite_reference(key, value) {
+                *value = rewritten;
+                self.rewritten += 1;
+                changed = true;
+            }
+        }
+
+        if changed {
+            set_attributes(element, &attrs);
+        }
+
+        if self.style_depth > 0 {
+            self.style_depth += 1;
+        } else if element.local_name().as_ref() == b"style" {
+            self.style_depth = 1;
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_end<'a>(&mut self, end: BytesEnd<'a>) -> io::Result<Option<BytesEnd<'a>>> {
+        self.style_depth = self.style_depth.saturating_sub(1);
+        Ok(Some(end))
+    }
+
+    fn process_text<'a>(&mut self, text: BytesText<'a>) -> io::Result<Option<BytesText<'a>>> {
+        if self.style_depth != 1 {
+            return Ok(Some(text));
+        }
+        match self.rewrite_style_sheet(&String::from_utf8_lossy(&text)) {
+            Some(css) => Ok(Some(BytesText::from_escaped(css))),
+            None => Ok(Some(text)),
+        }
+    }
+
+    fn process_cdata<'a>(&mut self, cdata: BytesCData<'a>) -> io::Result<Option<BytesCData<'a>>> {
+        if self.style_depth != 1 {
+            return Ok(Some(cdata));
+        }
+        match self.rewrite_style_sheet(&String::from_utf8_lossy(&cdata)) {
+            Some(css) => Ok(Some(BytesCData::new(css))),
+            None => Ok(Some(cdata)),
+        }
+    }
+
+    fn finalize(&mut self) -> io::Result<()> {
+        self.gradients_removed += self.removed.len();
+        self.references_updated += self.rewritten;
+        self.removed.clear();
+        self.rewritten = 0;
+        self.style_depth = 0;
+        self.replacements.clear();
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "DeduplicateGradients"
+    }
+}
+
+impl PluginStatistics for DeduplicateGradientsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Gradients removed", self.gradients_removed.to_string()),
+            ("References updated", self.references_updated.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    fn statistics(plugin: &DeduplicateGradientsPlugin) -> Vec<(String, String)> {
+        plugin
+            .get_statistics()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect()
+    }
+
+    #[test]
+    fn test_duplicate_gradients_are_collapsed() -> io::Result<()> {
+        let svg = r##"<svg><defs><linearGradient id="gradA" x2="1"><stop offset="0" stop-color="#fff"/><stop offset="1" stop-color="#000"/></linearGradient><linearGradient id="gradB" x2="1"><stop offset="0" stop-color="#fff"/><stop offset="1" stop-color="#000"/></linearGradient></defs><rect fill="url(#gradA)"/><rect fill="url(#gradB)"/></svg>"##;
+
+        let output = optimize_with(DeduplicateGradientsPlugin::new(), svg)?;
+
+        assert!(!output.contains(r#"id="gradB""#));
+        assert_eq!(output.matches("<linearGradient").count(), 1);
+        assert_eq!(output.matches(r#"fill="url(#gradA)""#).count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_style_sheet_references_are_rewritten() -> io::Result<()> {
+        let svg = r##"<svg><style>.a{fill:url(#b)}</style><style><![CDATA[.b{stroke:url("#b")}]]></style><linearGradient id="a"><stop offset="1"/></linearGradient><linearGradient id="b"><stop offset="1"/></linearGradient><rect class="a b"/></svg>"##;
+
+        assert_eq!(
+            optimize_with(DeduplicateGradientsPlugin::new(), svg)?,
+            r##"<svg><style>.a{fill:url(#a)}</style><style><![CDATA[.b{stroke:url(#a)}]]></style><linearGradient id="a"><stop offset="1"/></linearGradient><rect class="a b"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_aliased_xlink_references_are_rewritten() -> io::Result<()> {
+        let svg = r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><linearGradient id="a"><stop/></linearGradient><linearGradient id="b"><stop/></linearGradient><radialGradient x:href="#b"/></svg>"##;
+
+        assert_eq!(
+            optimize_with(DeduplicateGradientsPlugin::new(), svg)?,
+            r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><linearGradient id="a"><stop/></linearGradient><radialGradient x:href="#a"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_different_gradients_are_kept() -> io::Result<()> {
+        let svg = r##"<svg><defs><radialGradient id="a"><stop offset="0" stop-color="#fff"/></radialGradient><radialGradient id="b"><stop offset="0.5" stop-color="#fff"/></radialGradient></defs><rect fill="url(#b)"/></svg>"##;
+
+        let output = optimize_with(DeduplicateGradientsPlugin::new(), svg)?;
+
+        assert_eq!(output.matches("<radialGradient").count(), 2);
+        assert!(output.contains(r#"fill="url(#b)""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_statistics_are_reported_in_finalize() -> io::Result<()> {
+        let svg = r##"<svg><rect fill="url(#b)"/><defs><linearGradient id="a"><stop offset="0"/></linearGradient><linearGradient id="b"><stop offset="0"/></linearGradient><linearGradient id="c"><stop offset="0"/></linearGradient></defs><rect stroke="url(#c)" fill="url(#a)"/></svg>"##;
+        let mut plugin = DeduplicateGradientsPlugin::new();
+        plugin.init()?;
+        let mut reader = quick_xml::Reader::from_str(svg);
+        let mut events = Vec::new();
+        loop {
+            match reader.read_event().map_err(io::Error::other)? {
+                Event::Eof => break,
+                event => events.push(event.into_owned()),
+            }
+        }
+        plugin.analyze(&events)?;
+
+        let mut rect = BytesStart::new("rect");
+        rect.push_attribute(("fill", "url(#b)"));
+        assert_eq!(plugin.process_element(&mut rect)?, ElementAction::Keep);
+        assert_eq!(owned_attributes(&rect), vec![("fill".to_string(), "url(#a)".to_string())]);
+        for id in ["b", "c"] {
+            let mut gradient = BytesStart::new("linearGradient");
+            gradient.push_attribute(("id", id));
+            assert_eq!(plugin.process_element(&mut gradient)?, ElementAction::Remove);
+        }
+        assert_eq!(statistics(&plugin)[0].1, "0");
+
+        plugin.finalize()?;
+        assert_eq!(
+            statistics(&plugin),
+            vec![
+                ("Gradients removed".to_string(), "2".to_string()),
+                ("References updated".to_string(), "1".to_string()),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+//! Finds `<path>` elements drawing the same shape and optionally removes
+//! copies that paint the same thing twice.
+//!
+//! Whether a copy can go depends on its neighbours, so the plugin runs on
+//! the document tree.
+
+use crate::plugins::convert_colors::is_translucent;
+use crate::plugins::path::canonical_path_data;
+use crate::plugins::traits::{PluginStatistics, TreePlugin};
+use crate::plugins::util::style_declarations;
+use crate::tree::{Document, Element, Node};
+use std::collections::HashSet;
+use std::io;
+
+/// Decimal places two paths have to agree on to count as the same shape.
+const COMPARE_PRECISION: usize = 3;
+
+/// Reports paths whose `d` draws the same shape as an earlier path.
+///
+/// Such paths often come from copy-paste, possibly with a different fill or
+/// stroke. Every one of them is counted. With removal enabled, a path is
+/// dropped when it repeats the element right before it exactly, attributes
+/// included, since painting it a second time changes nothing. Paths with
+/// an `id` or children, and translucent ones, whose second coat darkens the
+/// first, are always kept. A path counts as translucent when it has an
+/// opacity or its fill or stroke color has an alpha below one.
+pub struct DeduplicatePathsPlugin {
+    remove: bool,
+    duplicates_found: usize,
+    duplicates_removed: usize,
+}
+
+impl DeduplicatePathsPlugin {
+    pub fn new() -> Self {
+        Self {
+            remove: false,
+            duplicates_found: 0,
+            duplicates_removed: 0,
+        }
+    }
+
+    /// Whether to remove paths repeating their previous sibling.
+    pub fn with_remove(mut self, remove: bool) -> Self {
+        self.remove = remove;
+        self
+    }
+
+    fn count(&mut self, nodes: &[Node], seen: &mut HashSet<String>) {
+        for node in nodes {
+            if let Node::Element(element) = node {
+                if let Some(shape) = path_shape(element) {
+                    if !seen.insert(shape) {
+                        self.duplicates_found += 1;
+                    }
+                }
+                self.count(&element.children, seen);
+            }
+        }
+    }
+
+    fn remove_repeats(&mut self, nodes: &mut Vec<Node>) {
+        let mut previous: Option<Vec<(String, String)>> = None;
+        let mut kept: Vec<Node> = Vec::with_capacity(nodes.len());
+
+        for mut node in nodes.drain(..) {
+            match &mut node {
+                Node::Element(element) => {
+                    let signature = removable_signature(element);
+                    if signature.is_some() && signature == previous {
+                        // Take the indentation before the copy along with it
+                        if matches!(kept.last(), Some(Node::Text(text)) if text.trim().is_empty()) {
+                            kept.pop();
+                        }
+                        self.duplicates_removed += 1;
+                        continue;
+                    }
+                    previous = signature;
+                    self.remove_repeats(&mut element.children);
+                }
+                Node::Text(text) if text.trim().is_empty() => {}
+                Node::Comment(_) => {}
+                _ => previous = None,
+            }
+            kept.push(node);
+        }
+
+        *nodes = kept;
+    }
+}
+
+impl Default for DeduplicatePathsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The canonical `d` of a path element.
+fn path_shape(element: &Element) -> Option<String> {
+    if element.name != "path" {
+        return None;
+    }
+    let d = element.attr("d")?;
+    Some(canonical_path_data(d, COMPARE_PRECISION).unwrap_or_else(|| d.trim().to_string()))
+}
+
+/// The sorted attributes of a path that could be dropped if repeated, with
+/// its `d` in canonical form.
+fn removable_signature(element: &Element) -> Option<Vec<(String, String)>> {
+    let shape = path_shape(element)?;
+    let keep = !element.children.is_empty()
+        || element.attrs.iter().any(|(key, _)| key == "id" || key.contains("opacity"))
+        || element.attr("style").is_some_and(|style| style.contains("opacity"))
+        || has_translucent_paint(element);
+    if keep {
+        return None;
+    }
+
+    let mut attrs: Vec<(String, String)> = element
+        .attrs
+        .iter()
+        .map(|(key, value)| match key.as_str() {
+            "d" => (key.clone(), shape.clone()),
+            _ => (key.clone(), value.clone()),
+        })
+        .collect();
+    attrs.sort();
+    Some(attrs)
+}
+
+/// Whether the fill or stroke of an element, as an attribute or in its
+/// `style`, is a color with alpha.
+fn has_translucent_paint(element: &Element) -> bool {
+    let is_paint = |property: &str| property == "fill" || property == "stroke";
+    let style = element.attr("style").unwrap_or_default();
+    element
+        .attrs
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .chain(style_declarations(style))
+        .any(|(property, value)| is_paint(property) && is_translucent(value))
+}
+
+impl TreePlugin for DeduplicatePathsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.duplicates_found = 0;
+        self.duplicates_removed = 0;
+        Ok(())
+    }
+
+    fn process_tree(&mut self, document: &mut Document) -> io::Result<()> {
+        self.count(&document.children, &mut HashSet::new());
+        if self.remove {
+            self.remove_repeats(&mut document.children);
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "DeduplicatePaths"
+    }
+}
+
+impl PluginStatistics for DeduplicatePathsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Duplicate paths found", self.duplicates_found.to_string()),
+            ("Duplicate paths removed", self.duplicates_removed.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::SVGProcessor;
+
+    fn optimize(plugin: DeduplicatePathsPlugin, svg: &str) -> io::Result<(String, Vec<String>)> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_tree_plugin(plugin);
+        let output = processor.optimize_str(svg)?;
+        let statistics = processor.plugin_statistics()[0].1.iter().map(|(_, value)| value.clone()).collect();
+        Ok((output, statistics))
+    }
+
+    #[test]
+    fn test_duplicates_are_reported() -> io::Result<()> {
+        let svg = r#"<svg><path d="M0 0L10 10" fill="red"/><g><path d="m0,0 l10,10" fill="blue"/></g><path d="M0 0L10 11"/></svg>"#;
+
+        let (output, statistics) = optimize(DeduplicatePathsPlugin::new(), svg)?;
+        assert_eq!(output, svg);
+        assert_eq!(statistics, ["1", "0"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_repeated_paths_are_removed() -> io::Result<()> {
+        let svg = r#"<svg>
+  <path d="M0 0L10 10" fill="red"/>
+  <path fill="red" d="m0 0 10 10"/>
+  <path d="M0 0L10 10" fill="red" opacity=".5"/>
+  <path d="M0 0L10 10" fill="red" opacity=".5"/>
+  <path d="M0 0L10 10" fill="blue"/>
+  <rect/>
+  <path d="M0 0L10 10" fill="blue"/>
+  <path d="M0 0L10 10" fill="blue" id="a"/>
+</svg>"#;
+
+        let (output, statistics) = optimize(DeduplicatePathsPlugin::new().with_remove(true), svg)?;
+        assert_eq!(
+            output,
+            r#"<svg>
+  <path d="M0 0L10 10" fill="red"/>
+  <path d="M0 0L10 10" fill="red" opacity=".5"/>
+  <path d="M0 0L10 10" fill="red" opacity=".5"/>
+  <path d="M0 0L10 10" fill="blue"/>
+  <rect/>
+  <path d="M0 0L10 10" fill="blue"/>
+  <path d="M0 0L10 10" fill="blue" id="a"/>
+</svg>"#
+        );
+        assert_eq!(statistics, ["6", "1"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_paths_with_translucent_paint_are_kept() -> io::Result<()> {
+        let svg = r##"<svg><path d="M0 0L10 10" fill="rgba(255, 0, 0, 0.5)"/><path d="M0 0L10 10" fill="rgba(255, 0, 0, 0.5)"/><path d="M0 0L10 10" stroke="#f008"/><path d="M0 0L10 10" stroke="#f008"/><path d="M0 0L10 10" style="fill: hsla(0, 100%, 50%, 50%)"/><path d="M0 0L10 10" style="fill: hsla(0, 100%, 50%, 50%)"/><path d="M0 0L10 10" fill="#ff0000ff"/><path d="M0 0L10 10" fill="#ff0000ff"/></svg>"##;
+
+        let (output, statistics) = optimize(DeduplicatePathsPlugin::new().with_remove(true), svg)?;
+        assert_eq!(
+            output,
+            r##"<svg><path d="M0 0L10 10" fill="rgba(255, 0, 0, 0.5)"/><path d="M0 0L10 10" fill="rgba(255, 0, 0, 0.5)"/><path d="M0 0L10 10" stroke="#f008"/><path d="M0 0L10 10" stroke="#f008"/><path d="M0 0L10 10" style="fill: hsla(0, 100%, 50%, 50%)"/><path d="M0 0L10 10" style="fill: hsla(0, 100%, 50%, 50%)"/><path d="M0 0L10 10" fill="#ff0000ff"/></svg>"##
+        );
+        assert_eq!(statistics, ["7", "1"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,237 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{has_stylesheet, owned_attributes, set_attributes, style_declarations};
+use quick_xml::events::{BytesStart, Event};
+use std::io;
+
+/// CSS properties that SVG also accepts as presentation attributes.
+pub(crate) const PRESENTATION_ATTRIBUTES: &[&str] = &[
+    "alignment-baseline",
+    "baseline-shift",
+    "clip",
+    "clip-path",
+    "clip-rule",
+    "color",
+    "color-interpolation",
+    "color-interpolation-filters",
+    "color-profile",
+    "color-rendering",
+    "cursor",
+    "direction",
+    "display",
+    "dominant-baseline",
+    "enable-background",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "filter",
+    "flood-color",
+    "flood-opacity",
+    "font-family",
+    "font-size",
+    "font-size-adjust",
+    "font-stretch",
+    "font-style",
+    "font-variant",
+    "font-weight",
+    "glyph-orientation-horizontal",
+    "glyph-orientation-vertical",
+    "image-rendering",
+    "kerning",
+    "letter-spacing",
+    "lighting-color",
+    "marker-end",
+    "marker-mid",
+    "marker-start",
+    "mask",
+    "opacity",
+    "overflow",
+    "pointer-events",
+    "shape-rendering",
+    "stop-color",
+    "stop-opacity",
+    "stroke",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+    "text-anchor",
+    "text-decoration",
+    "text-rendering",
+    "unicode-bidi",
+    "visibility",
+    "word-spacing",
+    "writing-mode",
+];
+
+/// Moves `style` declarations into presentation attributes:
+/// `style="fill:red; stroke:blue"` becomes `fill="red" stroke="blue"`.
+///
+/// Only properties with a presentation attribute are moved, with any
+/// `!important` stripped; others, such as shorthands like `font` or
+/// `marker`, stay in `style`. A declaration is also left in place when the
+/// element already has that attribute, since the attribute must not be
+/// overridden and dropping the declaration would change the result.
+///
+/// Declarations in `style` beat stylesheet rules, presentation attributes
+/// don't, so documents with a `<style>` element or an `xml-stylesheet`
+/// instruction are left alone.
+pub struct InlineStylesPlugin {
+    has_stylesheet: bool,
+    elements_inlined: usize,
+    properties_inlined: usize,
+}
+
+impl InlineStylesPlugin {
+    pub fn new() -> Self {
+        Self {
+            has_stylesheet: false,
+            elements_inlined: 0,
+            properties_inlined: 0,
+        }
+    }
+}
+
+impl Default for InlineStylesPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The declarations of a `style` attribute that take effect, in order, with
+/// `!important` stripped. A later declaration of a property replaces an
+/// earlier one, unless only the earlier one is important.
+fn effective_declarations(style: &str) -> Vec<(String, String)> {
+    let mut declarations: Vec<(String, String, bool)> = Vec::new();
+    for (property, value) in style_declarations(style) {
+        let property = property.to_ascii_lowercase();
+        let (value, important) = match value.to_ascii_lowercase().rfind("!important") {
+            Some(index) => (value[..index].trim_end(), true),
+            None => (value, false),
+        };
+        match declarations.iter_mut().find(|(existing, _, _)| *existing == property) {
+            Some((_, _, true)) if !important => {}
+            Some(declaration) => *declaration = (property, value.to_string(), important),
+            None => declarations.push((property, value.to_string(), important)),
+        }
+    }
+    declarations.into_iter().map(|(property, value, _)| (property, value)).collect()
+}
+
+impl SVGPlugin for InlineStylesPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.has_stylesheet = false;
+        self.elements_inlined = 0;
+        self.properties_inlined = 0;
+        Ok(())
+    }
+
+    fn requires_analysis(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, events: &[Event<'static>]) -> io::Result<()> {
+        self.has_stylesheet = has_stylesheet(events);
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        if self.has_stylesheet {
+            return Ok(ElementAction::Keep);
+        }
+        let attrs = owned_attributes(element);
+        let Some(style) = attrs.iter().find(|(key, _)| key == "style").map(|(_, value)| value) else {
+            return Ok(ElementAction::Keep);
+        };
+        // Comments could hide or split declarations
+        if style.contains("/*") {
+            return Ok(ElementAction::Keep);
+        }
+
+        let mut inlined = Vec::new();
+        let mut remaining = Vec::new();
+        for (property, value) in effective_declarations(style) {
+            let movable = PRESENTATION_ATTRIBUTES.contains(&property.as_str())
+                && !value.is_empty()
+                && !attrs.iter().any(|(key, _)| *key == property);
+            if movable {
+                inlined.push((property, value));
+            } else {
+                remaining.push(format!("{}:{}", property, value));
+            }
+        }
+        if inlined.is_empty() {
+            return Ok(ElementAction::Keep);
+        }
+
+        self.elements_inlined += 1;
+        self.properties_inlined += inlined.len();
+
+        let mut new_attrs = Vec::with_capacity(attrs.len() + inlined.len());
+        for (key, value) in attrs {
+            if key != "style" {
+                new_attrs.push((key, value));
+                continue;
+            }
+            new_attrs.append(&mut inlined);
+            if !remaining.is_empty() {
+                new_attrs.push((key, remaining.join(";")));
+            }
+        }
+        set_attributes(element, &new_attrs);
+        Ok(ElementAction::Keep)
+    }
+
+    fn name(&self) -> &str {
+        "InlineStyles"
+    }
+}
+
+impl PluginStatistics for InlineStylesPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Elements inlined", self.elements_inlined.to_string()),
+            ("Properties inlined", self.properties_inlined.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_styles_become_attributes() -> io::Result<()> {
+        let svg = r#"<svg><rect id="r" style="fill: red; stroke:blue !important; font: 12px serif" width="1"/><g style="Opacity:.5;opacity:1"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(InlineStylesPlugin::new(), svg)?,
+            r#"<svg><rect id="r" fill="red" stroke="blue" style="font:12px serif" width="1"/><g opacity="1"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_existing_attributes_are_not_overridden() -> io::Result<()> {
+        let svg = r#"<svg><rect fill="green" style="fill:red;stroke:blue"/><rect style="fill:red !IMPORTANT;fill:blue"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(InlineStylesPlugin::new(), svg)?,
+            r#"<svg><rect fill="green" stroke="blue" style="fill:red"/><rect fill="red"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_documents_with_stylesheets_are_left_alone() -> io::Result<()> {
+        let svg = r#"<svg><style>rect { fill: blue }</style><rect style="fill:red"/></svg>"#;
+        assert_eq!(optimize_with(InlineStylesPlugin::new(), svg)?, svg);
+
+        let svg = r#"<svg><rect style="fill:red /* note */"/></svg>"#;
+        assert_eq!(optimize_with(InlineStylesPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+}
@@ -0,0 +1,392 @@
+//! Inlining of simple `<style>` rules as presentation attributes.
+//!
+//! Which rules match an element, and which of them wins, depends on the
+//! whole style sheet and document, so the plugin runs on the document tree.
+
+use crate::plugins::inline_styles::PRESENTATION_ATTRIBUTES;
+use crate::plugins::minify_css::minify_css;
+use crate::plugins::remove_unused_css::skip_token;
+use crate::plugins::traits::{PluginStatistics, TreePlugin};
+use crate::tree::{Document, Element, Node};
+use quick_xml::escape::unescape;
+use std::io;
+
+/// `(ids, classes, types)`, compared in that order.
+type Specificity = (usize, usize, usize);
+
+/// A compound selector of an optional type, classes and an optional id, as
+/// in `rect.icon#logo`.
+#[derive(Debug, Default, PartialEq)]
+struct Selector {
+    element: Option<String>,
+    classes: Vec<String>,
+    id: Option<String>,
+}
+
+impl Selector {
+    /// `None` for anything else, such as combinators, pseudo-classes or
+    /// attribute selectors.
+    fn parse(selector: &str) -> Option<Self> {
+        let name_end = |s: &str| {
+            s.find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+                .unwrap_or(s.len())
+        };
+        let mut parsed = Selector::default();
+        let mut rest = selector.trim();
+        if rest.is_empty() {
+            return None;
+        }
+
+        if let Some(after) = rest.strip_prefix('*') {
+            rest = after;
+        } else {
+            let end = name_end(rest);
+            if end > 0 {
+                parsed.element = Some(rest[..end].to_string());
+                rest = &rest[end..];
+            }
+        }
+        while let Some(kind) = rest.chars().next() {
+            let body = &rest[kind.len_utf8()..];
+            let end = name_end(body);
+            if end == 0 {
+                return None;
+            }
+            let name = body[..end].to_string();
+            match kind {
+                '.' => parsed.classes.push(name),
+                '#' if parsed.id.is_none() => parsed.id = Some(name),
+                _ => return None,
+            }
+            rest = &body[end..];
+        }
+        Some(parsed)
+    }
+
+    fn specificity(&self) -> Specificity {
+        (
+            usize::from(self.id.is_some()),
+            self.classes.len(),
+            usize::from(self.element.is_some()),
+        )
+    }
+
+    fn matches(&self, element: &Element) -> bool {
+        let local_name = element.name.rsplit(':').next().unwrap_or_default();
+        let classes = element.attr("class").unwrap_or_default();
+
+        self.element.as_ref().is_none_or(|name| name == local_name)
+            && self.id.as_ref().is_none_or(|id| element.attr("id") == Some(id.as_str()))
+            && self
+                .classes
+                .iter()
+                .all(|class| classes.split_whitespace().any(|c| c == class))
+    }
+}
+
+#[derive(Debug)]
+struct Rule {
+    selectors: Vec<Selector>,
+    declarations: Vec<(String, String)>,
+}
+
+impl Rule {
+    /// `None` when the rule can't be expressed with presentation attributes.
+    fn parse(prelude: &str, block: &str) -> Option<Self> {
+        let selectors = prelude
+            .split(',')
+            .map(Selector::parse)
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut declarations: Vec<(String, String)> = Vec::new();
+        for declaration in split_declarations(block) {
+            if declaration.trim().is_empty() {
+                continue;
+            }
+            let (property, value) = declaration.split_once(':')?;
+            let property = property.trim().to_ascii_lowercase();
+            let value = value.trim();
+            if !PRESENTATION_ATTRIBUTES.contains(&property.as_str()) || !attribute_value(value) {
+                return None;
+            }
+            // A later declaration of the same property wins
+            match declarations.iter_mut().find(|(key, _)| *key == property) {
+                Some((_, existing)) => *existing = value.to_string(),
+                None => declarations.push((property, value.to_string())),
+            }
+        }
+        Some(Self { selectors, declarations })
+    }
+}
+
+/// Whether a CSS value means the same as a presentation attribute.
+fn attribute_value(value: &str) -> bool {
+    let lower = value.to_ascii_lowercase();
+    !value.is_empty()
+        && !lower.contains("!important")
+        && !lower.contains("var(")
+        && !lower.contains("calc(")
+        && !matches!(lower.as_str(), "initial" | "unset" | "revert" | "revert-layer")
+}
+
+/// The declarations of a block, split on `;` outside of strings.
+fn split_declarations(block: &str) -> Vec<&str> {
+    let bytes = block.as_bytes();
+    let mut declarations = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' | b'\'' => {
+                i = skip_token(block, i);
+                continue;
+            }
+            b';' => {
+                declarations.push(&block[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    declarations.push(&block[start..]);
+    declarations
+}
+
+/// The rules of a style sheet, or `None` when it holds anything but plain
+/// rules with simple selectors, such as at-rules or escapes.
+fn parse_stylesheet(css: &str) -> Option<Vec<Rule>> {
+    if css.contains('\\') {
+        return None;
+    }
+    let css = minify_css(css);
+    let bytes = css.as_bytes();
+    let mut rules = Vec::new();
+    let mut start = 0;
+    let mut open = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' | b'\'' => {
+                i = skip_token(&css, i);
+                continue;
+            }
+            b'{' if open.is_none() => open = Some(i),
+            b'}' => {
+                let open = open.take()?;
+                rules.push(Rule::parse(&css[start..open], &css[open + 1..i])?);
+                start = i + 1;
+            }
+            b'{' | b'@' => return None,
+            _ => {}
+        }
+        i += 1;
+    }
+    (open.is_none() && css[start..].trim().is_empty()).then_some(rules)
+}
+
+/// Moves the rules of `<style>` elements into presentation attributes and
+/// drops the `<style>` elements.
+///
+/// Only style sheets made of plain rules are inlined, whose selectors are a
+/// type, classes and an id without combinators, pseudo-classes or
+/// attributes, and whose properties all have a presentation attribute.
+/// Style sheets with at-rules such as `@media`, `!important` or two rules of
+/// the same specificity setting a property of one element differently are
+/// left alone, as are documents with scripts, `<foreignObject>` or an
+/// external style sheet.
+///
+/// The rule with the highest specificity wins and replaces the element's
+/// presentation attribute, as CSS would; `style` attributes still override
+/// the result.
+pub struct InlineStylesheetPlugin {
+    rules_inlined: usize,
+    properties_inlined: usize,
+}
+
+impl InlineStylesheetPlugin {
+    pub fn new() -> Self {
+        Self {
+            rules_inlined: 0,
+            properties_inlined: 0,
+        }
+    }
+}
+
+impl Default for InlineStylesheetPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The CSS of all `<style>` elements in document order, or `None` when the
+/// document can't be inlined safely.
+fn collect_css(nodes: &[Node], css: &mut String) -> Option<()> {
+    for node in nodes {
+        match node {
+            Node::ProcessingInstruction(pi) if pi.trim_start().starts_with("xml-stylesheet") => return None,
+            Node::Element(element) if element.name == "script" || element.name == "foreignObject" => return None,
+            Node::Element(element) if element.name == "style" => {
+                let css_type = element.attr("type").is_none_or(|t| t.is_empty() || t == "text/css");
+                if !css_type || element.attr("media").is_some() {
+                    return None;
+                }
+                for child in &element.children {
+                    match child {
+                        Node::Text(text) => css.push_str(&unescape(text).ok()?),
+                        Node::CData(text) => css.push_str(text),
+                        Node::Comment(_) => {}
+                        _ => return None,
+                    }
+                }
+                css.push('\n');
+            }
+            Node::Element(element) => collect_css(&element.children, css)?,
+            _ => {}
+        }
+    }
+    Some(())
+}
+
+/// Sets the winning declarations on every element, returning how many were
+/// set, or `None` on a conflict between rules of the same specificity.
+fn apply_rules(nodes: &mut [Node], rules: &[Rule]) -> Option<usize> {
+    let mut applied = 0;
+    for node in nodes {
+        let Node::Element(element) = node else { continue };
+        if element.name == "style" {
+            continue;
+        }
+
+        let mut winners: Vec<(&str, Specificity, &str)> = Vec::new();
+        for rule in rules {
+            let Some(specificity) = rule
+                .selectors
+                .iter()
+                .filter(|selector| selector.matches(element))
+                .map(Selector::specificity)
+                .max()
+            else {
+                continue;
+            };
+            for (property, value) in &rule.declarations {
+                match winners.iter_mut().find(|(key, ..)| key == property) {
+                    Some((_, best, winner)) if specificity > *best => {
+                        *best = specificity;
+                        *winner = value;
+                    }
+                    Some((_, best, winner)) if specificity == *best && winner != value => return None,
+                    Some(_) => {}
+                    None => winners.push((property, specificity, value)),
+                }
+            }
+        }
+
+        let winners: Vec<(String, String)> = winners
+            .into_iter()
+            .map(|(property, _, value)| (property.to_string(), value.to_string()))
+            .collect();
+        applied += winners.len();
+        for (property, value) in winners {
+            element.set_attr(&property, value);
+        }
+        applied += apply_rules(&mut element.children, rules)?;
+    }
+    Some(applied)
+}
+
+fn remove_styles(nodes: &mut Vec<Node>) {
+    nodes.retain(|node| !matches!(node, Node::Element(element) if element.name == "style"));
+    for node in nodes {
+        if let Node::Element(element) = node {
+            remove_styles(&mut element.children);
+        }
+    }
+}
+
+impl TreePlugin for InlineStylesheetPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.rules_inlined = 0;
+        self.properties_inlined = 0;
+        Ok(())
+    }
+
+    fn process_tree(&mut self, document: &mut Document) -> io::Result<()> {
+        let mut css = String::new();
+        if collect_css(&document.children, &mut css).is_none() || css.is_empty() {
+            return Ok(());
+        }
+        let Some(rules) = parse_stylesheet(&css) else {
+            return Ok(());
+        };
+
+        // Work on a copy so that a conflict found halfway leaves the
+        // document untouched
+        let mut children = document.children.clone();
+        let Some(applied) = apply_rules(&mut children, &rules) else {
+            return Ok(());
+        };
+        remove_styles(&mut children);
+        document.children = children;
+        self.rules_inlined += rules.len();
+        self.properties_inlined += applied;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "InlineStylesheet"
+    }
+}
+
+impl PluginStatistics for InlineStylesheetPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Rules inlined", self.rules_inlined.to_string()),
+            ("Properties inlined", self.properties_inlined.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_tree_with;
+
+    #[test]
+    fn test_class_rule_is_inlined() -> io::Result<()> {
+        let svg = r#"<svg><style>.cls { fill: red }</style><rect class="cls" fill="blue"/><circle/></svg>"#;
+
+        assert_eq!(
+            optimize_tree_with(InlineStylesheetPlugin::new(), svg)?,
+            r#"<svg><rect class="cls" fill="red"/><circle/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_specificity_decides() -> io::Result<()> {
+        let svg = r#"<svg><style><![CDATA[#a{fill:green} rect.b{fill:blue;stroke:red} rect,circle{fill:red}]]></style><rect id="a" class="b"/><rect class="b"/><circle/></svg>"#;
+
+        assert_eq!(
+            optimize_tree_with(InlineStylesheetPlugin::new(), svg)?,
+            r#"<svg><rect id="a" class="b" fill="green" stroke="red"/><rect class="b" fill="blue" stroke="red"/><circle fill="red"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported_style_sheets_are_left_alone() -> io::Result<()> {
+        for svg in [
+            r#"<svg><style>@media (min-width: 10px) { .a { fill: red } }</style><rect class="a"/></svg>"#,
+            r#"<svg><style>.a:hover { fill: red }</style><rect class="a"/></svg>"#,
+            r#"<svg><style>g .a { fill: red }</style><g><rect class="a"/></g></svg>"#,
+            r#"<svg><style>.a { fill: red !important }</style><rect class="a"/></svg>"#,
+            r#"<svg><style>.a { transition: fill 1s }</style><rect class="a"/></svg>"#,
+            r#"<svg><style>.a { fill: red } .b { fill: blue }</style><rect class="a b"/></svg>"#,
+            r#"<svg><style>.a { fill: red }</style><script>x()</script><rect class="a"/></svg>"#,
+        ] {
+            assert_eq!(optimize_tree_with(InlineStylesheetPlugin::new(), svg)?, svg);
+        }
+        Ok(())
+    }
+}
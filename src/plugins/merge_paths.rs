@@ -0,0 +1,282 @@
+//! Merging of neighbouring `<path>` elements that are painted the same.
+//!
+//! Whether two paths can be merged depends on their siblings and on the
+//! styles they inherit, so the plugin runs on the document tree.
+
+use crate::plugins::path::{appendable_path_data, path_bounding_box, PathParser};
+use crate::plugins::traits::{PluginStatistics, TreePlugin};
+use crate::tree::{Document, Element, Node};
+use std::io;
+
+/// The attributes other than `d` of a path, sorted, which must be equal for
+/// two paths to merge.
+type Signature = Vec<(String, String)>;
+
+/// Stroke and marker properties inherited from ancestors, as far as
+/// merging cares.
+#[derive(Clone, Copy, Debug)]
+struct Stroke {
+    painted: bool,
+    /// `None` when the width isn't a plain number, such as `2%`.
+    width: Option<f64>,
+    miter_limit: f64,
+    /// Whether `fill` or `stroke` is a paint server like a gradient, whose
+    /// size can depend on the element's bounding box.
+    paint_server: bool,
+    /// Whether `marker-start`, `marker-mid` and `marker-end` are set, in
+    /// that order. Merging would move or drop the markers.
+    markers: [bool; 3],
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Self {
+            painted: false,
+            width: Some(1.0),
+            miter_limit: 4.0,
+            paint_server: false,
+            markers: [false; 3],
+        }
+    }
+}
+
+impl Stroke {
+    /// The properties of `element`, given those of its parent.
+    fn of(element: &Element, parent: Stroke) -> Self {
+        let mut stroke = parent;
+        let style = element.attr("style").unwrap_or_default();
+        let declarations = style
+            .split(';')
+            .filter_map(|declaration| declaration.split_once(':'))
+            .map(|(key, value)| (key.trim(), value.trim()));
+        let attrs = element.attrs.iter().map(|(key, value)| (key.as_str(), value.trim()));
+
+        // Style declarations win over presentation attributes
+        for (key, value) in attrs.chain(declarations) {
+            match key {
+                "stroke" => {
+                    stroke.painted = value != "none";
+                    stroke.paint_server = stroke.paint_server || value.contains("url(");
+                }
+                "fill" => stroke.paint_server = stroke.paint_server || value.contains("url("),
+                "stroke-width" => stroke.width = value.strip_suffix("px").unwrap_or(value).parse().ok(),
+                "stroke-miterlimit" => stroke.miter_limit = value.parse().unwrap_or(4.0),
+                "marker" => stroke.markers = [value != "none"; 3],
+                "marker-start" => stroke.markers[0] = value != "none",
+                "marker-mid" => stroke.markers[1] = value != "none",
+                "marker-end" => stroke.markers[2] = value != "none",
+                _ => {}
+            }
+        }
+        stroke
+    }
+
+    /// How far the stroke can reach beyond the geometry: half the width,
+    /// times the miter limit for sharp corners. `None` when unknown.
+    fn reach(&self) -> Option<f64> {
+        if !self.painted {
+            return Some(0.0);
+        }
+        Some(self.width? * self.miter_limit.max(std::f64::consts::SQRT_2) / 2.0)
+    }
+}
+
+/// Merges consecutive `<path>` siblings with identical attributes into a
+/// single path, by concatenating their `d` data.
+///
+/// Overlapping paths can render differently when merged: under `evenodd`,
+/// or `nonzero` with opposite winding, the overlap becomes a hole, and one
+/// path's fill no longer covers the other's stroke. Paths are therefore
+/// only merged when their bounding boxes, grown by the stroke, are
+/// disjoint.
+///
+/// Paths with an `id`, children, markers of their own or inherited, or
+/// `pathLength`, paths painted or clipped with anything referenced through
+/// `url()`, and documents with a `<style>` element, whose selectors may
+/// tell the paths apart, are left alone.
+pub struct MergePathsPlugin {
+    paths_merged: usize,
+}
+
+impl MergePathsPlugin {
+    pub fn new() -> Self {
+        Self { paths_merged: 0 }
+    }
+
+    fn merge_children(&mut self, nodes: &mut Vec<Node>, stroke: Stroke) {
+        // The run being merged into: index in `kept`, signature and the
+        // bounds drawn so far
+        let mut run: Option<(usize, Signature, [f64; 4])> = None;
+        let mut kept: Vec<Node> = Vec::with_capacity(nodes.len());
+
+        for mut node in nodes.drain(..) {
+            let Node::Element(element) = &mut node else {
+                if !matches!(&node, Node::Text(text) if text.trim().is_empty()) {
+                    run = None;
+                }
+                kept.push(node);
+                continue;
+            };
+
+            let element_stroke = Stroke::of(element, stroke);
+            let Some((signature, bounds)) = mergeable(element, element_stroke) else {
+                run = None;
+                self.merge_children(&mut element.children, element_stroke);
+                kept.push(node);
+                continue;
+            };
+
+            if let Some((index, run_signature, run_bounds)) = &mut run {
+                let appended = appendable_path_data(element.attr("d").unwrap_or_default());
+                if let (true, false, Some(appended)) = (*run_signature == signature, overlap(*run_bounds, bounds), appended) {
+                    let Node::Element(target) = &mut kept[*index] else { unreachable!("runs start at a path") };
+                    let d = format!("{}{}", target.attr("d").unwrap_or_default().trim_end(), appended);
+                    target.set_attr("d", d);
+                    *run_bounds = union(*run_bounds, bounds);
+                    // Drop the whitespace between the merged paths
+                    kept.truncate(*index + 1);
+                    self.paths_merged += 1;
+                    continue;
+                }
+            }
+            run = Some((kept.len(), signature, bounds));
+            kept.push(node);
+        }
+
+        *nodes = kept;
+    }
+}
+
+impl Default for MergePathsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The signature of a path that may be merged, and the bounds of what it
+/// paints.
+fn mergeable(element: &Element, stroke: Stroke) -> Option<(Signature, [f64; 4])> {
+    if element.name != "path" || !element.children.is_empty() || stroke.paint_server || stroke.markers.contains(&true) {
+        return None;
+    }
+    let blocked = element.attrs.iter().any(|(key, value)| {
+        key == "id" || key == "pathLength" || value.contains("url(") || value.contains("marker")
+    });
+    if blocked {
+        return None;
+    }
+
+    let path = path_bounding_box(&PathParser::parse(element.attr("d")?).ok()?)?;
+    let reach = stroke.reach()?;
+    let bounds = [path.min_x - reach, path.min_y - reach, path.max_x + reach, path.max_y + reach];
+
+    let mut signature: Signature = element.attrs.iter().filter(|(key, _)| key != "d").cloned().collect();
+    signature.sort();
+    Some((signature, bounds))
+}
+
+/// Whether two boxes share any point, edges included.
+fn overlap(a: [f64; 4], b: [f64; 4]) -> bool {
+    a[0] <= b[2] && b[0] <= a[2] && a[1] <= b[3] && b[1] <= a[3]
+}
+
+fn union(a: [f64; 4], b: [f64; 4]) -> [f64; 4] {
+    [a[0].min(b[0]), a[1].min(b[1]), a[2].max(b[2]), a[3].max(b[3])]
+}
+
+fn has_style(nodes: &[Node]) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Element(element) => element.name == "style" || has_style(&element.children),
+        _ => false,
+    })
+}
+
+impl TreePlugin for MergePathsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.paths_merged = 0;
+        Ok(())
+    }
+
+    fn process_tree(&mut self, document: &mut Document) -> io::Result<()> {
+        if !has_style(&document.children) {
+            self.merge_children(&mut document.children, Stroke::default());
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "MergePaths"
+    }
+}
+
+impl PluginStatistics for MergePathsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Paths merged", self.paths_merged.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_tree_with;
+
+    #[test]
+    fn test_same_styled_paths_are_merged() -> io::Result<()> {
+        let svg = r#"<svg>
+  <path fill="red" d="M0 0h10v10H0z"/>
+  <path d="m20 0 h10v10h-10z" fill="red"/>
+  <path fill="red" d="M40 0h10v10H40z"/>
+</svg>"#;
+
+        assert_eq!(
+            optimize_tree_with(MergePathsPlugin::new(), svg)?,
+            r#"<svg>
+  <path fill="red" d="M0 0h10v10H0zM20 0 h10v10h-10zM40 0h10v10H40z"/>
+</svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_differing_fill_blocks_the_merge() -> io::Result<()> {
+        let svg = r#"<svg><path fill="red" d="M0 0h10v10H0z"/><path fill="blue" d="M20 0h10v10H20z"/><path fill="blue" fill-rule="evenodd" d="M40 0h10v10H40z"/></svg>"#;
+
+        assert_eq!(optimize_tree_with(MergePathsPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_overlapping_paths_are_kept_apart() -> io::Result<()> {
+        // The second square overlaps the first, and the strokes of the
+        // third and fourth would touch
+        let svg = r##"<svg><path d="M0 0h10v10H0z"/><path d="M5 5h10v10H5z"/><g stroke="#000" stroke-width="4"><path d="M0 20h10v10H0z"/><path d="M15 20h10v10H15z"/><path d="M50 20h10v10H50z"/></g></svg>"##;
+
+        assert_eq!(
+            optimize_tree_with(MergePathsPlugin::new(), svg)?,
+            r##"<svg><path d="M0 0h10v10H0z"/><path d="M5 5h10v10H5z"/><g stroke="#000" stroke-width="4"><path d="M0 20h10v10H0z"/><path d="M15 20h10v10H15zM50 20h10v10H50z"/></g></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_paths_that_must_stay_separate() -> io::Result<()> {
+        let svg = r##"<svg><path id="a" d="M0 0h1v1H0z"/><path d="M2 0h1v1H2z"/><!-- x --><path d="M4 0h1v1H4z"/><rect/><path d="M6 0h1v1H6z" marker-end="url(#m)"/><path d="M8 0h1v1H8z" marker-end="url(#m)"/><g fill="url(#g)"><path d="M0 0h1v1H0z"/><path d="M2 0h1v1H2z"/></g><path d="m0 9 1 1"/><path d="m2 9 1 1"/></svg>"##;
+
+        assert_eq!(optimize_tree_with(MergePathsPlugin::new(), svg)?, svg);
+
+        let styled = r#"<svg><style>path:first-child{fill:red}</style><path d="M0 0h1v1H0z"/><path d="M2 0h1v1H2z"/></svg>"#;
+        assert_eq!(optimize_tree_with(MergePathsPlugin::new(), styled)?, styled);
+        Ok(())
+    }
+
+    #[test]
+    fn test_inherited_markers_block_the_merge() -> io::Result<()> {
+        let svg = r##"<svg><g marker-start="url(#m)"><path d="M0 0h1"/><path d="M2 0h1"/></g><g style="marker-end:url(#m)"><path d="M0 5h1"/><path d="M2 5h1"/></g><g marker="url(#m)"><g marker-mid="none"><path d="M0 9h1"/><path d="M2 9h1"/></g></g></svg>"##;
+
+        assert_eq!(optimize_tree_with(MergePathsPlugin::new(), svg)?, svg);
+
+        let unset = r#"<svg><g marker-end="none"><path d="M0 0h1"/><path d="M2 0h1"/></g></svg>"#;
+        assert_eq!(optimize_tree_with(MergePathsPlugin::new(), unset)?, r#"<svg><g marker-end="none"><path d="M0 0h1M2 0h1"/></g></svg>"#);
+        Ok(())
+    }
+}
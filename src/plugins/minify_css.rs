@@ -0,0 +1,303 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes};
+use quick_xml::escape::minimal_escape;
+use quick_xml::events::{BytesCData, BytesEnd, BytesStart, BytesText};
+use std::io;
+
+/// Characters a space next to them never matters for, on either side.
+const TIGHT: &[char] = &['{', '}', ';', ',', '>', '~'];
+
+/// Minifies the CSS of `<style>` elements and `style` attributes.
+///
+/// Comments are stripped, whitespace is collapsed and dropped where it
+/// doesn't separate anything, the last `;` of a block is removed, and so are
+/// empty rules such as `a{}`, including at-rules left empty by that.
+/// Quoted strings are kept as they are, and declarations are never
+/// reordered. Styles with a `type` other than `text/css` are left alone.
+pub struct MinifyCSSPlugin {
+    /// Open elements from the current `<style>` down; 0 outside of one.
+    depth: usize,
+    /// Whether the current `<style>` holds CSS.
+    css: bool,
+    styles_minified: usize,
+    attributes_minified: usize,
+    chars_saved: usize,
+}
+
+/// The name the plugin is also known by.
+pub type MinifyStylesPlugin = MinifyCSSPlugin;
+
+impl MinifyCSSPlugin {
+    pub fn new() -> Self {
+        Self {
+            depth: 0,
+            css: false,
+            styles_minified: 0,
+            attributes_minified: 0,
+            chars_saved: 0,
+        }
+    }
+
+    /// Whether content at the current position is style sheet text.
+    fn in_style(&self) -> bool {
+        self.depth == 1 && self.css
+    }
+
+    fn record(&mut self, before: usize, after: usize) {
+        if after < before {
+            self.styles_minified += 1;
+            self.chars_saved += before - after;
+        }
+    }
+}
+
+impl Default for MinifyCSSPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Minify a style sheet.
+pub(crate) fn minify_css(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    // Where the prelude of each open block starts, to drop the block if it
+    // turns out empty
+    let mut blocks: Vec<usize> = Vec::new();
+    let mut statement_start = 0;
+    let mut pending_space = false;
+
+    while let Some(c) = chars.next() {
+        if c == '/' && chars.peek() == Some(&'*') {
+            chars.next();
+            let mut previous = '\0';
+            for c in chars.by_ref() {
+                if previous == '*' && c == '/' {
+                    break;
+                }
+                previous = c;
+            }
+            continue;
+        }
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+
+        if pending_space {
+            // In `a :hover` the space is a descendant combinator, in
+            // `fill : red` it is nothing: a declaration ends before a block opens
+            let declaration_colon = c == ':'
+                && !blocks.is_empty()
+                && chars.clone().find(|&next| matches!(next, '{' | ';' | '}')) != Some('{');
+            let needed = out
+                .chars()
+                .last()
+                .is_some_and(|last| !TIGHT.contains(&last) && !matches!(last, ':' | '('))
+                && !TIGHT.contains(&c)
+                && c != ')'
+                && !declaration_colon;
+            if needed {
+                out.push(' ');
+            }
+            pending_space = false;
+        }
+
+        match c {
+            '"' | '\'' => {
+                out.push(c);
+                while let Some(next) = chars.next() {
+                    out.push(next);
+                    if next == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            out.push(escaped);
+                        }
+                    } else if next == c {
+                        break;
+                    }
+                }
+            }
+            '{' => {
+                out.push(c);
+                blocks.push(statement_start);
+                statement_start = out.len();
+            }
+            ';' => {
+                if !out.is_empty() && !out.ends_with(['{', ';']) {
+                    out.push(c);
+                }
+                statement_start = out.len();
+            }
+            '}' => {
+                if out.ends_with(';') {
+                    out.pop();
+                }
+                match blocks.pop() {
+                    Some(start) if out.ends_with('{') => out.truncate(start),
+                    _ => out.push(c),
+                }
+                statement_start = out.len();
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Minify the declarations of a `style` attribute, as in
+/// `fill:red;stroke:blue`. Returns `None` if they don't form a block on
+/// their own, such as with a stray `}`.
+pub(crate) fn minify_declarations(style: &str) -> Option<String> {
+    let block = minify_css(&format!("{{{}}}", style));
+    if block.is_empty() {
+        return Some(String::new());
+    }
+    let inner = block.strip_prefix('{')?.strip_suffix('}')?;
+    (!inner.contains(['{', '}'])).then(|| inner.to_string())
+}
+
+impl SVGPlugin for MinifyCSSPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.depth = 0;
+        self.css = false;
+        self.styles_minified = 0;
+        self.attributes_minified = 0;
+        self.chars_saved = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        if let Ok(Some(style)) = element.try_get_attribute("style") {
+            let style = style.unescape_value().map(|value| value.into_owned()).unwrap_or_default();
+            if let Some(minified) = minify_declarations(&style).filter(|minified| minified.len() < style.len()) {
+                self.attributes_minified += 1;
+                self.chars_saved += style.len() - minified.len();
+                let mut attrs = owned_attributes(element);
+                for (key, value) in &mut attrs {
+                    if key == "style" {
+                        *value = minified.clone();
+                    }
+                }
+                set_attributes(element, &attrs);
+            }
+        }
+
+        if self.depth > 0 {
+            self.depth += 1;
+        } else if element.local_name().as_ref() == b"style" {
+            self.depth = 1;
+            self.css = match element.try_get_attribute("type") {
+                Ok(Some(kind)) => kind.value.is_empty() || kind.value.as_ref() == b"text/css",
+                _ => true,
+            };
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_end<'a>(&mut self, end: BytesEnd<'a>) -> io::Result<Option<BytesEnd<'a>>> {
+        self.depth = self.depth.saturating_sub(1);
+        Ok(Some(end))
+    }
+
+    fn process_text<'a>(&mut self, text: BytesText<'a>) -> io::Result<Option<BytesText<'a>>> {
+        if !self.in_style() {
+            return Ok(Some(text));
+        }
+        let Ok(css) = text.unescape() else { return Ok(Some(text)) };
+        let minified = minimal_escape(minify_css(&css)).into_owned();
+        self.record(text.len(), minified.len());
+        if minified.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(BytesText::from_escaped(minified)))
+    }
+
+    fn process_cdata<'a>(&mut self, cdata: BytesCData<'a>) -> io::Result<Option<BytesCData<'a>>> {
+        if !self.in_style() {
+            return Ok(Some(cdata));
+        }
+        let minified = minify_css(&String::from_utf8_lossy(&cdata));
+        self.record(cdata.len(), minified.len());
+        if minified.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(BytesCData::new(minified)))
+    }
+
+    fn name(&self) -> &str {
+        "MinifyCSS"
+    }
+}
+
+impl PluginStatistics for MinifyCSSPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Style blocks minified", self.styles_minified.to_string()),
+            ("Style attributes minified", self.attributes_minified.to_string()),
+            ("Characters saved", self.chars_saved.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_minify_css() {
+        assert_eq!(
+            minify_css("/* icons */\n.a ,\n .b > c {\n  fill : red ;\n  stroke: blue;\n}\n"),
+            ".a,.b>c{fill:red;stroke:blue}"
+        );
+        assert_eq!(
+            minify_css("a :hover { margin: 0 auto; width: calc( 1px + 2px ) } @media print { a :focus { x : y } }"),
+            "a :hover{margin:0 auto;width:calc(1px + 2px)}@media print{a :focus{x:y}}"
+        );
+        assert_eq!(minify_css("b{content:\"a  /* b */  c\";;}"), "b{content:\"a  /* b */  c\"}");
+
+        // Empty rules go, and so do at-rules emptied by that
+        assert_eq!(minify_css(".a {} .b { } @media print { .c { /* x */ } } .d{x:1}"), ".d{x:1}");
+    }
+
+    #[test]
+    fn test_style_elements_are_minified() -> io::Result<()> {
+        let svg = "<svg><style>\n  .a > .b { fill: red; }\n  .c {}\n</style><text>  keep  this  </text><style><![CDATA[\n .d { fill: blue; }\n]]></style></svg>";
+
+        assert_eq!(
+            optimize_with(MinifyCSSPlugin::new(), svg)?,
+            "<svg><style>.a>.b{fill:red}</style><text>  keep  this  </text><style><![CDATA[.d{fill:blue}]]></style></svg>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_minify_declarations() {
+        assert_eq!(minify_declarations("fill: red; stroke:  blue;").as_deref(), Some("fill:red;stroke:blue"));
+        assert_eq!(
+            minify_declarations(" font-family : 'A  B' , serif ; /* x */ margin : 0 auto ").as_deref(),
+            Some("font-family:'A  B',serif;margin:0 auto")
+        );
+        assert_eq!(minify_declarations(" ; ").as_deref(), Some(""));
+        assert_eq!(minify_declarations("fill: red} a {x: y"), None);
+    }
+
+    #[test]
+    fn test_style_attributes_are_minified() -> io::Result<()> {
+        let svg = r#"<svg><rect style="fill: red; stroke:  blue;" width="1"/><text style="font-family: &quot;A  B&quot;;">x</text><g style="fill:red"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(MinifyCSSPlugin::new(), svg)?,
+            r#"<svg><rect style="fill:red;stroke:blue" width="1"/><text style="font-family:&quot;A  B&quot;">x</text><g style="fill:red"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_other_style_languages_are_kept() -> io::Result<()> {
+        let svg = "<svg><style type=\"text/less\">\n  .a { }\n</style></svg>";
+        assert_eq!(optimize_with(MinifyCSSPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+}
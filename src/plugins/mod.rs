@@ -1,5 +1,85 @@
-mod traits;
+pub(crate) mod traits;
+pub(crate) mod util;
 mod path;
+mod transform;
+mod dedupe_gradients;
+mod dedupe_paths;
+mod merge_paths;
+mod inline_styles;
+mod inline_stylesheet;
+mod minify_css;
+mod presentation_to_style;
+mod remove_id;
+mod remove_data;
+mod remove_comments;
+mod collapse_groups;
+mod remove_invisible;
+mod remove_default_attrs;
+mod remove_metadata;
+mod shape_to_path;
+mod remove_doctype;
+mod remove_unused_defs;
+mod remove_xml_proc_inst;
+mod remove_editor_ns;
+mod convert_colors;
+mod remove_title_desc;
+mod remove_empty_attrs;
+mod normalize_colors;
+mod clean_namespaces;
+mod sort_attrs;
+mod cleanup_numeric_values;
+mod optimize_transforms;
+mod remove_useless_paint;
+mod remove_unused_css;
+mod normalize_text_whitespace;
+mod remove_scripts;
+mod collapse_whitespace;
+mod remove_empty_containers;
+mod remove_hidden_elems;
+mod remove_dimensions;
+mod remove_attrs;
+mod cleanup_ids;
+mod prefix_ids;
+mod remove_external_refs;
 
-pub use traits::SVGPlugin;
-pub use path::PathOptimizerPlugin;
+pub use traits::{ConfigurablePlugin, ElementAction, PluginStatistics, SVGPlugin, TreePlugin};
+pub use path::{path_bounding_box, BoundingBox, PathCommand, PathOptimizerPlugin, PathParseError, PathParser};
+pub use dedupe_gradients::DeduplicateGradientsPlugin;
+pub use dedupe_paths::DeduplicatePathsPlugin;
+pub use merge_paths::MergePathsPlugin;
+pub use inline_styles::InlineStylesPlugin;
+pub use inline_stylesheet::InlineStylesheetPlugin;
+pub use minify_css::{MinifyCSSPlugin, MinifyStylesPlugin};
+pub use presentation_to_style::PresentationToStylePlugin;
+pub use remove_id::RemoveIDPlugin;
+pub use remove_data::RemoveDataAttributesPlugin;
+pub use remove_comments::RemoveCommentsPlugin;
+pub use collapse_groups::CollapseGroupsPlugin;
+pub use remove_invisible::RemoveInvisiblePlugin;
+pub use remove_default_attrs::{RemoveDefaultAttributesPlugin, DEFAULT_ATTRIBUTES};
+pub use remove_metadata::RemoveMetadataPlugin;
+pub use shape_to_path::{ConvertShapeToPathPlugin, Shape, ShapeToPathPlugin};
+pub use remove_doctype::RemoveDoctypePlugin;
+pub use remove_unused_defs::RemoveUnusedDefsPlugin;
+pub use remove_xml_proc_inst::RemoveXmlProcInstPlugin;
+pub use remove_editor_ns::RemoveEditorNamespacesPlugin;
+pub use convert_colors::ConvertColorsPlugin;
+pub use remove_title_desc::RemoveTitleDescPlugin;
+pub use remove_empty_attrs::{RemoveEmptyAttributesPlugin, RemoveEmptyAttrsPlugin};
+pub use normalize_colors::NormalizeColorsPlugin;
+pub use clean_namespaces::CleanNamespacesPlugin;
+pub use sort_attrs::{SortAttrsPlugin, DEFAULT_ORDER};
+pub use cleanup_numeric_values::CleanupNumericValuesPlugin;
+pub use optimize_transforms::OptimizeTransformsPlugin;
+pub use remove_useless_paint::RemoveUselessPaintPlugin;
+pub use remove_unused_css::RemoveUnusedCSSPlugin;
+pub use normalize_text_whitespace::NormalizeTextWhitespacePlugin;
+pub use remove_scripts::RemoveScriptsPlugin;
+pub use collapse_whitespace::CollapseWhitespacePlugin;
+pub use remove_empty_containers::RemoveEmptyContainersPlugin;
+pub use remove_hidden_elems::RemoveHiddenElemsPlugin;
+pub use remove_dimensions::RemoveDimensionsPlugin;
+pub use remove_attrs::RemoveAttrsPlugin;
+pub use cleanup_ids::CleanupIdsPlugin;
+pub use prefix_ids::PrefixIdsPlugin;
+pub use remove_external_refs::RemoveExternalRefsPlugin;
@@ -0,0 +1,135 @@
+use crate::plugins::convert_colors::shorten_color;
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes, style_declarations};
+use quick_xml::events::BytesStart;
+use std::io;
+
+/// Properties whose value is a color, as attributes or in `style`.
+const COLOR_PROPERTIES: &[&str] = &["fill", "stroke", "stop-color"];
+
+/// Color keywords that aren't fixed colors and are never rewritten.
+const SPECIAL_COLORS: &[&str] = &["currentColor", "none", "inherit", "transparent"];
+
+/// Rewrites every color of `fill`, `stroke` and `stop-color` to one
+/// canonical, shortest form.
+///
+/// Named colors, hex values, `rgb()` and `hsl()` all become lowercase
+/// `#rrggbb`, `#rgb` when both digits of every channel match, or a color
+/// keyword when that is shorter still. Declarations inside `style`
+/// attributes are normalized the same way. `currentColor`, `none`,
+/// `inherit` and `transparent` are kept as they are.
+pub struct NormalizeColorsPlugin {
+    colors_normalized: usize,
+}
+
+impl NormalizeColorsPlugin {
+    pub fn new() -> Self {
+        Self { colors_normalized: 0 }
+    }
+
+    fn normalize(&mut self, value: &str) -> Option<String> {
+        if SPECIAL_COLORS.iter().any(|special| special.eq_ignore_ascii_case(value.trim())) {
+            return None;
+        }
+        let normalized = shorten_color(value, true, true)?;
+        self.colors_normalized += 1;
+        Some(normalized)
+    }
+
+    fn normalize_style(&mut self, style: &str) -> Option<String> {
+        let mut changed = false;
+        let declarations: Vec<String> = style_declarations(style)
+            .map(|(property, value)| {
+                let normalized = COLOR_PROPERTIES
+                    .contains(&property)
+                    .then(|| self.normalize(value))
+                    .flatten();
+                changed |= normalized.is_some();
+                format!("{}:{}", property, normalized.as_deref().unwrap_or(value))
+            })
+            .collect();
+
+        changed.then(|| declarations.join(";"))
+    }
+}
+
+impl Default for NormalizeColorsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for NormalizeColorsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.colors_normalized = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let mut attrs = owned_attributes(element);
+        let mut changed = false;
+
+        for (key, value) in &mut attrs {
+            let normalized = match key.as_str() {
+                "style" => self.normalize_style(value),
+                key if COLOR_PROPERTIES.contains(&key) => self.normalize(value),
+                _ => None,
+            };
+            if let Some(normalized) = normalized {
+                *value = normalized;
+                changed = true;
+            }
+        }
+
+        if changed {
+            set_attributes(element, &attrs);
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn name(&self) -> &str {
+        "NormalizeColors"
+    }
+}
+
+impl PluginStatistics for NormalizeColorsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Colors normalized", self.colors_normalized.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_colors_are_normalized() -> io::Result<()> {
+        let svg = r##"<svg><rect fill="#FF0000" stroke="lime"/><stop stop-color="hsl(0, 0%, 100%)"/><path fill="rgb(18, 52, 86)" stroke="#AbC"/></svg>"##;
+
+        assert_eq!(
+            optimize_with(NormalizeColorsPlugin::new(), svg)?,
+            r##"<svg><rect fill="red" stroke="#0f0"/><stop stop-color="#fff"/><path fill="#123456" stroke="#abc"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_style_colors_are_normalized() -> io::Result<()> {
+        let svg = r##"<svg><rect style="fill: #FFFFFF; stroke-width: 2; stroke: rgb(0,0,255)"/></svg>"##;
+
+        assert_eq!(
+            optimize_with(NormalizeColorsPlugin::new(), svg)?,
+            r##"<svg><rect style="fill:#fff;stroke-width:2;stroke:#00f"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_special_colors_are_kept() -> io::Result<()> {
+        let svg = r##"<svg><rect fill="currentColor" stroke="none"/><rect fill="inherit" stroke="transparent" style="fill:none"/><rect fill="url(#g)" color="#FFFFFF"/></svg>"##;
+
+        assert_eq!(optimize_with(NormalizeColorsPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+//! Collapses the whitespace of text content the way a renderer would.
+//!
+//! Whether trailing whitespace can go depends on the text that follows it,
+//! possibly in a later `<tspan>`, so the plugin runs on the document tree.
+
+use crate::plugins::traits::{PluginStatistics, TreePlugin};
+use crate::tree::{Document, Element, Node};
+use std::io;
+
+/// Normalizes the whitespace inside `<text>` elements.
+///
+/// Runs of whitespace become a single space, and whitespace at the start
+/// and end of the text as a whole is trimmed, including where it falls in
+/// a nested `<tspan>` or `<textPath>`. A space is dropped only where it
+/// would collapse into a neighbouring one anyway, so the words stay
+/// separated across element boundaries.
+///
+/// Content with `xml:space="preserve"`, and `<pre>` elements, are left as
+/// they are.
+pub struct NormalizeTextWhitespacePlugin {
+    text_nodes_normalized: usize,
+    chars_removed: usize,
+}
+
+impl NormalizeTextWhitespacePlugin {
+    pub fn new() -> Self {
+        Self {
+            text_nodes_normalized: 0,
+            chars_removed: 0,
+        }
+    }
+
+    fn process_nodes(&mut self, nodes: &mut [Node], preserve: bool) {
+        for node in nodes {
+            if let Node::Element(element) = node {
+                let preserve = preserves_space(element, preserve);
+                if element.name == "text" && !preserve {
+                    self.normalize(element);
+                } else {
+                    self.process_nodes(&mut element.children, preserve);
+                }
+            }
+        }
+    }
+
+    fn normalize(&mut self, text: &mut Element) {
+        let mut segments = Vec::new();
+        collect_segments(&mut text.children, false, &mut segments);
+
+        let before: Vec<usize> = segments.iter().flatten().map(|text| text.len()).collect();
+
+        // Collapse runs, and leading whitespace after a space or at the start
+        let mut after_space = true;
+        for segment in &mut segments {
+            let Some(text) = segment else {
+                after_space = false;
+                continue;
+            };
+            let mut collapsed = String::with_capacity(text.len());
+            for c in text.chars() {
+                if !c.is_ascii_whitespace() {
+                    collapsed.push(c);
+                    after_space = false;
+                } else if !after_space {
+                    collapsed.push(' ');
+                    after_space = true;
+                }
+            }
+            **text = collapsed;
+        }
+
+        // Trailing whitespace, until the last text that isn't only a space
+        for segment in segments.iter_mut().rev() {
+            let Some(text) = segment else { break };
+            let trimmed = text.trim_end_matches(' ').len();
+            text.truncate(trimmed);
+            if !text.is_empty() {
+                break;
+            }
+        }
+
+        for (text, before) in segments.iter().flatten().zip(before) {
+            if text.len() < before {
+                self.text_nodes_normalized += 1;
+                self.chars_removed += before - text.len();
+            }
+        }
+        remove_empty_text(&mut text.children);
+    }
+}
+
+impl Default for NormalizeTextWhitespacePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether whitespace inside `element` must be kept, given whether its
+/// parent's must.
+fn preserves_space(element: &Element, inherited: bool) -> bool {
+    if element.name == "pre" {
+        return true;
+    }
+    match element.attr("xml:space") {
+        Some("preserve") => true,
+        Some("default") => false,
+        _ => inherited,
+    }
+}
+
+/// The text nodes under `nodes` in document order. Content that must not
+/// change is `None`, and stops whitespace from collapsing across it.
+fn collect_segments<'a>(nodes: &'a mut [Node], preserve: bool, segments: &mut Vec<Option<&'a mut String>>) {
+    for node in nodes {
+        match node {
+            Node::Text(text) if !preserve => segments.push(Some(text)),
+            Node::Text(_) | Node::CData(_) => segments.push(None),
+            Node::Element(element) => {
+                let preserve = preserves_space(element, preserve);
+                collect_segments(&mut element.children, preserve, segments);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn remove_empty_text(nodes: &mut Vec<Node>) {
+    nodes.retain(|node| !matches!(node, Node::Text(text) if text.is_empty()));
+    for node in nodes {
+        if let Node::Element(element) = node {
+            remove_empty_text(&mut element.children);
+        }
+    }
+}
+
+impl TreePlugin for NormalizeTextWhitespacePlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.text_nodes_normalized = 0;
+        self.chars_removed = 0;
+        Ok(())
+    }
+
+    fn process_tree(&mut self, document: &mut Document) -> io::Result<()> {
+        self.process_nodes(&mut document.children, false);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "NormalizeTextWhitespace"
+    }
+}
+
+impl PluginStatistics for NormalizeTextWhitespacePlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Text nodes normalized", self.text_nodes_normalized.to_string()),
+            ("Characters removed", self.chars_removed.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_tree_with;
+
+    #[test]
+    fn test_whitespace_is_collapsed_and_trimmed() -> io::Result<()> {
+        let svg = "<svg>\n  <text x=\"1\">\n    Hello\t\n    <tspan> big </tspan>\n    world\n  </text>\n</svg>";
+
+        assert_eq!(
+            optimize_tree_with(NormalizeTextWhitespacePlugin::new(), svg)?,
+            "<svg>\n  <text x=\"1\">Hello <tspan>big </tspan>world</text>\n</svg>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_trailing_whitespace_in_nested_elements() -> io::Result<()> {
+        let svg = "<svg><text>  a  <tspan>b  </tspan>  </text><text><tspan>  </tspan></text></svg>";
+
+        assert_eq!(optimize_tree_with(NormalizeTextWhitespacePlugin::new(), svg)?, "<svg><text>a <tspan>b</tspan></text><text><tspan/></text></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_preserved_whitespace_is_kept() -> io::Result<()> {
+        let svg = "<svg><text xml:space=\"preserve\">  a   b  </text><text> c  <tspan xml:space=\"preserve\">  d  </tspan>  e </text></svg>";
+
+        assert_eq!(
+            optimize_tree_with(NormalizeTextWhitespacePlugin::new(), svg)?,
+            "<svg><text xml:space=\"preserve\">  a   b  </text><text>c <tspan xml:space=\"preserve\">  d  </tspan> e</text></svg>"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+//! Shortens `transform` attributes and optionally pushes group transforms
+//! down to the group's children.
+//!
+//! Moving a transform from a group onto its children needs the children at
+//! hand, so the plugin runs on the document tree.
+
+use crate::plugins::traits::{PluginStatistics, TreePlugin};
+use crate::plugins::transform::{parse_transform, Matrix};
+use crate::tree::{Document, Element, Node};
+use std::io;
+
+/// Attributes holding a transform list.
+const TRANSFORM_ATTRIBUTES: &[&str] = &["transform", "gradientTransform", "patternTransform"];
+
+/// Elements a group transform can be moved onto without changing their
+/// meaning.
+const TRANSFORMABLE_ELEMENTS: &[&str] = &[
+    "circle", "ellipse", "g", "image", "line", "path", "polygon", "polyline", "rect", "text",
+    "use",
+];
+
+/// Rewrites transform lists to the shortest single transform.
+///
+/// An identity transform such as `translate(0,0)` or `scale(1)` is removed.
+/// A chain such as `translate(10) rotate(45) scale(2)` is composed into one
+/// matrix and written as `translate()`, `scale()`, `rotate()` or `matrix()`,
+/// whichever is shortest; the original is kept if it is shorter still.
+///
+/// With propagation enabled, a `<g>` whose only attribute is its transform
+/// passes the transform on to its children, so a later group collapse can
+/// remove the group.
+pub struct OptimizeTransformsPlugin {
+    propagate: bool,
+    transforms_removed: usize,
+    transforms_collapsed: usize,
+    transforms_propagated: usize,
+}
+
+impl OptimizeTransformsPlugin {
+    pub fn new() -> Self {
+        Self {
+            propagate: false,
+            transforms_removed: 0,
+            transforms_collapsed: 0,
+            transforms_propagated: 0,
+        }
+    }
+
+    /// Whether to move group transforms onto the group's children.
+    pub fn with_propagate(mut self, propagate: bool) -> Self {
+        self.propagate = propagate;
+        self
+    }
+
+    fn optimize(&mut self, nodes: &mut [Node]) {
+        for node in nodes {
+            if let Node::Element(element) = node {
+                self.optimize_attributes(element);
+                if self.propagate {
+                    self.propagate_transform(element);
+                }
+                self.optimize(&mut element.children);
+            }
+        }
+    }
+
+    fn optimize_attributes(&mut self, element: &mut Element) {
+        element.attrs.retain_mut(|(key, value)| {
+            if !TRANSFORM_ATTRIBUTES.contains(&key.as_str()) {
+                return true;
+            }
+            let Some(matrix) = parse_transform(value) else { return true };
+
+            let optimized = matrix.to_transform_string();
+            if optimized.is_empty() {
+                self.transforms_removed += 1;
+                return false;
+            }
+            if optimized.len() < value.len() {
+                self.transforms_collapsed += 1;
+                *value = optimized;
+            }
+            true
+        });
+    }
+
+    /// Move the transform of a group with no other attributes onto its
+    /// children, composing it with their own transforms.
+    fn propagate_transform(&mut self, group: &mut Element) {
+        if group.name != "g" {
+            return;
+        }
+        let [(key, value)] = group.attrs.as_slice() else { return };
+        if key != "transform" {
+            return;
+        }
+        let Some(matrix) = parse_transform(value) else { return };
+
+        let movable = group.children.iter().all(|node| match node {
+            Node::Element(child) => {
+                TRANSFORMABLE_ELEMENTS.contains(&child.name.as_str())
+                    && child.attr("transform").is_none_or(|transform| parse_transform(transform).is_some())
+            }
+            Node::Text(text) => text.trim().is_empty(),
+            Node::Comment(_) => true,
+            _ => false,
+        });
+        if !movable {
+            return;
+        }
+
+        for node in &mut group.children {
+            if let Node::Element(child) = node {
+                let own = child.attr("transform").and_then(parse_transform).unwrap_or(Matrix::IDENTITY);
+                let combined = matrix.multiply(&own).to_transform_string();
+                child.attrs.retain(|(key, _)| key != "transform");
+                if !combined.is_empty() {
+                    child.attrs.push(("transform".to_string(), combined));
+                }
+            }
+        }
+        group.attrs.clear();
+        self.transforms_propagated += 1;
+    }
+}
+
+impl Default for OptimizeTransformsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreePlugin for OptimizeTransformsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.transforms_removed = 0;
+        self.transforms_collapsed = 0;
+        self.transforms_propagated = 0;
+        Ok(())
+    }
+
+    fn process_tree(&mut self, document: &mut Document) -> io::Result<()> {
+        self.optimize(&mut document.children);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "OptimizeTransforms"
+    }
+}
+
+impl PluginStatistics for OptimizeTransformsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Identity transforms removed", self.transforms_removed.to_string()),
+            ("Transforms collapsed", self.transforms_collapsed.to_string()),
+            ("Group transforms propagated", self.transforms_propagated.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_tree_with;
+
+    #[test]
+    fn test_identity_transforms_are_removed() -> io::Result<()> {
+        let svg = r#"<svg><rect transform="translate(0,0)"/><circle transform="scale(1) rotate(0)"/><linearGradient gradientTransform="matrix(1 0 0 1 0 0)"/></svg>"#;
+
+        assert_eq!(
+            optimize_tree_with(OptimizeTransformsPlugin::new(), svg)?,
+            "<svg><rect/><circle/><linearGradient/></svg>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_chains_are_collapsed() -> io::Result<()> {
+        let svg = r#"<svg><rect transform="translate(10, 0) translate(5, 5)"/><path transform="translate(10 20) scale(2, 2)"/><use transform="rotate(45)"/><g transform="bogus(1)"/></svg>"#;
+
+        assert_eq!(
+            optimize_tree_with(OptimizeTransformsPlugin::new(), svg)?,
+            r#"<svg><rect transform="translate(15 5)"/><path transform="matrix(2 0 0 2 10 20)"/><use transform="rotate(45)"/><g transform="bogus(1)"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_transforms_are_propagated() -> io::Result<()> {
+        let svg = r#"<svg><g transform="translate(10)">
+  <rect/><path transform="scale(2)"/></g><g transform="scale(2)" fill="red"><rect/></g><g transform="scale(2)"><title>t</title><rect/></g></svg>"#;
+
+        let output = optimize_tree_with(OptimizeTransformsPlugin::new(), svg)?;
+        assert_eq!(output, svg);
+
+        let output = optimize_tree_with(OptimizeTransformsPlugin::new().with_propagate(true), svg)?;
+        assert_eq!(
+            output,
+            r#"<svg><g>
+  <rect transform="translate(10)"/><path transform="matrix(2 0 0 2 10 0)"/></g><g transform="scale(2)" fill="red"><rect/></g><g transform="scale(2)"><title>t</title><rect/></g></svg>"#
+        );
+        Ok(())
+    }
+}
@@ -1,86 +1,1077 @@
-use crate::plugins::traits::{PluginStatistics, SVGPlugin};
+use crate::diagnostics::{Diagnostic, DiagnosticsCollector};
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
 use quick_xml::events::BytesStart;
+use std::error::Error;
+use std::fmt;
 use std::io;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// Number of parameters of one elliptical arc segment.
+const ARC_PARAMETERS: usize = 7;
 
 pub struct PathOptimizerPlugin {
     decimal_places: usize,
+    relative: bool,
+    shortest: bool,
+    collapse_repeated: bool,
     path_count: usize,
     total_chars_saved: usize,
+    segments_removed: usize,
+    /// The union of the bounding boxes of all path data, ignoring
+    /// transforms.
+    bounds: Option<BoundingBox>,
+    diagnostics: DiagnosticsCollector,
+}
+
+/// One command of path data with a single group of parameters, so the
+/// implicit repeat in `L1 2 3 4` makes two `LineTo`s. `abs` tells the
+/// absolute form, as in `L`, from the relative one, as in `l`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PathCommand {
+    MoveTo { abs: bool, x: f64, y: f64 },
+    LineTo { abs: bool, x: f64, y: f64 },
+    HorizLineTo { abs: bool, x: f64 },
+    VertLineTo { abs: bool, y: f64 },
+    CurveTo { abs: bool, x1: f64, y1: f64, x2: f64, y2: f64, x: f64, y: f64 },
+    SmoothCurveTo { abs: bool, x2: f64, y2: f64, x: f64, y: f64 },
+    QuadBezierTo { abs: bool, x1: f64, y1: f64, x: f64, y: f64 },
+    SmoothQuadBezierTo { abs: bool, x: f64, y: f64 },
+    ArcTo {
+        abs: bool,
+        rx: f64,
+        ry: f64,
+        x_axis_rotation: f64,
+        large_arc: bool,
+        sweep: bool,
+        x: f64,
+        y: f64,
+    },
+    ClosePath { abs: bool },
+}
+
+/// What a parameter of a command measures, which decides how it changes
+/// between the absolute and relative forms.
+#[derive(Clone, Copy, PartialEq)]
+enum Axis {
+    X,
+    Y,
+    /// Radii and angles, which are the same in both forms.
+    Other,
+}
+
+impl PathCommand {
+    /// A command from its letter and one group of parameters. Returns
+    /// `None` for an unknown letter, the wrong number of parameters or arc
+    /// flags other than 0 and 1.
+    fn from_params(letter: char, params: &[f64]) -> Option<Self> {
+        use PathCommand::*;
+        let abs = letter.is_ascii_uppercase();
+        let flag = |value: f64| (value == 0.0 || value == 1.0).then_some(value == 1.0);
+        Some(match (letter.to_ascii_uppercase(), params) {
+            ('M', &[x, y]) => MoveTo { abs, x, y },
+            ('L', &[x, y]) => LineTo { abs, x, y },
+            ('H', &[x]) => HorizLineTo { abs, x },
+            ('V', &[y]) => VertLineTo { abs, y },
+            ('C', &[x1, y1, x2, y2, x, y]) => CurveTo { abs, x1, y1, x2, y2, x, y },
+            ('S', &[x2, y2, x, y]) => SmoothCurveTo { abs, x2, y2, x, y },
+            ('Q', &[x1, y1, x, y]) => QuadBezierTo { abs, x1, y1, x, y },
+            ('T', &[x, y]) => SmoothQuadBezierTo { abs, x, y },
+            ('A', &[rx, ry, x_axis_rotation, large_arc, sweep, x, y]) => ArcTo {
+                abs,
+                rx,
+                ry,
+                x_axis_rotation,
+                large_arc: flag(large_arc)?,
+                sweep: flag(sweep)?,
+                x,
+                y,
+            },
+            ('Z', &[]) => ClosePath { abs },
+            _ => return None,
+        })
+    }
+
+    /// The command letter, uppercase for the absolute form.
+    pub fn letter(&self) -> char {
+        use PathCommand::*;
+        let letter = match self {
+            MoveTo { .. } => 'm',
+            LineTo { .. } => 'l',
+            HorizLineTo { .. } => 'h',
+            VertLineTo { .. } => 'v',
+            CurveTo { .. } => 'c',
+            SmoothCurveTo { .. } => 's',
+            QuadBezierTo { .. } => 'q',
+            SmoothQuadBezierTo { .. } => 't',
+            ArcTo { .. } => 'a',
+            ClosePath { .. } => 'z',
+        };
+        if self.is_absolute() { letter.to_ascii_uppercase() } else { letter }
+    }
+
+    pub fn is_absolute(&self) -> bool {
+        let mut command = *self;
+        *command.abs_mut()
+    }
+
+    fn abs_mut(&mut self) -> &mut bool {
+        use PathCommand::*;
+        match self {
+            MoveTo { abs, .. }
+            | LineTo { abs, .. }
+            | HorizLineTo { abs, .. }
+            | VertLineTo { abs, .. }
+            | CurveTo { abs, .. }
+            | SmoothCurveTo { abs, .. }
+            | QuadBezierTo { abs, .. }
+            | SmoothQuadBezierTo { abs, .. }
+            | ArcTo { abs, .. }
+            | ClosePath { abs } => abs,
+        }
+    }
+
+    /// The parameters in the order they are written, arc flags as 0 or 1.
+    pub fn params(&self) -> Vec<f64> {
+        use PathCommand::*;
+        match *self {
+            MoveTo { x, y, .. } | LineTo { x, y, .. } | SmoothQuadBezierTo { x, y, .. } => vec![x, y],
+            HorizLineTo { x, .. } => vec![x],
+            VertLineTo { y, .. } => vec![y],
+            CurveTo { x1, y1, x2, y2, x, y, .. } => vec![x1, y1, x2, y2, x, y],
+            SmoothCurveTo { x2, y2, x, y, .. } => vec![x2, y2, x, y],
+            QuadBezierTo { x1, y1, x, y, .. } => vec![x1, y1, x, y],
+            ArcTo { rx, ry, x_axis_rotation, large_arc, sweep, x, y, .. } => {
+                vec![rx, ry, x_axis_rotation, f64::from(u8::from(large_arc)), f64::from(u8::from(sweep)), x, y]
+            }
+            ClosePath { .. } => Vec::new(),
+        }
+    }
+
+    /// The end point as written, for commands that have both coordinates.
+    fn target(&self) -> Option<(f64, f64)> {
+        use PathCommand::*;
+        match *self {
+            MoveTo { x, y, .. }
+            | LineTo { x, y, .. }
+            | CurveTo { x, y, .. }
+            | SmoothCurveTo { x, y, .. }
+            | QuadBezierTo { x, y, .. }
+            | SmoothQuadBezierTo { x, y, .. }
+            | ArcTo { x, y, .. } => Some((x, y)),
+            HorizLineTo { .. } | VertLineTo { .. } | ClosePath { .. } => None,
+        }
+    }
+
+    /// Apply `f` to every numeric parameter, told what it measures.
+    fn map_values(self, f: impl Fn(Axis, f64) -> f64) -> Self {
+        use Axis::*;
+        use PathCommand::*;
+        match self {
+            MoveTo { abs, x, y } => MoveTo { abs, x: f(X, x), y: f(Y, y) },
+            LineTo { abs, x, y } => LineTo { abs, x: f(X, x), y: f(Y, y) },
+            HorizLineTo { abs, x } => HorizLineTo { abs, x: f(X, x) },
+            VertLineTo { abs, y } => VertLineTo { abs, y: f(Y, y) },
+            CurveTo { abs, x1, y1, x2, y2, x, y } => CurveTo {
+                abs,
+                x1: f(X, x1),
+                y1: f(Y, y1),
+                x2: f(X, x2),
+                y2: f(Y, y2),
+                x: f(X, x),
+                y: f(Y, y),
+            },
+            SmoothCurveTo { abs, x2, y2, x, y } => SmoothCurveTo { abs, x2: f(X, x2), y2: f(Y, y2), x: f(X, x), y: f(Y, y) },
+            QuadBezierTo { abs, x1, y1, x, y } => QuadBezierTo { abs, x1: f(X, x1), y1: f(Y, y1), x: f(X, x), y: f(Y, y) },
+            SmoothQuadBezierTo { abs, x, y } => SmoothQuadBezierTo { abs, x: f(X, x), y: f(Y, y) },
+            ArcTo { abs, rx, ry, x_axis_rotation, large_arc, sweep, x, y } => ArcTo {
+                abs,
+                rx: f(Other, rx),
+                ry: f(Other, ry),
+                x_axis_rotation: f(Other, x_axis_rotation),
+                large_arc,
+                sweep,
+                x: f(X, x),
+                y: f(Y, y),
+            },
+            ClosePath { abs } => ClosePath { abs },
+        }
+    }
+
+    /// The command in its absolute or relative form as seen from `cursor`,
+    /// rounded to `decimal_places`.
+    fn converted(self, abs: bool, cursor: &PathCursor, decimal_places: usize) -> Self {
+        // Add the current point to go absolute, subtract it to go relative
+        let (dx, dy) = match (self.is_absolute(), abs) {
+            (false, true) => (cursor.x, cursor.y),
+            (true, false) => (-cursor.x, -cursor.y),
+            _ => (0.0, 0.0),
+        };
+        let mut converted = self.map_values(|axis, value| {
+            let offset = match axis {
+                Axis::X => dx,
+                Axis::Y => dy,
+                Axis::Other => 0.0,
+            };
+            round(value + offset, decimal_places)
+        });
+        *converted.abs_mut() = abs;
+        converted
+    }
+
+    /// Write commands as compact path data, with numbers rounded to
+    /// `decimal_places`. A command letter is left out where the previous
+    /// command continues into it, as in `M0 0 10 10` for a moveto and a
+    /// lineto.
+    pub fn serialize(commands: &[PathCommand], decimal_places: usize) -> String {
+        let segments = commands.iter().map(|&command| PathSegment { commands: vec![command] }).collect();
+        write_path_data(&merge_implicit_linetos(merge_repeated_commands(segments)), decimal_places)
+    }
+}
+
+/// Why path data couldn't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathParseError {
+    /// Byte offset in the path data where the problem was found.
+    pub position: usize,
+    pub message: String,
+}
+
+impl PathParseError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self { position, message: message.into() }
+    }
+}
+
+impl fmt::Display for PathParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.position)
+    }
+}
+
+impl Error for PathParseError {}
+
+/// Parser turning the `d` attribute of a path into [`PathCommand`]s.
+///
+/// ```
+/// use svgo_rs::plugins::{PathCommand, PathParser};
+///
+/// let commands = PathParser::parse("M 10,20 l 5 5 5 -5")?;
+/// assert_eq!(commands[0], PathCommand::MoveTo { abs: true, x: 10.0, y: 20.0 });
+/// assert_eq!(commands.len(), 3);
+/// assert_eq!(PathCommand::serialize(&commands, 2), "M10 20l5 5 5-5");
+/// # Ok::<(), svgo_rs::plugins::PathParseError>(())
+/// ```
+pub struct PathParser;
+
+impl PathParser {
+    /// Parse path data into one command per parameter group. Fails on
+    /// anything that isn't valid path data, such as an unknown command, a
+    /// number before the first command or an incomplete parameter group.
+    pub fn parse(path_data: &str) -> Result<Vec<PathCommand>, PathParseError> {
+        Ok(parse_segments(path_data)?.into_iter().flat_map(|segment| segment.commands).collect())
+    }
+}
+
+/// The commands written after one command letter: `L1 2 3 4` is one
+/// segment of two `LineTo`s, and the pairs after a moveto are `LineTo`s
+/// continuing its segment.
+#[derive(Clone, Debug, PartialEq)]
+struct PathSegment {
+    commands: Vec<PathCommand>,
+}
+
+impl PathSegment {
+    /// The letter the segment is written with.
+    fn letter(&self) -> char {
+        self.commands.first().map_or('Z', PathCommand::letter)
+    }
+
+    /// The letter of a command that could follow on without one.
+    fn continuation(&self) -> char {
+        match self.letter() {
+            'M' => 'L',
+            'm' => 'l',
+            letter => letter,
+        }
+    }
+}
+
+/// The current point while walking path data, and the start of the current
+/// subpath that `Z` returns to.
+#[derive(Clone, Copy, Debug, Default)]
+struct PathCursor {
+    x: f64,
+    y: f64,
+    start_x: f64,
+    start_y: f64,
+}
+
+impl PathCursor {
+    /// Move past one command.
+    fn advance(&mut self, command: &PathCommand) {
+        let (base_x, base_y) = if command.is_absolute() { (0.0, 0.0) } else { (self.x, self.y) };
+        match *command {
+            PathCommand::ClosePath { .. } => {
+                self.x = self.start_x;
+                self.y = self.start_y;
+            }
+            PathCommand::HorizLineTo { x, .. } => self.x = base_x + x,
+            PathCommand::VertLineTo { y, .. } => self.y = base_y + y,
+            _ => {
+                let Some((x, y)) = command.target() else { return };
+                self.x = base_x + x;
+                self.y = base_y + y;
+                if matches!(command, PathCommand::MoveTo { .. }) {
+                    self.start_x = self.x;
+                    self.start_y = self.y;
+                }
+            }
+        }
+    }
+}
+
+/// Number of parameters in one group of a command.
+fn parameter_count(command: char) -> usize {
+    match command.to_ascii_lowercase() {
+        'm' | 'l' | 't' => 2,
+        'h' | 'v' => 1,
+        's' | 'q' => 4,
+        'c' => 6,
+        'a' => ARC_PARAMETERS,
+        _ => 0,
+    }
+}
+
+/// The command a parameter group stands for: pairs after a moveto are
+/// implicit linetos.
+fn group_command(command: char, group: usize) -> char {
+    match command {
+        'M' if group > 0 => 'L',
+        'm' if group > 0 => 'l',
+        _ => command,
+    }
+}
+
+/// Split path data into segments of commands.
+fn parse_segments(path_data: &str) -> Result<Vec<PathSegment>, PathParseError> {
+    tokenize(path_data)?
+        .into_iter()
+        .map(|(position, letter, params)| {
+            let count = parameter_count(letter);
+            let incomplete = if count == 0 { !params.is_empty() } else { params.is_empty() || params.len() % count != 0 };
+            if incomplete {
+                let message = match count {
+                    0 => format!("'{}' takes no parameters", letter),
+                    _ => format!("'{}' takes parameters in groups of {}", letter, count),
+                };
+                return Err(PathParseError::new(position, message));
+            }
+            let groups: Vec<&[f64]> = if count == 0 { vec![&[]] } else { params.chunks(count).collect() };
+            let commands = groups
+                .into_iter()
+                .enumerate()
+                .map(|(group, values)| {
+                    PathCommand::from_params(group_command(letter, group), values)
+                        .ok_or_else(|| PathParseError::new(position, format!("arc flags after '{}' must be 0 or 1", letter)))
+                })
+                .collect::<Result<_, _>>()?;
+            Ok(PathSegment { commands })
+        })
+        .collect()
+}
+
+/// Split path data into command letters with their position and the
+/// numbers written after them.
+fn tokenize(path_data: &str) -> Result<Vec<(usize, char, Vec<f64>)>, PathParseError> {
+    let mut segments: Vec<(usize, char, Vec<f64>)> = Vec::new();
+    let mut chars = path_data.char_indices().peekable();
+
+    while let Some((position, c)) = chars.next() {
+        match c {
+            'M' | 'm' | 'L' | 'l' | 'H' | 'h' | 'V' | 'v' |
+            'C' | 'c' | 'S' | 's' | 'Q' | 'q' | 'T' | 't' |
+            'A' | 'a' | 'Z' | 'z' => {
+                segments.push((position, c, Vec::new()));
+            },
+
+            '0'..='9' | '.' | '-' | '+' => {
+                let Some((_, command, params)) = segments.last_mut() else {
+                    return Err(PathParseError::new(position, "number before the first command"));
+                };
+
+                // The large-arc and sweep flags are single digits that may be
+                // written without separators, as in `A10 10 0 1020 20`
+                let is_flag = matches!(command, 'A' | 'a')
+                    && matches!(params.len() % ARC_PARAMETERS, 3 | 4);
+                if is_flag {
+                    let flag = c.to_digit(10).ok_or_else(|| PathParseError::new(position, "arc flags must be 0 or 1"))?;
+                    params.push(flag as f64);
+                    continue;
+                }
+
+                let mut number = String::new();
+                number.push(c);
+
+                while let Some(&(_, next)) = chars.peek() {
+                    match next {
+                        '0'..='9' => {}
+                        // A second decimal point starts the next number, as
+                        // does one after an exponent: `.5.5`, `1e2.5`
+                        '.' if !number.contains(['.', 'e', 'E']) => {}
+                        // An exponent is only taken with its digits, so `1e`
+                        // doesn't swallow what follows. Its sign is the only
+                        // one inside a number: `10-20` is two numbers
+                        'e' | 'E' if !number.contains(['e', 'E']) && starts_exponent(chars.clone()) => {
+                            number.push(next);
+                            chars.next();
+                            if let Some((_, sign)) = chars.next_if(|&(_, c)| c == '-' || c == '+') {
+                                number.push(sign);
+                            }
+                            continue;
+                        }
+                        _ => break,
+                    }
+                    number.push(next);
+                    chars.next();
+                }
+
+                let value = number
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .ok_or_else(|| PathParseError::new(position, format!("invalid number '{}'", number)))?;
+                params.push(value);
+            },
+
+            ' ' | ',' | '\t' | '\n' | '\r' => continue,
+
+            _ => return Err(PathParseError::new(position, format!("unexpected '{}'", c))),
+        }
+    }
+
+    Ok(segments)
+}
+
+/// Whether the characters start a complete exponent: `e` or `E`, an
+/// optional sign and at least one digit.
+fn starts_exponent(mut chars: Peekable<CharIndices>) -> bool {
+    chars.next();
+    chars.next_if(|&(_, c)| c == '-' || c == '+');
+    chars.peek().is_some_and(|(_, c)| c.is_ascii_digit())
+}
+
+/// The shorter of a formatted number and its exponent notation:
+/// `100000` becomes `1e5` and `.0001` becomes `1e-4`, while `1500` stays.
+fn shortest_notation(number: String) -> String {
+    let (sign, digits) = match number.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", number.as_str()),
+    };
+    let exponent = match digits.split_once('.') {
+        Some(("", fraction)) => {
+            let significant = fraction.trim_start_matches('0');
+            format!("{}{}e-{}", sign, significant, fraction.len())
+        }
+        Some(_) => return number,
+        None => {
+            let significant = digits.trim_end_matches('0');
+            if significant.is_empty() {
+                return number;
+            }
+            format!("{}{}e{}", sign, significant, digits.len() - significant.len())
+        }
+    };
+    if exponent.len() < number.len() { exponent } else { number }
+}
+
+/// Serialize segments, without leading zeros. Numbers are separated by a
+/// space, unless the next one starts with a minus sign, or with a decimal
+/// point while the previous one has a decimal point or an exponent: either
+/// separates them. Exponent notation is used where it is shorter.
+fn write_path_data(segments: &[PathSegment], decimal_places: usize) -> String {
+    let mut optimized = String::new();
+    for segment in segments {
+        optimized.push(segment.letter());
+        write_numbers(&mut optimized, segment.commands.iter().flat_map(PathCommand::params), decimal_places);
+    }
+    optimized
+}
+
+/// Serialize tokenized path data like [`write_path_data`], for data whose
+/// parameter groups don't add up and can only be shortened number by number.
+fn write_tokens(tokens: &[(usize, char, Vec<f64>)], decimal_places: usize) -> String {
+    let mut optimized = String::new();
+    for (_, letter, params) in tokens {
+        optimized.push(*letter);
+        write_numbers(&mut optimized, params.iter().copied(), decimal_places);
+    }
+    optimized
+}
+
+/// Append the numbers following a command letter.
+fn write_numbers(optimized: &mut String, params: impl Iterator<Item = f64>, decimal_places: usize) {
+    let mut previous = String::new();
+    for (index, param) in params.enumerate() {
+        let number = shortest_notation(strip_leading_zero(format_number(param, decimal_places)));
+        let separated = number.starts_with('-')
+            || (number.starts_with('.') && previous.contains(['.', 'e']));
+        if index > 0 && !separated {
+            optimized.push(' ');
+        }
+        optimized.push_str(&number);
+        previous = number;
+    }
+}
+
+/// Drop linetos that end where they start: `L x y` at the current point,
+/// `H x` at the current x and `V y` at the current y, relative ones of
+/// length zero included. A subpath made only of such segments still
+/// renders a dot with round or square caps, so one of them is kept. One
+/// right before `S` or `T` is kept too, since without it the smooth curve
+/// would reflect the control point of the curve before.
+///
+/// Returns the remaining segments and the number of commands removed.
+fn remove_zero_length(segments: &[PathSegment], decimal_places: usize) -> (Vec<PathSegment>, usize) {
+    use PathCommand::*;
+
+    // Per command: whether it is a zero-length lineto, and its subpath
+    let mut commands: Vec<(bool, usize)> = Vec::new();
+    let mut subpath_draws: Vec<bool> = vec![false];
+    let mut cursor = PathCursor::default();
+
+    for command in segments.iter().flat_map(|segment| &segment.commands) {
+        if let MoveTo { .. } = command {
+            subpath_draws.push(false);
+        }
+        let same = |value: f64, current: f64| round(value, decimal_places) == round(current, decimal_places);
+        let zero = |value: f64| round(value, decimal_places) == 0.0;
+        let zero_length = match *command {
+            LineTo { abs: true, x, y } => same(x, cursor.x) && same(y, cursor.y),
+            HorizLineTo { abs: true, x } => same(x, cursor.x),
+            VertLineTo { abs: true, y } => same(y, cursor.y),
+            LineTo { abs: false, x, y } => zero(x) && zero(y),
+            HorizLineTo { abs: false, x } => zero(x),
+            VertLineTo { abs: false, y } => zero(y),
+            _ => false,
+        };
+        let subpath = subpath_draws.len() - 1;
+        if !zero_length && !matches!(command, MoveTo { .. }) {
+            subpath_draws[subpath] = true;
+        }
+        if matches!(command, SmoothCurveTo { .. } | SmoothQuadBezierTo { .. }) {
+            if let Some((previous, _)) = commands.last_mut() {
+                *previous = false;
+            }
+        }
+        commands.push((zero_length, subpath));
+        cursor.advance(command);
+    }
+
+    let mut kept_dot = vec![false; subpath_draws.len()];
+    let mut commands = commands.into_iter();
+    let mut removed = 0;
+    let mut result = Vec::with_capacity(segments.len());
+
+    for segment in segments {
+        let mut kept = Vec::with_capacity(segment.commands.len());
+        for &command in &segment.commands {
+            let (zero_length, subpath) = commands.next().unwrap_or_default();
+            if zero_length && (subpath_draws[subpath] || kept_dot[subpath]) {
+                removed += 1;
+                continue;
+            }
+            kept_dot[subpath] |= zero_length;
+            kept.push(command);
+        }
+        if !kept.is_empty() {
+            result.push(PathSegment { commands: kept });
+        }
+    }
+
+    (result, removed)
+}
+
+/// Fold linetos into a preceding moveto, whose extra coordinate pairs are
+/// implicit linetos already: `M10 20L30 40` becomes `M10 20 30 40`.
+fn merge_implicit_linetos(segments: Vec<PathSegment>) -> Vec<PathSegment> {
+    let mut merged: Vec<PathSegment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if let Some(previous) = merged.last_mut() {
+            if matches!((previous.letter(), segment.letter()), ('M', 'L') | ('m', 'l')) {
+                previous.commands.extend(segment.commands);
+                continue;
+            }
+        }
+        merged.push(segment);
+    }
+    merged
+}
+
+/// Drop command letters repeating the previous command, whose parameters
+/// may simply follow on: `L1 1L2 2` becomes `L1 1 2 2`. A repeated moveto
+/// keeps its letter, since pairs after a moveto are linetos.
+fn merge_repeated_commands(segments: Vec<PathSegment>) -> Vec<PathSegment> {
+    let mut merged: Vec<PathSegment> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        if let Some(previous) = merged.last_mut() {
+            let repeatable = !matches!(segment.letter(), 'M' | 'm' | 'Z' | 'z');
+            if repeatable && previous.letter() == segment.letter() {
+                previous.commands.extend(segment.commands);
+                continue;
+            }
+        }
+        merged.push(segment);
+    }
+    merged
+}
+
+fn round(value: f64, decimal_places: usize) -> f64 {
+    format_number(value, decimal_places).parse().unwrap_or(value)
+}
+
+/// Rewrite every command as its relative form.
+///
+/// The cursor follows the rounded values that will be written, so rounding
+/// errors don't add up along the path.
+fn to_relative(segments: &[PathSegment], decimal_places: usize) -> Vec<PathSegment> {
+    let mut cursor = PathCursor::default();
+    segments
+        .iter()
+        .map(|segment| {
+            let commands = segment
+                .commands
+                .iter()
+                .map(|command| {
+                    let relative = command.converted(false, &cursor, decimal_places);
+                    cursor.advance(&relative);
+                    relative
+                })
+                .collect();
+            PathSegment { commands }
+        })
+        .collect()
+}
+
+/// Path data with every command made absolute and rounded, so that paths
+/// drawing the same shape compare equal however they were written: `M0 0
+/// l10 0` and `M0,0 L10.0001,0` give the same result. Returns `None` for
+/// data the parser doesn't understand.
+pub(crate) fn canonical_path_data(path_data: &str, decimal_places: usize) -> Option<String> {
+    let mut cursor = PathCursor::default();
+    let absolute: Vec<PathSegment> = PathParser::parse(path_data)
+        .ok()?
+        .into_iter()
+        .map(|command| {
+            let absolute = command.converted(true, &cursor, decimal_places);
+            cursor.advance(&absolute);
+            PathSegment { commands: vec![absolute] }
+        })
+        .collect();
+
+    Some(write_path_data(&absolute, decimal_places))
+}
+
+/// An axis-aligned box in user units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    fn point(x: f64, y: f64) -> Self {
+        Self { min_x: x, min_y: y, max_x: x, max_y: y }
+    }
+
+    fn include(&mut self, x: f64, y: f64) {
+        self.min_x = self.min_x.min(x);
+        self.min_y = self.min_y.min(y);
+        self.max_x = self.max_x.max(x);
+        self.max_y = self.max_y.max(y);
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    /// The smallest box containing both boxes.
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+}
+
+/// The tight bounding box of the geometry `commands` draw, ignoring stroke
+/// and transforms, or `None` if they draw nothing.
+///
+/// Curves and arcs are bounded by their extremes, not their control points,
+/// so `M0 0C0 10 10 10 10 0` gives a box 7.5 high rather than 10. A moveto
+/// only counts once something is drawn from it.
+///
+/// ```
+/// use svgo_rs::plugins::{path_bounding_box, PathParser};
+///
+/// let commands = PathParser::parse("M0 0Q5 10 10 0").unwrap();
+/// let bounds = path_bounding_box(&commands).unwrap();
+/// assert_eq!((bounds.min_x, bounds.min_y, bounds.max_x, bounds.max_y), (0.0, 0.0, 10.0, 5.0));
+/// ```
+pub fn path_bounding_box(commands: &[PathCommand]) -> Option<BoundingBox> {
+    use PathCommand::*;
+
+    let mut cursor = PathCursor::default();
+    let mut bounds: Option<BoundingBox> = None;
+    let mut include = |x: f64, y: f64| match &mut bounds {
+        Some(bounds) => bounds.include(x, y),
+        None => bounds = Some(BoundingBox::point(x, y)),
+    };
+    // The last control point of a cubic or quadratic curve, which `S` and
+    // `T` reflect through the current point
+    let mut control: Option<(char, f64, f64)> = None;
+
+    for command in commands {
+        let command = command.converted(true, &cursor, 9);
+        let (x0, y0) = (cursor.x, cursor.y);
+        let reflected = |kind: char| match control {
+            Some((previous, cx, cy)) if previous == kind => (2.0 * x0 - cx, 2.0 * y0 - cy),
+            _ => (x0, y0),
+        };
+        if !matches!(command, MoveTo { .. }) {
+            include(x0, y0);
+        }
+
+        let mut next_control = None;
+        match command {
+            MoveTo { .. } => {}
+            LineTo { x, y, .. } => include(x, y),
+            HorizLineTo { x, .. } => include(x, y0),
+            VertLineTo { y, .. } => include(x0, y),
+            ClosePath { .. } => {}
+            CurveTo { x1, y1, x2, y2, x, y, .. } => {
+                cubic_extremes([x0, x1, x2, x], [y0, y1, y2, y], &mut include);
+                next_control = Some(('C', x2, y2));
+            }
+            SmoothCurveTo { x2, y2, x, y, .. } => {
+                let (x1, y1) = reflected('C');
+                cubic_extremes([x0, x1, x2, x], [y0, y1, y2, y], &mut include);
+                next_control = Some(('C', x2, y2));
+            }
+            QuadBezierTo { x1, y1, x, y, .. } => {
+                quadratic_extremes([x0, x1, x], [y0, y1, y], &mut include);
+                next_control = Some(('Q', x1, y1));
+            }
+            SmoothQuadBezierTo { x, y, .. } => {
+                let (x1, y1) = reflected('Q');
+                quadratic_extremes([x0, x1, x], [y0, y1, y], &mut include);
+                next_control = Some(('Q', x1, y1));
+            }
+            ArcTo { .. } => arc_extremes((x0, y0), &command, &mut include),
+        }
+        control = next_control;
+        cursor.advance(&command);
+    }
+    bounds
+}
+
+/// Include the end point of a cubic Bézier curve and the points where it
+/// turns, at the roots of its derivative.
+fn cubic_extremes(xs: [f64; 4], ys: [f64; 4], include: &mut impl FnMut(f64, f64)) {
+    let at = |p: [f64; 4], t: f64| {
+        let s = 1.0 - t;
+        s * s * s * p[0] + 3.0 * s * s * t * p[1] + 3.0 * s * t * t * p[2] + t * t * t * p[3]
+    };
+    include(xs[3], ys[3]);
+    for p in [xs, ys] {
+        // The derivative divided by 3 is a t² + b t + c
+        let a = -p[0] + 3.0 * p[1] - 3.0 * p[2] + p[3];
+        let b = 2.0 * (p[0] - 2.0 * p[1] + p[2]);
+        let c = p[1] - p[0];
+        for t in quadratic_roots(a, b, c) {
+            if t > 0.0 && t < 1.0 {
+                include(at(xs, t), at(ys, t));
+            }
+        }
+    }
+}
+
+/// Real roots of a t² + b t + c, also when `a` is zero.
+fn quadratic_roots(a: f64, b: f64, c: f64) -> Vec<f64> {
+    const EPSILON: f64 = 1e-12;
+    if a.abs() < EPSILON {
+        return if b.abs() < EPSILON { Vec::new() } else { vec![-c / b] };
+    }
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+    let root = discriminant.sqrt();
+    vec![(-b + root) / (2.0 * a), (-b - root) / (2.0 * a)]
+}
+
+/// Include the end point of a quadratic Bézier curve and the point where
+/// it turns on each axis.
+fn quadratic_extremes(xs: [f64; 3], ys: [f64; 3], include: &mut impl FnMut(f64, f64)) {
+    let at = |p: [f64; 3], t: f64| {
+        let s = 1.0 - t;
+        s * s * p[0] + 2.0 * s * t * p[1] + t * t * p[2]
+    };
+    include(xs[2], ys[2]);
+    for p in [xs, ys] {
+        let denominator = p[0] - 2.0 * p[1] + p[2];
+        if denominator != 0.0 {
+            let t = (p[0] - p[1]) / denominator;
+            if t > 0.0 && t < 1.0 {
+                include(at(xs, t), at(ys, t));
+            }
+        }
+    }
+}
+
+/// Include the end point of an absolute arc from `start` and the points
+/// where its ellipse is furthest left, right, up and down, if the arc
+/// passes them.
+fn arc_extremes(start: (f64, f64), arc: &PathCommand, include: &mut impl FnMut(f64, f64)) {
+    use std::f64::consts::{PI, TAU};
+
+    let PathCommand::ArcTo { rx, ry, x_axis_rotation, large_arc, sweep, x, y, .. } = *arc else { return };
+    let (x1, y1) = start;
+    include(x, y);
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    // Such arcs are straight lines, or not drawn at all
+    if rx == 0.0 || ry == 0.0 || (x1 == x && y1 == y) {
+        return;
+    }
+
+    // The center parameterization, as in appendix B.2.4 of SVG 2
+    let (sin, cos) = x_axis_rotation.to_radians().sin_cos();
+    let (dx, dy) = ((x1 - x) / 2.0, (y1 - y) / 2.0);
+    let x1p = cos * dx + sin * dy;
+    let y1p = -sin * dx + cos * dy;
+    // Radii too small to reach the endpoint are scaled up
+    let lambda = (x1p / rx).powi(2) + (y1p / ry).powi(2);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let numerator = (rx * ry).powi(2) - (rx * y1p).powi(2) - (ry * x1p).powi(2);
+    let denominator = (rx * y1p).powi(2) + (ry * x1p).powi(2);
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coefficient = sign * (numerator / denominator).max(0.0).sqrt();
+    let cxp = coefficient * rx * y1p / ry;
+    let cyp = -coefficient * ry * x1p / rx;
+    let cx = cos * cxp - sin * cyp + (x1 + x) / 2.0;
+    let cy = sin * cxp + cos * cyp + (y1 + y) / 2.0;
+
+    let start_angle = ((y1p - cyp) / ry).atan2((x1p - cxp) / rx);
+    let end_angle = ((-y1p - cyp) / ry).atan2((-x1p - cxp) / rx);
+    let mut sweep_angle = end_angle - start_angle;
+    if sweep && sweep_angle < 0.0 {
+        sweep_angle += TAU;
+    } else if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= TAU;
+    }
+
+    let x_extreme = (-ry * sin).atan2(rx * cos);
+    let y_extreme = (ry * cos).atan2(rx * sin);
+    for angle in [x_extreme, x_extreme + PI, y_extreme, y_extreme + PI] {
+        let passed = if sweep_angle >= 0.0 {
+            (angle - start_angle).rem_euclid(TAU) <= sweep_angle
+        } else {
+            (start_angle - angle).rem_euclid(TAU) <= -sweep_angle
+        };
+        if passed {
+            let (sin_angle, cos_angle) = angle.sin_cos();
+            include(
+                cx + rx * cos * cos_angle - ry * sin * sin_angle,
+                cy + rx * sin * cos_angle + ry * cos * sin_angle,
+            );
+        }
+    }
+}
+
+/// Path data that draws the same when appended to the data of another
+/// path, or `None` if it can't be appended as it is.
+///
+/// A leading relative `m` is made absolute, as it would otherwise move
+/// from wherever the other path ends. Implicit linetos after it would need
+/// rewriting too, so data starting like `m1 1 2 2` gives `None`.
+pub(crate) fn appendable_path_data(path_data: &str) -> Option<String> {
+    let data = path_data.trim();
+    match data.strip_prefix('m') {
+        Some(rest) => {
+            let first = parse_segments(data).ok()?.into_iter().next()?;
+            (first.commands.len() == 1).then(|| format!("M{}", rest))
+        }
+        None => data.starts_with('M').then(|| data.to_string()),
+    }
+}
+
+/// Write every command in whichever of its absolute and relative forms is
+/// shorter, so `M100 100L110 100L5 5` becomes `M100 100l10 0L5 5`. Keeping
+/// the command of the previous one wins ties, since that saves repeating
+/// the letter.
+///
+/// Like [`to_relative`], the cursor follows the rounded values written.
+fn to_shortest(segments: &[PathSegment], decimal_places: usize) -> Vec<PathSegment> {
+    let mut cursor = PathCursor::default();
+    let mut shortest: Vec<PathSegment> = Vec::with_capacity(segments.len());
+
+    for &command in segments.iter().flat_map(|segment| &segment.commands) {
+        if let PathCommand::ClosePath { .. } = command {
+            cursor.advance(&command);
+            shortest.push(PathSegment { commands: vec![command] });
+            continue;
+        }
+
+        // The command a group without a letter would continue
+        let previous = shortest.last().map(PathSegment::continuation);
+        let candidates = [false, true].map(|abs| {
+            let converted = command.converted(abs, &cursor, decimal_places);
+            let written = write_path_data(&[PathSegment { commands: vec![converted] }], decimal_places);
+            // A command continuing the previous one drops its letter but may
+            // need a separating space instead
+            let cost = if previous == Some(converted.letter()) { written.len() } else { written.len() + 1 };
+            (cost, abs != command.is_absolute(), converted)
+        });
+        let [relative, absolute] = candidates;
+        let (_, _, chosen) = if (relative.0, relative.1) <= (absolute.0, absolute.1) { relative } else { absolute };
+        cursor.advance(&chosen);
+
+        // Movetos can't repeat, their extra pairs would become linetos
+        match shortest.last_mut() {
+            Some(last) if last.letter() == chosen.letter() && !matches!(chosen, PathCommand::MoveTo { .. }) => {
+                last.commands.push(chosen);
+            }
+            _ => shortest.push(PathSegment { commands: vec![chosen] }),
+        }
+    }
+
+    shortest
 }
 
 impl PathOptimizerPlugin {
     pub fn new(decimal_places: usize) -> Self {
         Self {
             decimal_places,
+            relative: false,
+            shortest: false,
+            collapse_repeated: false,
             path_count: 0,
             total_chars_saved: 0,
+            segments_removed: 0,
+            bounds: None,
+            diagnostics: DiagnosticsCollector::new(),
         }
     }
 
-    fn optimize_path_data(&mut self, path_data: &str) -> String {
-        let mut optimized = String::with_capacity(path_data.len());
-        let mut chars = path_data.chars().peekable();
-        let mut prev_was_number = false;
-
-        while let Some(c) = chars.next() {
-            match c {
-                'M' | 'm' | 'L' | 'l' | 'H' | 'h' | 'V' | 'v' |
-                'C' | 'c' | 'S' | 's' | 'Q' | 'q' | 'T' | 't' |
-                'A' | 'a' | 'Z' | 'z' => {
-                    prev_was_number = false;
-                    optimized.push(c);
-                },
-
-                '0'..='9' | '.' | '-' => {
-                    if prev_was_number {
-                        optimized.push(' ');
-                    }
-
-                    let mut number = String::new();
-                    number.push(c);
-
-                    while let Some(&next) = chars.peek() {
-                        if next.is_ascii_digit() || next == '.' || next == 'e' || next == 'E' || next == '-' {
-                            number.push(chars.next().unwrap());
-                        } else {
-                            break;
-                        }
-                    }
+    /// Whether to rewrite absolute commands as relative ones, whose smaller
+    /// coordinates are usually shorter.
+    pub fn with_relative(mut self, relative: bool) -> Self {
+        self.relative = relative;
+        self
+    }
 
-                    if let Ok(num) = number.parse::<f64>() {
-                        let rounded = format!("{:.1$}", num, self.decimal_places);
-                        let trimmed = rounded.trim_end_matches('0').trim_end_matches('.');
-                        optimized.push_str(trimmed);
-                        prev_was_number = true;
-                    } else {
-                        optimized.push_str(&number);
-                        prev_was_number = false;
-                    }
-                },
+    /// Whether to pick the shorter of the absolute and relative form for
+    /// each segment. Takes precedence over rewriting everything relative.
+    pub fn with_shortest(mut self, shortest: bool) -> Self {
+        self.shortest = shortest;
+        self
+    }
 
-                ' ' | ',' => {
-                    // Skip all whitespace and commas - we handle spacing in the number processing
-                    continue;
-                },
+    /// Whether to drop command letters that repeat the previous command.
+    pub fn with_collapse_repeated(mut self, collapse_repeated: bool) -> Self {
+        self.collapse_repeated = collapse_repeated;
+        self
+    }
 
-                _ => optimized.push(c),
-            }
+    fn optimize_path_data(&mut self, path_data: &str) -> String {
+        let mut segments = match parse_segments(path_data) {
+            Ok(segments) => segments,
+            Err(error) => match tokenize(path_data) {
+                // Only the parameter groups are off, so the numbers can
+                // still be shortened
+                Ok(tokens) => {
+                    let optimized = write_tokens(&tokens, self.decimal_places);
+                    self.total_chars_saved += path_data.len().saturating_sub(optimized.len());
+                    return optimized;
+                }
+                Err(_) => {
+                    self.diagnostics.warn(format!("malformed path data left as is: {}", error), Some("path"));
+                    return path_data.to_string();
+                }
+            },
+        };
+        if segments.is_empty() {
+            self.diagnostics.warn("path has no commands and draws nothing", Some("path"));
+        }
+        let commands: Vec<PathCommand> = segments.iter().flat_map(|segment| segment.commands.iter().copied()).collect();
+        if let Some(bounds) = path_bounding_box(&commands) {
+            self.bounds = Some(self.bounds.map_or(bounds, |total| total.union(&bounds)));
+        }
+        if self.shortest {
+            segments = to_shortest(&segments, self.decimal_places);
+        } else if self.relative {
+            segments = to_relative(&segments, self.decimal_places);
         }
+        let (segments, removed) = remove_zero_length(&segments, self.decimal_places);
+        self.segments_removed += removed;
+        let segments = if self.collapse_repeated { merge_repeated_commands(segments) } else { segments };
+        let segments = merge_implicit_linetos(segments);
 
-        self.total_chars_saved += path_data.len() - optimized.len();
+        let optimized = write_path_data(&segments, self.decimal_places);
+        // Separating packed arc flags can make the data longer
+        self.total_chars_saved += path_data.len().saturating_sub(optimized.len());
         optimized
     }
 }
 
+/// Round a number to `decimal_places` and drop trailing zeros, so that
+/// `100.000` becomes `100` and `0.50` becomes `0.5`.
+pub(crate) fn format_number(value: f64, decimal_places: usize) -> String {
+    let mut rounded = format!("{:.1$}", value, decimal_places);
+    if rounded.contains('.') {
+        let trimmed = rounded.trim_end_matches('0').trim_end_matches('.').len();
+        rounded.truncate(trimmed);
+    }
+    if rounded == "-0" {
+        rounded.remove(0);
+    }
+    rounded
+}
+
+/// Drop the zero before the decimal point: `0.5` becomes `.5` and `-0.5`
+/// becomes `-.5`. Integers, `0` included, are returned as they are.
+pub(crate) fn strip_leading_zero(mut number: String) -> String {
+    if number.starts_with("0.") {
+        number.remove(0);
+    } else if number.starts_with("-0.") {
+        number.remove(1);
+    }
+    number
+}
+
 impl SVGPlugin for PathOptimizerPlugin {
     fn init(&mut self) -> io::Result<()> {
         self.path_count = 0;
         self.total_chars_saved = 0;
+        self.segments_removed = 0;
+        self.bounds = None;
+        self.diagnostics.clear();
         Ok(())
     }
 
     // In src/plugins/path.rs, update the process_element method:
-    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<()> {
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
         if element.name().as_ref() == b"path" {
             self.path_count += 1;
 
@@ -115,7 +1106,7 @@ impl SVGPlugin for PathOptimizerPlugin {
                 element.push_attribute(("d", optimized_path.as_str()));
             }
         }
-        Ok(())
+        Ok(ElementAction::Keep)
     }
 
     fn finalize(&mut self) -> io::Result<()> {
@@ -129,10 +1120,21 @@ impl SVGPlugin for PathOptimizerPlugin {
 
 impl PluginStatistics for PathOptimizerPlugin {
     fn get_statistics(&self) -> Vec<(&str, String)> {
-        vec![
+        let mut stats = vec![
             ("Paths optimized", self.path_count.to_string()),
             ("Total characters saved", self.total_chars_saved.to_string()),
-        ]
+            ("Zero-length segments removed", self.segments_removed.to_string()),
+        ];
+        if let Some(bounds) = self.bounds {
+            let corners = [bounds.min_x, bounds.min_y, bounds.max_x, bounds.max_y];
+            let corners: Vec<String> = corners.iter().map(|&value| format_number(value, self.decimal_places)).collect();
+            stats.push(("Path data bounds", corners.join(" ")));
+        }
+        stats
+    }
+
+    fn diagnostics(&self) -> &[Diagnostic] {
+        self.diagnostics.as_slice()
     }
 }
 
@@ -150,13 +1152,332 @@ mod tests {
         // Test multiple commands
         assert_eq!(
             optimizer.optimize_path_data("M 10.123,20.456 L 30.789,40.012"),
-            "M10.12 20.46L30.79 40.01"
+            "M10.12 20.46 30.79 40.01"
         );
 
         // Test relative commands
         assert_eq!(
             optimizer.optimize_path_data("m 5.123,5.456 l 10.789,10.012"),
-            "m5.12 5.46l10.79 10.01"
+            "m5.12 5.46 10.79 10.01"
         );
     }
+
+    #[test]
+    fn test_no_space_before_negative_numbers() {
+        let mut optimizer = PathOptimizerPlugin::new(2);
+
+        assert_eq!(optimizer.optimize_path_data("M 10 -10 C -5 -5 0 0 1 1"), "M10-10C-5-5 0 0 1 1");
+        assert_eq!(optimizer.optimize_path_data("M10-10C-5-5 0 0 1 1"), "M10-10C-5-5 0 0 1 1");
+        assert_eq!(optimizer.optimize_path_data("M 10 10"), "M10 10");
+        assert_eq!(optimizer.optimize_path_data("M 1,-2 3,4"), "M1-2 3 4");
+    }
+
+    #[test]
+    fn test_implicit_lineto_after_moveto() {
+        let mut optimizer = PathOptimizerPlugin::new(2);
+
+        assert_eq!(optimizer.optimize_path_data("M 10 20 L 30 40"), "M10 20 30 40");
+        assert_eq!(optimizer.optimize_path_data("m 10 20 l 30 40 l 5 5"), "m10 20 30 40 5 5");
+
+        // The implicit command of M is L, of m it is l
+        assert_eq!(optimizer.optimize_path_data("M 10 20 l 30 40"), "M10 20l30 40");
+        assert_eq!(optimizer.optimize_path_data("M0 0 H 10 L 5 5"), "M0 0H10L5 5");
+    }
+
+    #[test]
+    fn test_minus_separates_numbers() {
+        let mut optimizer = PathOptimizerPlugin::new(5);
+
+        assert_eq!(optimizer.optimize_path_data("M10-20L30-40"), "M10-20 30-40");
+        assert_eq!(optimizer.optimize_path_data("M1e-4-2"), "M1e-4-2");
+        assert_eq!(optimizer.optimize_path_data("M 2E-3,-1"), "M.002-1");
+        assert_eq!(optimizer.optimize_path_data("M-1-2-3-4"), "M-1-2-3-4");
+    }
+
+    #[test]
+    fn test_exponents() {
+        let mut optimizer = PathOptimizerPlugin::new(5);
+
+        assert_eq!(optimizer.optimize_path_data("M1e-5 0"), "M1e-5 0");
+        assert_eq!(optimizer.optimize_path_data("M1.5e3 0"), "M1500 0");
+        assert_eq!(optimizer.optimize_path_data("M1E+5 2.5e-1.5"), "M1e5.25.5");
+        assert_eq!(optimizer.optimize_path_data("M0.00015 1e-5.5"), "M15e-5 1e-5.5");
+
+        // An incomplete exponent is malformed, not merged with what follows
+        assert_eq!(optimizer.optimize_path_data("M1e L2 2"), "M1e L2 2");
+        assert_eq!(optimizer.optimize_path_data("M1e999 0"), "M1e999 0");
+    }
+
+    #[test]
+    fn test_repeated_commands_are_collapsed() {
+        let collapse = |path_data: &str| write_path_data(&merge_repeated_commands(parse_segments(path_data).unwrap()), 2);
+
+        assert_eq!(collapse("M0 0 L1 1 L2 2"), "M0 0L1 1 2 2");
+        assert_eq!(collapse("m0 0 l1 1 l2 2 L3 3"), "m0 0l1 1 2 2L3 3");
+        assert_eq!(collapse("C1 1 2 2 3 3 C4 4 5 5 6 6 Z Z"), "C1 1 2 2 3 3 4 4 5 5 6 6ZZ");
+
+        // A second moveto starts a new subpath, so its letter stays
+        assert_eq!(collapse("M0 0 M5 5 m1 1 m2 2"), "M0 0M5 5m1 1m2 2");
+
+        let mut optimizer = PathOptimizerPlugin::new(2);
+        assert_eq!(optimizer.optimize_path_data("M0 0 H1 H2 M1 1"), "M0 0H1H2M1 1");
+        let mut optimizer = PathOptimizerPlugin::new(2).with_collapse_repeated(true);
+        assert_eq!(optimizer.optimize_path_data("M0 0 H1 H2 M1 1"), "M0 0H1 2M1 1");
+        assert_eq!(optimizer.optimize_path_data("M0 0 L1 1 L2 2"), "M0 0 1 1 2 2");
+    }
+
+    #[test]
+    fn test_leading_zeros_are_stripped() {
+        let mut optimizer = PathOptimizerPlugin::new(2);
+
+        assert_eq!(optimizer.optimize_path_data("M0.5.5"), "M.5.5");
+        assert_eq!(optimizer.optimize_path_data("M 0.5 0.5 L 1 0.5"), "M.5.5 1 .5");
+        assert_eq!(optimizer.optimize_path_data("m0 0 l-0.25 0"), "m0 0-.25 0");
+        assert_eq!(optimizer.optimize_path_data("M0 0 H 10.0 V 1"), "M0 0H10V1");
+    }
+
+    #[test]
+    fn test_arc_flags_survive() {
+        let mut optimizer = PathOptimizerPlugin::new(2);
+
+        assert_eq!(
+            optimizer.optimize_path_data("M 0 0 A 10 10 0 1 0 20 20"),
+            "M0 0A10 10 0 1 0 20 20"
+        );
+
+        // Flags packed together and against the following coordinate
+        assert_eq!(optimizer.optimize_path_data("M0 0A10 10 0 1020 20"), "M0 0A10 10 0 1 0 20 20");
+        assert_eq!(
+            optimizer.optimize_path_data("M0 0a5.5 5.5 0 0 1 10 0 5 5 0 11-10 0"),
+            "M0 0a5.5 5.5 0 0 1 10 0 5 5 0 1 1-10 0"
+        );
+    }
+
+    #[test]
+    fn test_relative_commands() {
+        let mut optimizer = PathOptimizerPlugin::new(2).with_relative(true);
+
+        assert_eq!(
+            optimizer.optimize_path_data("M100 100 L110 100 H120 V130 Z L100 90"),
+            "m100 100 10 0h10v30zl0-10"
+        );
+        assert_eq!(
+            optimizer.optimize_path_data("M10 10 20 20 C30 30 40 40 50 50 A5 5 0 0 1 60 50"),
+            "m10 10 10 10c10 10 20 20 30 30a5 5 0 0 1 10 0"
+        );
+
+        // Rounding errors don't accumulate along the path
+        assert_eq!(
+            optimizer.optimize_path_data("M0.004 0 L0.008 0 L0.012 0"),
+            "m0 0 .01 0"
+        );
+    }
+
+    /// The absolute command and parameters of every parameter group, so
+    /// that paths can be compared by geometry rather than by text.
+    fn absolute_groups(path_data: &str) -> Vec<(char, Vec<f64>)> {
+        let mut cursor = PathCursor::default();
+        PathParser::parse(path_data)
+            .unwrap()
+            .into_iter()
+            .map(|command| {
+                let absolute = command.converted(true, &cursor, 6);
+                cursor.advance(&command);
+                (absolute.letter(), absolute.params())
+            })
+            .collect()
+    }
+
+    fn assert_same_geometry(original: &str, optimized: &str, tolerance: f64) {
+        let (original, optimized) = (absolute_groups(original), absolute_groups(optimized));
+        assert_eq!(original.len(), optimized.len(), "{:?} vs {:?}", original, optimized);
+        for ((command, params), (optimized_command, optimized_params)) in original.iter().zip(&optimized) {
+            assert_eq!(command, optimized_command);
+            for (value, optimized_value) in params.iter().zip(optimized_params) {
+                assert!((value - optimized_value).abs() <= tolerance, "{:?} vs {:?}", params, optimized_params);
+            }
+        }
+    }
+
+    #[test]
+    fn test_path_parser() {
+        use PathCommand::*;
+
+        assert_eq!(
+            PathParser::parse("M1 2 3 4h5V6c1 2 3 4 5 6s1 2 3 4q1 2 3 4t5 6a1 2 30 1020 20z"),
+            Ok(vec![
+                MoveTo { abs: true, x: 1.0, y: 2.0 },
+                LineTo { abs: true, x: 3.0, y: 4.0 },
+                HorizLineTo { abs: false, x: 5.0 },
+                VertLineTo { abs: true, y: 6.0 },
+                CurveTo { abs: false, x1: 1.0, y1: 2.0, x2: 3.0, y2: 4.0, x: 5.0, y: 6.0 },
+                SmoothCurveTo { abs: false, x2: 1.0, y2: 2.0, x: 3.0, y: 4.0 },
+                QuadBezierTo { abs: false, x1: 1.0, y1: 2.0, x: 3.0, y: 4.0 },
+                SmoothQuadBezierTo { abs: false, x: 5.0, y: 6.0 },
+                ArcTo { abs: false, rx: 1.0, ry: 2.0, x_axis_rotation: 30.0, large_arc: true, sweep: false, x: 20.0, y: 20.0 },
+                ClosePath { abs: false },
+            ])
+        );
+        assert_eq!(PathParser::parse(""), Ok(Vec::new()));
+
+        let error = |path_data: &str| PathParser::parse(path_data).unwrap_err().to_string();
+        assert_eq!(error("M1 1 X 2"), "unexpected 'X' at byte 5");
+        assert_eq!(error("1 1"), "number before the first command at byte 0");
+        assert_eq!(error("M0 0L1 2 3"), "'L' takes parameters in groups of 2 at byte 4");
+        assert_eq!(error("M0 0Z1"), "'Z' takes no parameters at byte 4");
+        assert_eq!(error("M0 0A1 1 0 2 0 5 5"), "arc flags after 'A' must be 0 or 1 at byte 4");
+        assert_eq!(error("M1e999 0"), "invalid number '1e999' at byte 1");
+    }
+
+    #[test]
+    fn test_serialize() {
+        let commands = PathParser::parse("M 0.5 0.5 L 1 1 L 2 2 m 1 1 l 1 -1 H 3 H 4 z").unwrap();
+        assert_eq!(PathCommand::serialize(&commands, 2), "M.5.5 1 1 2 2m1 1 1-1H3 4z");
+
+        let round_trip = PathCommand::serialize(&PathParser::parse("M1.234 5C1 2 3 4 5 6S7 8 9 10").unwrap(), 1);
+        assert_eq!(round_trip, "M1.2 5C1 2 3 4 5 6S7 8 9 10");
+    }
+
+    #[test]
+    fn test_canonical_path_data() {
+        assert_eq!(canonical_path_data("M0 0 l10 0", 3), canonical_path_data("M0,0 L10.0001,0", 3));
+        assert_eq!(canonical_path_data("m5 5 5 5z", 3).as_deref(), Some("M5 5L10 10Z"));
+        assert_eq!(canonical_path_data("M 1 1 X 2", 3), None);
+    }
+
+    #[test]
+    fn test_shortest_commands() {
+        let mut optimizer = PathOptimizerPlugin::new(2).with_shortest(true);
+
+        assert_eq!(optimizer.optimize_path_data("M 100 100 L 110 100"), "M100 100l10 0");
+        assert_eq!(optimizer.optimize_path_data("M100 100 L110 100 L5 5"), "M100 100l10 0L5 5");
+        assert_eq!(optimizer.optimize_path_data("m10 10 l5 5 l990 990"), "m10 10 5 5 990 990");
+        assert_eq!(optimizer.optimize_path_data("M1000 1000 H1010 V1020 H5"), "M1e3 1e3h10v20H5");
+
+        // Both forms of each segment are measured separately
+        assert_eq!(
+            optimizer.optimize_path_data("M500 500 C510 510 520 520 530 500 A5 5 0 0 1 540 500"),
+            "M500 500c10 10 20 20 30 0a5 5 0 0 1 10 0"
+        );
+    }
+
+    #[test]
+    fn test_shortest_commands_keep_the_geometry() {
+        let paths = [
+            "M100 100 L110 100 L200 200 Z M50 50 l10 10 H0 V300",
+            "M10.123 20.456 C30.789 40.012 100 100 200 200 S300 300 10 10 Q 5 5 100 100 T 120 120",
+            "M0 0A10 10 0 1020 20 a5 5 0 0 1 10 0 L1000 1000 h-999.5",
+            "m 5,5 l 10,10 m 100,100 l 1,1 z l 50 50",
+            "M 12.5 12.5 L 12.25 12.75 L 987.125 12.75 V 0.001 H 999.999",
+        ];
+        let mut optimizer = PathOptimizerPlugin::new(2).with_shortest(true);
+        for path in paths {
+            let optimized = optimizer.optimize_path_data(path);
+            assert!(optimized.len() <= path.len(), "{} grew to {}", path, optimized);
+            assert_same_geometry(path, &optimized, 0.006);
+        }
+        let mut precise = PathOptimizerPlugin::new(3).with_shortest(true);
+        for path in paths {
+            assert_same_geometry(path, &precise.optimize_path_data(path), 0.0006);
+        }
+    }
+
+    #[test]
+    fn test_zero_length_segments_are_removed() {
+        let mut optimizer = PathOptimizerPlugin::new(2);
+
+        assert_eq!(
+            optimizer.optimize_path_data("M10 10 L10 10 L20 20 H20 V20 l0 0 h5 v0 Z"),
+            "M10 10 20 20h5Z"
+        );
+        assert_eq!(optimizer.optimize_path_data("M10 10 10 10 20 20"), "M10 10 20 20");
+        assert_eq!(optimizer.segments_removed, 6);
+
+        // A lone zero-length segment draws a dot with round caps
+        assert_eq!(optimizer.optimize_path_data("M5 5 L5 5 L5 5 M0 0 h0"), "M5 5 5 5M0 0h0");
+
+        let mut relative = PathOptimizerPlugin::new(2).with_relative(true);
+        assert_eq!(relative.optimize_path_data("M0 0 L0.001 0 L10 0"), "m0 0 10 0");
+    }
+
+    #[test]
+    fn test_zero_length_segment_before_smooth_curve_is_kept() {
+        let mut optimizer = PathOptimizerPlugin::new(2);
+
+        assert_eq!(
+            optimizer.optimize_path_data("M0 0C10 10 20 10 30 0L30 0S50 -10 60 0"),
+            "M0 0C10 10 20 10 30 0L30 0S50-10 60 0"
+        );
+        assert_eq!(
+            optimizer.optimize_path_data("M0 0Q10 10 20 0L20 0L20 0T40 0"),
+            "M0 0Q10 10 20 0L20 0T40 0"
+        );
+        assert_eq!(optimizer.segments_removed, 1);
+    }
+
+    #[test]
+    fn test_unparseable_data_is_kept() {
+        let mut optimizer = PathOptimizerPlugin::new(2);
+        assert_eq!(optimizer.optimize_path_data("M 1 1 X 2"), "M 1 1 X 2");
+        assert_eq!(optimizer.optimize_path_data(""), "");
+
+        let messages: Vec<_> = optimizer.diagnostics().iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            ["malformed path data left as is: unexpected 'X' at byte 6", "path has no commands and draws nothing"]
+        );
+        optimizer.init().unwrap();
+        assert!(optimizer.diagnostics().is_empty());
+    }
+
+    fn bounds(path_data: &str) -> Option<[f64; 4]> {
+        let bounds = path_bounding_box(&PathParser::parse(path_data).ok()?)?;
+        let round = |value: f64| (value * 1000.0).round() / 1000.0 + 0.0;
+        Some([bounds.min_x, bounds.min_y, bounds.max_x, bounds.max_y].map(round))
+    }
+
+    #[test]
+    fn test_path_bounding_box() {
+        assert_eq!(bounds("M10 20h30v-15z"), Some([10.0, 5.0, 40.0, 20.0]));
+        assert_eq!(bounds("M0 0C0 10 10 10 10 0"), Some([0.0, 0.0, 10.0, 7.5]));
+        assert_eq!(bounds("M0 0C5-10 15 10 20 0S30 20 40 0"), Some([0.0, -2.887, 40.0, 7.042]));
+        assert_eq!(bounds("M0 0Q10 10 20 0T40 0"), Some([0.0, -5.0, 40.0, 5.0]));
+        assert_eq!(bounds("M0 0A5 5 0 0 1 10 0"), Some([0.0, -5.0, 10.0, 0.0]));
+        assert_eq!(bounds("M0 0A10 10 0 1 1 10 0"), Some([-5.0, -18.66, 15.0, 0.0]));
+        assert_eq!(bounds("M0 0A5 5 0 0 0 10 0"), Some([0.0, 0.0, 10.0, 5.0]));
+        // Radii too small are scaled up until the chord is a diameter
+        assert_eq!(bounds("M0 0A1 1 0 0 1 10 0"), Some([0.0, -5.0, 10.0, 0.0]));
+        assert_eq!(bounds("M0 0A10 5 90 0 1 0 20"), Some([0.0, 0.0, 5.0, 20.0]));
+        assert_eq!(bounds("m5 5 l1 1 m-10 0"), Some([5.0, 5.0, 6.0, 6.0]));
+        assert_eq!(bounds("M5 5"), None);
+        assert_eq!(bounds(""), None);
+    }
+
+    #[test]
+    fn test_bounds_statistic() {
+        let mut optimizer = PathOptimizerPlugin::new(2);
+        optimizer.optimize_path_data("M0 0Q5 10 10 0");
+        optimizer.optimize_path_data("M20-5h5");
+        optimizer.optimize_path_data("M 1 1 X 2");
+
+        let stats = optimizer.get_statistics();
+        assert_eq!(stats.last(), Some(&("Path data bounds", "0 -5 25 5".to_string())));
+    }
+
+    #[test]
+    fn test_appendable_path_data() {
+        assert_eq!(appendable_path_data(" M1 2L3 4 ").as_deref(), Some("M1 2L3 4"));
+        assert_eq!(appendable_path_data("m1 2l3 4z").as_deref(), Some("M1 2l3 4z"));
+        assert_eq!(appendable_path_data("m1 2 3 4"), None);
+        assert_eq!(appendable_path_data("L1 2"), None);
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(100.0, 2), "100");
+        assert_eq!(format_number(0.5, 3), "0.5");
+        assert_eq!(format_number(10.0, 0), "10");
+        assert_eq!(format_number(1.23456, 2), "1.23");
+        assert_eq!(format_number(-0.001, 2), "0");
+    }
 }
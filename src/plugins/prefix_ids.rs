@@ -0,0 +1,351 @@
+//! Prefixing of ids and class names, so several SVGs can be inlined into
+//! one HTML page without their ids colliding.
+//!
+//! Ids and classes are collected from the whole document first, so only
+//! references to the document's own ids and selectors for its own classes
+//! are rewritten. A `href="#top"` pointing into the surrounding page keeps
+//! working.
+
+use crate::plugins::cleanup_ids::{rewrite_timing, ID_LIST_ATTRIBUTES, TIMING_ATTRIBUTES};
+use crate::plugins::remove_unused_css::{skip_token, GROUPING_AT_RULES};
+use crate::plugins::traits::{PluginStatistics, TreePlugin};
+use crate::plugins::util::{rewrite_url_references, XLinkPrefixes};
+use crate::tree::{Document, Element, Node};
+use quick_xml::escape::{minimal_escape, unescape};
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+/// Prefix used when none is configured and the input isn't a file.
+const DEFAULT_PREFIX: &str = "prefix";
+
+/// Prepends a prefix to every `id` and class name and rewrites the
+/// references to them.
+///
+/// The prefix defaults to the input's file name without its extension, with
+/// characters that can't appear in an id replaced by `_`, and is joined to
+/// the id with a delimiter, `__` by default: `icon.svg` turns `id="a"` into
+/// `id="icon__a"`.
+///
+/// References are rewritten in `href` and `xlink:href` (under any prefix
+/// bound to the XLink namespace), `url(#id)` in any attribute, animation
+/// `begin` and `end` times and `aria-labelledby` and `aria-describedby`.
+/// In `<style>` elements the `#id` and `.class` selectors and `url(#id)`
+/// values are prefixed too. Selectors in attribute form, like `[id="a"]`
+/// or `[class~="b"]`, and ids used by scripts are not rewritten.
+pub struct PrefixIdsPlugin {
+    prefix: Option<String>,
+    delimiter: String,
+    /// Prefix derived from the name of the file being processed.
+    source_prefix: Option<String>,
+    /// The prefix and delimiter used for the current document.
+    current: String,
+    ids: HashSet<String>,
+    classes: HashSet<String>,
+    xlink: XLinkPrefixes,
+    ids_prefixed: usize,
+    classes_prefixed: usize,
+    references_rewritten: usize,
+}
+
+impl PrefixIdsPlugin {
+    pub fn new() -> Self {
+        Self {
+            prefix: None,
+            delimiter: "__".to_string(),
+            source_prefix: None,
+            current: String::new(),
+            ids: HashSet::new(),
+            classes: HashSet::new(),
+            xlink: XLinkPrefixes::default(),
+            ids_prefixed: 0,
+            classes_prefixed: 0,
+            references_rewritten: 0,
+        }
+    }
+
+    /// Use `prefix` for every document instead of one from its file name.
+    pub fn with_prefix(mut self, prefix: String) -> Self {
+        self.prefix = Some(prefix);
+        self
+    }
+
+    /// Join the prefix and the original name with `delimiter`.
+    pub fn with_delimiter(mut self, delimiter: String) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    fn collect(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            let Node::Element(element) = node else { continue };
+            if let Some(id) = element.attr("id") {
+                self.ids.insert(id.to_string());
+            }
+            if let Some(class) = element.attr("class") {
+                self.classes.extend(class.split_whitespace().map(str::to_string));
+            }
+            self.collect(&element.children);
+        }
+    }
+
+    /// The prefixed form of `id`, if it is one of the document's ids.
+    fn prefixed_id(&self, id: &str) -> Option<String> {
+        self.ids.contains(id).then(|| format!("{}{}", self.current, id))
+    }
+
+    fn rewrite(&mut self, nodes: &mut [Node], in_style: bool) {
+        for node in nodes {
+            match node {
+                Node::Element(element) => {
+                    self.rewrite_element(element);
+                    self.rewrite(&mut element.children, element.name == "style");
+                }
+                Node::Text(text) if in_style => {
+                    let css = unescape(text).map(|css| css.into_owned()).unwrap_or_else(|_| text.clone());
+                    if let Some(css) = self.prefix_css(&css) {
+                        *text = minimal_escape(css.as_str()).into_owned();
+                    }
+                }
+                Node::CData(css) if in_style => {
+                    if let Some(prefixed) = self.prefix_css(css) {
+                        *css = prefixed;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn rewrite_element(&mut self, element: &mut Element) {
+        let mut references = 0;
+        for (key, value) in &mut element.attrs {
+            let rename = |id: &str| self.prefixed_id(id);
+            let rewritten = if key == "id" {
+                self.ids_prefixed += 1;
+                Some(format!("{}{}", self.current, value))
+            } else if key == "class" {
+                let classes: Vec<String> = value
+                    .split_whitespace()
+                    .map(|class| format!("{}{}", self.current, class))
+                    .collect();
+                self.classes_prefixed += classes.len();
+                Some(classes.join(" "))
+            } else if self.xlink.is_link(key) {
+                value.strip_prefix('#').and_then(rename).map(|id| format!("#{}", id))
+            } else if TIMING_ATTRIBUTES.contains(&key.as_str()) {
+                rewrite_timing(value, rename)
+            } else if ID_LIST_ATTRIBUTES.contains(&key.as_str()) {
+                let ids: Vec<String> = value
+                    .split_whitespace()
+                    .map(|id| rename(id).unwrap_or_else(|| id.to_string()))
+                    .collect();
+                Some(ids.join(" ")).filter(|ids| ids != value)
+            } else {
+                rewrite_url_references(value, rename)
+            };
+            if let Some(rewritten) = rewritten {
+                if key != "id" && key != "class" {
+                    references += 1;
+                }
+                *value = rewritten;
+            }
+        }
+        self.references_rewritten += references;
+    }
+
+    /// Prefix the `#id` and `.class` selectors and `url(#id)` references of
+    /// a style sheet; `None` if nothing changed.
+    ///
+    /// Only selectors of rules and of grouping at-rules like `@media` are
+    /// touched, so colors such as `#fff` and numbers such as `.5` inside
+    /// declarations stay as they are.
+    fn prefix_css(&mut self, css: &str) -> Option<String> {
+        let bytes = css.as_bytes();
+        let mut out = String::with_capacity(css.len());
+        // For each open block, whether it holds declarations rather than rules
+        let mut blocks: Vec<bool> = Vec::new();
+        let mut prelude_start = 0;
+        let mut at_rule = false;
+        let mut copied = 0;
+        let mut changed = 0;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let in_rules = blocks.last() != Some(&true);
+            match bytes[i] {
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    i = skip_token(css, i);
+                    continue;
+                }
+                b'"' | b'\'' => {
+                    i = skip_token(css, i);
+                    continue;
+                }
+                b'{' => {
+                    let prelude = css[prelude_start..i].trim();
+                    let grouping = at_rule
+                        && prelude[1..]
+                            .split(|c: char| c.is_whitespace() || c == '(')
+                            .next()
+                            .is_some_and(|name| GROUPING_AT_RULES.contains(&name.to_ascii_lowercase().as_str()));
+                    blocks.push(!grouping);
+                    prelude_start = i + 1;
+                    at_rule = false;
+                }
+                b'}' => {
+                    blocks.pop();
+                    prelude_start = i + 1;
+                    at_rule = false;
+                }
+                b';' => {
+                    prelude_start = i + 1;
+                    at_rule = false;
+                }
+                b'@' if in_rules && css[prelude_start..i].trim().is_empty() => at_rule = true,
+                marker @ (b'#' | b'.') if in_rules && !at_rule => {
+                    let name_start = i + 1;
+                    let name_end = css[name_start..]
+                        .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+                        .map_or(css.len(), |end| name_start + end);
+                    let name = &css[name_start..name_end];
+                    let known = if marker == b'#' { &self.ids } else { &self.classes };
+                    if known.contains(name) {
+                        out.push_str(&css[copied..name_start]);
+                        out.push_str(&self.current);
+                        copied = name_start;
+                        changed += 1;
+                    }
+                    i = name_end;
+                    continue;
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        out.push_str(&css[copied..]);
+
+        if let Some(rewritten) = rewrite_url_references(&out, |id| self.prefixed_id(id)) {
+            out = rewritten;
+            changed += 1;
+        }
+        self.references_rewritten += changed;
+        (changed > 0).then_some(out)
+    }
+}
+
+/// A prefix from a file name: its stem, with characters not allowed in an
+/// id replaced by `_`, and an `_` in front if it would start with a digit.
+fn file_prefix(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_string_lossy();
+    let mut prefix: String = stem
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if prefix.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+        prefix.insert(0, '_');
+    }
+    (!prefix.is_empty()).then_some(prefix)
+}
+
+impl Default for PrefixIdsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreePlugin for PrefixIdsPlugin {
+    fn set_source(&mut self, path: Option<&Path>) {
+        self.source_prefix = path.and_then(file_prefix);
+    }
+
+    fn init(&mut self) -> io::Result<()> {
+        let prefix = self.prefix.as_deref().or(self.source_prefix.as_deref()).unwrap_or(DEFAULT_PREFIX);
+        self.current = format!("{}{}", prefix, self.delimiter);
+        self.ids.clear();
+        self.classes.clear();
+        self.xlink.clear();
+        self.ids_prefixed = 0;
+        self.classes_prefixed = 0;
+        self.references_rewritten = 0;
+        Ok(())
+    }
+
+    fn process_tree(&mut self, document: &mut Document) -> io::Result<()> {
+        self.collect(&document.children);
+        self.xlink = XLinkPrefixes::from_nodes(&document.children);
+        self.rewrite(&mut document.children, false);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "PrefixIds"
+    }
+}
+
+impl PluginStatistics for PrefixIdsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Ids prefixed", self.ids_prefixed.to_string()),
+            ("Classes prefixed", self.classes_prefixed.to_string()),
+            ("References rewritten", self.references_rewritten.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_tree_with;
+    use crate::processor::SVGProcessor;
+    use std::fs;
+
+    #[test]
+    fn test_internal_references_resolve_after_prefixing() -> io::Result<()> {
+        let svg = r##"<svg><defs><linearGradient id="g"/><path id="p" class="shape big"/></defs><rect fill="url(#g)" style="stroke:url('#g')"/><use xlink:href="#p"/><a href="#top"/><animate id="in" begin="in.end"/></svg>"##;
+
+        assert_eq!(
+            optimize_tree_with(PrefixIdsPlugin::new().with_prefix("logo".to_string()), svg)?,
+            r##"<svg><defs><linearGradient id="logo__g"/><path id="logo__p" class="logo__shape logo__big"/></defs><rect fill="url(#logo__g)" style="stroke:url(#logo__g)"/><use xlink:href="#logo__p"/><a href="#top"/><animate id="logo__in" begin="logo__in.end"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_aliased_xlink_references_are_prefixed() -> io::Result<()> {
+        let svg = r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><path id="p"/><use x:href="#p"/></svg>"##;
+
+        assert_eq!(
+            optimize_tree_with(PrefixIdsPlugin::new().with_prefix("logo".to_string()), svg)?,
+            r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><path id="logo__p"/><use x:href="#logo__p"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_style_selectors_are_prefixed() -> io::Result<()> {
+        let svg = "<svg><style>#p, .a &gt; .other { fill: #fff; opacity: .5 }\n@media (min-width: 1.5em) { .a { stroke: url(#g) } }\n@import url(x.css);</style><path id=\"p\" class=\"a\"/><linearGradient id=\"g\"/></svg>";
+
+        assert_eq!(
+            optimize_tree_with(PrefixIdsPlugin::new().with_prefix("x".to_string()).with_delimiter("-".to_string()), svg)?,
+            "<svg><style>#x-p, .x-a > .other { fill: #fff; opacity: .5 }\n@media (min-width: 1.5em) { .x-a { stroke: url(#x-g) } }\n@import url(x.css);</style><path id=\"x-p\" class=\"x-a\"/><linearGradient id=\"x-g\"/></svg>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_prefix_from_file_name() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input = temp_dir.path().join("24px icon.svg");
+        let output = temp_dir.path().join("out.svg");
+        fs::write(&input, r##"<svg><path id="a"/><use href="#a"/></svg>"##)?;
+
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_tree_plugin(PrefixIdsPlugin::new());
+        processor.process_file(&input, &output)?;
+        assert_eq!(fs::read_to_string(&output)?, r##"<svg><path id="_24px_icon__a"/><use href="#_24px_icon__a"/></svg>"##);
+
+        assert_eq!(processor.optimize_str(r#"<svg id="a"/>"#)?, r#"<svg id="prefix__a"/>"#);
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use crate::plugins::inline_styles::PRESENTATION_ATTRIBUTES;
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{has_stylesheet, owned_attributes, set_attributes, style_declarations};
+use quick_xml::events::{BytesStart, Event};
+use std::io;
+
+/// Moves presentation attributes into the `style` attribute:
+/// `fill="red" stroke="blue"` becomes `style="fill:red;stroke:blue"`.
+///
+/// The inverse of [`InlineStylesPlugin`](super::InlineStylesPlugin).
+/// Declarations are appended to an existing `style`. An attribute that
+/// `style` already declares had no effect, so it is dropped rather than
+/// appended over the declaration.
+///
+/// Inline styles beat stylesheet rules, presentation attributes don't, so
+/// documents with a `<style>` element or an `xml-stylesheet` instruction
+/// are left alone.
+pub struct PresentationToStylePlugin {
+    has_stylesheet: bool,
+    elements_converted: usize,
+    attributes_converted: usize,
+}
+
+impl PresentationToStylePlugin {
+    pub fn new() -> Self {
+        Self {
+            has_stylesheet: false,
+            elements_converted: 0,
+            attributes_converted: 0,
+        }
+    }
+}
+
+impl Default for PresentationToStylePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for PresentationToStylePlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.has_stylesheet = false;
+        self.elements_converted = 0;
+        self.attributes_converted = 0;
+        Ok(())
+    }
+
+    fn requires_analysis(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, events: &[Event<'static>]) -> io::Result<()> {
+        self.has_stylesheet = has_stylesheet(events);
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        if self.has_stylesheet {
+            return Ok(ElementAction::Keep);
+        }
+        let attrs = owned_attributes(element);
+        let style = attrs.iter().find(|(key, _)| key == "style").map(|(_, value)| value.as_str());
+        let styled: Vec<String> = style
+            .into_iter()
+            .flat_map(style_declarations)
+            .map(|(property, _)| property.to_ascii_lowercase())
+            .collect();
+
+        let mut declarations: Vec<String> = Vec::new();
+        let mut kept = Vec::with_capacity(attrs.len());
+        let mut converted = 0;
+        for (key, value) in &attrs {
+            // A semicolon would end the declaration early
+            let movable = PRESENTATION_ATTRIBUTES.contains(&key.as_str())
+                && !value.trim().is_empty()
+                && !value.contains(';');
+            if !movable {
+                if key != "style" {
+                    kept.push((key.clone(), value.clone()));
+                }
+                continue;
+            }
+            converted += 1;
+            if !styled.contains(key) {
+                declarations.push(format!("{}:{}", key, value.trim()));
+            }
+        }
+        if converted == 0 {
+            return Ok(ElementAction::Keep);
+        }
+
+        self.elements_converted += 1;
+        self.attributes_converted += converted;
+
+        let existing = style.map(|style| style.trim().trim_end_matches(';')).filter(|style| !style.is_empty());
+        let style = existing.into_iter().map(str::to_string).chain(declarations).collect::<Vec<_>>().join(";");
+        if !style.is_empty() {
+            kept.push(("style".to_string(), style));
+        }
+        set_attributes(element, &kept);
+        Ok(ElementAction::Keep)
+    }
+
+    fn name(&self) -> &str {
+        "PresentationToStyle"
+    }
+}
+
+impl PluginStatistics for PresentationToStylePlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Elements converted", self.elements_converted.to_string()),
+            ("Attributes converted", self.attributes_converted.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+    use crate::plugins::InlineStylesPlugin;
+    use crate::processor::SVGProcessor;
+
+    #[test]
+    fn test_attributes_become_styles() -> io::Result<()> {
+        let svg = r#"<svg><rect id="r" fill="red" width="1" stroke=" blue " font-family="serif"/><g style="opacity:.5;" fill="red"/><g fill="red" style="fill:blue"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(PresentationToStylePlugin::new(), svg)?,
+            r#"<svg><rect id="r" width="1" style="fill:red;stroke:blue;font-family:serif"/><g style="opacity:.5;fill:red"/><g style="fill:blue"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip_with_inline_styles() -> io::Result<()> {
+        let svg = r#"<svg><rect width="1" fill="red" stroke="blue" opacity=".5"/><path d="M0 0" style="font:12px serif" fill="none"/></svg>"#;
+
+        let styled = optimize_with(PresentationToStylePlugin::new(), svg)?;
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(InlineStylesPlugin::new());
+        assert_eq!(
+            processor.optimize_str(&styled)?,
+            r#"<svg><rect width="1" fill="red" stroke="blue" opacity=".5"/><path d="M0 0" fill="none" style="font:12px serif"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_documents_with_stylesheets_are_left_alone() -> io::Result<()> {
+        let svg = r#"<svg><style>rect { fill: blue }</style><rect fill="red"/></svg>"#;
+        assert_eq!(optimize_with(PresentationToStylePlugin::new(), svg)?, svg);
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes};
+use quick_xml::events::BytesStart;
+use regex::Regex;
+use std::io;
+
+/// An `element:attribute:value` pattern, each part compiled to a regex.
+struct AttrPattern {
+    element: Regex,
+    attribute: Regex,
+    value: Regex,
+}
+
+impl AttrPattern {
+    /// Parse a pattern the way SVGO's `removeAttrs` does.
+    ///
+    /// A single part matches attributes on any element, two parts are
+    /// `element:attribute`, and three are `element:attribute:value`. Parts
+    /// between the first and the last belong to the attribute, so
+    /// `*:inkscape:*:*` matches every `inkscape:` attribute. A part that is
+    /// just `*` matches anything; the others are regexes matched against
+    /// the whole name or value.
+    fn parse(pattern: &str) -> io::Result<Self> {
+        let parts: Vec<&str> = pattern.split(':').collect();
+        let (element, attribute, value) = match parts.as_slice() {
+            [attribute] => (&["*"][..], std::slice::from_ref(attribute), &["*"][..]),
+            [element, attribute] => (
+                std::slice::from_ref(element),
+                std::slice::from_ref(attribute),
+                &["*"][..],
+            ),
+            [element, attribute @ .., value] => (
+                std::slice::from_ref(element),
+                attribute,
+                std::slice::from_ref(value),
+            ),
+            [] => unreachable!("split always yields a part"),
+        };
+
+        let compile = |segments: &[&str]| {
+            let source = segments
+                .iter()
+                .map(|segment| if *segment == "*" { ".*" } else { segment })
+                .collect::<Vec<_>>()
+                .join(":");
+            Regex::new(&format!("^(?:{})$", source)).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid removeAttrs pattern '{}': {}", pattern, e),
+                )
+            })
+        };
+        Ok(Self {
+            element: compile(element)?,
+            attribute: compile(attribute)?,
+            value: compile(value)?,
+        })
+    }
+}
+
+/// Removes attributes matching `element:attribute:value` patterns.
+///
+/// Useful for editor-specific attributes like `inkscape:label` or
+/// `sodipodi:nodetypes`, or for stripping a hard-coded color so it can be
+/// set from CSS. Patterns are compiled in `init`, so an invalid one fails
+/// the run before any output is written.
+pub struct RemoveAttrsPlugin {
+    patterns: Vec<String>,
+    compiled: Vec<AttrPattern>,
+    attributes_removed: usize,
+}
+
+impl RemoveAttrsPlugin {
+    pub fn new(patterns: Vec<String>) -> Self {
+        Self {
+            patterns,
+            compiled: Vec::new(),
+            attributes_removed: 0,
+        }
+    }
+
+    fn is_removed(&self, element: &str, key: &str, value: &str) -> bool {
+        self.compiled.iter().any(|pattern| {
+            pattern.element.is_match(element) && pattern.attribute.is_match(key) && pattern.value.is_match(value)
+        })
+    }
+}
+
+impl SVGPlugin for RemoveAttrsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.compiled = self
+            .patterns
+            .iter()
+            .map(|pattern| AttrPattern::parse(pattern))
+            .collect::<io::Result<_>>()?;
+        self.attributes_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+        let attrs = owned_attributes(element);
+        let kept: Vec<_> = attrs
+            .iter()
+            .filter(|(key, value)| !self.is_removed(&name, key, value))
+            .cloned()
+            .collect();
+
+        if kept.len() != attrs.len() {
+            self.attributes_removed += attrs.len() - kept.len();
+            set_attributes(element, &kept);
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn name(&self) -> &str {
+        "RemoveAttrs"
+    }
+}
+
+impl PluginStatistics for RemoveAttrsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Attributes removed", self.attributes_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_element_attribute_value_pattern() -> io::Result<()> {
+        let svg = r##"<svg fill="#000000"><path fill="#000000" d="M0 0"/><rect fill="#ff0000"/></svg>"##;
+
+        assert_eq!(
+            optimize_with(RemoveAttrsPlugin::new(vec!["*:fill:#000000".to_string()]), svg)?,
+            r##"<svg><path d="M0 0"/><rect fill="#ff0000"/></svg>"##
+        );
+        assert_eq!(
+            optimize_with(RemoveAttrsPlugin::new(vec!["path:fill".to_string()]), svg)?,
+            r##"<svg fill="#000000"><path d="M0 0"/><rect fill="#ff0000"/></svg>"##
+        );
+        assert_eq!(optimize_with(RemoveAttrsPlugin::new(vec!["(fill|d)".to_string()]), svg)?, "<svg><path/><rect/></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_namespace_prefix_wildcard() -> io::Result<()> {
+        let svg = r#"<svg inkscape:version="1.3" sodipodi:docname="a.svg"><g inkscape:label="Layer" inkscape:groupmode="layer" id="g"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(
+                RemoveAttrsPlugin::new(vec!["*:inkscape:*:*".to_string(), "*:sodipodi:.*:*".to_string()]),
+                svg
+            )?,
+            r#"<svg><g id="g"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_pattern_fails_init() {
+        let mut plugin = RemoveAttrsPlugin::new(vec!["*:fill(:*".to_string()]);
+        assert!(plugin.init().is_err());
+    }
+}
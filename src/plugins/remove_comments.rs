@@ -0,0 +1,113 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use quick_xml::events::{BytesStart, BytesText};
+use regex::Regex;
+use std::io;
+
+/// Removes XML comments.
+///
+/// Comments matching one of the preserve patterns are kept. By default the
+/// only pattern is `^!`, which keeps legal and license notices written as
+/// `<!--! ... -->`, matching SVGO.
+pub struct RemoveCommentsPlugin {
+    preserve: Vec<Regex>,
+    comments_removed: usize,
+}
+
+impl RemoveCommentsPlugin {
+    pub fn new() -> Self {
+        Self::with_preserve_patterns(vec![Regex::new("^!").unwrap()])
+    }
+
+    /// Create a plugin that keeps comments matching any of `patterns`.
+    ///
+    /// The patterns replace the default `^!` rule; pass an empty list to
+    /// remove every comment.
+    pub fn with_preserve_patterns(patterns: Vec<Regex>) -> Self {
+        Self {
+            preserve: patterns,
+            comments_removed: 0,
+        }
+    }
+
+    /// Additionally keep comments that start with the literal `prefix`.
+    pub fn preserve_prefix(mut self, prefix: &str) -> Self {
+        let pattern = format!("^{}", regex::escape(prefix));
+        self.preserve.push(Regex::new(&pattern).unwrap());
+        self
+    }
+}
+
+impl Default for RemoveCommentsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveCommentsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.comments_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, _element: &mut BytesStart) -> io::Result<ElementAction> {
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_comment<'a>(&mut self, comment: BytesText<'a>) -> io::Result<Option<BytesText<'a>>> {
+        let content = String::from_utf8_lossy(&comment);
+        if self.preserve.iter().any(|pattern| pattern.is_match(&content)) {
+            return Ok(Some(comment));
+        }
+
+        self.comments_removed += 1;
+        Ok(None)
+    }
+
+    fn name(&self) -> &str {
+        "RemoveComments"
+    }
+}
+
+impl PluginStatistics for RemoveCommentsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Comments removed", self.comments_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_comments_are_removed_except_legal() -> io::Result<()> {
+        let output = optimize_with(
+            RemoveCommentsPlugin::new(),
+            "<svg><!-- foo --><!--! keep --><g/></svg>",
+        )?;
+
+        assert_eq!(output, "<svg><!--! keep --><g/></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_preserve_patterns() -> io::Result<()> {
+        let plugin =
+            RemoveCommentsPlugin::with_preserve_patterns(vec![Regex::new("(?i)copyright").unwrap()]);
+
+        let output = optimize_with(plugin, "<svg><!-- Copyright ACME --><!--! legal --></svg>")?;
+
+        assert_eq!(output, "<svg><!-- Copyright ACME --></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_literal_prefix() -> io::Result<()> {
+        let plugin = RemoveCommentsPlugin::with_preserve_patterns(Vec::new()).preserve_prefix("[keep]");
+
+        let output = optimize_with(plugin, "<svg><!--[keep] a--><!--k b--></svg>")?;
+
+        assert_eq!(output, "<svg><!--[keep] a--></svg>");
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes};
+use quick_xml::events::BytesStart;
+use std::io;
+
+/// Removes `data-*` attributes from every element.
+pub struct RemoveDataAttributesPlugin {
+    attributes_removed: usize,
+}
+
+impl RemoveDataAttributesPlugin {
+    pub fn new() -> Self {
+        Self {
+            attributes_removed: 0,
+        }
+    }
+
+    fn is_data_attribute(key: &str) -> bool {
+        let local_name = key.rsplit(':').next().unwrap_or(key);
+        local_name.starts_with("data-")
+    }
+}
+
+impl Default for RemoveDataAttributesPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveDataAttributesPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.attributes_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let attrs = owned_attributes(element);
+        let kept: Vec<_> = attrs
+            .iter()
+            .filter(|(key, _)| !Self::is_data_attribute(key))
+            .cloned()
+            .collect();
+
+        if kept.len() != attrs.len() {
+            self.attributes_removed += attrs.len() - kept.len();
+            set_attributes(element, &kept);
+        }
+
+        Ok(ElementAction::Keep)
+    }
+
+    fn name(&self) -> &str {
+        "RemoveDataAttributes"
+    }
+}
+
+impl PluginStatistics for RemoveDataAttributesPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Data attributes removed", self.attributes_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::SVGProcessor;
+
+    #[test]
+    fn test_data_attributes_are_removed() -> io::Result<()> {
+        let mut plugin = RemoveDataAttributesPlugin::new();
+        let mut element = BytesStart::new("rect")
+            .with_attributes([("data-name", "box"), ("fill", "red"), ("data-id", "1")]);
+
+        plugin.process_element(&mut element)?;
+
+        assert_eq!(owned_attributes(&element), vec![("fill".to_string(), "red".to_string())]);
+        assert_eq!(
+            plugin.get_statistics(),
+            vec![("Data attributes removed", "2".to_string())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_attribute_order_is_preserved() -> io::Result<()> {
+        let mut plugin = RemoveDataAttributesPlugin::new();
+        let mut element = BytesStart::new("g")
+            .with_attributes([("id", "a"), ("data-x", "1"), ("fill", "red"), ("stroke", "blue")]);
+
+        plugin.process_element(&mut element)?;
+
+        let keys: Vec<_> = owned_attributes(&element).into_iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec!["id", "fill", "stroke"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_start_and_empty_elements() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(RemoveDataAttributesPlugin::new());
+
+        let output = processor
+            .optimize_str(r#"<svg data-a="1"><g data-b="2"><rect data-c="3" width="1"/></g></svg>"#)?;
+
+        assert_eq!(output, r#"<svg><g><rect width="1"/></g></svg>"#);
+        Ok(())
+    }
+}
@@ -0,0 +1,472 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{has_stylesheet, owned_attributes, set_attributes, style_declarations, XLinkPrefixes};
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use std::collections::HashMap;
+use std::io;
+
+/// Default values of SVG 1.1 attributes as `(element, attribute, value)`.
+///
+/// An element name of `*` applies to every element. An attribute may be
+/// listed more than once with different spellings of the same default.
+pub const DEFAULT_ATTRIBUTES: &[(&str, &str, &str)] = &[
+    // Presentation attributes
+    ("*", "alignment-baseline", "auto"),
+    ("*", "baseline-shift", "baseline"),
+    ("*", "clip-path", "none"),
+    ("*", "clip-rule", "nonzero"),
+    ("*", "color-interpolation", "sRGB"),
+    ("*", "color-interpolation-filters", "linearRGB"),
+    ("*", "color-rendering", "auto"),
+    ("*", "cursor", "auto"),
+    ("*", "direction", "ltr"),
+    ("*", "display", "inline"),
+    ("*", "dominant-baseline", "auto"),
+    ("*", "enable-background", "accumulate"),
+    ("*", "fill", "black"),
+    ("*", "fill", "#000"),
+    ("*", "fill", "#000000"),
+    ("*", "fill-opacity", "1"),
+    ("*", "fill-rule", "nonzero"),
+    ("*", "filter", "none"),
+    ("*", "flood-color", "black"),
+    ("*", "flood-opacity", "1"),
+    ("*", "font-size-adjust", "none"),
+    ("*", "font-stretch", "normal"),
+    ("*", "font-style", "normal"),
+    ("*", "font-variant", "normal"),
+    ("*", "font-weight", "normal"),
+    ("*", "glyph-orientation-horizontal", "0deg"),
+    ("*", "glyph-orientation-vertical", "auto"),
+    ("*", "image-rendering", "auto"),
+    ("*", "kerning", "auto"),
+    ("*", "letter-spacing", "normal"),
+    ("*", "lighting-color", "white"),
+    ("*", "marker-end", "none"),
+    ("*", "marker-mid", "none"),
+    ("*", "marker-start", "none"),
+    ("*", "mask", "none"),
+    ("*", "opacity", "1"),
+    ("*", "pointer-events", "visiblePainted"),
+    ("*", "shape-rendering", "auto"),
+    ("*", "stop-color", "black"),
+    ("*", "stop-opacity", "1"),
+    ("*", "stroke", "none"),
+    ("*", "stroke-dasharray", "none"),
+    ("*", "stroke-dashoffset", "0"),
+    ("*", "stroke-linecap", "butt"),
+    ("*", "stroke-linejoin", "miter"),
+    ("*", "stroke-miterlimit", "4"),
+    ("*", "stroke-opacity", "1"),
+    ("*", "stroke-width", "1"),
+    ("*", "text-anchor", "start"),
+    ("*", "text-decoration", "none"),
+    ("*", "text-rendering", "auto"),
+    ("*", "unicode-bidi", "normal"),
+    ("*", "visibility", "visible"),
+    ("*", "word-spacing", "normal"),
+    ("*", "writing-mode", "lr-tb"),
+    // Structure
+    ("svg", "x", "0"),
+    ("svg", "y", "0"),
+    ("svg", "preserveAspectRatio", "xMidYMid meet"),
+    ("symbol", "preserveAspectRatio", "xMidYMid meet"),
+    ("use", "x", "0"),
+    ("use", "y", "0"),
+    ("image", "x", "0"),
+    ("image", "y", "0"),
+    ("image", "preserveAspectRatio", "xMidYMid meet"),
+    // Shapes
+    ("rect", "x", "0"),
+    ("rect", "y", "0"),
+    ("circle", "cx", "0"),
+    ("circle", "cy", "0"),
+    ("ellipse", "cx", "0"),
+    ("ellipse", "cy", "0"),
+    ("line", "x1", "0"),
+    ("line", "y1", "0"),
+    ("line", "x2", "0"),
+    ("line", "y2", "0"),
+    // Text
+    ("textPath", "startOffset", "0"),
+    ("textPath", "method", "align"),
+    ("textPath", "spacing", "exact"),
+    // Paint servers
+    ("linearGradient", "x1", "0%"),
+    ("linearGradient", "y1", "0%"),
+    ("linearGradient", "x2", "100%"),
+    ("linearGradient", "y2", "0%"),
+    ("linearGradient", "gradientUnits", "objectBoundingBox"),
+    ("linearGradient", "spreadMethod", "pad"),
+    ("radialGradient", "cx", "50%"),
+    ("radialGradient", "cy", "50%"),
+    ("radialGradient", "r", "50%"),
+    ("radialGradient", "gradientUnits", "objectBoundingBox"),
+    ("radialGradient", "spreadMethod", "pad"),
+    ("stop", "offset", "0"),
+    ("pattern", "x", "0"),
+    ("pattern", "y", "0"),
+    ("pattern", "patternUnits", "objectBoundingBox"),
+    ("pattern", "patternContentUnits", "userSpaceOnUse"),
+    ("pattern", "preserveAspectRatio", "xMidYMid meet"),
+    // Clipping, masking and markers
+    ("clipPath", "clipPathUnits", "userSpaceOnUse"),
+    ("mask", "maskUnits", "objectBoundingBox"),
+    ("mask", "maskContentUnits", "userSpaceOnUse"),
+    ("mask", "x", "-10%"),
+    ("mask", "y", "-10%"),
+    ("mask", "width", "120%"),
+    ("mask", "height", "120%"),
+    ("marker", "markerUnits", "strokeWidth"),
+    ("marker", "refX", "0"),
+    ("marker", "refY", "0"),
+    ("marker", "markerWidth", "3"),
+    ("marker", "markerHeight", "3"),
+    ("marker", "orient", "0"),
+    // Filters
+    ("filter", "filterUnits", "objectBoundingBox"),
+    ("filter", "primitiveUnits", "userSpaceOnUse"),
+    ("filter", "x", "-10%"),
+    ("filter", "y", "-10%"),
+    ("filter", "width", "120%"),
+    ("filter", "height", "120%"),
+    ("feBlend", "mode", "normal"),
+    ("feColorMatrix", "type", "matrix"),
+    ("feComposite", "operator", "over"),
+    ("feComposite", "k1", "0"),
+    ("feComposite", "k2", "0"),
+    ("feComposite", "k3", "0"),
+    ("feComposite", "k4", "0"),
+    ("feConvolveMatrix", "divisor", "1"),
+    ("feConvolveMatrix", "bias", "0"),
+    ("feConvolveMatrix", "edgeMode", "duplicate"),
+    ("feConvolveMatrix", "preserveAlpha", "false"),
+    ("feDiffuseLighting", "surfaceScale", "1"),
+    ("feDiffuseLighting", "diffuseConstant", "1"),
+    ("feDisplacementMap", "scale", "0"),
+    ("feDisplacementMap", "xChannelSelector", "A"),
+    ("feDisplacementMap", "yChannelSelector", "A"),
+    ("feDistantLight", "azimuth", "0"),
+    ("feDistantLight", "elevation", "0"),
+    ("fePointLight", "x", "0"),
+    ("fePointLight", "y", "0"),
+    ("fePointLight", "z", "0"),
+    ("feFuncA", "type", "identity"),
+    ("feFuncB", "type", "identity"),
+    ("feFuncG", "type", "identity"),
+    ("feFuncR", "type", "identity"),
+    ("feGaussianBlur", "stdDeviation", "0"),
+    ("feMorphology", "operator", "erode"),
+    ("feMorphology", "radius", "0"),
+    ("feOffset", "dx", "0"),
+    ("feOffset", "dy", "0"),
+    ("feSpecularLighting", "surfaceScale", "1"),
+    ("feSpecularLighting", "specularConstant", "1"),
+    ("feSpecularLighting", "specularExponent", "1"),
+    ("feSpotLight", "x", "0"),
+    ("feSpotLight", "y", "0"),
+    ("feSpotLight", "z", "0"),
+    ("feSpotLight", "pointsAtX", "0"),
+    ("feSpotLight", "pointsAtY", "0"),
+    ("feSpotLight", "pointsAtZ", "0"),
+    ("feSpotLight", "specularExponent", "1"),
+    ("feTurbulence", "baseFrequency", "0"),
+    ("feTurbulence", "numOctaves", "1"),
+    ("feTurbulence", "seed", "0"),
+    ("feTurbulence", "stitchTiles", "noStitch"),
+    ("feTurbulence", "type", "turbulence"),
+];
+
+/// Properties that children inherit from their ancestors.
+///
+/// A default value for one of these is only redundant when no ancestor
+/// sets the property, since it would otherwise override the inherited value.
+pub(crate) const INHERITED_PROPERTIES: &[&str] = &[
+    "clip-rule",
+    "color-interpolation",
+    "color-interpolation-filters",
+    "color-rendering",
+    "cursor",
+    "direction",
+    "fill",
+    "fill-opacity",
+    "fill-rule",
+    "font-size-adjust",
+    "font-stretch",
+    "font-style",
+    "font-variant",
+    "font-weight",
+    "glyph-orientation-horizontal",
+    "glyph-orientation-vertical",
+    "image-rendering",
+    "kerning",
+    "letter-spacing",
+    "marker-end",
+    "marker-mid",
+    "marker-start",
+    "pointer-events",
+    "shape-rendering",
+    "stroke",
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+    "text-anchor",
+    "text-rendering",
+    "visibility",
+    "word-spacing",
+    "writing-mode",
+];
+
+/// Elements whose own attributes can be inherited from the element their
+/// `href` points to, so removing a default would expose that value.
+const TEMPLATE_ELEMENTS: &[&[u8]] = &[b"linearGradient", b"radialGradient", b"pattern", b"filter"];
+
+/// Removes attributes whose value equals their default in the SVG spec.
+///
+/// Values must match the table exactly; `fill="#000"` is only removed
+/// because the table lists it alongside `black`.
+///
+/// Defaults of inherited properties are kept wherever the inherited value
+/// isn't known: below an ancestor setting the property, inside an element
+/// with a `class` or an `id` (a `<use>` may give it other values), and
+/// anywhere in a document with a stylesheet.
+pub struct RemoveDefaultAttributesPlugin {
+    /// Default values by attribute name, as `(element, value)`.
+    defaults: HashMap<String, Vec<(String, String)>>,
+    /// Inherited properties set on each open element. `None` marks an
+    /// element with a `class` or an `id`, which may get any of them from
+    /// CSS or a `<use>`.
+    ancestors: Vec<Option<Vec<String>>>,
+    /// Whether the document has a stylesheet, which may set any inherited
+    /// property on any element.
+    has_stylesheet: bool,
+    xlink: XLinkPrefixes,
+    attributes_removed: usize,
+}
+
+impl RemoveDefaultAttributesPlugin {
+    pub fn new() -> Self {
+        Self::with_defaults(DEFAULT_ATTRIBUTES.iter().copied())
+    }
+
+    /// Create a plugin with a custom table of `(element, attribute, value)`
+    /// defaults, e.g. `DEFAULT_ATTRIBUTES` extended with entries of your own.
+    pub fn with_defaults<'a, I>(defaults: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str, &'a str)>,
+    {
+        let mut table: HashMap<String, Vec<(String, String)>> = HashMap::new();
+        for (element, attribute, value) in defaults {
+            table
+                .entry(attribute.to_string())
+                .or_default()
+                .push((element.to_string(), value.to_string()));
+        }
+
+        Self {
+            defaults: table,
+            ancestors: Vec::new(),
+            has_stylesheet: false,
+            xlink: XLinkPrefixes::default(),
+            attributes_removed: 0,
+        }
+    }
+
+    fn is_default(&self, element: &str, key: &str, value: &str) -> bool {
+        self.defaults.get(key).is_some_and(|defaults| {
+            defaults
+                .iter()
+                .any(|(name, default)| (name == "*" || name == element) && default == value)
+        })
+    }
+
+    /// Whether an ancestor or a stylesheet may set the inherited property `key`.
+    fn is_inherited(&self, key: &str) -> bool {
+        self.has_stylesheet || self.ancestors.iter().any(|properties| match properties {
+            Some(properties) => properties.iter().any(|property| property == key),
+            None => true,
+        })
+    }
+
+    /// Inherited properties an element sets for its children.
+    fn inherited_properties(attrs: &[(String, String)]) -> Option<Vec<String>> {
+        if attrs.iter().any(|(key, _)| key == "class" || key == "id") {
+            return None;
+        }
+
+        let styles = attrs
+            .iter()
+            .filter(|(key, _)| key == "style")
+            .flat_map(|(_, style)| style_declarations(style).map(|(property, _)| property));
+        let properties = attrs
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .chain(styles)
+            .filter(|property| INHERITED_PROPERTIES.contains(property))
+            .map(str::to_string)
+            .collect();
+        Some(properties)
+    }
+}
+
+impl Default for RemoveDefaultAttributesPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveDefaultAttributesPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.ancestors.clear();
+        self.has_stylesheet = false;
+        self.xlink.clear();
+        self.attributes_removed = 0;
+        Ok(())
+    }
+
+    fn requires_analysis(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, events: &[Event<'static>]) -> io::Result<()> {
+        self.has_stylesheet = has_stylesheet(events);
+        self.xlink = XLinkPrefixes::from_events(events);
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+        let attrs = owned_attributes(element);
+
+        let is_template = TEMPLATE_ELEMENTS.contains(&name.as_bytes())
+            && attrs.iter().any(|(key, _)| self.xlink.is_link(key));
+        let has_id = attrs.iter().any(|(key, _)| key == "id");
+
+        let kept: Vec<_> = attrs
+            .iter()
+            .filter(|(key, value)| {
+                let default = if is_template {
+                    self.is_default("*", key, value)
+                } else {
+                    self.is_default(&name, key, value)
+                };
+                let inherited = INHERITED_PROPERTIES.contains(&key.as_str())
+                    && (has_id || self.is_inherited(key));
+                !default || inherited
+            })
+            .cloned()
+            .collect();
+
+        if kept.len() != attrs.len() {
+            self.attributes_removed += attrs.len() - kept.len();
+            set_attributes(element, &kept);
+        }
+
+        // Children inherit what was set before removal, which is what renders
+        self.ancestors.push(Self::inherited_properties(&attrs));
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_end<'a>(&mut self, end: BytesEnd<'a>) -> io::Result<Option<BytesEnd<'a>>> {
+        self.ancestors.pop();
+        Ok(Some(end))
+    }
+
+    fn name(&self) -> &str {
+        "RemoveDefaultAttributes"
+    }
+}
+
+impl PluginStatistics for RemoveDefaultAttributesPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Default attributes removed", self.attributes_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_default_attributes_are_removed() -> io::Result<()> {
+        let output = optimize_with(
+            RemoveDefaultAttributesPlugin::new(),
+            r#"<svg x="0"><rect x="0" y="5" fill="black" stroke="none" opacity="1" stroke-width="2"/><circle cx="0" fill-rule="evenodd"/></svg>"#,
+        )?;
+
+        assert_eq!(output, r#"<svg><rect y="5" stroke-width="2"/><circle fill-rule="evenodd"/></svg>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_defaults_are_element_specific() -> io::Result<()> {
+        let svg = r#"<svg><path cx="0"/><linearGradient x2="100%"/><radialGradient x2="100%"/></svg>"#;
+        let output = optimize_with(RemoveDefaultAttributesPlugin::new(), svg)?;
+
+        assert_eq!(output, r#"<svg><path cx="0"/><linearGradient/><radialGradient x2="100%"/></svg>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_inherited_values_are_respected() -> io::Result<()> {
+        let svg = r#"<svg><g fill="red"><path fill="black"/></g><g style="stroke:blue"><path stroke="none"/></g><g class="c"><path fill="black"/></g><g opacity="0.5"><path fill="black" opacity="1"/></g></svg>"#;
+        let output = optimize_with(RemoveDefaultAttributesPlugin::new(), svg)?;
+
+        assert_eq!(
+            output,
+            r#"<svg><g fill="red"><path fill="black"/></g><g style="stroke:blue"><path stroke="none"/></g><g class="c"><path fill="black"/></g><g opacity="0.5"><path/></g></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_referenced_elements_keep_inherited_defaults() -> io::Result<()> {
+        let svg = r##"<svg><path id="p" fill="black" opacity="1"/><g id="g"><path fill="black"/></g><use href="#p" fill="red"/><use href="#g" fill="red"/></svg>"##;
+        let output = optimize_with(RemoveDefaultAttributesPlugin::new(), svg)?;
+
+        assert_eq!(
+            output,
+            r##"<svg><path id="p" fill="black"/><g id="g"><path fill="black"/></g><use href="#p" fill="red"/><use href="#g" fill="red"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_stylesheets_keep_inherited_defaults() -> io::Result<()> {
+        let svg = "<svg><style>g{fill:red}</style><g><rect fill=\"black\" x=\"0\"/></g></svg>";
+        let output = optimize_with(RemoveDefaultAttributesPlugin::new(), svg)?;
+        assert_eq!(output, "<svg><style>g{fill:red}</style><g><rect fill=\"black\"/></g></svg>");
+
+        let svg = "<?xml-stylesheet href=\"a.css\"?><svg><rect fill=\"black\"/></svg>";
+        assert_eq!(optimize_with(RemoveDefaultAttributesPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_referencing_gradient_keeps_geometry() -> io::Result<()> {
+        let svg = r##"<svg><linearGradient id="b" x1="0%" href="#a" spreadMethod="pad"/></svg>"##;
+        let output = optimize_with(RemoveDefaultAttributesPlugin::new(), svg)?;
+
+        assert_eq!(output, r##"<svg><linearGradient id="b" x1="0%" href="#a" spreadMethod="pad"/></svg>"##);
+
+        let aliased = r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><linearGradient x1="0%" x:href="#a"/></svg>"##;
+        assert_eq!(optimize_with(RemoveDefaultAttributesPlugin::new(), aliased)?, aliased);
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_defaults() -> io::Result<()> {
+        let defaults = DEFAULT_ATTRIBUTES.iter().copied().chain([("text", "x", "0")]);
+        let output = optimize_with(
+            RemoveDefaultAttributesPlugin::with_defaults(defaults),
+            r#"<svg><text x="0" opacity="1">Hi</text></svg>"#,
+        )?;
+
+        assert_eq!(output, "<svg><text>Hi</text></svg>");
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes};
+use quick_xml::events::{BytesEnd, BytesStart};
+use std::io;
+
+/// Removes `width` and `height` from the outermost `<svg>` so it scales
+/// with its container.
+///
+/// The dimensions are only removed when the element has a `viewBox`, or
+/// when one can be made from them: `viewBox="0 0 W H"` is added when both
+/// are plain numbers or pixel values. Dimensions in other units, such as
+/// percentages or `em`, are left alone. Nested `<svg>` elements keep their
+/// dimensions, since those position them inside the parent.
+pub struct RemoveDimensionsPlugin {
+    depth: usize,
+    dimensions_removed: usize,
+    viewboxes_added: usize,
+}
+
+impl RemoveDimensionsPlugin {
+    pub fn new() -> Self {
+        Self {
+            depth: 0,
+            dimensions_removed: 0,
+            viewboxes_added: 0,
+        }
+    }
+
+    /// A `width` or `height` as a number of user units, if it is one.
+    fn user_units(value: &str) -> Option<f64> {
+        let value = value.trim();
+        let number = value.strip_suffix("px").unwrap_or(value);
+        number.parse::<f64>().ok().filter(|n| n.is_finite() && *n > 0.0)
+    }
+
+    fn remove_dimensions(&mut self, element: &mut BytesStart) {
+        let mut attrs = owned_attributes(element);
+        let value = |attrs: &[(String, String)], key: &str| {
+            attrs.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+        };
+        let (width, height) = (value(&attrs, "width"), value(&attrs, "height"));
+        if width.is_none() && height.is_none() {
+            return;
+        }
+
+        if value(&attrs, "viewBox").is_none() {
+            let size = width.as_deref().and_then(Self::user_units).zip(height.as_deref().and_then(Self::user_units));
+            let Some((width, height)) = size else { return };
+            attrs.push(("viewBox".to_string(), format!("0 0 {} {}", width, height)));
+            self.viewboxes_added += 1;
+        }
+
+        attrs.retain(|(key, _)| key != "width" && key != "height");
+        set_attributes(element, &attrs);
+        self.dimensions_removed += 1;
+    }
+}
+
+impl Default for RemoveDimensionsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveDimensionsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.depth = 0;
+        self.dimensions_removed = 0;
+        self.viewboxes_added = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        if self.depth == 0 && element.local_name().as_ref() == b"svg" {
+            self.remove_dimensions(element);
+        }
+        self.depth += 1;
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_end<'a>(&mut self, end: BytesEnd<'a>) -> io::Result<Option<BytesEnd<'a>>> {
+        self.depth = self.depth.saturating_sub(1);
+        Ok(Some(end))
+    }
+
+    fn name(&self) -> &str {
+        "RemoveDimensions"
+    }
+}
+
+impl PluginStatistics for RemoveDimensionsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Dimensions removed", self.dimensions_removed.to_string()),
+            ("viewBoxes added", self.viewboxes_added.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_dimensions_are_removed_with_a_viewbox() -> io::Result<()> {
+        let svg = r#"<svg width="100" height="50" viewBox="0 0 200 100"><svg width="10" height="10"/><symbol width="5"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(RemoveDimensionsPlugin::new(), svg)?,
+            r#"<svg viewBox="0 0 200 100"><svg width="10" height="10"/><symbol width="5"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_viewbox_is_made_from_dimensions() -> io::Result<()> {
+        assert_eq!(optimize_with(RemoveDimensionsPlugin::new(), r#"<svg width="24px" height="16.5"/>"#)?, r#"<svg viewBox="0 0 24 16.5"/>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_other_units_are_kept() -> io::Result<()> {
+        let svg = r#"<svg width="100%" height="2em"><rect/></svg>"#;
+
+        assert_eq!(optimize_with(RemoveDimensionsPlugin::new(), svg)?, svg);
+        assert_eq!(optimize_with(RemoveDimensionsPlugin::new(), r#"<svg width="10"/>"#)?, r#"<svg width="10"/>"#);
+        Ok(())
+    }
+}
@@ -0,0 +1,77 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use quick_xml::events::{BytesStart, BytesText};
+use std::io;
+
+/// Removes the `<!DOCTYPE svg ...>` declaration.
+///
+/// Renderers ignore the DTD, so the declaration is dead weight. A DOCTYPE
+/// whose internal subset declares entities is kept, since the document may
+/// use them (Illustrator writes `xmlns="&ns_svg;"`, for example).
+pub struct RemoveDoctypePlugin {
+    doctypes_removed: usize,
+}
+
+impl RemoveDoctypePlugin {
+    pub fn new() -> Self {
+        Self { doctypes_removed: 0 }
+    }
+}
+
+impl Default for RemoveDoctypePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveDoctypePlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.doctypes_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, _element: &mut BytesStart) -> io::Result<ElementAction> {
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_doctype<'a>(&mut self, doctype: BytesText<'a>) -> io::Result<Option<BytesText<'a>>> {
+        if String::from_utf8_lossy(&doctype).contains("<!ENTITY") {
+            return Ok(Some(doctype));
+        }
+        self.doctypes_removed += 1;
+        Ok(None)
+    }
+
+    fn name(&self) -> &str {
+        "RemoveDoctype"
+    }
+}
+
+impl PluginStatistics for RemoveDoctypePlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("DOCTYPE declarations removed", self.doctypes_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_multi_line_doctype_is_removed() -> io::Result<()> {
+        let svg = "<?xml version=\"1.0\"?>\n<!DOCTYPE svg PUBLIC \"-//W3C//DTD SVG 1.1//EN\"\n  \"http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd\">\n<svg><rect/></svg>";
+
+        let output = optimize_with(RemoveDoctypePlugin::new(), svg)?;
+
+        assert_eq!(output, "<?xml version=\"1.0\"?>\n\n<svg><rect/></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_doctype_declaring_entities_is_kept() -> io::Result<()> {
+        let svg = "<!DOCTYPE svg [\n  <!ENTITY ns_svg \"http://www.w3.org/2000/svg\">\n]><svg xmlns=\"&ns_svg;\"/>";
+
+        assert_eq!(optimize_with(RemoveDoctypePlugin::new(), svg)?, svg);
+        Ok(())
+    }
+}
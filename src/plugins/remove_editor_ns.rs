@@ -0,0 +1,174 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes};
+use quick_xml::events::BytesStart;
+use std::collections::HashSet;
+use std::io;
+
+/// Namespaces used by editors for their own bookkeeping.
+pub const EDITOR_NAMESPACES: &[&str] = &[
+    "http://www.inkscape.org/namespaces/inkscape",
+    "http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd",
+    "http://ns.adobe.com/AdobeIllustrator/10.0/",
+    "http://ns.adobe.com/AdobeSVGViewerExtensions/3.0/",
+    "http://ns.adobe.com/Extensibility/1.0/",
+    "http://ns.adobe.com/Flows/1.0/",
+    "http://ns.adobe.com/GenericCustomNamespace/1.0/",
+    "http://ns.adobe.com/Graphs/1.0/",
+    "http://ns.adobe.com/ImageReplacement/1.0/",
+    "http://ns.adobe.com/SaveForWeb/1.0/",
+    "http://ns.adobe.com/Variables/1.0/",
+    "http://ns.adobe.com/XPath/1.0/",
+    "http://www.bohemiancoding.com/sketch/ns",
+    "http://purl.org/dc/elements/1.1/",
+    "http://creativecommons.org/ns#",
+    "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+];
+
+/// Removes editor namespaces (Inkscape, Sodipodi, Illustrator, Sketch and
+/// the RDF/Dublin Core/Creative Commons metadata vocabularies).
+///
+/// A prefix becomes an editor prefix when it is declared with `xmlns:` for
+/// one of the `EDITOR_NAMESPACES`. Its declaration is dropped, along with
+/// every attribute using the prefix and every element in it (such as
+/// `<sodipodi:namedview>`), so the output has no unbound prefixes left.
+/// Prefixes on the preserve list are left alone.
+pub struct RemoveEditorNamespacesPlugin {
+    preserve: HashSet<String>,
+    /// Editor prefixes declared so far.
+    prefixes: HashSet<String>,
+    namespaces_removed: usize,
+    attributes_removed: usize,
+    elements_removed: usize,
+}
+
+impl RemoveEditorNamespacesPlugin {
+    pub fn new(preserve: Vec<String>) -> Self {
+        Self {
+            preserve: preserve.into_iter().collect(),
+            prefixes: HashSet::new(),
+            namespaces_removed: 0,
+            attributes_removed: 0,
+            elements_removed: 0,
+        }
+    }
+
+    fn is_editor_name(&self, name: &str) -> bool {
+        name.split_once(':')
+            .is_some_and(|(prefix, _)| self.prefixes.contains(prefix))
+    }
+}
+
+impl Default for RemoveEditorNamespacesPlugin {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl SVGPlugin for RemoveEditorNamespacesPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.prefixes.clear();
+        self.namespaces_removed = 0;
+        self.attributes_removed = 0;
+        self.elements_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let attrs = owned_attributes(element);
+
+        // Declarations come first, they may bind the element's own prefix
+        for (key, value) in &attrs {
+            if let Some(prefix) = key.strip_prefix("xmlns:") {
+                if EDITOR_NAMESPACES.contains(&value.as_str()) && !self.preserve.contains(prefix) {
+                    self.prefixes.insert(prefix.to_string());
+                }
+            }
+        }
+
+        if self.is_editor_name(&String::from_utf8_lossy(element.name().as_ref())) {
+            self.elements_removed += 1;
+            return Ok(ElementAction::Remove);
+        }
+
+        let mut changed = false;
+        let kept: Vec<_> = attrs
+            .into_iter()
+            .filter(|(key, _)| {
+                let declaration = key
+                    .strip_prefix("xmlns:")
+                    .is_some_and(|prefix| self.prefixes.contains(prefix));
+                if declaration {
+                    self.namespaces_removed += 1;
+                } else if self.is_editor_name(key) {
+                    self.attributes_removed += 1;
+                } else {
+                    return true;
+                }
+                changed = true;
+                false
+            })
+            .collect();
+
+        if changed {
+            set_attributes(element, &kept);
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn name(&self) -> &str {
+        "RemoveEditorNamespaces"
+    }
+}
+
+impl PluginStatistics for RemoveEditorNamespacesPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Namespaces removed", self.namespaces_removed.to_string()),
+            ("Editor attributes removed", self.attributes_removed.to_string()),
+            ("Editor elements removed", self.elements_removed.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    const INKSCAPE_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" xmlns:sodipodi="http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd" xmlns:xlink="http://www.w3.org/1999/xlink" inkscape:version="1.3"><sodipodi:namedview pagecolor="white"><inkscape:grid/></sodipodi:namedview><g inkscape:label="Layer 1" inkscape:groupmode="layer"><path sodipodi:nodetypes="cc" d="M0 0"/></g></svg>"#;
+
+    #[test]
+    fn test_editor_namespaces_are_removed() -> io::Result<()> {
+        let output = optimize_with(RemoveEditorNamespacesPlugin::default(), INKSCAPE_SVG)?;
+
+        assert_eq!(
+            output,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"><g><path d="M0 0"/></g></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_preserved_prefixes_are_kept() -> io::Result<()> {
+        let output = optimize_with(
+            RemoveEditorNamespacesPlugin::new(vec!["inkscape".to_string()]),
+            INKSCAPE_SVG,
+        )?;
+
+        assert!(output.contains("xmlns:inkscape="));
+        assert!(output.contains(r#"inkscape:label="Layer 1""#));
+        assert!(!output.contains("sodipodi"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_metadata_vocabularies_are_removed() -> io::Result<()> {
+        let svg = r#"<svg xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:cc="http://creativecommons.org/ns#"><metadata><rdf:RDF><cc:Work><dc:format>image/svg+xml</dc:format></cc:Work></rdf:RDF></metadata></svg>"#;
+
+        assert_eq!(
+            optimize_with(RemoveEditorNamespacesPlugin::default(), svg)?,
+            "<svg><metadata></metadata></svg>"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+use crate::plugins::remove_default_attrs::INHERITED_PROPERTIES;
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes};
+use quick_xml::events::BytesStart;
+use std::io;
+
+/// Attributes besides the inherited presentation attributes that are safe
+/// to drop when empty.
+const REMOVABLE_ATTRIBUTES: &[&str] = &["style", "class", "id"];
+
+/// Geometry attributes that may optionally be dropped when empty.
+const GEOMETRY_ATTRIBUTES: &[&str] = &["transform", "d"];
+
+/// Removes attributes with an empty or all-whitespace value, such as
+/// `fill=""` or `style=""`.
+///
+/// Only inherited presentation attributes, `style`, `class` and `id` are
+/// removed by default: an empty value there is ignored by renderers, and an
+/// empty id can't be referenced, so dropping it changes nothing. Empty
+/// `transform` and `d` attributes are removed only when enabled with
+/// [`with_geometry`](Self::with_geometry); other required attributes are
+/// left alone. The allow list adds attribute names, the deny list protects
+/// them.
+pub struct RemoveEmptyAttrsPlugin {
+    allow: Vec<String>,
+    deny: Vec<String>,
+    geometry: bool,
+    attributes_removed: usize,
+}
+
+/// The name the plugin is also known by.
+pub type RemoveEmptyAttributesPlugin = RemoveEmptyAttrsPlugin;
+
+impl RemoveEmptyAttrsPlugin {
+    pub fn new() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            geometry: false,
+            attributes_removed: 0,
+        }
+    }
+
+    /// Also remove these attributes when they are empty.
+    pub fn with_allow(mut self, allow: Vec<String>) -> Self {
+        self.allow = allow;
+        self
+    }
+
+    /// Never remove these attributes, even when they are empty.
+    pub fn with_deny(mut self, deny: Vec<String>) -> Self {
+        self.deny = deny;
+        self
+    }
+
+    /// Also remove empty `transform` attributes, which are the identity,
+    /// and empty `d` attributes, which draw nothing just like a missing one.
+    pub fn with_geometry(mut self, geometry: bool) -> Self {
+        self.geometry = geometry;
+        self
+    }
+
+    fn is_removable(&self, key: &str) -> bool {
+        if self.deny.iter().any(|name| name == key) {
+            return false;
+        }
+        self.allow.iter().any(|name| name == key)
+            || REMOVABLE_ATTRIBUTES.contains(&key)
+            || (self.geometry && GEOMETRY_ATTRIBUTES.contains(&key))
+            || INHERITED_PROPERTIES.contains(&key)
+    }
+}
+
+impl Default for RemoveEmptyAttrsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveEmptyAttrsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.attributes_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let attrs = owned_attributes(element);
+        let kept: Vec<_> = attrs
+            .iter()
+            .filter(|(key, value)| !(value.trim().is_empty() && self.is_removable(key)))
+            .cloned()
+            .collect();
+
+        if kept.len() < attrs.len() {
+            self.attributes_removed += attrs.len() - kept.len();
+            set_attributes(element, &kept);
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn name(&self) -> &str {
+        "RemoveEmptyAttrs"
+    }
+}
+
+impl PluginStatistics for RemoveEmptyAttrsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Empty attributes removed", self.attributes_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+    use crate::processor::SVGProcessor;
+
+    #[test]
+    fn test_empty_presentation_attributes_are_removed() -> io::Result<()> {
+        let svg = r#"<svg><path d="" fill="" stroke=" " style="" class="" id=" " transform="" data-x=""/></svg>"#;
+
+        let output = optimize_with(RemoveEmptyAttrsPlugin::new(), svg)?;
+
+        assert_eq!(output, r#"<svg><path d="" transform="" data-x=""/></svg>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_geometry_is_removed_when_enabled() -> io::Result<()> {
+        let svg = r#"<svg><g transform=" "><path d="" x=""/></g><path d="M0 0"/></svg>"#;
+
+        let output = optimize_with(RemoveEmptyAttrsPlugin::new().with_geometry(true), svg)?;
+
+        assert_eq!(output, r#"<svg><g><path x=""/></g><path d="M0 0"/></svg>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_allow_and_deny_lists() -> io::Result<()> {
+        let svg = r#"<svg><rect fill="" class="" data-x="" stroke="none"/></svg>"#;
+        let plugin = RemoveEmptyAttrsPlugin::new()
+            .with_allow(vec!["data-x".to_string()])
+            .with_deny(vec!["class".to_string()]);
+
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(plugin);
+        let output = processor.optimize_str(svg)?;
+
+        assert_eq!(output, r#"<svg><rect class="" stroke="none"/></svg>"#);
+        assert_eq!(processor.plugin_statistics()[0].1[0].1, "2");
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+//! Removes container elements left without content.
+//!
+//! Other plugins often leave `<defs>` or groups behind whose children they
+//! removed. Whether a container can go depends on its subtree and on
+//! references elsewhere in the document, so the plugin runs on the document
+//! tree. Children are handled before their parent, so a container whose
+//! only content was an empty container is removed as well.
+
+use crate::plugins::traits::{PluginStatistics, TreePlugin};
+use crate::plugins::util::{referenced_ids, XLinkPrefixes};
+use crate::tree::{Document, Element, Node};
+use std::collections::HashSet;
+use std::io;
+
+/// Elements that render nothing by themselves, only through their content.
+const CONTAINERS: &[&str] = &[
+    "a", "clipPath", "defs", "g", "marker", "mask", "pattern", "switch", "symbol",
+];
+
+/// Removes empty container elements such as `<g>`, `<defs>` and `<symbol>`.
+///
+/// A container is empty when it holds nothing but whitespace and comments.
+/// It is kept when its `id` is referenced, by `<use>` or a `url(#...)`
+/// value, when it has a `filter`, which can paint without content, or when
+/// it has an `href`, through which a pattern inherits another's content.
+/// The root `<svg>` is never removed.
+pub struct RemoveEmptyContainersPlugin {
+    /// Ids referenced anywhere in the document.
+    references: HashSet<String>,
+    xlink: XLinkPrefixes,
+    containers_removed: usize,
+}
+
+impl RemoveEmptyContainersPlugin {
+    pub fn new() -> Self {
+        Self {
+            references: HashSet::new(),
+            xlink: XLinkPrefixes::default(),
+            containers_removed: 0,
+        }
+    }
+
+    /// Remove the empty containers among `nodes` and their descendants.
+    fn remove(&mut self, nodes: &mut Vec<Node>) {
+        nodes.retain_mut(|node| {
+            let Node::Element(element) = node else { return true };
+            self.remove(&mut element.children);
+
+            if self.is_removable(element) {
+                self.containers_removed += 1;
+                return false;
+            }
+            true
+        });
+    }
+
+    fn is_removable(&self, element: &Element) -> bool {
+        CONTAINERS.contains(&element.name.as_str())
+            && is_empty(element)
+            && element.attr("filter").is_none()
+            && !element.attrs.iter().any(|(key, _)| self.xlink.is_link(key))
+            && !element.attr("id").is_some_and(|id| self.references.contains(id))
+    }
+}
+
+/// Whether an element has no content besides whitespace and comments.
+fn is_empty(element: &Element) -> bool {
+    element.children.iter().all(|node| match node {
+        Node::Text(text) => text.trim().is_empty(),
+        Node::Comment(_) => true,
+        _ => false,
+    })
+}
+
+impl Default for RemoveEmptyContainersPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreePlugin for RemoveEmptyContainersPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.references.clear();
+        self.xlink.clear();
+        self.containers_removed = 0;
+        Ok(())
+    }
+
+    fn process_tree(&mut self, document: &mut Document) -> io::Result<()> {
+        self.references = referenced_ids(&document.children);
+        self.xlink = XLinkPrefixes::from_nodes(&document.children);
+        self.remove(&mut document.children);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "RemoveEmptyContainers"
+    }
+}
+
+impl PluginStatistics for RemoveEmptyContainersPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Empty containers removed", self.containers_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_tree_with;
+
+    #[test]
+    fn test_nested_empty_groups_are_removed() -> io::Result<()> {
+        let svg = "<svg><g fill=\"red\">\n  <g><!-- layer --><g/></g>\n  <defs></defs>\n</g><rect/><symbol id=\"s\"/></svg>";
+
+        assert_eq!(optimize_tree_with(RemoveEmptyContainersPlugin::new(), svg)?, "<svg><rect/></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_referenced_and_meaningful_containers_are_kept() -> io::Result<()> {
+        let svg = r##"<svg><symbol id="icon"/><use href="#icon"/><mask id="m"/><rect mask="url(#m)"/><g filter="url(#f)"/><pattern id="p" href="#q"/><g><text/></g></svg>"##;
+
+        assert_eq!(optimize_tree_with(RemoveEmptyContainersPlugin::new(), svg)?, svg);
+        assert_eq!(optimize_tree_with(RemoveEmptyContainersPlugin::new(), "<svg/>")?, "<svg/>");
+
+        let aliased = r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><symbol id="s"/><use x:href="#s"/><pattern x:href="#q"/></svg>"##;
+        assert_eq!(optimize_tree_with(RemoveEmptyContainersPlugin::new(), aliased)?, aliased);
+        Ok(())
+    }
+
+    #[test]
+    fn test_statistics() -> io::Result<()> {
+        let mut plugin = RemoveEmptyContainersPlugin::new();
+        let mut document = Document::parse(b"<svg><g><g/><defs/></g></svg>").unwrap();
+
+        plugin.process_tree(&mut document)?;
+        assert_eq!(plugin.get_statistics(), [("Empty containers removed", "3".to_string())]);
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{event_len, owned_attributes, set_attributes, XLinkPrefixes};
+use quick_xml::events::{BytesStart, Event};
+use std::io;
+
+/// Removes content that makes a viewer load or embed other documents.
+///
+/// `<foreignObject>` elements are dropped with their subtree, since they can
+/// carry arbitrary HTML. `<use>` elements referencing anything but an
+/// element of the same document are dropped too, as they render nothing
+/// once the other document is gone. On the remaining elements, `href` and
+/// `xlink:href` attributes pointing at `http:`, `https:` or
+/// protocol-relative `//` URLs are stripped, so `<image>` and `<a>` can't
+/// fetch from or link to a remote host. Links are recognized under any
+/// prefix bound to the XLink namespace.
+pub struct RemoveExternalRefsPlugin {
+    xlink: XLinkPrefixes,
+    foreign_objects_removed: usize,
+    uses_removed: usize,
+    refs_removed: usize,
+    bytes_removed: usize,
+}
+
+impl RemoveExternalRefsPlugin {
+    pub fn new() -> Self {
+        Self {
+            xlink: XLinkPrefixes::default(),
+            foreign_objects_removed: 0,
+            uses_removed: 0,
+            refs_removed: 0,
+            bytes_removed: 0,
+        }
+    }
+
+    /// A URL as a browser resolves it: without leading whitespace or
+    /// control characters, and without tabs and newlines anywhere.
+    fn normalized_url(value: &str) -> String {
+        value
+            .trim_start_matches(|c: char| c.is_ascii_whitespace() || c.is_ascii_control())
+            .chars()
+            .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+            .collect()
+    }
+
+    fn is_remote(url: &str) -> bool {
+        let starts_with = |prefix: &str| url.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix));
+        starts_with("http:") || starts_with("https:") || url.starts_with("//") || url.starts_with("\\\\")
+    }
+}
+
+impl Default for RemoveExternalRefsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveExternalRefsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.xlink.clear();
+        self.foreign_objects_removed = 0;
+        self.uses_removed = 0;
+        self.refs_removed = 0;
+        self.bytes_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let attrs = owned_attributes(element);
+        self.xlink.declare(&attrs);
+
+        match element.local_name().as_ref() {
+            b"foreignObject" => {
+                self.foreign_objects_removed += 1;
+                return Ok(ElementAction::Remove);
+            }
+            b"use" => {
+                let external = attrs
+                    .iter()
+                    .any(|(key, value)| self.xlink.is_link(key) && !Self::normalized_url(value).starts_with('#'));
+                if external {
+                    self.uses_removed += 1;
+                    return Ok(ElementAction::Remove);
+                }
+                return Ok(ElementAction::Keep);
+            }
+            _ => {}
+        }
+
+        let kept: Vec<_> = attrs
+            .iter()
+            .filter(|(key, value)| !(self.xlink.is_link(key) && Self::is_remote(&Self::normalized_url(value))))
+            .cloned()
+            .collect();
+
+        if kept.len() != attrs.len() {
+            self.refs_removed += attrs.len() - kept.len();
+            set_attributes(element, &kept);
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_removed(&mut self, event: &Event) -> io::Result<()> {
+        self.bytes_removed += event_len(event);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "RemoveExternalRefs"
+    }
+}
+
+impl PluginStatistics for RemoveExternalRefsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Foreign objects removed", self.foreign_objects_removed.to_string()),
+            ("External uses removed", self.uses_removed.to_string()),
+            ("External references removed", self.refs_removed.to_string()),
+            ("Bytes removed", self.bytes_removed.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_foreign_objects_are_removed() -> io::Result<()> {
+        let svg = r#"<svg><foreignObject width="10" height="10"><iframe xmlns="http://www.w3.org/1999/xhtml" src="https://example.com"/></foreignObject><rect/></svg>"#;
+
+        assert_eq!(optimize_with(RemoveExternalRefsPlugin::new(), svg)?, "<svg><rect/></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_external_uses_are_removed() -> io::Result<()> {
+        let svg = r##"<svg><use href="#icon"/><use xlink:href="sprite.svg#icon"/><use href="https://example.com/a.svg#b"></use><use x="1"/></svg>"##;
+
+        assert_eq!(optimize_with(RemoveExternalRefsPlugin::new(), svg)?, r##"<svg><use href="#icon"/><use x="1"/></svg>"##);
+        Ok(())
+    }
+
+    #[test]
+    fn test_remote_references_are_stripped() -> io::Result<()> {
+        let svg = r#"<svg><image href="https://example.com/a.png" width="1"/><image xlink:href="//cdn.example.com/b.png"/><a href="HTTP://example.com"/><image href="data:image/png;base64,AA=="/><image href="local.png"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(RemoveExternalRefsPlugin::new(), svg)?,
+            r#"<svg><image width="1"/><image/><a/><image href="data:image/png;base64,AA=="/><image href="local.png"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_aliased_xlink_prefixes_are_recognized() -> io::Result<()> {
+        let svg = r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><use x:href="https://evil.example/a.svg#b"/><use x:href="#icon"/><image x:href="https://evil.example/a.png"/></svg>"##;
+
+        assert_eq!(
+            optimize_with(RemoveExternalRefsPlugin::new(), svg)?,
+            r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><use x:href="#icon"/><image/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_statistics() -> io::Result<()> {
+        let mut plugin = RemoveExternalRefsPlugin::new();
+        let mut foreign = BytesStart::new("foreignObject");
+        let mut image = BytesStart::new("image").with_attributes([("href", " https://a.b/c.png")]);
+
+        assert_eq!(plugin.process_element(&mut foreign)?, ElementAction::Remove);
+        assert_eq!(plugin.process_element(&mut image)?, ElementAction::Keep);
+        assert_eq!(plugin.get_statistics()[..3], [
+            ("Foreign objects removed", "1".to_string()),
+            ("External uses removed", "0".to_string()),
+            ("External references removed", "1".to_string()),
+        ]);
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+//! Removes elements that render nothing, together with their subtree.
+//!
+//! Unlike `RemoveInvisiblePlugin`, which only looks at `display` and
+//! `visibility` while streaming, this plugin runs on the document tree and
+//! also catches fully transparent elements and shapes without area. Each of
+//! these checks can be turned off on its own.
+
+use crate::plugins::traits::{PluginStatistics, TreePlugin};
+use crate::plugins::util::{animates_visibility, referenced_ids, style_declarations};
+use crate::tree::{Document, Element, Node};
+use std::collections::HashSet;
+use std::io;
+
+/// Shapes and the attributes of which a zero value leaves them without area.
+const SIZE_ATTRIBUTES: &[(&str, &[&str])] = &[
+    ("rect", &["width", "height"]),
+    ("circle", &["r"]),
+    ("ellipse", &["rx", "ry"]),
+    ("image", &["width", "height"]),
+];
+
+/// Removes hidden elements and everything inside them.
+///
+/// An element is hidden when it has
+/// - `display="none"`,
+/// - `visibility="hidden"` and no descendant made visible again,
+/// - `opacity="0"`, except inside a `<clipPath>`, which ignores opacity, or
+/// - a zero `width` or `height` for `<rect>` and `<image>`, a zero `r` for
+///   `<circle>`, or a zero `rx` or `ry` for `<ellipse>`.
+///
+/// Both presentation attributes and `style` declarations are honored.
+/// Elements that are referenced by id, by `<use>` or `url(#...)`, or that
+/// contain a referenced element, are kept, as is the root element. So are
+/// elements with a `<set>` or `<animate>` child animating `display`,
+/// `visibility` or `opacity`, which may show them later.
+/// Documents with a stylesheet are left alone, since its rules may override
+/// any of these properties.
+pub struct RemoveHiddenElemsPlugin {
+    display_none: bool,
+    visibility_hidden: bool,
+    opacity_zero: bool,
+    zero_size: bool,
+    /// Ids referenced anywhere in the document.
+    references: HashSet<String>,
+    elements_removed: usize,
+}
+
+impl RemoveHiddenElemsPlugin {
+    pub fn new() -> Self {
+        Self {
+            display_none: true,
+            visibility_hidden: true,
+            opacity_zero: true,
+            zero_size: true,
+            references: HashSet::new(),
+            elements_removed: 0,
+        }
+    }
+
+    /// Whether to remove elements with `display="none"`.
+    pub fn with_display_none(mut self, enabled: bool) -> Self {
+        self.display_none = enabled;
+        self
+    }
+
+    /// Whether to remove elements with `visibility="hidden"`.
+    pub fn with_visibility_hidden(mut self, enabled: bool) -> Self {
+        self.visibility_hidden = enabled;
+        self
+    }
+
+    /// Whether to remove elements with `opacity="0"`.
+    pub fn with_opacity_zero(mut self, enabled: bool) -> Self {
+        self.opacity_zero = enabled;
+        self
+    }
+
+    /// Whether to remove shapes with a zero width, height or radius.
+    pub fn with_zero_size(mut self, enabled: bool) -> Self {
+        self.zero_size = enabled;
+        self
+    }
+
+    /// Remove the hidden elements among `nodes` and their descendants.
+    fn remove(&mut self, nodes: &mut Vec<Node>, in_clip_path: bool) {
+        nodes.retain_mut(|node| {
+            let Node::Element(element) = node else { return true };
+            if self.is_hidden(element, in_clip_path) && !self.is_referenced(element) && !is_animated(element) {
+                self.elements_removed += 1;
+                return false;
+            }
+            self.remove(&mut element.children, in_clip_path || element.name == "clipPath");
+            true
+        });
+    }
+
+    /// Whether an element renders nothing. Clip paths only use the geometry
+    /// of their content, so there a transparent shape still clips. Mask
+    /// content is not special: a transparent shape adds nothing to a mask.
+    fn is_hidden(&self, element: &Element, in_clip_path: bool) -> bool {
+        if self.display_none && property(element, "display") == Some("none") {
+            return true;
+        }
+        if self.visibility_hidden
+            && property(element, "visibility") == Some("hidden")
+            && !has_visible_descendant(element)
+        {
+            return true;
+        }
+        if self.opacity_zero && !in_clip_path && property(element, "opacity").is_some_and(is_zero) {
+            return true;
+        }
+        self.zero_size
+            && SIZE_ATTRIBUTES.iter().any(|(name, attrs)| {
+                element.name == *name && attrs.iter().any(|attr| element.attr(attr).is_some_and(is_zero))
+            })
+    }
+
+    /// Whether the element or one of its descendants is referenced by id.
+    fn is_referenced(&self, element: &Element) -> bool {
+        element.attr("id").is_some_and(|id| self.references.contains(id))
+            || element.children.iter().any(|node| match node {
+                Node::Element(child) => self.is_referenced(child),
+                _ => false,
+            })
+    }
+}
+
+/// The value of a presentation property, from the `style` attribute if it
+/// sets one and from the attribute of the same name otherwise.
+fn property<'a>(element: &'a Element, name: &str) -> Option<&'a str> {
+    let declared = element
+        .attr("style")
+        .into_iter()
+        .flat_map(style_declarations)
+        .filter(|(property, _)| *property == name)
+        .last()
+        .map(|(_, value)| value);
+    declared.or_else(|| element.attr(name)).map(str::trim)
+}
+
+fn is_animated(element: &Element) -> bool {
+    element.children.iter().any(|node| match node {
+        Node::Element(child) => animates_visibility(&child.name, &child.attrs),
+        _ => false,
+    })
+}
+
+fn has_visible_descendant(element: &Element) -> bool {
+    element.children.iter().any(|node| match node {
+        Node::Element(child) => {
+            property(child, "visibility") == Some("visible") || has_visible_descendant(child)
+        }
+        _ => false,
+    })
+}
+
+/// Whether a length or number is zero, with or without a unit.
+fn is_zero(value: &str) -> bool {
+    value
+        .trim()
+        .trim_end_matches(|c: char| c.is_ascii_alphabetic() || c == '%')
+        .parse::<f64>()
+        .is_ok_and(|number| number == 0.0)
+}
+
+fn has_stylesheet(nodes: &[Node]) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Element(element) => element.name == "style" || has_stylesheet(&element.children),
+        Node::ProcessingInstruction(pi) => pi.starts_with("xml-stylesheet"),
+        _ => false,
+    })
+}
+
+impl Default for RemoveHiddenElemsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreePlugin for RemoveHiddenElemsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.references.clear();
+        self.elements_removed = 0;
+        Ok(())
+    }
+
+    fn process_tree(&mut self, document: &mut Document) -> io::Result<()> {
+        if has_stylesheet(&document.children) {
+            return Ok(());
+        }
+        self.references = referenced_ids(&document.children);
+        for node in &mut document.children {
+            if let Node::Element(root) = node {
+                self.remove(&mut root.children, false);
+            }
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "RemoveHiddenElems"
+    }
+}
+
+impl PluginStatistics for RemoveHiddenElemsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Hidden elements removed", self.elements_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_tree_with;
+
+    #[test]
+    fn test_display_none_group_is_removed() -> io::Result<()> {
+        let svg = r#"<svg><g style="display:none"><rect/><g><circle/></g></g><path d="M0 0"/></svg>"#;
+
+        assert_eq!(optimize_tree_with(RemoveHiddenElemsPlugin::new(), svg)?, r#"<svg><path d="M0 0"/></svg>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_referenced_hidden_def_is_kept() -> io::Result<()> {
+        let svg = r##"<svg><defs><path id="icon" display="none" d="M0 0"/><g opacity="0"><rect id="r" width="1"/></g></defs><use href="#icon"/><use xlink:href="#r"/></svg>"##;
+
+        assert_eq!(optimize_tree_with(RemoveHiddenElemsPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_each_check() -> io::Result<()> {
+        let svg = r#"<svg><g visibility="hidden"><rect/></g><g visibility="hidden"><rect visibility="visible"/></g><rect opacity="0.0"/><rect width="0" height="10"/><circle r="0px"/><ellipse rx="1" ry="2"/></svg>"#;
+
+        assert_eq!(
+            optimize_tree_with(RemoveHiddenElemsPlugin::new(), svg)?,
+            r#"<svg><g visibility="hidden"><rect visibility="visible"/></g><ellipse rx="1" ry="2"/></svg>"#
+        );
+        assert_eq!(
+            optimize_tree_with(
+                RemoveHiddenElemsPlugin::new()
+                    .with_visibility_hidden(false)
+                    .with_opacity_zero(false)
+                    .with_zero_size(false),
+                svg,
+            )?,
+            svg
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_transparent_clip_path_content_is_kept() -> io::Result<()> {
+        let svg = r##"<svg><clipPath id="c"><rect opacity="0" width="5"/><g><circle style="opacity:0" r="1"/></g><rect display="none"/></clipPath><mask id="m"><rect opacity="0"/></mask><rect clip-path="url(#c)" mask="url(#m)"/></svg>"##;
+
+        assert_eq!(
+            optimize_tree_with(RemoveHiddenElemsPlugin::new(), svg)?,
+            r##"<svg><clipPath id="c"><rect opacity="0" width="5"/><g><circle style="opacity:0" r="1"/></g></clipPath><mask id="m"/><rect clip-path="url(#c)" mask="url(#m)"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_animated_elements_are_kept() -> io::Result<()> {
+        let svg = r#"<svg><rect display="none"><set attributeName="display" to="inline" begin="1s"/></rect><rect opacity="0"><animate attributeName="opacity" to="1" dur="1s"/></rect><g visibility="hidden"><set attributeName="visibility" to="visible"/></g></svg>"#;
+
+        assert_eq!(optimize_tree_with(RemoveHiddenElemsPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_root_and_styled_documents_are_kept() -> io::Result<()> {
+        let root = r#"<svg display="none"><rect/></svg>"#;
+        let styled = r#"<svg><style>.a{display:inline}</style><rect class="a" display="none"/></svg>"#;
+
+        assert_eq!(optimize_tree_with(RemoveHiddenElemsPlugin::new(), root)?, root);
+        assert_eq!(optimize_tree_with(RemoveHiddenElemsPlugin::new(), styled)?, styled);
+        Ok(())
+    }
+}
@@ -0,0 +1,216 @@
+//! Removal of unreferenced `id` attributes.
+//!
+//! References can appear anywhere in a document, including after the element
+//! they point to, so the plugin works in two passes:
+//!
+//! 1. During `analyze` the buffered document is scanned for references:
+//!    `href`/`xlink:href` values starting with `#`, `url(#id)` in any
+//!    attribute value (including `style`), `id.event` in animation timing
+//!    and the id lists of `aria-labelledby` and `aria-describedby`.
+//! 2. During `process_element` each `id` is dropped unless it was referenced
+//!    or appears in the `preserve` list.
+//!
+//! Style sheets can select elements by id, so documents with a `<style>`
+//! element or an `xml-stylesheet` instruction keep all their ids.
+
+use crate::plugins::cleanup_ids::{timing_references, ID_LIST_ATTRIBUTES, TIMING_ATTRIBUTES};
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{has_stylesheet, owned_attributes, set_attributes, url_references, XLinkPrefixes};
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashSet;
+use std::io;
+
+/// Removes `id` attributes that nothing in the document refers to.
+///
+/// Ids listed in `preserve` are always kept.
+pub struct RemoveIDPlugin {
+    preserve: HashSet<String>,
+    referenced: HashSet<String>,
+    /// Set when a style sheet may select elements by id.
+    keep_all: bool,
+    ids_removed: usize,
+}
+
+impl RemoveIDPlugin {
+    pub fn new(preserve: Vec<String>) -> Self {
+        Self {
+            preserve: preserve.into_iter().collect(),
+            referenced: HashSet::new(),
+            keep_all: false,
+            ids_removed: 0,
+        }
+    }
+
+    fn collect_references(&mut self, element: &BytesStart, xlink: &XLinkPrefixes) {
+        for (key, value) in owned_attributes(element) {
+            let ids: Vec<&str> = if xlink.is_link(&key) {
+                value.strip_prefix('#').into_iter().collect()
+            } else if TIMING_ATTRIBUTES.contains(&key.as_str()) {
+                timing_references(&value).map(|(_, id)| id).collect()
+            } else if ID_LIST_ATTRIBUTES.contains(&key.as_str()) {
+                value.split_whitespace().collect()
+            } else {
+                url_references(&value)
+            };
+            self.referenced.extend(ids.into_iter().map(str::to_string));
+        }
+    }
+
+    fn should_keep(&self, id: &str) -> bool {
+        self.keep_all || self.preserve.contains(id) || self.referenced.contains(id)
+    }
+}
+
+impl SVGPlugin for RemoveIDPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.referenced.clear();
+        self.keep_all = false;
+        self.ids_removed = 0;
+        Ok(())
+    }
+
+    fn requires_analysis(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, events: &[Event<'static>]) -> io::Result<()> {
+        self.keep_all = has_stylesheet(events);
+
+        let xlink = XLinkPrefixes::from_events(events);
+        for event in events {
+            if let Event::Start(elem) | Event::Empty(elem) = event {
+                self.collect_references(elem, &xlink);
+            }
+        }
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let attrs = owned_attributes(element);
+        let removable = attrs
+            .iter()
+            .any(|(key, value)| key == "id" && !self.should_keep(value));
+
+        if removable {
+            let attrs: Vec<_> = attrs.into_iter().filter(|(key, _)| key != "id").collect();
+            set_attributes(element, &attrs);
+            self.ids_removed += 1;
+        }
+
+        Ok(ElementAction::Keep)
+    }
+
+    fn name(&self) -> &str {
+        "RemoveID"
+    }
+}
+
+impl PluginStatistics for RemoveIDPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("IDs removed", self.ids_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_unreferenced_ids_are_removed() -> io::Result<()> {
+        let output = optimize_with(RemoveIDPlugin::new(Vec::new()), r#"<svg id="root"><path id="p" d="M0 0"/></svg>"#)?;
+
+        assert!(!output.contains("id="));
+        Ok(())
+    }
+
+    #[test]
+    fn test_referenced_ids_are_kept() -> io::Result<()> {
+        let svg = r##"<svg><clipPath id="clip"/><path id="shape" d="M0 0"/><use href="#shape"/><rect clip-path="url(#clip)"/></svg>"##;
+
+        let output = optimize_with(RemoveIDPlugin::new(Vec::new()), svg)?;
+
+        assert!(output.contains(r#"id="clip""#));
+        assert!(output.contains(r#"id="shape""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_aliased_xlink_references_keep_ids() -> io::Result<()> {
+        let svg = r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><path id="p"/><use x:href="#p"/><path id="q"/><use y:href="#q"/></svg>"##;
+
+        assert_eq!(
+            optimize_with(RemoveIDPlugin::new(Vec::new()), svg)?,
+            r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><path id="p"/><use x:href="#p"/><path/><use y:href="#q"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_any_url_reference_keeps_id() -> io::Result<()> {
+        let svg = r##"<svg><marker id="arrow"/><linearGradient id="grad"/><pattern id="dots"/><path marker-end="url(#arrow)" style="stroke: url('#grad')"/><style><![CDATA[ rect { fill: url(#dots) } ]]></style></svg>"##;
+
+        let output = optimize_with(RemoveIDPlugin::new(Vec::new()), svg)?;
+
+        assert!(output.contains(r#"id="arrow""#));
+        assert!(output.contains(r#"id="grad""#));
+        assert!(output.contains(r#"id="dots""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_style_sheets_keep_all_ids() -> io::Result<()> {
+        let svg = r#"<svg><style>#logo{fill:red}</style><rect id="logo"/><rect id="other"/></svg>"#;
+
+        assert_eq!(optimize_with(RemoveIDPlugin::new(Vec::new()), svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_timing_references_keep_ids() -> io::Result<()> {
+        let svg = r#"<svg><animate id="fade" attributeName="opacity" to="0"/><animate id="next" begin="fade.end; 2s" attributeName="x"/></svg>"#;
+
+        let output = optimize_with(RemoveIDPlugin::new(Vec::new()), svg)?;
+
+        assert!(output.contains(r#"id="fade""#));
+        assert!(!output.contains(r#"id="next""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_aria_references_keep_ids() -> io::Result<()> {
+        let svg = r#"<svg aria-labelledby="title" aria-describedby="desc note"><title id="title">Logo</title><desc id="desc">A logo</desc><text id="note">x</text><g id="unused"/></svg>"#;
+
+        let output = optimize_with(RemoveIDPlugin::new(Vec::new()), svg)?;
+
+        assert!(output.contains(r#"id="title""#));
+        assert!(output.contains(r#"id="desc""#));
+        assert!(output.contains(r#"id="note""#));
+        assert!(!output.contains(r#"id="unused""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_preserved_ids_are_kept() -> io::Result<()> {
+        let svg = r#"<svg><g id="logo"/><g id="other"/></svg>"#;
+
+        let output = optimize_with(RemoveIDPlugin::new(vec!["logo".to_string()]), svg)?;
+
+        assert!(output.contains(r#"id="logo""#));
+        assert!(!output.contains(r#"id="other""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_statistics_count_removed_ids() -> io::Result<()> {
+        let mut plugin = RemoveIDPlugin::new(Vec::new());
+        plugin.init()?;
+
+        let mut element = BytesStart::new("g").with_attributes([("id", "x"), ("fill", "red")]);
+        plugin.process_element(&mut element)?;
+
+        assert_eq!(owned_attributes(&element), vec![("fill".to_string(), "red".to_string())]);
+        assert_eq!(plugin.get_statistics(), vec![("IDs removed", "1".to_string())]);
+        Ok(())
+    }
+}
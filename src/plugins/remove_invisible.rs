@@ -0,0 +1,313 @@
+//! Removal of elements that are never rendered.
+//!
+//! Whether an invisible element can go depends on its subtree and on
+//! references elsewhere in the document, so the plugin works in two passes:
+//!
+//! 1. During `analyze` every referenced id is collected, then each invisible
+//!    element is checked for referenced ids and `visibility="visible"`
+//!    overrides in its subtree, and for animations that show it again.
+//! 2. During `process_element` invisible elements are matched to those
+//!    decisions by their position among all invisible elements, and removed
+//!    together with their subtree.
+
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{animates_visibility, has_stylesheet, owned_attributes, style_declarations, url_references, XLinkPrefixes};
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashSet;
+use std::io;
+
+/// Removes elements with `display:none`, and elements with
+/// `visibility:hidden` that have no descendant made visible again.
+///
+/// Both presentation attributes and `style` declarations are honored.
+/// Elements that are referenced (e.g. by `<use>`), that contain a referenced
+/// element, or whose `display`, `visibility` or `opacity` is animated by a
+/// `<set>` or `<animate>` child, are kept, as is the root element.
+/// Documents with a stylesheet are left alone, since its rules may override
+/// these properties.
+pub struct RemoveInvisiblePlugin {
+    /// Removal decision and attributes of each invisible element, in
+    /// document order.
+    candidates: Vec<(bool, Vec<(String, String)>)>,
+    /// Number of invisible elements seen during processing.
+    candidates_seen: usize,
+    /// Whether the root element has been seen during processing.
+    root_seen: bool,
+    elements_removed: usize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visibility {
+    DisplayNone,
+    Hidden,
+    Visible,
+}
+
+/// An invisible element whose subtree is being scanned during analysis.
+struct Candidate {
+    index: usize,
+    hidden_only: bool,
+    keep: bool,
+}
+
+impl RemoveInvisiblePlugin {
+    pub fn new() -> Self {
+        Self {
+            candidates: Vec::new(),
+            candidates_seen: 0,
+            root_seen: false,
+            elements_removed: 0,
+        }
+    }
+
+    /// Read `display` and `visibility` from attributes, letting `style`
+    /// declarations take precedence as in CSS.
+    fn visibility(attrs: &[(String, String)]) -> Option<Visibility> {
+        let mut display = None;
+        let mut visibility = None;
+
+        let presentation = attrs.iter().map(|(k, v)| (k.as_str(), v.as_str()));
+        let styles = attrs
+            .iter()
+            .filter(|(key, _)| key == "style")
+            .flat_map(|(_, style)| style_declarations(style));
+
+        for (property, value) in presentation.chain(styles) {
+            match property {
+                "display" => display = Some(value.trim()),
+                "visibility" => visibility = Some(value.trim()),
+                _ => {}
+            }
+        }
+
+        if display.is_some_and(|value| value.eq_ignore_ascii_case("none")) {
+            return Some(Visibility::DisplayNone);
+        }
+        match visibility {
+            Some(value) if value.eq_ignore_ascii_case("hidden") => Some(Visibility::Hidden),
+            Some(value) if value.eq_ignore_ascii_case("visible") => Some(Visibility::Visible),
+            _ => None,
+        }
+    }
+
+    fn is_candidate(visibility: Option<Visibility>) -> bool {
+        matches!(visibility, Some(Visibility::DisplayNone | Visibility::Hidden))
+    }
+
+    fn collect_references(attrs: &[(String, String)], xlink: &XLinkPrefixes, referenced: &mut HashSet<String>) {
+        for (key, value) in attrs {
+            if xlink.is_link(key) {
+                if let Some(id) = value.strip_prefix('#') {
+                    referenced.insert(id.to_string());
+                }
+            } else {
+                referenced.extend(url_references(value).into_iter().map(str::to_string));
+            }
+        }
+    }
+
+    /// Update the open candidates for an element inside them, and start a
+    /// new candidate if the element itself is invisible. `in_candidate`
+    /// tells whether the element's parent is the innermost open candidate.
+    /// The root element is never a candidate.
+    fn visit(
+        &mut self,
+        element: &BytesStart,
+        referenced: &HashSet<String>,
+        open: &mut [Candidate],
+        in_candidate: bool,
+        is_root: bool,
+    ) -> Option<Candidate> {
+        let attrs = owned_attributes(element);
+        let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+        let visibility = Self::visibility(&attrs);
+        let is_referenced = attrs
+            .iter()
+            .any(|(key, value)| key == "id" && referenced.contains(value));
+
+        for candidate in open.iter_mut() {
+            if is_referenced || (candidate.hidden_only && visibility == Some(Visibility::Visible)) {
+                candidate.keep = true;
+            }
+        }
+        if let (true, Some(parent)) = (in_candidate, open.last_mut()) {
+            parent.keep |= animates_visibility(&name, &attrs);
+        }
+
+        if is_root || !Self::is_candidate(visibility) {
+            return None;
+        }
+
+        self.candidates.push((false, attrs));
+        Some(Candidate {
+            index: self.candidates.len() - 1,
+            hidden_only: visibility == Some(Visibility::Hidden),
+            keep: is_referenced,
+        })
+    }
+}
+
+impl Default for RemoveInvisiblePlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveInvisiblePlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.candidates.clear();
+        self.candidates_seen = 0;
+        self.root_seen = false;
+        self.elements_removed = 0;
+        Ok(())
+    }
+
+    fn requires_analysis(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, events: &[Event<'static>]) -> io::Result<()> {
+        if has_stylesheet(events) {
+            return Ok(());
+        }
+
+        let xlink = XLinkPrefixes::from_events(events);
+        let mut referenced = HashSet::new();
+        for event in events {
+            if let Event::Start(elem) | Event::Empty(elem) = event {
+                Self::collect_references(&owned_attributes(elem), &xlink, &mut referenced);
+            }
+        }
+
+        // Open candidates, and for every open element whether it is one
+        let mut open: Vec<Candidate> = Vec::new();
+        let mut stack: Vec<bool> = Vec::new();
+        let mut root_seen = false;
+
+        for event in events {
+            match event {
+                Event::Start(elem) => {
+                    let in_candidate = stack.last() == Some(&true);
+                    let is_root = !std::mem::replace(&mut root_seen, true);
+                    let candidate = self.visit(elem, &referenced, &mut open, in_candidate, is_root);
+                    stack.push(candidate.is_some());
+                    open.extend(candidate);
+                }
+                Event::Empty(elem) => {
+                    let in_candidate = stack.last() == Some(&true);
+                    let is_root = !std::mem::replace(&mut root_seen, true);
+                    if let Some(candidate) = self.visit(elem, &referenced, &mut open, in_candidate, is_root) {
+                        self.candidates[candidate.index].0 = !candidate.keep;
+                    }
+                }
+                Event::End(_) => {
+                    let Some(true) = stack.pop() else { continue };
+                    let candidate = open.pop().unwrap();
+                    self.candidates[candidate.index].0 = !candidate.keep;
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let is_root = !std::mem::replace(&mut self.root_seen, true);
+        let attrs = owned_attributes(element);
+        if is_root || !Self::is_candidate(Self::visibility(&attrs)) {
+            return Ok(ElementAction::Keep);
+        }
+
+        let index = self.candidates_seen;
+        self.candidates_seen += 1;
+
+        match self.candidates.get(index) {
+            Some((true, recorded)) if *recorded == attrs => {
+                self.elements_removed += 1;
+                Ok(ElementAction::Remove)
+            }
+            _ => Ok(ElementAction::Keep),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "RemoveInvisible"
+    }
+}
+
+impl PluginStatistics for RemoveInvisiblePlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Invisible elements removed", self.elements_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_display_none_is_removed() -> io::Result<()> {
+        let output = optimize_with(RemoveInvisiblePlugin::new(), 
+            r#"<svg><g display="none"><rect/></g><circle style="fill:red; display: none"/><path/></svg>"#,
+        )?;
+
+        assert_eq!(output, "<svg><path/></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_hidden_without_visible_descendants_is_removed() -> io::Result<()> {
+        let output = optimize_with(RemoveInvisiblePlugin::new(), 
+            r#"<svg><g visibility="hidden"><rect/></g><g style="visibility:hidden"><rect visibility="visible"/></g></svg>"#,
+        )?;
+
+        assert_eq!(
+            output,
+            r#"<svg><g style="visibility:hidden"><rect visibility="visible"/></g></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_referenced_elements_are_kept() -> io::Result<()> {
+        let svg = r##"<svg><g display="none"><path id="icon" d="M0 0"/></g><symbol id="s" display="none"/><use href="#icon"/><use xlink:href="#s"/></svg>"##;
+        let aliased = r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><symbol id="s" display="none"/><use x:href="#s"/></svg>"##;
+
+        assert_eq!(optimize_with(RemoveInvisiblePlugin::new(), svg)?, svg);
+        assert_eq!(optimize_with(RemoveInvisiblePlugin::new(), aliased)?, aliased);
+        Ok(())
+    }
+
+    #[test]
+    fn test_animated_elements_are_kept() -> io::Result<()> {
+        let svg = r#"<svg><rect display="none"><set attributeName="display" to="inline" begin="1s"/></rect><g visibility="hidden"><animate attributeName="visibility" values="hidden;visible" dur="2s"/></g><g display="none"><rect><set attributeName="display" to="none"/></rect></g></svg>"#;
+
+        assert_eq!(
+            optimize_with(RemoveInvisiblePlugin::new(), svg)?,
+            r#"<svg><rect display="none"><set attributeName="display" to="inline" begin="1s"/></rect><g visibility="hidden"><animate attributeName="visibility" values="hidden;visible" dur="2s"/></g></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_root_and_styled_documents_are_kept() -> io::Result<()> {
+        let root = r#"<svg display="none"><rect/></svg>"#;
+        let styled = r#"<svg><style>.a{display:inline}</style><rect class="a" display="none"/></svg>"#;
+        let linked = r#"<?xml-stylesheet href="a.css"?><svg><rect visibility="hidden"/></svg>"#;
+
+        assert_eq!(optimize_with(RemoveInvisiblePlugin::new(), root)?, root);
+        assert_eq!(optimize_with(RemoveInvisiblePlugin::new(), styled)?, styled);
+        assert_eq!(optimize_with(RemoveInvisiblePlugin::new(), linked)?, linked);
+        Ok(())
+    }
+
+    #[test]
+    fn test_style_overrides_attribute() -> io::Result<()> {
+        let svg = r#"<svg><rect display="none" style="display:inline"/></svg>"#;
+
+        assert_eq!(optimize_with(RemoveInvisiblePlugin::new(), svg)?, svg);
+        Ok(())
+    }
+}
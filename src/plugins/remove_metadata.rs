@@ -0,0 +1,89 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::event_len;
+use quick_xml::events::{BytesStart, Event};
+use std::io;
+
+/// Removes `<metadata>` elements together with everything inside them.
+///
+/// Returning `ElementAction::Remove` for the start tag makes the processor
+/// drop the whole subtree up to the matching end tag, so nested RDF and
+/// Dublin Core elements never reach later plugins. The dropped events are
+/// passed back through `process_removed`, where their size is counted.
+pub struct RemoveMetadataPlugin {
+    blocks_removed: usize,
+    bytes_removed: usize,
+}
+
+impl RemoveMetadataPlugin {
+    pub fn new() -> Self {
+        Self {
+            blocks_removed: 0,
+            bytes_removed: 0,
+        }
+    }
+}
+
+impl Default for RemoveMetadataPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveMetadataPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.blocks_removed = 0;
+        self.bytes_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        if element.local_name().as_ref() == b"metadata" {
+            self.blocks_removed += 1;
+            return Ok(ElementAction::Remove);
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_removed(&mut self, event: &Event) -> io::Result<()> {
+        self.bytes_removed += event_len(event);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "RemoveMetadata"
+    }
+}
+
+impl PluginStatistics for RemoveMetadataPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Metadata blocks removed", self.blocks_removed.to_string()),
+            ("Bytes removed", self.bytes_removed.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::SVGProcessor;
+
+    #[test]
+    fn test_nested_metadata_is_removed() -> io::Result<()> {
+        let svg = r#"<svg><metadata><rdf:RDF><cc:Work><dc:title>Logo</dc:title><dc:format/></cc:Work></rdf:RDF></metadata><rect/><svg:metadata/></svg>"#;
+
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(RemoveMetadataPlugin::new());
+        let output = processor.optimize_str(svg)?;
+
+        assert_eq!(output, "<svg><rect/></svg>");
+        assert_eq!(
+            processor.plugin_statistics()[0].1,
+            vec![
+                ("Metadata blocks removed".to_string(), "2".to_string()),
+                ("Bytes removed".to_string(), (svg.len() - output.len()).to_string()),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{event_len, owned_attributes, set_attributes, XLinkPrefixes};
+use quick_xml::events::{BytesStart, Event};
+use std::io;
+
+/// Removes `<script>` elements, `on*` event handler attributes and
+/// `javascript:` links.
+///
+/// Scripts are dropped together with their content, CDATA included, the
+/// same way `RemoveMetadataPlugin` drops metadata. Event handlers such as
+/// `onclick` or `onload` are stripped from every element that is kept, as
+/// are `href` and `xlink:href` attributes running code when followed. Links
+/// are recognized under any prefix bound to the XLink namespace.
+pub struct RemoveScriptsPlugin {
+    xlink: XLinkPrefixes,
+    scripts_removed: usize,
+    handlers_removed: usize,
+    links_removed: usize,
+    bytes_removed: usize,
+}
+
+impl RemoveScriptsPlugin {
+    pub fn new() -> Self {
+        Self {
+            xlink: XLinkPrefixes::default(),
+            scripts_removed: 0,
+            handlers_removed: 0,
+            links_removed: 0,
+            bytes_removed: 0,
+        }
+    }
+
+    fn is_event_handler(key: &str) -> bool {
+        let local_name = key.rsplit(':').next().unwrap_or(key);
+        local_name.len() > 2 && local_name[..2].eq_ignore_ascii_case("on")
+    }
+
+    /// Whether `key` is a link whose URL uses the `javascript:` scheme.
+    ///
+    /// Browsers ignore the case of the scheme and drop tabs and newlines
+    /// anywhere in a URL, so `JavaScript:` or `java&#9;script:` run too.
+    fn is_script_link(&self, key: &str, value: &str) -> bool {
+        if !self.xlink.is_link(key) {
+            return false;
+        }
+        let scheme: String = value
+            .trim_start_matches(|c: char| c.is_ascii_whitespace() || c.is_ascii_control())
+            .chars()
+            .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+            .take("javascript:".len())
+            .collect();
+        scheme.eq_ignore_ascii_case("javascript:")
+    }
+}
+
+impl Default for RemoveScriptsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveScriptsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.xlink.clear();
+        self.scripts_removed = 0;
+        self.handlers_removed = 0;
+        self.links_removed = 0;
+        self.bytes_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        if element.local_name().as_ref() == b"script" {
+            self.scripts_removed += 1;
+            return Ok(ElementAction::Remove);
+        }
+
+        let attrs = owned_attributes(element);
+        self.xlink.declare(&attrs);
+        let mut kept = Vec::with_capacity(attrs.len());
+        for (key, value) in &attrs {
+            if Self::is_event_handler(key) {
+                self.handlers_removed += 1;
+            } else if self.is_script_link(key, value) {
+                self.links_removed += 1;
+            } else {
+                kept.push((key.clone(), value.clone()));
+            }
+        }
+
+        if kept.len() != attrs.len() {
+            set_attributes(element, &kept);
+        }
+
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_removed(&mut self, event: &Event) -> io::Result<()> {
+        self.bytes_removed += event_len(event);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "RemoveScripts"
+    }
+}
+
+impl PluginStatistics for RemoveScriptsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Scripts removed", self.scripts_removed.to_string()),
+            ("Event handlers removed", self.handlers_removed.to_string()),
+            ("Script links removed", self.links_removed.to_string()),
+            ("Script bytes removed", self.bytes_removed.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_scripts_are_removed() -> io::Result<()> {
+        let svg = "<svg><script type=\"text/javascript\"><![CDATA[alert('<g/>')]]></script><rect/><svg:script href=\"a.js\"/></svg>";
+
+        assert_eq!(optimize_with(RemoveScriptsPlugin::new(), svg)?, "<svg><rect/></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_handlers_are_removed() -> io::Result<()> {
+        let svg = r#"<svg onload="init()"><rect onclick="go()" ONMOUSEOVER="x()" offset="1" fill="red"/><a on="1"/></svg>"#;
+
+        assert_eq!(optimize_with(RemoveScriptsPlugin::new(), svg)?, r#"<svg><rect offset="1" fill="red"/><a on="1"/></svg>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_javascript_links_are_removed() -> io::Result<()> {
+        let svg = "<svg><a href=\"javascript:alert(1)\"><rect/></a><a xlink:href=\" JavaScript:go()\"/><a href=\"java&#x9;script:x()\"/><a href=\"https://example.com/javascript:\"/><use href=\"#icon\"/></svg>";
+
+        assert_eq!(
+            optimize_with(RemoveScriptsPlugin::new(), svg)?,
+            "<svg><a><rect/></a><a/><a/><a href=\"https://example.com/javascript:\"/><use href=\"#icon\"/></svg>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_aliased_xlink_prefixes_are_recognized() -> io::Result<()> {
+        let svg = r#"<svg xmlns:x="http://www.w3.org/1999/xlink"><a x:href="javascript:alert(1)"><rect/></a><a y:href="javascript:x()"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(RemoveScriptsPlugin::new(), svg)?,
+            r#"<svg xmlns:x="http://www.w3.org/1999/xlink"><a><rect/></a><a y:href="javascript:x()"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_statistics() -> io::Result<()> {
+        let mut plugin = RemoveScriptsPlugin::new();
+        let mut script = BytesStart::new("script");
+        let mut element = BytesStart::new("a").with_attributes([
+            ("onclick", "a()"),
+            ("onfocus", "b()"),
+            ("href", "javascript:c()"),
+        ]);
+
+        assert_eq!(plugin.process_element(&mut script)?, ElementAction::Remove);
+        assert_eq!(plugin.process_element(&mut element)?, ElementAction::Keep);
+        assert_eq!(plugin.get_statistics()[..3], [
+            ("Scripts removed", "1".to_string()),
+            ("Event handlers removed", "2".to_string()),
+            ("Script links removed", "1".to_string()),
+        ]);
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use quick_xml::events::BytesStart;
+use std::io;
+
+/// Removes `<title>` and `<desc>` elements together with their text.
+///
+/// They carry accessibility information for interactive SVGs, but are
+/// overhead when the file is used as a static image. Either kind can be
+/// preserved.
+pub struct RemoveTitleDescPlugin {
+    preserve_title: bool,
+    preserve_desc: bool,
+    titles_removed: usize,
+    descs_removed: usize,
+}
+
+impl RemoveTitleDescPlugin {
+    pub fn new() -> Self {
+        Self {
+            preserve_title: false,
+            preserve_desc: false,
+            titles_removed: 0,
+            descs_removed: 0,
+        }
+    }
+
+    /// Keep `<title>` elements.
+    pub fn with_preserve_title(mut self, preserve: bool) -> Self {
+        self.preserve_title = preserve;
+        self
+    }
+
+    /// Keep `<desc>` elements.
+    pub fn with_preserve_desc(mut self, preserve: bool) -> Self {
+        self.preserve_desc = preserve;
+        self
+    }
+}
+
+impl Default for RemoveTitleDescPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveTitleDescPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.titles_removed = 0;
+        self.descs_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        match element.local_name().as_ref() {
+            b"title" if !self.preserve_title => {
+                self.titles_removed += 1;
+                Ok(ElementAction::Remove)
+            }
+            b"desc" if !self.preserve_desc => {
+                self.descs_removed += 1;
+                Ok(ElementAction::Remove)
+            }
+            _ => Ok(ElementAction::Keep),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "RemoveTitleDesc"
+    }
+}
+
+impl PluginStatistics for RemoveTitleDescPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Titles removed", self.titles_removed.to_string()),
+            ("Descriptions removed", self.descs_removed.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    const SVG: &str = "<svg><title>Logo</title><desc>Company <tspan>logo</tspan></desc><g><title/><rect/></g></svg>";
+
+    #[test]
+    fn test_title_and_desc_are_removed() -> io::Result<()> {
+        assert_eq!(optimize_with(RemoveTitleDescPlugin::new(), SVG)?, "<svg><g><rect/></g></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_preserved_elements_are_kept() -> io::Result<()> {
+        assert_eq!(
+            optimize_with(RemoveTitleDescPlugin::new().with_preserve_title(true), SVG)?,
+            "<svg><title>Logo</title><g><title/><rect/></g></svg>"
+        );
+        assert_eq!(
+            optimize_with(RemoveTitleDescPlugin::new().with_preserve_desc(true), SVG)?,
+            "<svg><desc>Company <tspan>logo</tspan></desc><g><rect/></g></svg>"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use quick_xml::escape::minimal_escape;
+use quick_xml::events::{BytesCData, BytesEnd, BytesStart, BytesText, Event};
+use std::collections::HashSet;
+use std::io;
+
+/// At-rules holding rules of their own, which are pruned like top-level ones.
+pub(crate) const GROUPING_AT_RULES: &[&str] = &["media", "supports", "layer", "container", "document"];
+
+/// Removes rules of `<style>` elements whose selectors match no element.
+///
+/// The document is read twice: first to collect the class names used in
+/// `class` attributes, then to drop every rule where each selector
+/// requires a class that no element has. Grouping at-rules such as
+/// `@media` are pruned too and removed once empty; other at-rules are kept.
+///
+/// Selectors with functional pseudo-classes, attribute selectors or escapes
+/// are always kept, as are all rules of documents with a `<script>`, which
+/// could add classes at runtime.
+pub struct RemoveUnusedCSSPlugin {
+    used_classes: HashSet<String>,
+    has_script: bool,
+    /// Open elements from the current `<style>` down; 0 outside of one.
+    depth: usize,
+    /// Whether the current `<style>` holds CSS.
+    css: bool,
+    rules_removed: usize,
+}
+
+impl RemoveUnusedCSSPlugin {
+    pub fn new() -> Self {
+        Self {
+            used_classes: HashSet::new(),
+            has_script: false,
+            depth: 0,
+            css: false,
+            rules_removed: 0,
+        }
+    }
+
+    /// The pruned style sheet, if any rule was removed from it.
+    fn prune(&mut self, css: &str) -> Option<String> {
+        if self.depth != 1 || !self.css || self.has_script {
+            return None;
+        }
+        let pruned = prune_rules(css, &self.used_classes)?;
+        if pruned.removed == 0 {
+            return None;
+        }
+        self.rules_removed += pruned.removed;
+        Some(pruned.css)
+    }
+}
+
+impl Default for RemoveUnusedCSSPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Pruned {
+    css: String,
+    removed: usize,
+    kept: usize,
+}
+
+/// Index just past the comment or string starting at `start`, which holds
+/// `/*`, `"` or `'`.
+pub(crate) fn skip_token(css: &str, start: usize) -> usize {
+    let bytes = css.as_bytes();
+    if bytes[start] == b'/' {
+        return css[start + 2..].find("*/").map_or(css.len(), |end| start + 2 + end + 2);
+    }
+    let quote = bytes[start];
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b if b == quote => return i + 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Index of the `}` closing the block opened at `open`.
+fn block_end(css: &str, open: usize) -> Option<usize> {
+    let bytes = css.as_bytes();
+    let mut depth = 0;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = skip_token(css, i);
+                continue;
+            }
+            b'"' | b'\'' => {
+                i = skip_token(css, i);
+                continue;
+            }
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Whether a selector can't match because it needs a class nobody has.
+fn is_unmatched(selector: &str, used: &HashSet<String>) -> bool {
+    if selector.contains(['(', '[', '\\']) {
+        return false;
+    }
+    let mut rest = selector;
+    while let Some(dot) = rest.find('.') {
+        rest = &rest[dot + 1..];
+        let end = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(rest.len());
+        if end > 0 && !used.contains(&rest[..end]) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Remove the rules of a style sheet that match no element. Returns `None`
+/// if the braces don't balance.
+fn prune_rules(css: &str, used: &HashSet<String>) -> Option<Pruned> {
+    let bytes = css.as_bytes();
+    let mut out = String::with_capacity(css.len());
+    let (mut removed, mut kept) = (0, 0);
+    // Input up to here is already in `out`
+    let mut copied = 0;
+    // End of the last comment or statement, where a removed rule's
+    // leading whitespace starts
+    let mut last_end = 0;
+    let mut prelude_start = None;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = skip_token(css, i);
+                if prelude_start.is_none() {
+                    last_end = i;
+                }
+                continue;
+            }
+            b'"' | b'\'' => {
+                prelude_start.get_or_insert(i);
+                i = skip_token(css, i);
+                continue;
+            }
+            b';' => {
+                prelude_start = None;
+                last_end = i + 1;
+            }
+            b'{' => {
+                let close = block_end(css, i)?;
+                let prelude = css[prelude_start.unwrap_or(i)..i].trim();
+                let body = &css[i + 1..close];
+
+                if let Some(at_rule) = prelude.strip_prefix('@') {
+                    let name = at_rule.split(|c: char| c.is_whitespace() || c == '(').next().unwrap_or("");
+                    if GROUPING_AT_RULES.contains(&name.to_ascii_lowercase().as_str()) {
+                        let inner = prune_rules(body, used)?;
+                        removed += inner.removed;
+                        if inner.kept == 0 && inner.removed > 0 {
+                            out.push_str(&css[copied..last_end]);
+                            copied = close + 1;
+                        } else {
+                            kept += 1;
+                            out.push_str(&css[copied..i + 1]);
+                            out.push_str(&inner.css);
+                            copied = close;
+                        }
+                    } else {
+                        kept += 1;
+                    }
+                } else if prelude.split(',').all(|selector| is_unmatched(selector, used)) {
+                    removed += 1;
+                    out.push_str(&css[copied..last_end]);
+                    copied = close + 1;
+                } else {
+                    kept += 1;
+                }
+
+                i = close + 1;
+                last_end = i;
+                prelude_start = None;
+                continue;
+            }
+            b'}' => return None,
+            b if b.is_ascii_whitespace() => {}
+            _ => {
+                prelude_start.get_or_insert(i);
+            }
+        }
+        i += 1;
+    }
+
+    out.push_str(&css[copied..]);
+    Some(Pruned { css: out, removed, kept })
+}
+
+impl SVGPlugin for RemoveUnusedCSSPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.used_classes.clear();
+        self.has_script = false;
+        self.depth = 0;
+        self.css = false;
+        self.rules_removed = 0;
+        Ok(())
+    }
+
+    fn requires_analysis(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, events: &[Event<'static>]) -> io::Result<()> {
+        for event in events {
+            if let Event::Start(element) | Event::Empty(element) = event {
+                if element.local_name().as_ref() == b"script" {
+                    self.has_script = true;
+                }
+                if let Ok(Some(class)) = element.try_get_attribute("class") {
+                    let class = String::from_utf8_lossy(&class.value).into_owned();
+                    self.used_classes.extend(class.split_whitespace().map(str::to_string));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        if self.depth > 0 {
+            self.depth += 1;
+        } else if element.local_name().as_ref() == b"style" {
+            self.depth = 1;
+            self.css = match element.try_get_attribute("type") {
+                Ok(Some(kind)) => kind.value.is_empty() || kind.value.as_ref() == b"text/css",
+                _ => true,
+            };
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_end<'a>(&mut self, end: BytesEnd<'a>) -> io::Result<Option<BytesEnd<'a>>> {
+        self.depth = self.depth.saturating_sub(1);
+        Ok(Some(end))
+    }
+
+    fn process_text<'a>(&mut self, text: BytesText<'a>) -> io::Result<Option<BytesText<'a>>> {
+        let Ok(css) = text.unescape() else { return Ok(Some(text)) };
+        match self.prune(&css) {
+            Some(pruned) => Ok(Some(BytesText::from_escaped(minimal_escape(pruned).into_owned()))),
+            None => Ok(Some(text)),
+        }
+    }
+
+    fn process_cdata<'a>(&mut self, cdata: BytesCData<'a>) -> io::Result<Option<BytesCData<'a>>> {
+        match self.prune(&String::from_utf8_lossy(&cdata)) {
+            Some(pruned) => Ok(Some(BytesCData::new(pruned))),
+            None => Ok(Some(cdata)),
+        }
+    }
+
+    fn name(&self) -> &str {
+        "RemoveUnusedCSS"
+    }
+}
+
+impl PluginStatistics for RemoveUnusedCSSPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Rules removed", self.rules_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_prune_rules() {
+        let used: HashSet<String> = ["a", "b-1"].iter().map(|class| class.to_string()).collect();
+        let pruned = prune_rules(
+            "/* kept */\n.a { fill: red }\n.c { fill: \"}\" }\n.b-1 > .c, .a {}\nrect.d:hover {}\n:not(.d) {}\n",
+            &used,
+        )
+        .unwrap();
+        assert_eq!(pruned.css, "/* kept */\n.a { fill: red }\n.b-1 > .c, .a {}\n:not(.d) {}\n");
+        assert_eq!((pruned.removed, pruned.kept), (2, 3));
+
+        let pruned = prune_rules("@import url(x.css); @media print { .c {} } @media screen { .c {} .a {} } @font-face { x: y }", &used).unwrap();
+        assert_eq!(pruned.css, "@import url(x.css); @media screen { .a {} } @font-face { x: y }");
+        assert_eq!(pruned.removed, 2);
+
+        assert!(prune_rules(".a { } }", &used).is_none());
+    }
+
+    #[test]
+    fn test_unused_rules_are_removed() -> io::Result<()> {
+        let svg = r#"<svg><style>
+  .used { fill: red }
+  .unused { fill: blue }
+  path &gt; .used { stroke: none }
+</style><style><![CDATA[.other{}]]></style><path class="used"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(RemoveUnusedCSSPlugin::new(), svg)?,
+            r#"<svg><style>
+  .used { fill: red }
+  path > .used { stroke: none }
+</style><style><![CDATA[]]></style><path class="used"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_documents_with_scripts_are_left_alone() -> io::Result<()> {
+        let svg = r#"<svg><style>.late { fill: red }</style><script>x()</script></svg>"#;
+        assert_eq!(optimize_with(RemoveUnusedCSSPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+}
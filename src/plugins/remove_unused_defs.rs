@@ -0,0 +1,288 @@
+//! Removal of unreferenced `<defs>` children.
+//!
+//! References can appear before or after the definitions they point to, so
+//! the plugin works in two passes:
+//!
+//! 1. During `analyze` every direct child of a `<defs>` that has an `id` is
+//!    recorded with the ids defined and referenced inside it. Starting from
+//!    the references made outside any definition, the definitions that are
+//!    reachable are resolved transitively, so a gradient only used by an
+//!    unused pattern is unused as well.
+//! 2. During `process_element` unreachable definitions are removed together
+//!    with their subtree.
+
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, url_references, XLinkPrefixes};
+use quick_xml::events::{BytesEnd, BytesStart, Event};
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+/// Removes `<defs>` children that nothing in the document refers to.
+///
+/// References are `href`/`xlink:href` values and `url(#id)` in any
+/// attribute (such as `fill`, `stroke`, `clip-path`, `mask` or `filter`)
+/// or in a `<style>` element. Definitions without an `id` are kept.
+pub struct RemoveUnusedDefsPlugin {
+    /// Ids of the definitions to remove.
+    unused: HashSet<String>,
+    /// Whether each open element is a `<defs>`.
+    open: Vec<bool>,
+    defs_removed: usize,
+}
+
+/// A `<defs>` child with an `id`, collected during analysis.
+#[derive(Default)]
+struct Definition {
+    id: String,
+    /// Ids defined anywhere in the definition, including its own.
+    ids: Vec<String>,
+    /// Ids referenced from inside the definition.
+    references: Vec<String>,
+}
+
+/// An open element during analysis.
+struct Frame {
+    is_defs: bool,
+    in_style: bool,
+    /// Indices of the enclosing definitions, innermost last.
+    definitions: Vec<usize>,
+}
+
+impl RemoveUnusedDefsPlugin {
+    pub fn new() -> Self {
+        Self {
+            unused: HashSet::new(),
+            open: Vec::new(),
+            defs_removed: 0,
+        }
+    }
+
+    fn references(attrs: &[(String, String)], xlink: &XLinkPrefixes) -> Vec<String> {
+        let mut references = Vec::new();
+        for (key, value) in attrs {
+            if xlink.is_link(key) {
+                if let Some(id) = value.strip_prefix('#') {
+                    references.push(id.to_string());
+                }
+            } else {
+                references.extend(url_references(value).into_iter().map(str::to_string));
+            }
+        }
+        references
+    }
+
+    fn id(attrs: &[(String, String)]) -> Option<&str> {
+        attrs
+            .iter()
+            .find(|(key, _)| key == "id")
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Record an element opened inside `parent` and return its frame.
+    fn visit(
+        elem: &BytesStart,
+        parent: Option<&Frame>,
+        definitions: &mut Vec<Definition>,
+        roots: &mut Vec<String>,
+        xlink: &XLinkPrefixes,
+    ) -> Frame {
+        let attrs = owned_attributes(elem);
+        let mut enclosing = parent.map(|frame| frame.definitions.clone()).unwrap_or_default();
+
+        if let (Some(id), Some(true)) = (Self::id(&attrs), parent.map(|frame| frame.is_defs)) {
+            definitions.push(Definition {
+                id: id.to_string(),
+                ..Definition::default()
+            });
+            enclosing.push(definitions.len() - 1);
+        }
+
+        if let Some(id) = Self::id(&attrs) {
+            for &index in &enclosing {
+                definitions[index].ids.push(id.to_string());
+            }
+        }
+
+        let references = Self::references(&attrs, xlink);
+        match enclosing.last() {
+            Some(&index) => definitions[index].references.extend(references),
+            None => roots.extend(references),
+        }
+
+        Frame {
+            is_defs: elem.local_name().as_ref() == b"defs",
+            in_style: elem.local_name().as_ref() == b"style",
+            definitions: enclosing,
+        }
+    }
+
+    /// Record the `url(#id)` references in text content if it belongs to a
+    /// `<style>` element.
+    fn visit_style(
+        content: &str,
+        parent: Option<&Frame>,
+        definitions: &mut [Definition],
+        roots: &mut Vec<String>,
+    ) {
+        let Some(frame) = parent.filter(|frame| frame.in_style) else { return };
+
+        let references = url_references(content).into_iter().map(str::to_string);
+        match frame.definitions.last() {
+            Some(&index) => definitions[index].references.extend(references),
+            None => roots.extend(references),
+        }
+    }
+}
+
+impl Default for RemoveUnusedDefsPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveUnusedDefsPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.unused.clear();
+        self.open.clear();
+        self.defs_removed = 0;
+        Ok(())
+    }
+
+    fn requires_analysis(&self) -> bool {
+        true
+    }
+
+    fn analyze(&mut self, events: &[Event<'static>]) -> io::Result<()> {
+        let mut definitions: Vec<Definition> = Vec::new();
+        let mut roots: Vec<String> = Vec::new();
+        let mut stack: Vec<Frame> = Vec::new();
+        let xlink = XLinkPrefixes::from_events(events);
+
+        for event in events {
+            match event {
+                Event::Start(elem) => {
+                    let frame = Self::visit(elem, stack.last(), &mut definitions, &mut roots, &xlink);
+                    stack.push(frame);
+                }
+                Event::Empty(elem) => {
+                    Self::visit(elem, stack.last(), &mut definitions, &mut roots, &xlink);
+                }
+                Event::End(_) => {
+                    stack.pop();
+                }
+                Event::Text(text) => {
+                    let content = String::from_utf8_lossy(text);
+                    Self::visit_style(&content, stack.last(), &mut definitions, &mut roots);
+                }
+                Event::CData(cdata) => {
+                    let content = String::from_utf8_lossy(cdata);
+                    Self::visit_style(&content, stack.last(), &mut definitions, &mut roots);
+                }
+                _ => {}
+            }
+        }
+
+        // Definitions containing each id
+        let mut containing: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, definition) in definitions.iter().enumerate() {
+            for id in &definition.ids {
+                containing.entry(id.as_str()).or_default().push(index);
+            }
+        }
+
+        let mut used = vec![false; definitions.len()];
+        let mut pending: Vec<&str> = roots.iter().map(String::as_str).collect();
+        while let Some(id) = pending.pop() {
+            for &index in containing.get(id).into_iter().flatten() {
+                if !used[index] {
+                    used[index] = true;
+                    pending.extend(definitions[index].references.iter().map(String::as_str));
+                }
+            }
+        }
+
+        self.unused = definitions
+            .iter()
+            .zip(used)
+            .filter(|(_, used)| !used)
+            .map(|(definition, _)| definition.id.clone())
+            .collect();
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        if self.open.last() == Some(&true) {
+            let attrs = owned_attributes(element);
+            if Self::id(&attrs).is_some_and(|id| self.unused.contains(id)) {
+                self.defs_removed += 1;
+                return Ok(ElementAction::Remove);
+            }
+        }
+
+        self.open.push(element.local_name().as_ref() == b"defs");
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_end<'a>(&mut self, end: BytesEnd<'a>) -> io::Result<Option<BytesEnd<'a>>> {
+        self.open.pop();
+        Ok(Some(end))
+    }
+
+    fn name(&self) -> &str {
+        "RemoveUnusedDefs"
+    }
+}
+
+impl PluginStatistics for RemoveUnusedDefsPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Unused definitions removed", self.defs_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_unreferenced_defs_are_removed() -> io::Result<()> {
+        let svg = r##"<svg><defs><linearGradient id="used"><stop/></linearGradient><linearGradient id="unused"><stop/></linearGradient><clipPath id="clip"><rect/></clipPath><filter id="blur"/><style>.a{}</style></defs><rect fill="url(#used)" clip-path="url('#clip')"/></svg>"##;
+
+        let output = optimize_with(RemoveUnusedDefsPlugin::new(), svg)?;
+
+        assert_eq!(
+            output,
+            r##"<svg><defs><linearGradient id="used"><stop/></linearGradient><clipPath id="clip"><rect/></clipPath><style>.a{}</style></defs><rect fill="url(#used)" clip-path="url('#clip')"/></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_aliased_xlink_references_keep_defs() -> io::Result<()> {
+        let svg = r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><defs><path id="p"/></defs><use x:href="#p"/></svg>"##;
+
+        assert_eq!(optimize_with(RemoveUnusedDefsPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_references_are_resolved_transitively() -> io::Result<()> {
+        let svg = r##"<svg><defs><linearGradient id="base"><stop/></linearGradient><linearGradient id="derived" xlink:href="#base"/><linearGradient id="orphan"/><pattern id="dead"><rect fill="url(#orphan)"/></pattern></defs><path stroke="url(#derived)"/></svg>"##;
+
+        let output = optimize_with(RemoveUnusedDefsPlugin::new(), svg)?;
+
+        assert!(output.contains(r#"id="base""#));
+        assert!(output.contains(r#"id="derived""#));
+        assert!(!output.contains(r#"id="orphan""#));
+        assert!(!output.contains(r#"id="dead""#));
+        Ok(())
+    }
+
+    #[test]
+    fn test_defs_containing_referenced_ids_are_kept() -> io::Result<()> {
+        let svg = r##"<svg><defs><g id="icons"><path id="star"/></g></defs><style>rect { fill: url(#paint) }</style><defs><pattern id="paint"/></defs><use href="#star"/></svg>"##;
+
+        assert_eq!(optimize_with(RemoveUnusedDefsPlugin::new(), svg)?, svg);
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes, style_declarations};
+use quick_xml::events::{BytesEnd, BytesStart};
+use std::io;
+
+/// Elements that paint themselves and have no children inheriting from them.
+const SHAPE_ELEMENTS: &[&str] = &["circle", "ellipse", "line", "path", "polygon", "polyline", "rect"];
+
+/// Properties that only matter when a stroke is painted.
+const STROKE_PROPERTIES: &[&str] = &[
+    "stroke-dasharray",
+    "stroke-dashoffset",
+    "stroke-linecap",
+    "stroke-linejoin",
+    "stroke-miterlimit",
+    "stroke-opacity",
+    "stroke-width",
+];
+
+/// Properties that only matter when a fill is painted.
+const FILL_PROPERTIES: &[&str] = &["fill-opacity", "fill-rule"];
+
+const MARKER_PROPERTIES: &[&str] = &["marker", "marker-start", "marker-mid", "marker-end"];
+
+/// What an open element passes on to its children.
+#[derive(Clone, Copy, Default)]
+struct Frame {
+    /// Inside a `display="none"` subtree.
+    hidden: bool,
+    /// An element of the hidden subtree has an `id`, so `<use>` may render
+    /// a copy of it elsewhere.
+    referenced: bool,
+    /// Markers are set here or on an ancestor.
+    markers: bool,
+}
+
+/// Removes paint properties that can't affect rendering.
+///
+/// - On a shape with `stroke="none"`, the `stroke-*` properties. The stroke
+///   width is kept when markers apply, since it scales them by default.
+/// - On a shape with `fill="none"`, `fill-opacity` and `fill-rule`; with
+///   `fill-opacity="0"`, `fill-rule`.
+/// - On an element with `display="none"` and everything inside it, all
+///   paint properties, unless an `id` in the hidden subtree could make a
+///   `<use>` render part of it.
+///
+/// Elements with a `class` are left alone, as CSS may set the properties
+/// these rules depend on.
+pub struct RemoveUselessPaintPlugin {
+    stack: Vec<Frame>,
+    stroke_removed: usize,
+    fill_removed: usize,
+    hidden_removed: usize,
+}
+
+impl RemoveUselessPaintPlugin {
+    pub fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            stroke_removed: 0,
+            fill_removed: 0,
+            hidden_removed: 0,
+        }
+    }
+}
+
+impl Default for RemoveUselessPaintPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The value of a property, from `style` if it is set there.
+fn property<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    let styled = attrs
+        .iter()
+        .filter(|(key, _)| key == "style")
+        .flat_map(|(_, style)| style_declarations(style))
+        .filter(|(property, _)| *property == name)
+        .last()
+        .map(|(_, value)| value);
+    styled.or_else(|| {
+        attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim())
+    })
+}
+
+fn is_zero(value: Option<&str>) -> bool {
+    value.and_then(|value| value.parse::<f64>().ok()) == Some(0.0)
+}
+
+impl SVGPlugin for RemoveUselessPaintPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.stack.clear();
+        self.stroke_removed = 0;
+        self.fill_removed = 0;
+        self.hidden_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+        let attrs = owned_attributes(element);
+        let parent = self.stack.last().copied().unwrap_or_default();
+
+        let hidden = parent.hidden || property(&attrs, "display") == Some("none");
+        let frame = Frame {
+            hidden,
+            referenced: hidden && (parent.referenced || attrs.iter().any(|(key, _)| key == "id")),
+            markers: parent.markers || MARKER_PROPERTIES.iter().any(|marker| property(&attrs, marker).is_some()),
+        };
+        self.stack.push(frame);
+
+        if attrs.iter().any(|(key, _)| key == "class") {
+            return Ok(ElementAction::Keep);
+        }
+
+        let is_shape = SHAPE_ELEMENTS.contains(&name.as_str());
+        let no_stroke = is_shape && property(&attrs, "stroke") == Some("none");
+        let fill = property(&attrs, "fill");
+        let no_fill = is_shape && fill == Some("none");
+        let transparent_fill = is_shape && !no_fill && is_zero(property(&attrs, "fill-opacity"));
+
+        let before = attrs.len();
+        let kept: Vec<_> = attrs
+            .into_iter()
+            .filter(|(key, _)| {
+                let key = key.as_str();
+                let is_stroke = STROKE_PROPERTIES.contains(&key);
+                let is_fill = FILL_PROPERTIES.contains(&key);
+                if frame.hidden && !frame.referenced && (is_stroke || is_fill || key == "fill" || key == "stroke") {
+                    self.hidden_removed += 1;
+                } else if no_stroke && is_stroke && !(key == "stroke-width" && frame.markers) {
+                    self.stroke_removed += 1;
+                } else if (no_fill && is_fill) || (transparent_fill && key == "fill-rule") {
+                    self.fill_removed += 1;
+                } else {
+                    return true;
+                }
+                false
+            })
+            .collect();
+
+        if kept.len() < before {
+            set_attributes(element, &kept);
+        }
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_end<'a>(&mut self, end: BytesEnd<'a>) -> io::Result<Option<BytesEnd<'a>>> {
+        self.stack.pop();
+        Ok(Some(end))
+    }
+
+    fn name(&self) -> &str {
+        "RemoveUselessPaint"
+    }
+}
+
+impl PluginStatistics for RemoveUselessPaintPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![
+            ("Stroke properties removed", self.stroke_removed.to_string()),
+            ("Fill properties removed", self.fill_removed.to_string()),
+            ("Hidden paint properties removed", self.hidden_removed.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_stroke_properties_without_stroke_are_removed() -> io::Result<()> {
+        let svg = r#"<svg><path d="M0 0" stroke="none" stroke-width="2" stroke-linecap="round"/><g stroke="none" stroke-width="2"><path d="M0 0" stroke="red"/></g><path d="M0 0" style="stroke:none" stroke-opacity=".5"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(RemoveUselessPaintPlugin::new(), svg)?,
+            r#"<svg><path d="M0 0" stroke="none"/><g stroke="none" stroke-width="2"><path d="M0 0" stroke="red"/></g><path d="M0 0" style="stroke:none"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fill_properties_without_fill_are_removed() -> io::Result<()> {
+        let svg = r#"<svg><rect fill="none" fill-rule="evenodd" fill-opacity=".5"/><rect fill="red" fill-opacity="0" fill-rule="evenodd"/><rect class="c" fill="none" fill-rule="evenodd"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(RemoveUselessPaintPlugin::new(), svg)?,
+            r#"<svg><rect fill="none"/><rect fill="red" fill-opacity="0"/><rect class="c" fill="none" fill-rule="evenodd"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_markers_keep_the_stroke_width() -> io::Result<()> {
+        let svg = r##"<svg><g marker-end="url(#m)"><path stroke="none" stroke-width="2" stroke-linejoin="round"/></g></svg>"##;
+
+        assert_eq!(
+            optimize_with(RemoveUselessPaintPlugin::new(), svg)?,
+            r##"<svg><g marker-end="url(#m)"><path stroke="none" stroke-width="2"/></g></svg>"##
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_hidden_subtrees_lose_their_paint() -> io::Result<()> {
+        let svg = r#"<svg><g display="none" fill="red"><path stroke="blue" stroke-width="3"/></g><g style="display:none"><path id="icon" fill="red"/></g><path fill="red"/></svg>"#;
+
+        assert_eq!(
+            optimize_with(RemoveUselessPaintPlugin::new(), svg)?,
+            r#"<svg><g display="none"><path/></g><g style="display:none"><path id="icon" fill="red"/></g><path fill="red"/></svg>"#
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use quick_xml::events::{BytesPI, BytesStart};
+use std::io;
+
+/// Removes processing instructions such as `<?xml-stylesheet ...?>`.
+///
+/// The XML declaration (`<?xml version="1.0"?>`) is a separate event that
+/// never reaches `process_pi`, so it is always kept.
+pub struct RemoveXmlProcInstPlugin {
+    instructions_removed: usize,
+}
+
+impl RemoveXmlProcInstPlugin {
+    pub fn new() -> Self {
+        Self {
+            instructions_removed: 0,
+        }
+    }
+}
+
+impl Default for RemoveXmlProcInstPlugin {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SVGPlugin for RemoveXmlProcInstPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.instructions_removed = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, _element: &mut BytesStart) -> io::Result<ElementAction> {
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_pi<'a>(&mut self, _pi: BytesPI<'a>) -> io::Result<Option<BytesPI<'a>>> {
+        self.instructions_removed += 1;
+        Ok(None)
+    }
+
+    fn name(&self) -> &str {
+        "RemoveXmlProcInst"
+    }
+}
+
+impl PluginStatistics for RemoveXmlProcInstPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Processing instructions removed", self.instructions_removed.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::processor::SVGProcessor;
+
+    #[test]
+    fn test_stylesheet_instruction_is_removed() -> io::Result<()> {
+        let svg = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><?xml-stylesheet type=\"text/css\" href=\"editor.css\"?><svg><?editor-state collapsed?><rect/></svg>";
+
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(RemoveXmlProcInstPlugin::new());
+        let output = processor.optimize_str(svg)?;
+
+        assert_eq!(output, "<?xml version=\"1.0\" encoding=\"UTF-8\"?><svg><rect/></svg>");
+        Ok(())
+    }
+}
@@ -0,0 +1,361 @@
+use crate::plugins::path::format_number;
+use crate::plugins::traits::{ElementAction, PluginStatistics, SVGPlugin};
+use crate::plugins::util::{owned_attributes, set_attributes};
+use quick_xml::events::{BytesEnd, BytesStart};
+use serde::Deserialize;
+use std::io;
+use std::str::FromStr;
+
+/// A basic shape that `ShapeToPathPlugin` can convert.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Shape {
+    Rect,
+    Circle,
+    Ellipse,
+    Line,
+    Polyline,
+    Polygon,
+}
+
+impl Shape {
+    pub const ALL: [Shape; 6] = [
+        Shape::Rect,
+        Shape::Circle,
+        Shape::Ellipse,
+        Shape::Line,
+        Shape::Polyline,
+        Shape::Polygon,
+    ];
+
+    fn from_element(name: &[u8]) -> Option<Self> {
+        match name {
+            b"rect" => Some(Shape::Rect),
+            b"circle" => Some(Shape::Circle),
+            b"ellipse" => Some(Shape::Ellipse),
+            b"line" => Some(Shape::Line),
+            b"polyline" => Some(Shape::Polyline),
+            b"polygon" => Some(Shape::Polygon),
+            _ => None,
+        }
+    }
+
+    /// Attributes that describe the shape's geometry and are replaced by `d`.
+    fn geometry(self) -> &'static [&'static str] {
+        match self {
+            Shape::Rect => &["x", "y", "width", "height", "rx", "ry"],
+            Shape::Circle => &["cx", "cy", "r"],
+            Shape::Ellipse => &["cx", "cy", "rx", "ry"],
+            Shape::Line => &["x1", "y1", "x2", "y2"],
+            Shape::Polyline | Shape::Polygon => &["points"],
+        }
+    }
+}
+
+impl FromStr for Shape {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::from_element(name.to_ascii_lowercase().as_bytes())
+            .ok_or_else(|| format!("unknown shape '{}'", name))
+    }
+}
+
+/// Converts basic shapes into equivalent `<path>` elements.
+///
+/// Rounded rectangles get an arc per corner. Shapes whose geometry uses
+/// units or percentages and shapes that would not render (zero size, no
+/// points) are left alone. Circles and ellipses become two arcs, which are
+/// not always shorter than the shape; leave them out of `shapes` to keep
+/// them.
+pub struct ShapeToPathPlugin {
+    shapes: Vec<Shape>,
+    /// Rounding for generated coordinates; `None` keeps full precision.
+    decimal_places: Option<usize>,
+    /// Whether each open element was converted, so its end tag can follow.
+    converted: Vec<bool>,
+    shapes_converted: usize,
+}
+
+/// The name the plugin is also known by.
+pub type ConvertShapeToPathPlugin = ShapeToPathPlugin;
+
+impl ShapeToPathPlugin {
+    /// Create a plugin that converts the given shapes; an empty list
+    /// converts all of them.
+    pub fn new(shapes: Vec<Shape>) -> Self {
+        Self {
+            shapes: if shapes.is_empty() { Shape::ALL.to_vec() } else { shapes },
+            decimal_places: None,
+            converted: Vec::new(),
+            shapes_converted: 0,
+        }
+    }
+
+    /// Round generated coordinates like the path optimizer does.
+    pub fn with_decimal_places(mut self, decimal_places: Option<usize>) -> Self {
+        self.decimal_places = decimal_places;
+        self
+    }
+
+    fn number(&self, value: f64) -> String {
+        match self.decimal_places {
+            Some(decimal_places) => format_number(value, decimal_places),
+            None => value.to_string(),
+        }
+    }
+
+    /// Build the path data for a shape, or `None` if it can't be converted.
+    fn path_data(&self, shape: Shape, attrs: &[(String, String)]) -> Option<String> {
+        let get = |key: &str| -> Option<f64> {
+            match attrs.iter().find(|(k, _)| k == key) {
+                Some((_, value)) => value.trim().parse().ok(),
+                None => Some(0.0),
+            }
+        };
+        let n = |value: f64| self.number(value);
+
+        match shape {
+            Shape::Rect => {
+                let (x, y, width, height) = (get("x")?, get("y")?, get("width")?, get("height")?);
+                if width <= 0.0 || height <= 0.0 {
+                    return None;
+                }
+                let radius = |key: &str| match attrs.iter().find(|(k, _)| k == key) {
+                    Some((_, value)) => value.trim().parse::<f64>().ok().filter(|r| *r >= 0.0).map(Some),
+                    None => Some(None),
+                };
+                // A missing radius takes the other's value, and both are
+                // clamped to half the side, as renderers do
+                let (rx, ry) = match (radius("rx")?, radius("ry")?) {
+                    (Some(rx), Some(ry)) => (rx, ry),
+                    (Some(r), None) | (None, Some(r)) => (r, r),
+                    (None, None) => (0.0, 0.0),
+                };
+                let (rx, ry) = (rx.min(width / 2.0), ry.min(height / 2.0));
+                if rx == 0.0 || ry == 0.0 {
+                    return Some(format!(
+                        "M{} {}H{}V{}H{}z",
+                        n(x),
+                        n(y),
+                        n(x + width),
+                        n(y + height),
+                        n(x)
+                    ));
+                }
+                Some(Self::rounded_rect(x, y, width, height, rx, ry, n))
+            }
+            Shape::Circle => {
+                let r = get("r")?;
+                Self::ellipse(get("cx")?, get("cy")?, r, r, n)
+            }
+            Shape::Ellipse => Self::ellipse(get("cx")?, get("cy")?, get("rx")?, get("ry")?, n),
+            Shape::Line => Some(format!(
+                "M{} {}L{} {}",
+                n(get("x1")?),
+                n(get("y1")?),
+                n(get("x2")?),
+                n(get("y2")?)
+            )),
+            Shape::Polyline | Shape::Polygon => {
+                let points = attrs.iter().find(|(k, _)| k == "points")?;
+                let coords: Vec<f64> = points
+                    .1
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .filter(|part| !part.is_empty())
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()
+                    .ok()?;
+
+                // An odd trailing coordinate is ignored, as renderers do
+                let pairs: Vec<String> = coords
+                    .chunks_exact(2)
+                    .map(|pair| format!("{} {}", n(pair[0]), n(pair[1])))
+                    .collect();
+                let (first, rest) = pairs.split_first()?;
+
+                let mut data = format!("M{}", first);
+                if !rest.is_empty() {
+                    data.push('L');
+                    data.push_str(&rest.join(" "));
+                }
+                if shape == Shape::Polygon {
+                    data.push('z');
+                }
+                Some(data)
+            }
+        }
+    }
+
+    /// A rectangle with an arc at each corner, clockwise from the end of
+    /// the top left corner. Straight sides of zero length are left out.
+    fn rounded_rect(x: f64, y: f64, width: f64, height: f64, rx: f64, ry: f64, n: impl Fn(f64) -> String) -> String {
+        let arc = format!("A{} {} 0 0 1 ", n(rx), n(ry));
+        let (right, bottom) = (x + width, y + height);
+        let mut data = format!("M{} {}", n(x + rx), n(y));
+        if width > 2.0 * rx {
+            data += &format!("H{}", n(right - rx));
+        }
+        data += &format!("{}{} {}", arc, n(right), n(y + ry));
+        if height > 2.0 * ry {
+            data += &format!("V{}", n(bottom - ry));
+        }
+        data += &format!("{}{} {}", arc, n(right - rx), n(bottom));
+        if width > 2.0 * rx {
+            data += &format!("H{}", n(x + rx));
+        }
+        data += &format!("{}{} {}", arc, n(x), n(bottom - ry));
+        if height > 2.0 * ry {
+            data += &format!("V{}", n(y + ry));
+        }
+        data += &format!("{}{} {}z", arc, n(x + rx), n(y));
+        data
+    }
+
+    /// Two half arcs around the center, starting at the leftmost point.
+    fn ellipse(cx: f64, cy: f64, rx: f64, ry: f64, n: impl Fn(f64) -> String) -> Option<String> {
+        if rx <= 0.0 || ry <= 0.0 {
+            return None;
+        }
+        let (rx_s, ry_s) = (n(rx), n(ry));
+        Some(format!(
+            "M{} {}A{} {} 0 1 0 {} {}A{} {} 0 1 0 {} {}z",
+            n(cx - rx),
+            n(cy),
+            rx_s,
+            ry_s,
+            n(cx + rx),
+            n(cy),
+            rx_s,
+            ry_s,
+            n(cx - rx),
+            n(cy)
+        ))
+    }
+}
+
+impl Default for ShapeToPathPlugin {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl SVGPlugin for ShapeToPathPlugin {
+    fn init(&mut self) -> io::Result<()> {
+        self.converted.clear();
+        self.shapes_converted = 0;
+        Ok(())
+    }
+
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+        let shape = Shape::from_element(element.name().as_ref())
+            .filter(|shape| self.shapes.contains(shape));
+
+        let Some(shape) = shape else {
+            self.converted.push(false);
+            return Ok(ElementAction::Keep);
+        };
+
+        let attrs = owned_attributes(element);
+        let Some(data) = self.path_data(shape, &attrs) else {
+            self.converted.push(false);
+            return Ok(ElementAction::Keep);
+        };
+
+        let mut kept: Vec<_> = attrs
+            .into_iter()
+            .filter(|(key, _)| !shape.geometry().contains(&key.as_str()))
+            .collect();
+        kept.push(("d".to_string(), data));
+
+        *element = BytesStart::new("path");
+        set_attributes(element, &kept);
+
+        self.converted.push(true);
+        self.shapes_converted += 1;
+        Ok(ElementAction::Keep)
+    }
+
+    fn process_end<'a>(&mut self, end: BytesEnd<'a>) -> io::Result<Option<BytesEnd<'a>>> {
+        if self.converted.pop() == Some(true) {
+            return Ok(Some(BytesEnd::new("path")));
+        }
+        Ok(Some(end))
+    }
+
+    fn name(&self) -> &str {
+        "ShapeToPath"
+    }
+}
+
+impl PluginStatistics for ShapeToPathPlugin {
+    fn get_statistics(&self) -> Vec<(&str, String)> {
+        vec![("Shapes converted", self.shapes_converted.to_string())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::util::optimize_with;
+
+    #[test]
+    fn test_shapes_are_converted() -> io::Result<()> {
+        let svg = r#"<svg><rect x="10" y="20" width="30" height="40" fill="red"/><line x1="0" y1="0" x2="5" y2="5"/><polyline points="0,0 10,10 20,0"/><polygon points="0 0, 10 0, 5 5"/></svg>"#;
+        let output = optimize_with(ShapeToPathPlugin::default(), svg)?;
+
+        assert_eq!(
+            output,
+            r#"<svg><path fill="red" d="M10 20H40V60H10z"/><path d="M0 0L5 5"/><path d="M0 0L10 10 20 0"/><path d="M0 0L10 0 5 5z"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_rounded_rects() -> io::Result<()> {
+        let svg = r#"<svg><rect x="10" y="10" width="30" height="20" rx="5"/><rect width="10" height="4" rx="1" ry="8"/><rect width="4" height="4" ry="2"/><rect width="4" height="4" rx="0" ry="1"/></svg>"#;
+        let output = optimize_with(ShapeToPathPlugin::default(), svg)?;
+
+        assert_eq!(
+            output,
+            concat!(
+                r#"<svg><path d="M15 10H35A5 5 0 0 1 40 15V25A5 5 0 0 1 35 30H15A5 5 0 0 1 10 25V15A5 5 0 0 1 15 10z"/>"#,
+                r#"<path d="M1 0H9A1 2 0 0 1 10 2A1 2 0 0 1 9 4H1A1 2 0 0 1 0 2A1 2 0 0 1 1 0z"/>"#,
+                r#"<path d="M2 0A2 2 0 0 1 4 2A2 2 0 0 1 2 4A2 2 0 0 1 0 2A2 2 0 0 1 2 0z"/>"#,
+                r#"<path d="M0 0H4V4H0z"/></svg>"#
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_circles_and_ellipses() -> io::Result<()> {
+        let svg = r#"<svg><circle cx="10" cy="10" r="5"></circle><ellipse cx="0.3333" cy="0" rx="1" ry="2"/></svg>"#;
+        let output = optimize_with(ShapeToPathPlugin::default().with_decimal_places(Some(2)), svg)?;
+
+        assert_eq!(
+            output,
+            r#"<svg><path d="M5 10A5 5 0 1 0 15 10A5 5 0 1 0 5 10z"></path><path d="M-0.67 0A1 2 0 1 0 1.33 0A1 2 0 1 0 -0.67 0z"/></svg>"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unconvertible_shapes_are_kept() -> io::Result<()> {
+        let svg = r#"<svg><rect width="10" height="10" rx="2%"/><rect width="50%" height="10"/><circle r="0"/><polygon points=""/></svg>"#;
+
+        assert_eq!(optimize_with(ShapeToPathPlugin::default(), svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_only_selected_shapes_are_converted() -> io::Result<()> {
+        let svg = r#"<svg><rect width="1" height="1"/><line x2="1"/></svg>"#;
+        let output = optimize_with(ShapeToPathPlugin::new(vec![Shape::Line]), svg)?;
+
+        assert_eq!(output, r#"<svg><rect width="1" height="1"/><path d="M0 0L1 0"/></svg>"#);
+        assert_eq!("Polygon".parse::<Shape>(), Ok(Shape::Polygon));
+        assert!("star".parse::<Shape>().is_err());
+        Ok(())
+    }
+}
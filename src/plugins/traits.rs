@@ -1,14 +1,50 @@
 use std::io;
-use quick_xml::events::BytesStart;
+use std::path::Path;
+use quick_xml::events::{BytesCData, BytesEnd, BytesPI, BytesStart, BytesText, Event};
+
+use crate::diagnostics::Diagnostic;
+use crate::tree::Document;
+
+/// What the processor should do with an element after a plugin has seen it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElementAction {
+    /// Keep the element, including any in-place modifications.
+    Keep,
+    /// Drop the element. For a start tag the whole subtree up to the
+    /// matching end tag is dropped as well.
+    Remove,
+    /// Drop the element's start and end tags but keep its content in
+    /// place. For a self-closing element this is the same as `Remove`.
+    Unwrap,
+    /// Write this start tag instead of the element's own, keeping its
+    /// content. Later plugins see the replacement, and when its name differs
+    /// the matching end tag is renamed too.
+    ReplaceWith(BytesStart<'static>),
+}
 
 /// Trait that must be implemented by all SVG optimization plugins.
 ///
 /// This trait defines the lifecycle and processing capabilities of a plugin:
+/// - `set_source`: Called with the input file's path before `init`
 /// - `init`: Called before processing begins
+/// - `analyze`: Called with the whole document when `requires_analysis` is set
+/// - `pre_document`: Called right before the first event is processed
 /// - `process_element`: Called for each XML element
+/// - `process_end`: Called for each closing tag
+/// - `process_removed`: Called with the content of elements the plugin removed
+/// - `process_text`, `process_cdata`, `process_comment`, `process_pi`,
+///   `process_doctype`: Called for text, CDATA sections, comments,
+///   processing instructions and the DOCTYPE declaration
+/// - `post_document`: Called after the last event to append extra content
 /// - `finalize`: Called after all elements have been processed
 /// - `name`: Returns the plugin's name for identification
 pub trait SVGPlugin: PluginStatistics {
+    /// Tell the plugin which file the next document is read from.
+    ///
+    /// Called before `init`, with `None` for documents that don't come
+    /// from a file, such as stdin or a string.
+    fn set_source(&mut self, _path: Option<&Path>) {}
+
     /// Initialize the plugin before processing begins.
     ///
     /// This method is called once before processing the SVG file.
@@ -17,6 +53,35 @@ pub trait SVGPlugin: PluginStatistics {
         Ok(()) // Default no-op implementation
     }
 
+    /// Whether the plugin needs to see the whole document before processing.
+    ///
+    /// When any plugin returns `true`, the processor buffers the document in
+    /// memory and calls `analyze` before the processing pass. Plugins that
+    /// only look at one element at a time should keep the default so the
+    /// file can be streamed.
+    fn requires_analysis(&self) -> bool {
+        false
+    }
+
+    /// Inspect the whole document before any element is processed.
+    ///
+    /// This is where plugins collect document-wide knowledge such as
+    /// referenced ids or duplicate definitions.
+    ///
+    /// # Arguments
+    /// * `events` - Every event of the document, in order
+    fn analyze(&mut self, _events: &[Event<'static>]) -> io::Result<()> {
+        Ok(()) // Default no-op implementation
+    }
+
+    /// Prepare for processing a document.
+    ///
+    /// Called after `init` (and after `analyze`, when the document is
+    /// buffered), right before the first event is processed.
+    fn pre_document(&mut self) -> io::Result<()> {
+        Ok(()) // Default no-op implementation
+    }
+
     /// Process a single XML element.
     ///
     /// This method is called for each element in the SVG file.
@@ -26,8 +91,95 @@ pub trait SVGPlugin: PluginStatistics {
     /// * `element` - Mutable reference to the XML element being processed
     ///
     /// # Returns
-    /// * `io::Result<()>` - Success or error during processing
-    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<()>;
+    /// * `io::Result<ElementAction>` - Whether to keep or remove the element
+    fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction>;
+
+    /// Process the closing tag of an element.
+    ///
+    /// Every call to `process_element` is matched by exactly one call to
+    /// `process_end`, in document order, so plugins can track nesting with a
+    /// simple depth counter:
+    /// - Self-closing elements get a synthesized end tag that is never written.
+    /// - When a plugin removes an element, the plugins that ran before it
+    ///   still receive the end tag; later plugins see neither tag.
+    ///
+    /// Return the end tag to keep it, or `None` to drop it from the output.
+    /// Plugins later in the chain still receive a dropped end tag.
+    ///
+    /// # Arguments
+    /// * `end` - The closing tag being processed
+    fn process_end<'a>(&mut self, end: BytesEnd<'a>) -> io::Result<Option<BytesEnd<'a>>> {
+        Ok(Some(end)) // Default keeps the end tag unchanged
+    }
+
+    /// Observe the content of an element this plugin removed.
+    ///
+    /// After `process_element` returns `ElementAction::Remove`, the plugin
+    /// receives every event of the dropped subtree, starting with the
+    /// element itself and ending with its end tag (a self-closing element is
+    /// a single `Event::Empty`). No other plugin sees these events.
+    ///
+    /// # Arguments
+    /// * `event` - An event that won't be written
+    fn process_removed(&mut self, _event: &Event) -> io::Result<()> {
+        Ok(()) // Default ignores removed content
+    }
+
+    /// Process a text node between elements.
+    ///
+    /// Like all content hooks, the value is passed through each plugin in
+    /// turn. Return it (possibly rewritten) to keep it, or `None` to drop it
+    /// from the output; later plugins don't see dropped content.
+    ///
+    /// # Arguments
+    /// * `text` - The escaped text content
+    fn process_text<'a>(&mut self, text: BytesText<'a>) -> io::Result<Option<BytesText<'a>>> {
+        Ok(Some(text)) // Default keeps the text unchanged
+    }
+
+    /// Process a `<![CDATA[...]]>` section.
+    ///
+    /// # Arguments
+    /// * `cdata` - The section content, without the CDATA markers
+    fn process_cdata<'a>(&mut self, cdata: BytesCData<'a>) -> io::Result<Option<BytesCData<'a>>> {
+        Ok(Some(cdata)) // Default keeps the section unchanged
+    }
+
+    /// Process a single XML comment.
+    ///
+    /// # Arguments
+    /// * `comment` - The comment content, without the `<!--` and `-->` markers
+    fn process_comment<'a>(&mut self, comment: BytesText<'a>) -> io::Result<Option<BytesText<'a>>> {
+        Ok(Some(comment)) // Default keeps the comment unchanged
+    }
+
+    /// Process a processing instruction such as `<?xml-stylesheet ...?>`.
+    ///
+    /// The XML declaration (`<?xml version="1.0"?>`) is not a processing
+    /// instruction and never reaches this hook.
+    ///
+    /// # Arguments
+    /// * `pi` - The instruction content, without the `<?` and `?>` markers
+    fn process_pi<'a>(&mut self, pi: BytesPI<'a>) -> io::Result<Option<BytesPI<'a>>> {
+        Ok(Some(pi)) // Default keeps the instruction unchanged
+    }
+
+    /// Process the `<!DOCTYPE ...>` declaration.
+    ///
+    /// # Arguments
+    /// * `doctype` - The declaration content after `<!DOCTYPE `, including
+    ///   any internal subset
+    fn process_doctype<'a>(&mut self, doctype: BytesText<'a>) -> io::Result<Option<BytesText<'a>>> {
+        Ok(Some(doctype)) // Default keeps the declaration unchanged
+    }
+
+    /// Emit events to append after the end of the document.
+    ///
+    /// Called once the whole input has been processed. The returned events
+    /// are written as-is, without passing through other plugins.
+    fn post_document(&mut self) -> io::Result<Vec<Event<'static>>> {
+        Ok(Vec::new()) // Default appends nothing
+    }
 
     /// Finalize processing and clean up.
     ///
@@ -43,10 +195,42 @@ pub trait SVGPlugin: PluginStatistics {
     fn name(&self) -> &str;
 }
 
+/// Trait for plugins that work on the whole document tree.
+///
+/// Tree plugins run when the processor uses the tree engine: the output of
+/// the streaming plugins is parsed into a `Document`, each tree plugin
+/// rewrites it in turn, and the result is serialized. Use this for
+/// optimizations that need to look at parents, children or siblings.
+pub trait TreePlugin: PluginStatistics {
+    /// Tell the plugin which file the next document is read from, like
+    /// [`SVGPlugin::set_source`].
+    fn set_source(&mut self, _path: Option<&Path>) {}
+
+    /// Initialize the plugin before processing begins.
+    fn init(&mut self) -> io::Result<()> {
+        Ok(()) // Default no-op implementation
+    }
+
+    /// Rewrite the document tree in place.
+    ///
+    /// # Arguments
+    /// * `document` - The parsed document, including anything around the root element
+    fn process_tree(&mut self, document: &mut Document) -> io::Result<()>;
+
+    /// Get the name of the plugin.
+    fn name(&self) -> &str;
+}
+
 /// Trait for plugins that can provide optimization statistics.
 pub trait PluginStatistics {
     /// Get human-readable statistics about the optimizations performed.
     fn get_statistics(&self) -> Vec<(&str, String)>;
+
+    /// Non-fatal problems found in the last document, in the order they
+    /// were found.
+    fn diagnostics(&self) -> &[Diagnostic] {
+        &[] // Default reports nothing
+    }
 }
 
 /// Trait for plugins that support configuration.
@@ -70,8 +254,8 @@ mod tests {
     struct TestPlugin;
 
     impl SVGPlugin for TestPlugin {
-        fn process_element(&mut self, _element: &mut BytesStart) -> io::Result<()> {
-            Ok(())
+        fn process_element(&mut self, _element: &mut BytesStart) -> io::Result<ElementAction> {
+            Ok(ElementAction::Keep)
         }
 
         fn name(&self) -> &str {
@@ -91,13 +275,25 @@ mod tests {
 
         // Test default implementations
         assert!(plugin.init().is_ok());
+        assert!(!plugin.requires_analysis());
+        assert!(plugin.analyze(&[]).is_ok());
+        assert!(plugin.pre_document().is_ok());
+        assert!(plugin.post_document().unwrap().is_empty());
+        assert!(plugin.process_text(BytesText::new("text")).unwrap().is_some());
+        assert!(plugin.process_cdata(BytesCData::new("data")).unwrap().is_some());
+        assert!(plugin.process_comment(BytesText::new("note")).unwrap().is_some());
+        assert!(plugin.process_pi(BytesPI::new("target")).unwrap().is_some());
+        assert!(plugin.process_doctype(BytesText::new("svg")).unwrap().is_some());
+        assert!(plugin.process_end(BytesEnd::new("test")).unwrap().is_some());
+        assert!(plugin.process_removed(&Event::Eof).is_ok());
         assert!(plugin.finalize().is_ok());
+        assert!(plugin.diagnostics().is_empty());
 
         // Test name
         assert_eq!(plugin.name(), "TestPlugin");
 
         // Test process_element with dummy element
         let mut element = BytesStart::new("test");
-        assert!(plugin.process_element(&mut element).is_ok());
+        assert_eq!(plugin.process_element(&mut element).unwrap(), ElementAction::Keep);
     }
 }
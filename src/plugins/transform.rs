@@ -0,0 +1,177 @@
+//! Parsing, composing and serializing SVG transforms.
+
+use crate::plugins::path::format_number;
+
+/// Decimal places of the scale, rotation and skew components.
+const LINEAR_PRECISION: usize = 5;
+
+/// Decimal places of the translation components.
+const TRANSLATE_PRECISION: usize = 3;
+
+/// How far rounded rotation components may be off a unit rotation.
+const ROTATION_TOLERANCE: f64 = 1e-4;
+
+/// A 2D affine transform as a 3×3 homogeneous matrix.
+///
+/// The last row is always `0 0 1`; an SVG `matrix(a b c d e f)` is
+/// `[[a, c, e], [b, d, f], [0, 0, 1]]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Matrix([[f64; 3]; 3]);
+
+impl Matrix {
+    pub(crate) const IDENTITY: Matrix = Matrix([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+
+    /// The matrix of `matrix(a b c d e f)`.
+    pub(crate) fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Matrix([[a, c, e], [b, d, f], [0.0, 0.0, 1.0]])
+    }
+
+    pub(crate) fn translate(tx: f64, ty: f64) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
+
+    pub(crate) fn scale(sx: f64, sy: f64) -> Self {
+        Self::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    /// A rotation by `degrees` around the origin.
+    pub(crate) fn rotate(degrees: f64) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    /// The `a b c d e f` components.
+    pub(crate) fn components(&self) -> [f64; 6] {
+        let [[a, c, e], [b, d, f], _] = self.0;
+        [a, b, c, d, e, f]
+    }
+
+    /// The transform applying `other` first, then `self`.
+    pub(crate) fn multiply(&self, other: &Matrix) -> Matrix {
+        let mut result = [[0.0; 3]; 3];
+        for (row, result_row) in result.iter_mut().enumerate() {
+            for (column, cell) in result_row.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| self.0[row][k] * other.0[k][column]).sum();
+            }
+        }
+        Matrix(result)
+    }
+
+    /// The components rounded to the precision they are written with.
+    fn rounded(&self) -> [f64; 6] {
+        let round = |value: f64, places: usize| {
+            let factor = 10f64.powi(places as i32);
+            (value * factor).round() / factor + 0.0
+        };
+        let [a, b, c, d, e, f] = self.components();
+        [
+            round(a, LINEAR_PRECISION),
+            round(b, LINEAR_PRECISION),
+            round(c, LINEAR_PRECISION),
+            round(d, LINEAR_PRECISION),
+            round(e, TRANSLATE_PRECISION),
+            round(f, TRANSLATE_PRECISION),
+        ]
+    }
+
+    /// The shortest transform list for this matrix: `translate()`,
+    /// `scale()` or `rotate()` when the matrix is one of those, `matrix()`
+    /// otherwise. The identity is an empty string.
+    pub(crate) fn to_transform_string(self) -> String {
+        let [a, b, c, d, e, f] = self.rounded();
+        let linear = |value: f64| format_number(value, LINEAR_PRECISION);
+        let translation = |value: f64| format_number(value, TRANSLATE_PRECISION);
+        let pair = |x: String, y: String, zero_y: bool| if zero_y { x } else { format!("{} {}", x, y) };
+
+        let mut candidates = Vec::new();
+        if [a, b, c, d] == [1.0, 0.0, 0.0, 1.0] {
+            if e == 0.0 && f == 0.0 {
+                return String::new();
+            }
+            candidates.push(format!("translate({})", pair(translation(e), translation(f), f == 0.0)));
+        }
+        if e == 0.0 && f == 0.0 {
+            if b == 0.0 && c == 0.0 {
+                candidates.push(format!("scale({})", pair(linear(a), linear(d), a == d)));
+            }
+            let (sin, cos) = (b, a);
+            if c == -sin && d == cos && (sin * sin + cos * cos - 1.0).abs() < ROTATION_TOLERANCE {
+                let degrees = sin.atan2(cos).to_degrees();
+                candidates.push(format!("rotate({})", format_number(degrees, TRANSLATE_PRECISION)));
+            }
+        }
+        candidates.push(format!(
+            "matrix({} {} {} {} {} {})",
+            linear(a),
+            linear(b),
+            linear(c),
+            linear(d),
+            translation(e),
+            translation(f)
+        ));
+
+        candidates.into_iter().min_by_key(String::len).unwrap_or_default()
+    }
+}
+
+/// Parse a transform list such as `translate(10 20) rotate(45)` into the
+/// single matrix it amounts to. Returns `None` for malformed lists.
+pub(crate) fn parse_transform(value: &str) -> Option<Matrix> {
+    let mut matrix = Matrix::IDENTITY;
+    let mut rest = value.trim();
+
+    while !rest.is_empty() {
+        let (name, after_name) = rest.split_once('(')?;
+        let (args, after_args) = after_name.split_once(')')?;
+        let args: Vec<f64> = args
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| arg.parse().ok())
+            .collect::<Option<_>>()?;
+
+        let transform = match (name.trim(), args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => Matrix::new(a, b, c, d, e, f),
+            ("translate", &[tx]) => Matrix::translate(tx, 0.0),
+            ("translate", &[tx, ty]) => Matrix::translate(tx, ty),
+            ("scale", &[s]) => Matrix::scale(s, s),
+            ("scale", &[sx, sy]) => Matrix::scale(sx, sy),
+            ("rotate", &[angle]) => Matrix::rotate(angle),
+            ("rotate", &[angle, cx, cy]) => Matrix::translate(cx, cy)
+                .multiply(&Matrix::rotate(angle))
+                .multiply(&Matrix::translate(-cx, -cy)),
+            ("skewX", &[angle]) => Matrix::new(1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0),
+            ("skewY", &[angle]) => Matrix::new(1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0),
+            _ => return None,
+        };
+        matrix = matrix.multiply(&transform);
+        rest = after_args.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+    }
+
+    Some(matrix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_compose() {
+        let matrix = parse_transform("translate(10, 20) scale(2)").unwrap();
+        assert_eq!(matrix.components(), [2.0, 0.0, 0.0, 2.0, 10.0, 20.0]);
+
+        assert!(parse_transform("rotate(90) rotate(-90)").unwrap().to_transform_string().is_empty());
+        assert!(parse_transform("translate(1").is_none());
+        assert!(parse_transform("shear(1)").is_none());
+    }
+
+    #[test]
+    fn test_shortest_serialization() {
+        let serialize = |value: &str| parse_transform(value).unwrap().to_transform_string();
+
+        assert_eq!(serialize("translate(0 0) scale(1)"), "");
+        assert_eq!(serialize("translate(5) translate(5 0)"), "translate(10)");
+        assert_eq!(serialize("scale(2 3)"), "scale(2 3)");
+        assert_eq!(serialize("rotate(30) rotate(15)"), "rotate(45)");
+        assert_eq!(serialize("translate(10) scale(2)"), "matrix(2 0 0 2 10 0)");
+    }
+}
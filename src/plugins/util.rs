@@ -0,0 +1,338 @@
+#[cfg(test)]
+use crate::plugins::traits::{SVGPlugin, TreePlugin};
+#[cfg(test)]
+use crate::processor::SVGProcessor;
+use crate::tree::Node;
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashSet;
+
+const XLINK_NAMESPACE: &str = "http://www.w3.org/1999/xlink";
+
+/// Collect the attributes of an element as owned, unescaped key/value pairs.
+///
+/// Attribute order is preserved. Malformed attributes are skipped.
+pub(crate) fn owned_attributes(element: &BytesStart) -> Vec<(String, String)> {
+    element
+        .attributes()
+        .flatten()
+        .map(|attr| {
+            let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+            let value = match attr.unescape_value() {
+                Ok(value) => value.into_owned(),
+                Err(_) => String::from_utf8_lossy(&attr.value).into_owned(),
+            };
+            (key, value)
+        })
+        .collect()
+}
+
+/// Replace all attributes of an element with the given key/value pairs.
+pub(crate) fn set_attributes(element: &mut BytesStart, attrs: &[(String, String)]) {
+    element.clear_attributes();
+    for (key, value) in attrs {
+        element.push_attribute((key.as_str(), value.as_str()));
+    }
+}
+
+/// Find all ids referenced through `url(#id)` in an attribute value.
+pub(crate) fn url_references(value: &str) -> Vec<&str> {
+    url_spans(value).into_iter().map(|(_, _, id)| id).collect()
+}
+
+/// Rewrite the ids referenced through `url(#id)` in an attribute value.
+///
+/// `rewrite` returns the new id for a reference, or `None` to leave it
+/// untouched. Returns `None` when nothing was rewritten.
+pub(crate) fn rewrite_url_references<F>(value: &str, mut rewrite: F) -> Option<String>
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let mut result = String::with_capacity(value.len());
+    let mut last = 0;
+    let mut changed = false;
+
+    for (start, end, id) in url_spans(value) {
+        if let Some(new_id) = rewrite(id) {
+            result.push_str(&value[last..start]);
+            result.push_str(&format!("url(#{})", new_id));
+            last = end;
+            changed = true;
+        }
+    }
+
+    if !changed {
+        return None;
+    }
+    result.push_str(&value[last..]);
+    Some(result)
+}
+
+/// Locate `url(#id)` references as `(start, end, id)`, where `start..end`
+/// covers the whole `url(...)` expression.
+fn url_spans(value: &str) -> Vec<(usize, usize, &str)> {
+    let mut spans = Vec::new();
+    let mut offset = 0;
+
+    while let Some(found) = value[offset..].find("url(") {
+        let start = offset + found;
+        let inner_start = start + 4;
+        let Some(close) = value[inner_start..].find(')') else { break };
+        let end = inner_start + close + 1;
+
+        let inner = value[inner_start..end - 1]
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'');
+        if let Some(id) = inner.strip_prefix('#') {
+            spans.push((start, end, id));
+        }
+        offset = end;
+    }
+
+    spans
+}
+
+/// Ids referenced anywhere among `nodes` and their descendants, through
+/// links under any XLink prefix and `url(#id)` in attribute values or
+/// `<style>` content.
+pub(crate) fn referenced_ids(nodes: &[Node]) -> HashSet<String> {
+    fn collect(nodes: &[Node], in_style: bool, xlink: &XLinkPrefixes, ids: &mut HashSet<String>) {
+        for node in nodes {
+            match node {
+                Node::Element(element) => {
+                    for (key, value) in &element.attrs {
+                        if xlink.is_link(key) {
+                            if let Some(id) = value.strip_prefix('#') {
+                                ids.insert(id.to_string());
+                            }
+                        } else {
+                            ids.extend(url_references(value).into_iter().map(str::to_string));
+                        }
+                    }
+                    collect(&element.children, element.name == "style", xlink, ids);
+                }
+                Node::Text(content) | Node::CData(content) if in_style => {
+                    ids.extend(url_references(content).into_iter().map(str::to_string));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut ids = HashSet::new();
+    collect(nodes, false, &XLinkPrefixes::from_nodes(nodes), &mut ids);
+    ids
+}
+
+/// Split a `style` attribute into trimmed `(property, value)` declarations.
+///
+/// Empty and malformed declarations are skipped.
+pub(crate) fn style_declarations(style: &str) -> impl Iterator<Item = (&str, &str)> {
+    style.split(';').filter_map(|declaration| {
+        let (property, value) = declaration.split_once(':')?;
+        let property = property.trim();
+        (!property.is_empty()).then(|| (property, value.trim()))
+    })
+}
+
+/// Whether a document has stylesheet rules, from a `<style>` element or an
+/// `xml-stylesheet` processing instruction.
+pub(crate) fn has_stylesheet(events: &[Event]) -> bool {
+    events.iter().any(|event| match event {
+        Event::Start(element) | Event::Empty(element) => element.local_name().as_ref() == b"style",
+        Event::PI(pi) => pi.target() == b"xml-stylesheet",
+        _ => false,
+    })
+}
+
+/// Whether an element is a `<set>` or `<animate>` of `display`, `visibility`
+/// or `opacity`, which can make its hidden parent show up later.
+pub(crate) fn animates_visibility(name: &str, attrs: &[(String, String)]) -> bool {
+    matches!(name, "set" | "animate")
+        && attrs.iter().any(|(key, value)| {
+            key == "attributeName" && matches!(value.trim(), "display" | "visibility" | "opacity")
+        })
+}
+
+/// Prefixes bound to the XLink namespace, for streaming plugins that must
+/// recognize links under any prefix rather than just `xlink:`.
+///
+/// Bindings are collected as elements go by, without scoping, so a prefix
+/// stays bound once any element has declared it. That errs on the side of
+/// treating an attribute as a link.
+#[derive(Debug, Default)]
+pub(crate) struct XLinkPrefixes {
+    prefixes: HashSet<String>,
+}
+
+impl XLinkPrefixes {
+    pub(crate) fn clear(&mut self) {
+        self.prefixes.clear();
+    }
+
+    /// Record the XLink bindings an element declares. Call this before
+    /// looking at its attributes, which may already use them.
+    pub(crate) fn declare(&mut self, attrs: &[(String, String)]) {
+        for (key, value) in attrs {
+            if let Some(prefix) = key.strip_prefix("xmlns:") {
+                if value == XLINK_NAMESPACE {
+                    self.prefixes.insert(prefix.to_string());
+                }
+            }
+        }
+    }
+
+    /// The XLink bindings declared anywhere among `events`, for plugins that
+    /// look at the whole document before processing it.
+    pub(crate) fn from_events(events: &[Event]) -> Self {
+        let mut prefixes = Self::default();
+        for event in events {
+            if let Event::Start(element) | Event::Empty(element) = event {
+                prefixes.declare(&owned_attributes(element));
+            }
+        }
+        prefixes
+    }
+
+    /// The XLink bindings declared anywhere among `nodes` and their
+    /// descendants.
+    pub(crate) fn from_nodes(nodes: &[Node]) -> Self {
+        fn collect(nodes: &[Node], prefixes: &mut XLinkPrefixes) {
+            for node in nodes {
+                if let Node::Element(element) = node {
+                    prefixes.declare(&element.attrs);
+                    collect(&element.children, prefixes);
+                }
+            }
+        }
+
+        let mut prefixes = Self::default();
+        collect(nodes, &mut prefixes);
+        prefixes
+    }
+
+    /// Whether the attribute is a link: `href`, `xlink:href`, or `href`
+    /// under any other prefix bound to the XLink namespace.
+    pub(crate) fn is_link(&self, key: &str) -> bool {
+        match key.split_once(':') {
+            None => key == "href",
+            Some((prefix, local)) => local == "href" && (prefix == "xlink" || self.prefixes.contains(prefix)),
+        }
+    }
+}
+
+/// Number of bytes an event takes up when written.
+pub(crate) fn event_len(event: &Event) -> usize {
+    match event {
+        Event::Start(start) => start.len() + 2, // <...>
+        Event::Empty(start) => start.len() + 3, // <.../>
+        Event::End(end) => end.len() + 3, // </...>
+        Event::Text(text) => text.len(),
+        Event::CData(cdata) => cdata.len() + 12, // <![CDATA[...]]>
+        Event::Comment(comment) => comment.len() + 7, // <!--...-->
+        Event::Decl(decl) => decl.len() + 4, // <?...?>
+        Event::PI(pi) => pi.len() + 4, // <?...?>
+        Event::DocType(doctype) => doctype.len() + 11, // <!DOCTYPE ...>
+        Event::Eof => 0,
+    }
+}
+
+/// Run `svg` through a processor with just `plugin`, for plugin tests.
+#[cfg(test)]
+pub(crate) fn optimize_with<P: SVGPlugin + 'static>(plugin: P, svg: &str) -> std::io::Result<String> {
+    let mut processor = SVGProcessor::new(1024);
+    processor.add_plugin(plugin);
+    Ok(processor.optimize_str(svg)?)
+}
+
+/// Run `svg` through a processor with just the tree plugin `plugin`, for
+/// plugin tests.
+#[cfg(test)]
+pub(crate) fn optimize_tree_with<P: TreePlugin + 'static>(plugin: P, svg: &str) -> std::io::Result<String> {
+    let mut processor = SVGProcessor::new(1024);
+    processor.add_tree_plugin(plugin);
+    Ok(processor.optimize_str(svg)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_references() {
+        assert_eq!(url_references("url(#a)"), vec!["a"]);
+        assert_eq!(url_references("url( '#a' ) url(\"#b\")"), vec!["a", "b"]);
+        assert!(url_references("url(other.svg#a)").is_empty());
+        assert!(url_references("red").is_empty());
+    }
+
+    #[test]
+    fn test_xlink_prefixes() {
+        let mut prefixes = XLinkPrefixes::default();
+        assert!(prefixes.is_link("href"));
+        assert!(prefixes.is_link("xlink:href"));
+        assert!(!prefixes.is_link("x:href"));
+
+        prefixes.declare(&[("xmlns:x".to_string(), XLINK_NAMESPACE.to_string())]);
+        assert!(prefixes.is_link("x:href"));
+        assert!(!prefixes.is_link("x:title"));
+        assert!(!prefixes.is_link("y:href"));
+    }
+
+    #[test]
+    fn test_referenced_ids() -> Result<(), crate::error::SVGError> {
+        let svg = r##"<svg xmlns:x="http://www.w3.org/1999/xlink"><use x:href="#a"/><use y:href="#b"/><rect fill="url(#c)"/><style>.d{fill:url(#d)}</style></svg>"##;
+        let document = crate::tree::Document::parse(svg.as_bytes())?;
+
+        let mut ids: Vec<_> = referenced_ids(&document.children).into_iter().collect();
+        ids.sort();
+        assert_eq!(ids, ["a", "c", "d"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_rewrite_url_references() {
+        let rewrite = |id: &str| (id == "a").then(|| "b".to_string());
+
+        assert_eq!(
+            rewrite_url_references("url('#a') url(#c)", rewrite),
+            Some("url(#b) url(#c)".to_string())
+        );
+        assert_eq!(rewrite_url_references("url(#c)", rewrite), None);
+        assert_eq!(rewrite_url_references("url(other.svg#a)", rewrite), None);
+    }
+
+    #[test]
+    fn test_style_declarations() {
+        let declarations: Vec<_> = style_declarations(" fill: red ;;display:none; bogus;").collect();
+        assert_eq!(declarations, vec![("fill", "red"), ("display", "none")]);
+    }
+
+    #[test]
+    fn test_event_len() {
+        use quick_xml::reader::Reader;
+
+        let svg = r#"<!DOCTYPE svg><svg a="1"><!-- c --><![CDATA[x]]>text<?pi?><g/></svg>"#;
+        let mut reader = Reader::from_str(svg);
+        let mut total = 0;
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Eof => break,
+                event => total += event_len(&event),
+            }
+        }
+        assert_eq!(total, svg.len());
+    }
+
+    #[test]
+    fn test_attribute_round_trip() {
+        let mut element = BytesStart::new("path");
+        element.push_attribute(("title", "a & b"));
+        element.push_attribute(("fill", "red"));
+
+        let attrs = owned_attributes(&element);
+        assert_eq!(attrs[0], ("title".to_string(), "a & b".to_string()));
+
+        set_attributes(&mut element, &attrs);
+        assert_eq!(owned_attributes(&element), attrs);
+    }
+}
@@ -0,0 +1,151 @@
+//! Indented output for `--pretty`.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::writer::Writer;
+use std::io::Write;
+
+use crate::error::SVGError;
+
+/// How to indent pretty-printed output: `width` copies of `character` per
+/// nesting level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Indent {
+    pub character: u8,
+    pub width: usize,
+}
+
+impl Indent {
+    pub fn spaces(width: usize) -> Self {
+        Self { character: b' ', width }
+    }
+
+    pub fn tabs(width: usize) -> Self {
+        Self { character: b'\t', width }
+    }
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Self::spaces(2)
+    }
+}
+
+/// Writes events as they are, or indented when an [`Indent`] is given.
+///
+/// When indenting, whitespace-only text between elements is dropped in
+/// favour of the indentation. Elements whose whitespace is significant,
+/// `<text>` and `<pre>` and anything with `xml:space="preserve"`, are
+/// written without indentation inside, since a line break there would show
+/// up as a space.
+pub(crate) struct OutputWriter<W: Write> {
+    writer: Writer<W>,
+    indented: bool,
+    /// Nesting depth inside an element written without indentation.
+    verbatim_depth: usize,
+    /// Whether the last event written was text, after which the writer
+    /// doesn't break the line.
+    after_text: bool,
+}
+
+impl<W: Write> OutputWriter<W> {
+    pub fn new(output: W, indent: Option<Indent>) -> Self {
+        let writer = match indent {
+            Some(indent) => Writer::new_with_indent(output, indent.character, indent.width),
+            None => Writer::new(output),
+        };
+        Self {
+            writer,
+            indented: indent.is_some(),
+            verbatim_depth: 0,
+            after_text: false,
+        }
+    }
+
+    pub fn write_event(&mut self, event: Event) -> Result<(), SVGError> {
+        if !self.indented {
+            return Ok(self.writer.write_event(event)?);
+        }
+
+        if self.verbatim_depth > 0 {
+            match &event {
+                Event::Start(_) => self.verbatim_depth += 1,
+                Event::End(_) => self.verbatim_depth -= 1,
+                _ => {}
+            }
+            // A writer without indentation over the same output
+            return Ok(Writer::new(self.writer.get_mut()).write_event(event)?);
+        }
+
+        match &event {
+            Event::Text(text) if text.iter().all(u8::is_ascii_whitespace) => return Ok(()),
+            Event::Start(start) if is_verbatim(start) => {
+                if !self.after_text {
+                    self.writer.write_indent()?;
+                }
+                self.verbatim_depth = 1;
+                self.after_text = false;
+                return Ok(Writer::new(self.writer.get_mut()).write_event(event)?);
+            }
+            _ => {}
+        }
+        self.after_text = matches!(event, Event::Text(_) | Event::CData(_));
+        Ok(self.writer.write_event(event)?)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+}
+
+/// Whether whitespace inside `start` is significant.
+fn is_verbatim(start: &BytesStart) -> bool {
+    matches!(start.local_name().as_ref(), b"text" | b"pre")
+        || start
+            .try_get_attribute("xml:space")
+            .ok()
+            .flatten()
+            .is_some_and(|attr| attr.value.as_ref() == b"preserve")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::events::{BytesEnd, BytesText};
+    use std::io;
+
+    fn write(events: Vec<Event>, indent: Option<Indent>) -> io::Result<String> {
+        let mut writer = OutputWriter::new(Vec::new(), indent);
+        for event in events {
+            writer.write_event(event)?;
+        }
+        Ok(String::from_utf8(writer.into_inner()).unwrap())
+    }
+
+    #[test]
+    fn test_verbatim_elements_are_not_indented() -> io::Result<()> {
+        let events = vec![
+            Event::Start(BytesStart::new("svg")),
+            Event::Text(BytesText::new("\n  ")),
+            Event::Start(BytesStart::new("text")),
+            Event::Start(BytesStart::new("tspan")),
+            Event::Text(BytesText::new("a")),
+            Event::End(BytesEnd::new("tspan")),
+            Event::Start(BytesStart::new("tspan")),
+            Event::Text(BytesText::new("b")),
+            Event::End(BytesEnd::new("tspan")),
+            Event::End(BytesEnd::new("text")),
+            Event::Empty(BytesStart::new("rect")),
+            Event::End(BytesEnd::new("svg")),
+        ];
+
+        assert_eq!(
+            write(events.clone(), Some(Indent::tabs(1)))?,
+            "<svg>\n\t<text><tspan>a</tspan><tspan>b</tspan></text>\n\t<rect/>\n</svg>"
+        );
+        assert_eq!(
+            write(events, None)?,
+            "<svg>\n  <text><tspan>a</tspan><tspan>b</tspan></text><rect/></svg>"
+        );
+        Ok(())
+    }
+}
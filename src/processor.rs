@@ -1,25 +1,68 @@
-use quick_xml::events::Event;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use quick_xml::events::{BytesEnd, BytesStart, Event};
 use quick_xml::reader::Reader;
-use quick_xml::writer::Writer;
-use std::fs::File;
-use std::io::{self, BufReader, BufWriter, Write};
-use std::path::Path;
-use std::time::Instant;
-
-use crate::cli::PluginConfig;
-use crate::plugins::{
-    PathOptimizerPlugin,
-    // DeduplicateGradientsPlugin,
-    // RemoveIDPlugin,
-    // RemoveDataAttributesPlugin,
-    SVGPlugin,
-};
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::batch::{BatchProcessor, BatchResult};
+use crate::cli::{is_stdio, Engine, PluginConfig, SvgFormat};
+use crate::diagnostics::Diagnostic;
+use crate::error::SVGError;
+use crate::plugins::{ElementAction, SVGPlugin, TreePlugin};
+use crate::pretty::{Indent, OutputWriter};
+use crate::registry::PluginRegistry;
+use crate::tree::{Document, Node};
+use crate::watch::{clock_time, FileWatcher, POLL_INTERVAL};
 
 pub struct SVGProcessor {
     chunk_size: usize,
     plugins: Vec<Box<dyn SVGPlugin>>,
+    /// Plugins run on the document tree after the streaming plugins.
+    tree_plugins: Vec<Box<dyn TreePlugin>>,
+    engine: Engine,
     start_time: Option<Instant>,
     processing_time: Option<f64>,
+    bytes_processed: u64,
+    /// Size of the input and output as stored, so compressed for SVGZ.
+    bytes_read: u64,
+    bytes_written: u64,
+    /// Size of the optimized document before any compression.
+    bytes_optimized: u64,
+    compressed_size: Option<usize>,
+    /// Input and output formats; `None` detects them from the file extension.
+    input_format: Option<SvgFormat>,
+    output_format: Option<SvgFormat>,
+    /// Run the plugins but discard the output instead of writing files.
+    dry_run: bool,
+    /// Path of the file being processed, for plugins that depend on it.
+    source: Option<PathBuf>,
+    /// Indentation of pretty-printed output; `None` writes it as is.
+    indent: Option<Indent>,
+    /// Nesting depth inside an element that a plugin removed; while
+    /// non-zero every event is dropped.
+    removed_depth: usize,
+    /// Index of the plugin that removed the current element. Plugins before
+    /// it saw the start tag and still receive the matching end tag.
+    removed_by: usize,
+    /// Number of currently open elements, including unwrapped ones.
+    depth: usize,
+    /// Open elements whose tags a plugin unwrapped, as `(depth, plugin index)`.
+    unwrapped: Vec<(usize, usize)>,
+    /// Open elements a plugin replaced with one of another name, as
+    /// `(depth, new name)`, so their end tags can be renamed to match.
+    renamed: Vec<(usize, String)>,
+    /// Elements and attributes read and dropped in the last run.
+    elements_seen: usize,
+    elements_removed: usize,
+    attributes_seen: usize,
+    attributes_removed: usize,
 }
 
 impl SVGProcessor {
@@ -27,57 +70,344 @@ impl SVGProcessor {
         Self {
             chunk_size,
             plugins: Vec::new(),
+            tree_plugins: Vec::new(),
+            engine: Engine::Streaming,
             start_time: None,
             processing_time: None,
+            bytes_processed: 0,
+            bytes_read: 0,
+            bytes_written: 0,
+            bytes_optimized: 0,
+            compressed_size: None,
+            input_format: None,
+            output_format: None,
+            dry_run: false,
+            source: None,
+            indent: None,
+            removed_depth: 0,
+            removed_by: 0,
+            depth: 0,
+            unwrapped: Vec::new(),
+            renamed: Vec::new(),
+            elements_seen: 0,
+            elements_removed: 0,
+            attributes_seen: 0,
+            attributes_removed: 0,
         }
     }
 
+    /// Start configuring a processor fluently.
+    pub fn builder() -> SVGProcessorBuilder {
+        SVGProcessorBuilder::new()
+    }
+
     pub fn add_plugin<P: SVGPlugin + 'static>(&mut self, plugin: P) {
         self.plugins.push(Box::new(plugin));
     }
 
+    /// Add a plugin that works on the document tree. Tree plugins need the
+    /// tree engine, so this switches to it.
+    pub fn add_tree_plugin<P: TreePlugin + 'static>(&mut self, plugin: P) {
+        self.add_boxed_tree_plugin(Box::new(plugin));
+    }
+
+    /// Like `add_plugin`, for a plugin that is already boxed.
+    pub fn add_boxed_plugin(&mut self, plugin: Box<dyn SVGPlugin>) {
+        self.plugins.push(plugin);
+    }
+
+    /// Like `add_tree_plugin`, for a plugin that is already boxed.
+    pub fn add_boxed_tree_plugin(&mut self, plugin: Box<dyn TreePlugin>) {
+        self.tree_plugins.push(plugin);
+        self.engine = Engine::Tree;
+    }
+
+    /// The engine documents are processed with.
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    /// Choose how plugins see the document.
+    pub fn set_engine(&mut self, engine: Engine) {
+        self.engine = engine;
+    }
+
+    /// Force the format of input files instead of detecting it from the extension.
+    pub fn set_input_format(&mut self, format: Option<SvgFormat>) {
+        self.input_format = format;
+    }
+
+    /// Force the format of output files instead of detecting it from the extension.
+    pub fn set_output_format(&mut self, format: Option<SvgFormat>) {
+        self.output_format = format;
+    }
+
+    /// In a dry run the file methods read their input and run every plugin,
+    /// so statistics and sizes are collected as usual, but no file is
+    /// created or changed.
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        self.dry_run = dry_run;
+    }
+
+    /// Pretty-print the output with `indent`, or write it without added
+    /// whitespace for `None`.
+    pub fn set_indent(&mut self, indent: Option<Indent>) {
+        self.indent = indent;
+    }
+
+    /// Format used for `path`: the forced input format, or one detected from the extension.
+    pub fn input_format_for(&self, path: &Path) -> SvgFormat {
+        self.input_format.unwrap_or_else(|| SvgFormat::from_path(path))
+    }
+
+    /// Format used for `path`: the forced output format, or one detected from the extension.
+    pub fn output_format_for(&self, path: &Path) -> SvgFormat {
+        self.output_format.unwrap_or_else(|| SvgFormat::from_path(path))
+    }
+
     pub fn process_file<P: AsRef<Path>>(
         &mut self,
         input_path: P,
         output_path: P,
-    ) -> io::Result<()> {
+    ) -> Result<(), SVGError> {
+        let input_format = self.input_format_for(input_path.as_ref());
+        let output_format = self.output_format_for(output_path.as_ref());
+
+        let input_file = File::open(input_path.as_ref())?;
+        let output_file: Box<dyn Write> = if self.dry_run {
+            Box::new(io::sink())
+        } else {
+            Box::new(File::create(output_path)?)
+        };
+
+        self.source = Some(input_path.as_ref().to_path_buf());
+        let result = self.process_with_formats(input_file, input_format, output_file, output_format);
+        self.source = None;
+        result
+    }
+
+    /// Optimize a file with the tree engine, whichever engine is configured.
+    pub fn process_file_tree<P: AsRef<Path>>(
+        &mut self,
+        input_path: P,
+        output_path: P,
+    ) -> Result<(), SVGError> {
+        let engine = std::mem::replace(&mut self.engine, Engine::Tree);
+        let result = self.process_file(input_path, output_path);
+        self.engine = engine;
+        result
+    }
+
+    /// Optimize a file in place.
+    ///
+    /// The result is written to a temporary file in the same directory, which
+    /// replaces the original only after processing succeeded. On error the
+    /// original file is left untouched.
+    pub fn process_file_in_place<P: AsRef<Path>>(&mut self, path: P) -> Result<(), SVGError> {
+        let path = path.as_ref();
+        if self.dry_run {
+            return self.process_file(path, path);
+        }
+        let input_format = self.input_format_for(path);
+        let output_format = self.output_format_for(path);
+
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let permissions = fs::metadata(path)?.permissions();
+
+        let input_file = File::open(path)?;
+        let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+        self.source = Some(path.to_path_buf());
+        let result = self.process_with_formats(input_file, input_format, temp.as_file_mut(), output_format);
+        self.source = None;
+        result?;
+
+        temp.as_file().set_permissions(permissions)?;
+        temp.persist(path).map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    /// Like `process`, but decompresses the input and compresses the output
+    /// when they are SVGZ.
+    ///
+    /// Input starting with the gzip magic bytes is decompressed even when
+    /// `input_format` is plain SVG, so misnamed files still work.
+    pub fn process_with_formats<R: Read, W: Write>(
+        &mut self,
+        reader: R,
+        input_format: SvgFormat,
+        writer: W,
+        output_format: SvgFormat,
+    ) -> Result<(), SVGError> {
+        let mut reader = CountingReader { inner: reader, count: 0 };
+        let mut buffered = BufReader::with_capacity(self.chunk_size.max(2), &mut reader);
+        let is_gzip = buffered.fill_buf()?.starts_with(&GZIP_MAGIC);
+        let input: Box<dyn Read + '_> = match input_format {
+            SvgFormat::Svg if !is_gzip => Box::new(buffered),
+            _ => Box::new(GzDecoder::new(buffered)),
+        };
+
+        self.compressed_size = None;
+        match output_format {
+            SvgFormat::Svg => self.process(input, writer)?,
+            SvgFormat::Svgz => {
+                let counter = CountingWriter { inner: writer, count: 0 };
+                let mut encoder = GzEncoder::new(counter, Compression::best());
+                self.process(input, &mut encoder)?;
+
+                let mut counter = encoder.finish()?;
+                counter.flush()?;
+                self.compressed_size = Some(counter.count);
+                self.bytes_written = counter.count as u64;
+            }
+        }
+        self.bytes_read = reader.count as u64;
+        Ok(())
+    }
+
+    /// Optimize an SVG read from any reader, writing the result to any writer.
+    ///
+    /// Both ends are buffered using the processor's chunk size, so unbuffered
+    /// sources such as files, sockets or stdin can be passed directly.
+    pub fn process<R: Read, W: Write>(&mut self, reader: R, writer: W) -> Result<(), SVGError> {
+        let buf_reader = BufReader::with_capacity(self.chunk_size, reader);
+        let buf_writer = BufWriter::with_capacity(self.chunk_size, writer);
+
+        self.run(buf_reader, buf_writer)
+    }
+
+    /// Optimize an SVG document held in memory and return the result.
+    ///
+    /// This runs the same event loop as `process_file`, reading from the
+    /// string's bytes and writing to a buffer, so nothing touches the
+    /// filesystem. The processor can be reused for any number of documents.
+    pub fn optimize_str(&mut self, input: &str) -> Result<String, SVGError> {
+        let mut output = Vec::with_capacity(input.len());
+        self.run(input.as_bytes(), &mut output)?;
+
+        String::from_utf8(output).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e).into())
+    }
+
+    /// Process a document with the configured engine.
+    fn run<R: BufRead, W: Write>(&mut self, input: R, output: W) -> Result<(), SVGError> {
+        let mut output = CountingWriter { inner: output, count: 0 };
+        match self.engine {
+            Engine::Streaming => self.process_stream(input, &mut output, self.indent)?,
+            Engine::Tree => self.process_tree(input, &mut output)?,
+        }
+        self.bytes_read = self.bytes_processed;
+        self.bytes_written = output.count as u64;
+        self.bytes_optimized = output.count as u64;
+        Ok(())
+    }
+
+    /// Run the streaming plugins, then parse their output into a tree and
+    /// run the tree plugins over it.
+    fn process_tree<R: BufRead, W: Write>(&mut self, input: R, output: W) -> Result<(), SVGError> {
+        let mut streamed = Vec::new();
+        self.process_stream(input, &mut streamed, None)?;
+
+        let tree_start = Instant::now();
+        let mut document = Document::parse(&streamed)?;
+        let (elements, attributes) = count_nodes(&document.children);
+        for plugin in &mut self.tree_plugins {
+            plugin.set_source(self.source.as_deref());
+            plugin
+                .init()
+                .and_then(|_| plugin.process_tree(&mut document))
+                .map_err(|e| SVGError::plugin(plugin.name(), e))?;
+        }
+        // Tree plugins only leave the result, so count what they dropped
+        let (kept_elements, kept_attributes) = count_nodes(&document.children);
+        self.elements_removed += elements.saturating_sub(kept_elements);
+        self.attributes_removed += attributes.saturating_sub(kept_attributes);
+        document.write_indented(output, self.indent)?;
+
+        let tree_duration = tree_start.elapsed().as_secs_f64();
+        self.processing_time = self.processing_time.map(|time| time + tree_duration);
+        Ok(())
+    }
+
+    /// Run all plugins over the SVG read from `input`, writing the result to `output`.
+    fn process_stream<R: BufRead, W: Write>(
+        &mut self,
+        input: R,
+        output: W,
+        indent: Option<Indent>,
+    ) -> Result<(), SVGError> {
         self.start_time = Some(Instant::now());
 
         // Initialize all plugins
         for plugin in &mut self.plugins {
-            plugin.init()?;
+            plugin.set_source(self.source.as_deref());
+            plugin.init().map_err(|e| SVGError::plugin(plugin.name(), e))?;
         }
 
-        let input_file = File::open(input_path)?;
-        let output_file = File::create(output_path)?;
+        let mut reader = Reader::from_reader(input);
+        let mut writer = OutputWriter::new(output, indent);
 
-        let buf_reader = BufReader::with_capacity(self.chunk_size, input_file);
-        let buf_writer = BufWriter::with_capacity(self.chunk_size, output_file);
-
-        let mut reader = Reader::from_reader(buf_reader);
-        let mut writer = Writer::new(buf_writer);
-
-        let mut xml_buf = Vec::with_capacity(self.chunk_size);
         let mut processed = false;
-        let mut bytes_processed = 0;
         let process_start = Instant::now();
+        self.removed_depth = 0;
+        self.depth = 0;
+        self.unwrapped.clear();
+        self.renamed.clear();
+        self.elements_seen = 0;
+        self.elements_removed = 0;
+        self.attributes_seen = 0;
+        self.attributes_removed = 0;
 
-        loop {
-            match reader.read_event_into(&mut xml_buf) {
-                Ok(Event::Eof) => break,
-                Ok(event) => {
-                    let processed_event = self.process_event(event)?;
+        if self.plugins.iter().any(|plugin| plugin.requires_analysis()) {
+            // Buffer the whole document so plugins can inspect it up front
+            let events = Self::read_events(&mut reader)?;
+            for plugin in &mut self.plugins {
+                plugin.analyze(&events).map_err(|e| SVGError::plugin(plugin.name(), e))?;
+            }
+            self.pre_document()?;
+
+            for event in events {
+                if let Some(processed_event) = self.process_event(event)? {
                     writer.write_event(processed_event)?;
-                    processed = true;
-                    xml_buf.clear();
                 }
-                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+                processed = true;
+            }
+        } else {
+            self.pre_document()?;
+            let mut xml_buf = Vec::with_capacity(self.chunk_size);
+
+            loop {
+                match reader.read_event_into(&mut xml_buf) {
+                    Ok(Event::Eof) => break,
+                    Ok(event) => {
+                        if let Some(processed_event) = self.process_event(event)? {
+                            writer.write_event(processed_event)?;
+                        }
+                        processed = true;
+                        xml_buf.clear();
+                    }
+                    Err(source) => {
+                        return Err(SVGError::Parse {
+                            position: reader.error_position(),
+                            source,
+                        })
+                    }
+                }
+            }
+        }
+
+        // Append anything plugins emit after the document
+        for plugin in &mut self.plugins {
+            let events = plugin.post_document().map_err(|e| SVGError::plugin(plugin.name(), e))?;
+            for event in events {
+                writer.write_event(event)?;
             }
         }
 
         // Finalize all plugins
         for plugin in &mut self.plugins {
-            plugin.finalize()?;
+            plugin.finalize().map_err(|e| SVGError::plugin(plugin.name(), e))?;
         }
 
         // Ensure all data is written
@@ -85,191 +415,2154 @@ impl SVGProcessor {
         inner.flush()?;
 
         if !processed {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "No SVG content was processed",
-            ));
+            return Err(SVGError::EmptyInput);
         }
 
         let process_duration = process_start.elapsed().as_secs_f64();
-        self.processing_time = Some(process_duration);
-
-        // Calculate processing speed
-        let speed_mb_per_sec = (bytes_processed as f64 / 1_048_576.0) / process_duration;
 
         // Store timing information for later retrieval
         self.processing_time = Some(process_duration);
+        self.bytes_processed = reader.buffer_position();
+
+        Ok(())
+    }
 
+    fn pre_document(&mut self) -> Result<(), SVGError> {
+        for plugin in &mut self.plugins {
+            plugin.pre_document().map_err(|e| SVGError::plugin(plugin.name(), e))?;
+        }
         Ok(())
     }
 
-    fn process_event<'a>(&mut self, mut event: Event<'a>) -> io::Result<Event<'a>> {
-        match &mut event {
-            Event::Start(elem) | Event::Empty(elem) => {
-                // Apply all plugins to the element
+    /// Read every event of a document into memory.
+    fn read_events<R: BufRead>(reader: &mut Reader<R>) -> Result<Vec<Event<'static>>, SVGError> {
+        let mut events = Vec::new();
+        let mut xml_buf = Vec::new();
+
+        loop {
+            match reader.read_event_into(&mut xml_buf) {
+                Ok(Event::Eof) => break,
+                Ok(event) => events.push(event.into_owned()),
+                Err(source) => {
+                    return Err(SVGError::Parse {
+                        position: reader.error_position(),
+                        source,
+                    })
+                }
+            }
+            xml_buf.clear();
+        }
+
+        Ok(events)
+    }
+
+    fn process_event<'a>(&mut self, event: Event<'a>) -> Result<Option<Event<'a>>, SVGError> {
+        // Drop everything inside an element that was removed by a plugin
+        if self.removed_depth > 0 {
+            self.process_removed(self.removed_by, &event)?;
+            match event {
+                Event::Start(elem) => {
+                    self.count_removed(&elem);
+                    self.removed_depth += 1;
+                }
+                Event::Empty(elem) => self.count_removed(&elem),
+                Event::End(end) => {
+                    self.removed_depth -= 1;
+                    if self.removed_depth == 0 {
+                        // Keep Start/End balanced for plugins that saw the start tag
+                        self.notify_end(&end, self.removed_by)?;
+                    }
+                }
+                _ => {}
+            }
+            return Ok(None);
+        }
+
+        match event {
+            Event::Start(mut elem) => {
+                let name = elem.name().as_ref().to_vec();
+                match self.apply_element_plugins(&mut elem)? {
+                    Some((index, ElementAction::Unwrap)) => {
+                        self.depth += 1;
+                        self.unwrapped.push((self.depth, index));
+                        Ok(None)
+                    }
+                    Some((index, _)) => {
+                        self.process_removed(index, &Event::Start(elem))?;
+                        self.removed_depth = 1;
+                        self.removed_by = index;
+                        Ok(None)
+                    }
+                    None => {
+                        self.depth += 1;
+                        if elem.name().as_ref() != name {
+                            let renamed = String::from_utf8_lossy(elem.name().as_ref()).into_owned();
+                            self.renamed.push((self.depth, renamed));
+                        }
+                        Ok(Some(Event::Start(elem)))
+                    }
+                }
+            }
+            Event::Empty(mut elem) => {
+                let action = self.apply_element_plugins(&mut elem)?;
+                let removed_by = action.as_ref().map(|(index, _)| *index);
+
+                // Self-closing elements get a synthesized end tag for plugins only
+                let seen_by = removed_by.unwrap_or(self.plugins.len());
+                self.notify_end(&elem.to_end(), seen_by)?;
+
+                match action {
+                    None => Ok(Some(Event::Empty(elem))),
+                    Some((index, ElementAction::Remove)) => {
+                        self.process_removed(index, &Event::Empty(elem))?;
+                        Ok(None)
+                    }
+                    Some(_) => Ok(None),
+                }
+            }
+            Event::End(end) if self.unwrapped.last().map(|&(depth, _)| depth) == Some(self.depth) => {
+                let (_, index) = self.unwrapped.pop().unwrap();
+                self.depth -= 1;
+                self.notify_end(&end, index)?;
+                Ok(None)
+            }
+            Event::End(mut end) => {
+                if self.renamed.last().is_some_and(|&(depth, _)| depth == self.depth) {
+                    let (_, name) = self.renamed.pop().unwrap();
+                    end = BytesEnd::new(name);
+                }
+                self.depth = self.depth.saturating_sub(1);
+                // Every plugin sees the end tag, even after one has dropped it
+                let mut keep = true;
                 for plugin in &mut self.plugins {
-                    plugin.process_element(elem)?;
+                    let kept = plugin.process_end(end.clone());
+                    match kept.map_err(|e| SVGError::plugin(plugin.name(), e))? {
+                        Some(kept) => end = kept,
+                        None => keep = false,
+                    }
+                }
+                Ok(keep.then_some(Event::End(end)))
+            }
+            Event::Text(text) => Ok(self
+                .chain(text, |plugin, text| plugin.process_text(text))?
+                .map(Event::Text)),
+            Event::CData(cdata) => Ok(self
+                .chain(cdata, |plugin, cdata| plugin.process_cdata(cdata))?
+                .map(Event::CData)),
+            Event::Comment(comment) => Ok(self
+                .chain(comment, |plugin, comment| plugin.process_comment(comment))?
+                .map(Event::Comment)),
+            Event::PI(pi) => Ok(self
+                .chain(pi, |plugin, pi| plugin.process_pi(pi))?
+                .map(Event::PI)),
+            Event::DocType(doctype) => Ok(self
+                .chain(doctype, |plugin, doctype| plugin.process_doctype(doctype))?
+                .map(Event::DocType)),
+            event => Ok(Some(event)),
+        }
+    }
+
+    /// Pass a value through every plugin's hook in turn. Each plugin may
+    /// rewrite it or return `None` to drop it, which ends the chain.
+    fn chain<T>(
+        &mut self,
+        mut value: T,
+        mut hook: impl FnMut(&mut dyn SVGPlugin, T) -> io::Result<Option<T>>,
+    ) -> Result<Option<T>, SVGError> {
+        for plugin in &mut self.plugins {
+            let kept = hook(plugin.as_mut(), value);
+            match kept.map_err(|e| SVGError::plugin(plugin.name(), e))? {
+                Some(kept) => value = kept,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(value))
+    }
+
+    /// Pass an end tag that won't be written to the first `count` plugins.
+    fn notify_end(&mut self, end: &BytesEnd, count: usize) -> Result<(), SVGError> {
+        for plugin in &mut self.plugins[..count] {
+            plugin
+                .process_end(end.borrow())
+                .map_err(|e| SVGError::plugin(plugin.name(), e))?;
+        }
+        Ok(())
+    }
+
+    /// Show an event that won't be written to the plugin that removed it.
+    fn process_removed(&mut self, index: usize, event: &Event) -> Result<(), SVGError> {
+        let plugin = &mut self.plugins[index];
+        plugin
+            .process_removed(event)
+            .map_err(|e| SVGError::plugin(plugin.name(), e))
+    }
+
+    /// Apply all plugins to an element, stopping at the first one that removes
+    /// or unwraps it. A replaced start tag is passed on to the next plugins.
+    ///
+    /// Returns the index of that plugin and its action, if any.
+    fn apply_element_plugins(
+        &mut self,
+        elem: &mut BytesStart,
+    ) -> Result<Option<(usize, ElementAction)>, SVGError> {
+        let attributes = count_attributes(elem);
+        self.elements_seen += 1;
+        self.attributes_seen += attributes;
+
+        for (index, plugin) in self.plugins.iter_mut().enumerate() {
+            let action = plugin
+                .process_element(elem)
+                .map_err(|e| SVGError::plugin(plugin.name(), e))?;
+            match action {
+                ElementAction::Keep => {}
+                ElementAction::ReplaceWith(replacement) => *elem = replacement,
+                action => {
+                    self.elements_removed += 1;
+                    self.attributes_removed += attributes;
+                    return Ok(Some((index, action)));
                 }
             }
-            _ => {}
         }
+        self.attributes_removed += attributes.saturating_sub(count_attributes(elem));
+        Ok(None)
+    }
+
+    /// Count an element inside a removed subtree as seen and removed.
+    fn count_removed(&mut self, elem: &BytesStart) {
+        let attributes = count_attributes(elem);
+        self.elements_seen += 1;
+        self.elements_removed += 1;
+        self.attributes_seen += attributes;
+        self.attributes_removed += attributes;
+    }
+
+    /// Collect the statistics of every plugin into owned values.
+    pub fn plugin_statistics(&self) -> PluginStatisticsReport {
+        let owned = |name: &str, stats: Vec<(&str, String)>| {
+            let stats = stats
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect();
+            (name.to_string(), stats)
+        };
 
-        // Return the modified event
-        Ok(event)
+        let streaming = self
+            .plugins
+            .iter()
+            .map(|plugin| owned(plugin.name(), plugin.get_statistics()));
+        let tree = self
+            .tree_plugins
+            .iter()
+            .map(|plugin| owned(plugin.name(), plugin.get_statistics()));
+        streaming.chain(tree).collect()
+    }
+
+    /// Collect the diagnostics of every plugin, in the order the plugins
+    /// run.
+    pub fn diagnostics(&self) -> DiagnosticsReport {
+        let streaming = self
+            .plugins
+            .iter()
+            .flat_map(|plugin| plugin.diagnostics().iter().map(|d| (plugin.name().to_string(), d.clone())));
+        let tree = self
+            .tree_plugins
+            .iter()
+            .flat_map(|plugin| plugin.diagnostics().iter().map(|d| (plugin.name().to_string(), d.clone())));
+        streaming.chain(tree).collect()
     }
 
     pub fn get_statistics(&self) -> ProcessingStatistics {
         ProcessingStatistics {
             processing_time: self.processing_time,
             total_time: self.start_time.map(|t| t.elapsed().as_secs_f64()),
+            throughput_mb_per_sec: self.throughput_mb_per_sec(),
+            compressed_size: self.compressed_size,
+            original_bytes: self.bytes_read,
+            optimized_bytes: self.bytes_written,
+            uncompressed_bytes: self.bytes_optimized,
+            elements_seen: self.elements_seen,
+            elements_removed: self.elements_removed,
+            attributes_seen: self.attributes_seen,
+            attributes_removed: self.attributes_removed,
         }
     }
-}
 
-pub struct ProcessingStatistics {
-    pub processing_time: Option<f64>,
-    pub total_time: Option<f64>,
+    /// Input throughput of the last run, or `None` if it finished too quickly
+    /// to measure.
+    fn throughput_mb_per_sec(&self) -> Option<f64> {
+        let duration = self.processing_time?;
+        if duration < f64::EPSILON {
+            return None;
+        }
+        Some((self.bytes_processed as f64 / 1_048_576.0) / duration)
+    }
 }
 
-pub struct SVGProcessorCLI {
+/// Builds an [`SVGProcessor`]:
+///
+/// ```
+/// use svgo_rs::{PathOptimizerPlugin, PluginConfig, SVGProcessor};
+///
+/// let mut processor = SVGProcessor::builder()
+///     .buffer_size(64 * 1024)
+///     .from_config(&PluginConfig { data_attr_remover: true, ..PluginConfig::default() })
+///     .plugin(PathOptimizerPlugin::new(1))
+///     .build();
+/// let optimized = processor.optimize_str(r#"<svg data-id="1"><path d="M 1.25 2"/></svg>"#)?;
+/// assert_eq!(optimized, r#"<svg><path d="M1.2 2"/></svg>"#);
+/// # Ok::<(), svgo_rs::SVGError>(())
+/// ```
+///
+/// Plugins run in the order they were added, `from_config` adding the
+/// configured ones at that point of the chain.
+pub struct SVGProcessorBuilder {
     processor: SVGProcessor,
-    verbose: bool,
 }
 
-impl SVGProcessorCLI {
-    pub fn new(buffer_size: usize, verbose: bool) -> Self {
+impl SVGProcessorBuilder {
+    /// A builder for a processor without plugins and an 8 KB buffer.
+    pub fn new() -> Self {
         Self {
-            processor: SVGProcessor::new(buffer_size * 1024), // Convert KB to bytes
-            verbose,
+            processor: SVGProcessor::new(8 * 1024),
         }
     }
 
-    pub fn configure(&mut self, config: PluginConfig) -> &mut Self {
-        if let Some(path_config) = config.path_optimizer {
-            if self.verbose {
-                println!(
-                    "Enabling path optimizer with {} decimal places",
-                    path_config.decimal_places
-                );
-            }
-            self.processor
-                .add_plugin(PathOptimizerPlugin::new(path_config.decimal_places));
-        }
+    /// Size in bytes of the read and write buffers.
+    pub fn buffer_size(mut self, size: usize) -> Self {
+        self.processor.chunk_size = size;
+        self
+    }
 
-        if config.gradient_deduplicator {
-            if self.verbose {
-                println!("Enabling gradient deduplicator");
-            }
-            // self.processor.add_plugin(DeduplicateGradientsPlugin::new());
-        }
+    /// Same as [`buffer_size`](Self::buffer_size).
+    pub fn with_buffer_size(self, bytes: usize) -> Self {
+        self.buffer_size(bytes)
+    }
 
-        if config.id_remover.enabled {
-            if self.verbose {
-                println!("Enabling ID remover");
-                if !config.id_remover.preserve.is_empty() {
-                    println!("Preserving IDs: {:?}", config.id_remover.preserve);
-                }
-            }
-            // self.processor.add_plugin(RemoveIDPlugin::new(
-            //     config.id_remover.preserve
-            // ));
-        }
+    /// Add a streaming plugin.
+    pub fn plugin<P: SVGPlugin + 'static>(mut self, plugin: P) -> Self {
+        self.processor.add_plugin(plugin);
+        self
+    }
 
-        if config.data_attr_remover {
-            if self.verbose {
-                println!("Enabling data attribute remover");
-            }
-            // self.processor.add_plugin(RemoveDataAttributesPlugin);
-        }
+    /// Same as [`plugin`](Self::plugin).
+    pub fn with_plugin<P: SVGPlugin + 'static>(self, plugin: P) -> Self {
+        self.plugin(plugin)
+    }
 
+    /// Add a tree plugin, which selects the tree engine.
+    pub fn tree_plugin<P: TreePlugin + 'static>(mut self, plugin: P) -> Self {
+        self.processor.add_tree_plugin(plugin);
         self
     }
 
-    pub fn process<P: AsRef<Path>>(&mut self, input: P, output: P) -> io::Result<()> {
-        if self.verbose {
-            println!(
-                "Processing {} -> {}",
-                input.as_ref().display(),
-                output.as_ref().display()
-            );
-        }
+    /// Add the plugins enabled in `config`, as the command-line tool would,
+    /// and use the engine they need.
+    pub fn from_config(mut self, config: &PluginConfig) -> Self {
+        add_plugins(&mut self.processor, config, false);
+        self
+    }
+
+    /// Pretty-print the output.
+    pub fn indent(mut self, indent: Indent) -> Self {
+        self.processor.set_indent(Some(indent));
+        self
+    }
 
-        let start = Instant::now();
-        let result = self.processor.process_file(input, output);
+    /// Choose how plugins see the document.
+    pub fn engine(mut self, engine: Engine) -> Self {
+        self.processor.set_engine(engine);
+        self
+    }
 
-        if let Err(ref e) = result {
-            if self.verbose {
-                eprintln!("Error during processing: {}", e);
-            }
-        } else if self.verbose {
-            let stats = self.processor.get_statistics();
-            println!("\nProcessing Statistics:");
-            println!("--------------------");
+    pub fn build(self) -> SVGProcessor {
+        self.processor
+    }
+}
 
-            if let Some(processing_time) = stats.processing_time {
-                println!("Processing time: {:.2} seconds", processing_time);
-            }
+impl Default for SVGProcessorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-            if let Some(total_time) = stats.total_time {
-                println!("Total time: {:.2} seconds", total_time);
+/// Shorter name for [`SVGProcessorBuilder`].
+pub type ProcessorBuilder = SVGProcessorBuilder;
+
+/// Statistics of each plugin as `(plugin name, [(statistic, value)])`.
+pub type PluginStatisticsReport = Vec<(String, Vec<(String, String)>)>;
+
+/// Diagnostics of all plugins as `(plugin name, diagnostic)`.
+pub type DiagnosticsReport = Vec<(String, Diagnostic)>;
+
+/// Add the plugins enabled in `config` to `processor`.
+pub(crate) fn add_plugins(processor: &mut SVGProcessor, config: &PluginConfig, verbose: bool) {
+    PluginRegistry::default().configure(processor, config, verbose);
+}
+
+/// Whether a path argument is a glob pattern rather than a literal path.
+pub fn is_glob_pattern(path: &Path) -> bool {
+    path.to_string_lossy().contains(['*', '?', '['])
+}
+
+fn is_svg_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| {
+        ext.eq_ignore_ascii_case("svg") || ext.eq_ignore_ascii_case("svgz")
+    })
+}
+
+/// Pair every SVG file matched by `input` with its destination under `output`.
+///
+/// Without an `output` directory every file is its own target, for in-place
+/// optimization.
+pub(crate) fn batch_files(
+    input: &Path,
+    output: Option<&Path>,
+    recursive: bool,
+) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+    let (base, sources) = if is_glob_pattern(input) {
+        let pattern = input.to_string_lossy();
+        let sources = glob::glob(&pattern)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+            .filter_map(Result::ok)
+            .filter(|path| path.is_file() && is_svg_file(path))
+            .collect();
+
+        // Mirror the layout below the literal part of the pattern
+        let base: PathBuf = input
+            .components()
+            .take_while(|c| !is_glob_pattern(Path::new(c.as_os_str())))
+            .filter(|c| !matches!(c, Component::CurDir))
+            .collect();
+        (base, sources)
+    } else {
+        let mut sources = Vec::new();
+        collect_svg_files(input, recursive, &mut sources)?;
+        (input.to_path_buf(), sources)
+    };
+
+    Ok(sources
+        .into_iter()
+        .map(|source| {
+            let target = match output {
+                Some(output) => output.join(source.strip_prefix(&base).unwrap_or(&source)),
+                None => source.clone(),
+            };
+            (source, target)
+        })
+        .collect())
+}
+
+/// Pair the SVG files in `input_dir` matching `pattern` with their
+/// destination under `output_dir`.
+fn dir_files(
+    input_dir: &Path,
+    output_dir: &Path,
+    pattern: Option<&str>,
+    recursive: bool,
+) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+    if !input_dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} is not a directory", input_dir.display()),
+        ));
+    }
+    let pattern = pattern
+        .map(glob::Pattern::new)
+        .transpose()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let mut sources = Vec::new();
+    collect_svg_files(input_dir, recursive, &mut sources)?;
+
+    Ok(sources
+        .into_iter()
+        .filter_map(|source| {
+            let relative = source.strip_prefix(input_dir).ok()?.to_path_buf();
+            if pattern.as_ref().is_some_and(|pattern| !pattern.matches_path(&relative)) {
+                return None;
             }
+            Some((source, output_dir.join(relative)))
+        })
+        .collect())
+}
 
-            println!("--------------------");
+/// Collect the SVG files in `dir` in sorted order, descending into
+/// subdirectories when `recursive` is set.
+fn collect_svg_files(dir: &Path, recursive: bool, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
 
-            // Loop over all plugins and call the get_statistics method
-            for plugin in &self.processor.plugins {
-                println!("\n{} Statistics:", plugin.name());
-                println!("--------------------");
-                let plugin_stats = plugin.get_statistics();
-                for (name, value) in plugin_stats {
-                    println!("{}: {}", name, value);
-                }
-                println!("--------------------");
+    for path in entries {
+        if path.is_dir() {
+            if recursive {
+                collect_svg_files(&path, recursive, files)?;
             }
+        } else if is_svg_file(&path) {
+            files.push(path);
         }
+    }
+    Ok(())
+}
 
-        result
+/// The first two bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Reader adapter that counts the bytes passing through it.
+struct CountingReader<R> {
+    inner: R,
+    count: usize,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count += read;
+        Ok(read)
     }
+}
 
-    pub fn list_plugins() {
-        println!("Available plugins:");
-        println!("  1. Path Optimizer");
-        println!("     --optimize-paths");
-        println!("     --path-decimals <VALUE>");
-        println!(
-            "     Optimizes path data by reducing decimal places and removing unnecessary spaces"
-        );
+/// Writer adapter that counts the bytes passing through it.
+struct CountingWriter<W> {
+    inner: W,
+    count: usize,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessingStatistics {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processing_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_time: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throughput_mb_per_sec: Option<f64>,
+    /// Size of the written file when the output was gzip-compressed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compressed_size: Option<usize>,
+    /// Size of the input as read, compressed for SVGZ.
+    #[serde(rename = "originalSize")]
+    pub original_bytes: u64,
+    /// Size of the output as written, compressed for SVGZ.
+    #[serde(rename = "optimizedSize")]
+    pub optimized_bytes: u64,
+    /// Size of the optimized SVG before compression; the same as
+    /// `optimized_bytes` for plain SVG output.
+    #[serde(rename = "uncompressedSize")]
+    pub uncompressed_bytes: u64,
+    /// Elements in the input, and how many of them plugins removed or
+    /// unwrapped.
+    pub elements_seen: usize,
+    pub elements_removed: usize,
+    /// Attributes in the input, and how many of them were dropped, with
+    /// their element or on their own.
+    pub attributes_seen: usize,
+    pub attributes_removed: usize,
+}
 
-    #[test]
-    fn test_path_optimization() -> io::Result<()> {
-        let mut processor = SVGProcessor::new(1024);
-        processor.add_plugin(PathOptimizerPlugin::new(2));
+impl ProcessingStatistics {
+    /// Size of the input, or `None` before a document was processed.
+    pub fn bytes_in(&self) -> Option<u64> {
+        self.processing_time.map(|_| self.original_bytes)
+    }
 
-        // Create test input file
-        let test_svg = r#"<?xml version="1.0"?>
-            <svg>
-                <path d="M 100.000 200.000 L 300.000 400.000"/>
-            </svg>"#;
+    /// Size of the output, or `None` before a document was processed.
+    pub fn bytes_out(&self) -> Option<u64> {
+        self.processing_time.map(|_| self.optimized_bytes)
+    }
 
-        let temp_dir = tempfile::tempdir()?;
-        let input_path = temp_dir.path().join("input.svg");
-        let output_path = temp_dir.path().join("output.svg");
+    /// Like `percent_saved`, but `None` before a document was processed or
+    /// when the input was empty.
+    pub fn reduction_pct(&self) -> Option<f64> {
+        self.bytes_in().filter(|&bytes| bytes > 0).map(|_| self.percent_saved())
+    }
 
-        std::fs::write(&input_path, test_svg)?;
+    /// How much smaller the output is than the input, in percent rounded to
+    /// two decimals. Negative when the output grew.
+    pub fn percent_saved(&self) -> f64 {
+        percent_saved(self.original_bytes, self.optimized_bytes)
+    }
 
-        // Process the file
-        processor.process_file(input_path, output_path.clone())?;
+    /// The size change as `12.4 KB → 8.1 KB (-34.6%)`. The percentage is
+    /// positive when the output grew.
+    pub fn size_summary(&self) -> String {
+        size_change(self.original_bytes, self.optimized_bytes)
+    }
+}
 
-        // Read and verify output
-        let output_content = std::fs::read_to_string(output_path)?;
-        assert!(output_content.contains(r#"d="M100 200L300 400""#));
+fn count_attributes(elem: &BytesStart) -> usize {
+    elem.attributes().with_checks(false).count()
+}
 
-        Ok(())
+/// The number of elements and attributes in a tree.
+fn count_nodes(nodes: &[Node]) -> (usize, usize) {
+    nodes.iter().fold((0, 0), |(elements, attributes), node| match node {
+        Node::Element(element) => {
+            let (child_elements, child_attributes) = count_nodes(&element.children);
+            (elements + 1 + child_elements, attributes + element.attrs.len() + child_attributes)
+        }
+        _ => (elements, attributes),
+    })
+}
+
+pub(crate) fn percent_saved(original: u64, optimized: u64) -> f64 {
+    if original == 0 {
+        return 0.0;
+    }
+    let saved = 100.0 * (1.0 - optimized as f64 / original as f64);
+    (saved * 100.0).round() / 100.0
+}
+
+pub(crate) fn size_change(original: u64, optimized: u64) -> String {
+    format!(
+        "{} → {} ({:+.1}%)",
+        format_size(original),
+        format_size(optimized),
+        -percent_saved(original, optimized) + 0.0
+    )
+}
+
+/// The non-zero statistics of each plugin, followed by the plugins that
+/// found nothing.
+fn findings_report(statistics: &PluginStatisticsReport) -> String {
+    let mut report = String::new();
+    let mut idle = Vec::new();
+    for (plugin, plugin_stats) in statistics {
+        let findings: Vec<_> = plugin_stats.iter().filter(|(_, value)| is_finding(value)).collect();
+        if findings.is_empty() {
+            idle.push(plugin.as_str());
+            continue;
+        }
+        report += &format!("\n{}:\n", plugin);
+        for (name, value) in findings {
+            report += &format!("  {}: {}\n", name, value);
+        }
+    }
+    if !idle.is_empty() {
+        report += &format!("\nNothing found by: {}\n", idle.join(", "));
+    }
+    report
+}
+
+/// Whether a plugin statistic reports something, rather than a zero count.
+fn is_finding(value: &str) -> bool {
+    match value.parse::<f64>() {
+        Ok(number) => number != 0.0,
+        Err(_) => !value.is_empty(),
+    }
+}
+
+/// A byte count in B, KB or MB, with one decimal above a kilobyte.
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+/// Statistics of one optimized file, as written by `--stats-json`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReport {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    #[serde(flatten)]
+    pub statistics: ProcessingStatistics,
+    pub percent_saved: f64,
+    #[serde(serialize_with = "serialize_plugin_statistics")]
+    pub plugins: PluginStatisticsReport,
+    #[serde(skip_serializing_if = "Vec::is_empty", serialize_with = "serialize_diagnostics")]
+    pub diagnostics: DiagnosticsReport,
+}
+
+impl FileReport {
+    pub(crate) fn new(input: &Path, output: &Path, processor: &SVGProcessor) -> Self {
+        let statistics = processor.get_statistics();
+        Self {
+            input: input.to_path_buf(),
+            output: output.to_path_buf(),
+            percent_saved: statistics.percent_saved(),
+            statistics,
+            plugins: processor.plugin_statistics(),
+            diagnostics: processor.diagnostics(),
+        }
+    }
+}
+
+/// Diagnostics as `[{ "plugin": ..., "level": ..., "message": ... }]`.
+fn serialize_diagnostics<S: Serializer>(report: &DiagnosticsReport, serializer: S) -> Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct Entry<'a> {
+        plugin: &'a str,
+        #[serde(flatten)]
+        diagnostic: &'a Diagnostic,
+    }
+
+    serializer.collect_seq(report.iter().map(|(plugin, diagnostic)| Entry { plugin, diagnostic }))
+}
+
+/// Print diagnostics to stderr as `input: warning: message (<element>) [Plugin]`.
+fn print_diagnostics(input: &Path, diagnostics: &DiagnosticsReport) {
+    for (plugin, diagnostic) in diagnostics {
+        eprintln!("{}: {} [{}]", input.display(), diagnostic, plugin);
+    }
+}
+
+/// Plugin statistics as `{ "Plugin": { "Statistic": value } }`, with
+/// numeric values written as numbers.
+fn serialize_plugin_statistics<S: Serializer>(
+    report: &PluginStatisticsReport,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut plugins = serializer.serialize_map(Some(report.len()))?;
+    for (plugin, stats) in report {
+        let stats: serde_json::Map<String, serde_json::Value> = stats
+            .iter()
+            .map(|(name, value)| {
+                let value = match value.parse::<u64>() {
+                    Ok(number) => number.into(),
+                    Err(_) => value.clone().into(),
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        plugins.serialize_entry(plugin, &stats)?;
+    }
+    plugins.end()
+}
+
+/// Write `value` as pretty-printed JSON to `path`, or to stdout for `-`.
+fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), SVGError> {
+    let json = serde_json::to_string_pretty(value).map_err(io::Error::other)?;
+    if is_stdio(path) {
+        println!("{}", json);
+    } else {
+        fs::write(path, json + "\n")?;
+    }
+    Ok(())
+}
+
+pub struct SVGProcessorCLI {
+    processor: SVGProcessor,
+    /// Kept so batch runs can build a fresh processor per file.
+    config: PluginConfig,
+    chunk_size: usize,
+    formats: (Option<SvgFormat>, Option<SvgFormat>),
+    indent: Option<Indent>,
+    jobs: usize,
+    verbose: bool,
+    /// Where to write statistics as JSON, `-` being stdout.
+    stats_json: Option<PathBuf>,
+    dry_run: bool,
+    /// Percentage the output must be smaller than the input by.
+    min_savings: Option<f64>,
+}
+
+impl SVGProcessorCLI {
+    pub fn new(buffer_size: usize, verbose: bool) -> Self {
+        let chunk_size = buffer_size * 1024; // Convert KB to bytes
+        Self {
+            processor: SVGProcessor::new(chunk_size),
+            config: PluginConfig::default(),
+            chunk_size,
+            formats: (None, None),
+            indent: None,
+            jobs: 1,
+            verbose,
+            stats_json: None,
+            dry_run: false,
+            min_savings: None,
+        }
+    }
+
+    pub fn configure(&mut self, config: PluginConfig) -> &mut Self {
+        add_plugins(&mut self.processor, &config, self.verbose);
+        self.config = config;
+        self
+    }
+
+    /// Number of files to process concurrently in batch mode.
+    pub fn jobs(&mut self, jobs: usize) -> &mut Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Write the statistics of each file as JSON to `path`, or to stdout if
+    /// it is `-`. Batch runs write an array with one object per file.
+    pub fn stats_json(&mut self, path: Option<PathBuf>) -> &mut Self {
+        self.stats_json = path;
+        self
+    }
+
+    /// Run the optimization without writing any output, and report what
+    /// each plugin would change.
+    pub fn dry_run(&mut self, dry_run: bool) -> &mut Self {
+        self.processor.set_dry_run(dry_run);
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Fail when the output isn't at least `percent` smaller than the input,
+    /// for the whole batch in batch mode.
+    pub fn min_savings_pct(&mut self, percent: Option<f64>) -> &mut Self {
+        self.min_savings = percent;
+        self
+    }
+
+    /// Pretty-print the output with `indent`.
+    pub fn indent(&mut self, indent: Option<Indent>) -> &mut Self {
+        self.processor.set_indent(indent);
+        self.indent = indent;
+        self
+    }
+
+    /// Override the input and output file formats.
+    pub fn formats(&mut self, input: Option<SvgFormat>, output: Option<SvgFormat>) -> &mut Self {
+        self.processor.set_input_format(input);
+        self.processor.set_output_format(output);
+        self.formats = (input, output);
+        self
+    }
+
+    /// Optimize `input` into `output`, reading from stdin when `input` is
+    /// `None` and writing to stdout when `output` is `None`.
+    ///
+    /// Verbose output goes to stderr so it never mixes with SVG on stdout.
+    pub fn process(&mut self, input: Option<PathBuf>, output: Option<PathBuf>) -> Result<(), SVGError> {
+        let (input, output) = (input.as_deref(), output.as_deref());
+
+        if self.verbose {
+            let name = |path: Option<&Path>, stdio: &str| path.map_or(stdio.to_string(), |p| p.display().to_string());
+            eprintln!("Processing {} -> {}", name(input, "stdin"), name(output, "stdout"));
+        }
+        if !self.dry_run && output.is_none() && self.stats_json.as_deref().is_some_and(is_stdio) {
+            return Err(SVGError::InvalidPath(
+                "JSON statistics need a file name (--stats-json FILE) when the SVG is written to stdout".to_string(),
+            ));
+        }
+
+        let result = self.process_paths(input, output);
+        self.print_statistics(&result);
+        result?;
+        // Reports name stdin and stdout `-`, as on the command line
+        let (input, output) = (input.unwrap_or(Path::new("-")), output.unwrap_or(Path::new("-")));
+        self.print_summary(input);
+        self.write_report(input, output)?;
+        self.check_savings(self.processor.get_statistics().percent_saved())
+    }
+
+    /// Optimize `path` in place. Reading from stdin is refused, since there is
+    /// no file to write back to.
+    pub fn process_in_place<P: AsRef<Path>>(&mut self, path: P) -> Result<(), SVGError> {
+        let path = path.as_ref();
+        if is_stdio(path) {
+            return Err(SVGError::InvalidPath("--in-place cannot be used with stdin".to_string()));
+        }
+
+        if self.verbose {
+            eprintln!("Processing {} in place", path.display());
+        }
+
+        let result = self.processor.process_file_in_place(path);
+        self.print_statistics(&result);
+        result?;
+        self.print_summary(path);
+        self.write_report(path, path)?;
+        self.check_savings(self.processor.get_statistics().percent_saved())
+    }
+
+    /// Print the size change, to stderr so it never mixes with SVG on stdout.
+    /// A dry run also lists what each plugin would change.
+    fn print_summary(&self, input: &Path) {
+        let stats = self.processor.get_statistics();
+        eprintln!("{}: {}", input.display(), stats.size_summary());
+        print_diagnostics(input, &self.processor.diagnostics());
+        if self.dry_run {
+            eprint!("{}", findings_report(&self.processor.plugin_statistics()));
+            eprintln!("\nDry run: nothing was written");
+        }
+    }
+
+    /// Fail if `percent_saved` is below the `--min-savings-pct` threshold.
+    fn check_savings(&self, percent_saved: f64) -> Result<(), SVGError> {
+        match self.min_savings {
+            Some(min) if percent_saved < min => Err(io::Error::other(format!(
+                "saved {:.1}%, less than the required {}%",
+                percent_saved, min
+            ))
+            .into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn write_report(&self, input: &Path, output: &Path) -> Result<(), SVGError> {
+        match &self.stats_json {
+            Some(path) => write_json(path, &FileReport::new(input, output, &self.processor)),
+            None => Ok(()),
+        }
+    }
+
+    fn print_statistics(&self, result: &Result<(), SVGError>) {
+        if let Err(e) = result {
+            if self.verbose {
+                eprintln!("Error during processing: {}", e);
+            }
+        } else if self.verbose {
+            let stats = self.processor.get_statistics();
+            eprintln!("\nProcessing Statistics:");
+            eprintln!("--------------------");
+
+            if let Some(processing_time) = stats.processing_time {
+                eprintln!("Processing time: {:.2} seconds", processing_time);
+            }
+
+            if let Some(throughput) = stats.throughput_mb_per_sec {
+                eprintln!("Processing speed: {:.2} MB/s", throughput);
+            }
+
+            if let (Some(bytes_in), Some(bytes_out)) = (stats.bytes_in(), stats.bytes_out()) {
+                eprintln!("Input size: {} bytes", bytes_in);
+                eprintln!("Output size: {} bytes", bytes_out);
+            }
+            if let Some(reduction) = stats.reduction_pct() {
+                eprintln!("Size reduction: {:.2}%", reduction);
+            }
+            eprintln!("Elements: {} ({} removed)", stats.elements_seen, stats.elements_removed);
+            eprintln!("Attributes: {} ({} removed)", stats.attributes_seen, stats.attributes_removed);
+
+            if let Some(compressed_size) = stats.compressed_size {
+                eprintln!("Uncompressed size: {} bytes", stats.uncompressed_bytes);
+                eprintln!("Compressed size: {} bytes", compressed_size);
+            }
+
+            if let Some(total_time) = stats.total_time {
+                eprintln!("Total time: {:.2} seconds", total_time);
+            }
+
+            eprintln!("--------------------");
+
+            // Loop over all plugins and print their statistics
+            for (plugin, plugin_stats) in self.processor.plugin_statistics() {
+                eprintln!("\n{} Statistics:", plugin);
+                eprintln!("--------------------");
+                for (name, value) in plugin_stats {
+                    eprintln!("{}: {}", name, value);
+                }
+                eprintln!("--------------------");
+            }
+        }
+
+    }
+
+    /// Optimize every SVG file matched by `input`, which is either a directory
+    /// or a glob pattern, into the `output` directory.
+    ///
+    /// The directory layout below `input` (or below the literal part of the
+    /// pattern) is mirrored under `output`; without an `output` every file is
+    /// optimized in place. Up to `jobs` files are processed
+    /// concurrently, each with its own set of plugins. Files that fail are
+    /// reported and skipped; an error is returned at the end if any of them
+    /// failed.
+    pub fn process_many(
+        &mut self,
+        input: &Path,
+        output: Option<&Path>,
+        recursive: bool,
+    ) -> Result<(), SVGError> {
+        let files = batch_files(input, output, recursive)?;
+        self.process_files(&files)
+    }
+
+    /// Optimize the files matched by `input` again whenever they change,
+    /// until the process is interrupted.
+    ///
+    /// `input` and `output` are handled like in [`process_many`](Self::process_many),
+    /// except that a single file is written to `output` or in place. After a
+    /// change the run waits until nothing has changed for `debounce`, so an
+    /// editor saving several times in quick succession triggers one run.
+    pub fn watch(
+        &mut self,
+        input: &Path,
+        output: Option<&Path>,
+        recursive: bool,
+        debounce: Duration,
+    ) -> Result<(), SVGError> {
+        if is_stdio(input) || output.is_some_and(is_stdio) {
+            return Err(SVGError::InvalidPath("--watch needs files, not stdin or stdout".to_string()));
+        }
+
+        let mut watcher = FileWatcher::new(input, output, recursive)?;
+        eprintln!("Watching {} for changes, press Ctrl+C to stop", input.display());
+
+        let mut pending: Vec<(PathBuf, PathBuf)> = Vec::new();
+        let mut last_change = Instant::now();
+        loop {
+            thread::sleep(POLL_INTERVAL);
+            let changes = watcher.changes()?;
+            if !changes.is_empty() {
+                for change in changes {
+                    if !pending.contains(&change) {
+                        pending.push(change);
+                    }
+                }
+                last_change = Instant::now();
+                continue;
+            }
+            if pending.is_empty() || last_change.elapsed() < debounce {
+                continue;
+            }
+
+            for (source, target) in pending.drain(..) {
+                self.rerun(&source, &target);
+                // Our own writes aren't changes, whether in place or below the input
+                watcher.record(&target);
+            }
+        }
+    }
+
+    /// Optimize one changed file, printing a timestamped line with the
+    /// savings or the error; failures don't stop the watch.
+    fn rerun(&mut self, source: &Path, target: &Path) {
+        let time = clock_time(SystemTime::now());
+        let result = if source == target {
+            self.processor.process_file_in_place(source)
+        } else {
+            target
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .map_err(SVGError::from)
+                .and_then(|()| self.processor.process_file(source, target))
+        };
+        match result {
+            Ok(()) => eprintln!(
+                "[{}] {}: {}",
+                time,
+                source.display(),
+                self.processor.get_statistics().size_summary()
+            ),
+            Err(e) => eprintln!("[{}] Failed to process {}: {}", time, source.display(), e),
+        }
+    }
+
+    /// Optimize the SVG files in `input_dir` into `output_dir`, recreating
+    /// the directory structure below it.
+    ///
+    /// With a `pattern`, only files whose path relative to `input_dir`
+    /// matches the glob are processed; `*` also matches across directories,
+    /// so `*.svgz` selects compressed files at any depth.
+    pub fn process_dir(
+        &mut self,
+        input_dir: &Path,
+        output_dir: &Path,
+        pattern: Option<&str>,
+        recursive: bool,
+    ) -> Result<(), SVGError> {
+        let files = dir_files(input_dir, output_dir, pattern, recursive)?;
+        self.process_files(&files)
+    }
+
+    /// Optimize each `(source, target)` pair with a [`BatchProcessor`] and
+    /// report the results.
+    fn process_files(&mut self, files: &[(PathBuf, PathBuf)]) -> Result<(), SVGError> {
+        let (input_format, output_format) = self.formats;
+        let mut batch = BatchProcessor::new(self.config.clone(), self.chunk_size);
+        batch
+            .jobs(self.jobs)
+            .formats(input_format, output_format)
+            .indent(self.indent)
+            .dry_run(self.dry_run);
+        let BatchResult { reports, failures, totals } = batch.process(files)?;
+
+        for report in &reports {
+            print_diagnostics(&report.input, &report.diagnostics);
+        }
+        for (source, e) in &failures {
+            eprintln!("Failed to process {}: {}", source.display(), e);
+        }
+        let failed = failures.len();
+
+        let summary = format!(
+            "Processed {} files: {} succeeded, {} failed",
+            files.len(),
+            files.len() - failed,
+            failed
+        );
+        let sizes = format!("Total: {}", totals.size_summary());
+        // Keep stdout valid JSON when the report goes there
+        if self.stats_json.as_deref().is_some_and(is_stdio) {
+            eprintln!("{}\n{}", summary, sizes);
+        } else {
+            println!("{}\n{}", summary, sizes);
+        }
+        if let Some(path) = &self.stats_json {
+            write_json(path, &reports)?;
+        }
+
+        if self.verbose || self.dry_run {
+            totals.print();
+        }
+        if self.dry_run {
+            eprintln!("\nDry run: nothing was written");
+        }
+
+        if failed > 0 {
+            return Err(io::Error::other(format!(
+                "{} of {} files failed",
+                failed,
+                files.len()
+            ))
+            .into());
+        }
+        self.check_savings(totals.percent_saved())
+    }
+
+    fn process_paths(&mut self, input: Option<&Path>, output: Option<&Path>) -> Result<(), SVGError> {
+        if let (Some(input), Some(output)) = (input, output) {
+            return self.processor.process_file(input, output);
+        }
+
+        let processor = &self.processor;
+        let input_format = input.map_or(processor.input_format.unwrap_or(SvgFormat::Svg), |path| {
+            processor.input_format_for(path)
+        });
+        let output_format = output.map_or(processor.output_format.unwrap_or(SvgFormat::Svg), |path| {
+            processor.output_format_for(path)
+        });
+
+        let reader: Box<dyn Read> = match input {
+            Some(input) => Box::new(File::open(input)?),
+            None => Box::new(io::stdin().lock()),
+        };
+        let writer: Box<dyn Write> = match output {
+            _ if self.dry_run => Box::new(io::sink()),
+            Some(output) => Box::new(File::create(output)?),
+            None => Box::new(io::stdout().lock()),
+        };
+
+        self.processor.source = input.map(Path::to_path_buf);
+        let result = self.processor.process_with_formats(reader, input_format, writer, output_format);
+        self.processor.source = None;
+        result
+    }
+
+    /// Run the configured plugins over `input` as a dry run and print what
+    /// they would change. Nothing is written, not even a temporary file.
+    pub fn analyze<P: AsRef<Path>>(&mut self, input: P) -> Result<(), SVGError> {
+        print!("{}", self.analysis_report(input.as_ref())?);
+        Ok(())
+    }
+
+    /// The sizes before and after optimizing `input`, followed by the
+    /// statistics of the plugins that found something to change.
+    fn analysis_report(&mut self, input: &Path) -> Result<String, SVGError> {
+        self.processor.set_dry_run(true);
+        let result = self.processor.process_file(input, input);
+        self.processor.set_dry_run(false);
+        result?;
+
+        let stats = self.processor.get_statistics();
+        let mut report = format!("Analysis of {}\n--------------------\n", input.display());
+        report += &format!("Original size: {}\n", format_size(stats.original_bytes));
+        report += &format!("Optimized size: {}\n", format_size(stats.optimized_bytes));
+        if stats.optimized_bytes <= stats.original_bytes {
+            report += &format!(
+                "Potential savings: {} ({:.1}%)\n",
+                format_size(stats.original_bytes - stats.optimized_bytes),
+                stats.percent_saved()
+            );
+        } else {
+            report += &format!(
+                "Potential savings: none, the file would grow by {}\n",
+                format_size(stats.optimized_bytes - stats.original_bytes)
+            );
+        }
+        report += "--------------------\n";
+        report += &findings_report(&self.processor.plugin_statistics());
+        Ok(report)
+    }
+
+    pub fn list_plugins() {
+        println!("Available plugins, in the order they run:");
+        for (index, plugin) in PluginRegistry::default().iter().enumerate() {
+            let number = format!("{}.", index + 1);
+            let indent = " ".repeat(number.len() + 3);
+            println!("  {} {} ({})", number, plugin.title(), plugin.name());
+            for flag in plugin.flags() {
+                println!("{}{}", indent, flag);
+            }
+            println!("{}{}", indent, plugin.description());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::traits::PluginStatistics;
+    use crate::plugins::{CollapseGroupsPlugin, PathOptimizerPlugin, RemoveCommentsPlugin, RemoveMetadataPlugin};
+    use crate::tree::Node;
+    use quick_xml::events::{BytesCData, BytesPI, BytesText};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Tracks element nesting and fails on unbalanced Start/End events.
+    struct DepthPlugin {
+        depth: usize,
+        max_depth: Rc<Cell<usize>>,
+    }
+
+    impl SVGPlugin for DepthPlugin {
+        fn process_element(&mut self, _element: &mut BytesStart) -> io::Result<ElementAction> {
+            self.depth += 1;
+            self.max_depth.set(self.max_depth.get().max(self.depth));
+            Ok(ElementAction::Keep)
+        }
+
+        fn process_end<'a>(&mut self, end: BytesEnd<'a>) -> io::Result<Option<BytesEnd<'a>>> {
+            self.depth -= 1;
+            Ok(Some(end))
+        }
+
+        fn finalize(&mut self) -> io::Result<()> {
+            assert_eq!(self.depth, 0, "unbalanced Start/End events");
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "Depth"
+        }
+    }
+
+    impl PluginStatistics for DepthPlugin {
+        fn get_statistics(&self) -> Vec<(&str, String)> {
+            Vec::new()
+        }
+    }
+
+    /// Removes every `<g>` element along with its children.
+    struct RemoveGroupsPlugin;
+
+    impl SVGPlugin for RemoveGroupsPlugin {
+        fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+            if element.name().as_ref() == b"g" {
+                return Ok(ElementAction::Remove);
+            }
+            Ok(ElementAction::Keep)
+        }
+
+        fn name(&self) -> &str {
+            "RemoveGroups"
+        }
+    }
+
+    impl PluginStatistics for RemoveGroupsPlugin {
+        fn get_statistics(&self) -> Vec<(&str, String)> {
+            Vec::new()
+        }
+    }
+
+    /// Removes groups left without children, innermost first, which needs
+    /// the whole tree.
+    struct PruneGroupsPlugin {
+        removed: usize,
+    }
+
+    impl PruneGroupsPlugin {
+        fn prune(&mut self, nodes: &mut Vec<Node>) {
+            for node in nodes.iter_mut() {
+                if let Node::Element(element) = node {
+                    self.prune(&mut element.children);
+                }
+            }
+            let before = nodes.len();
+            nodes.retain(|node| {
+                !matches!(node, Node::Element(element) if element.name == "g" && element.children.is_empty())
+            });
+            self.removed += before - nodes.len();
+        }
+    }
+
+    impl TreePlugin for PruneGroupsPlugin {
+        fn process_tree(&mut self, document: &mut Document) -> io::Result<()> {
+            self.prune(&mut document.children);
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "PruneGroups"
+        }
+    }
+
+    impl PluginStatistics for PruneGroupsPlugin {
+        fn get_statistics(&self) -> Vec<(&str, String)> {
+            vec![("Groups removed", self.removed.to_string())]
+        }
+    }
+
+    /// Unwraps every `<g>`, keeping its children.
+    struct UnwrapGroupsPlugin;
+
+    impl SVGPlugin for UnwrapGroupsPlugin {
+        fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+            if element.name().as_ref() == b"g" {
+                return Ok(ElementAction::Unwrap);
+            }
+            Ok(ElementAction::Keep)
+        }
+
+        fn name(&self) -> &str {
+            "UnwrapGroups"
+        }
+    }
+
+    impl PluginStatistics for UnwrapGroupsPlugin {
+        fn get_statistics(&self) -> Vec<(&str, String)> {
+            Vec::new()
+        }
+    }
+
+    /// Replaces every `<g>` with an `<a>` carrying only its `id`.
+    struct GroupsToLinksPlugin;
+
+    impl SVGPlugin for GroupsToLinksPlugin {
+        fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+            if element.name().as_ref() != b"g" {
+                return Ok(ElementAction::Keep);
+            }
+            let mut link = BytesStart::new("a");
+            if let Some(id) = element.try_get_attribute("id").map_err(io::Error::other)? {
+                link.push_attribute(("id", &*id.unescape_value().map_err(io::Error::other)?));
+            }
+            Ok(ElementAction::ReplaceWith(link))
+        }
+
+        fn name(&self) -> &str {
+            "GroupsToLinks"
+        }
+    }
+
+    impl PluginStatistics for GroupsToLinksPlugin {
+        fn get_statistics(&self) -> Vec<(&str, String)> {
+            Vec::new()
+        }
+    }
+
+    /// Uppercases text, drops CDATA sections and renames processing instructions.
+    struct ContentPlugin;
+
+    impl SVGPlugin for ContentPlugin {
+        fn process_element(&mut self, _element: &mut BytesStart) -> io::Result<ElementAction> {
+            Ok(ElementAction::Keep)
+        }
+
+        fn process_text<'a>(&mut self, text: BytesText<'a>) -> io::Result<Option<BytesText<'a>>> {
+            let upper = String::from_utf8_lossy(&text).to_uppercase();
+            Ok(Some(BytesText::from_escaped(upper)))
+        }
+
+        fn process_cdata<'a>(&mut self, _cdata: BytesCData<'a>) -> io::Result<Option<BytesCData<'a>>> {
+            Ok(None)
+        }
+
+        fn process_pi<'a>(&mut self, _pi: BytesPI<'a>) -> io::Result<Option<BytesPI<'a>>> {
+            Ok(Some(BytesPI::new("renamed")))
+        }
+
+        fn name(&self) -> &str {
+            "Content"
+        }
+    }
+
+    impl PluginStatistics for ContentPlugin {
+        fn get_statistics(&self) -> Vec<(&str, String)> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_content_hooks() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(ContentPlugin);
+
+        let output = processor
+            .optimize_str("<?xml-stylesheet href=\"a.css\"?><svg><text>hi</text><![CDATA[x]]></svg>")?;
+
+        assert_eq!(output, "<?renamed?><svg><text>HI</text></svg>");
+        Ok(())
+    }
+
+    /// Appends a comment after the document, counting the elements it saw.
+    struct TrailerPlugin {
+        started: bool,
+        elements: usize,
+    }
+
+    impl SVGPlugin for TrailerPlugin {
+        fn pre_document(&mut self) -> io::Result<()> {
+            self.started = true;
+            self.elements = 0;
+            Ok(())
+        }
+
+        fn process_element(&mut self, _element: &mut BytesStart) -> io::Result<ElementAction> {
+            assert!(self.started, "pre_document must run before processing");
+            self.elements += 1;
+            Ok(ElementAction::Keep)
+        }
+
+        fn post_document(&mut self) -> io::Result<Vec<Event<'static>>> {
+            let comment = format!(" {} elements ", self.elements);
+            Ok(vec![Event::Comment(BytesText::from_escaped(comment))])
+        }
+
+        fn name(&self) -> &str {
+            "Trailer"
+        }
+    }
+
+    impl PluginStatistics for TrailerPlugin {
+        fn get_statistics(&self) -> Vec<(&str, String)> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_document_hooks() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(TrailerPlugin { started: false, elements: 0 });
+
+        let output = processor.optimize_str("<svg><g/></svg>")?;
+
+        assert_eq!(output, "<svg><g/></svg><!-- 2 elements -->");
+        Ok(())
+    }
+
+    #[test]
+    fn test_end_events_balance_start_events() -> io::Result<()> {
+        let max_depth = Rc::new(Cell::new(0));
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(DepthPlugin { depth: 0, max_depth: max_depth.clone() });
+        processor.add_plugin(RemoveGroupsPlugin);
+
+        let output = processor
+            .optimize_str("<svg><g><g><rect/></g></g><defs><rect/></defs><g/></svg>")?;
+
+        assert_eq!(output, "<svg><defs><rect/></defs></svg>");
+        assert_eq!(max_depth.get(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_unwrap_keeps_children_and_balances_end_events() -> io::Result<()> {
+        let max_depth = Rc::new(Cell::new(0));
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(DepthPlugin { depth: 0, max_depth: max_depth.clone() });
+        processor.add_plugin(UnwrapGroupsPlugin);
+
+        let output = processor.optimize_str("<svg><g><g><rect/></g><circle/></g><g/></svg>")?;
+
+        assert_eq!(output, "<svg><rect/><circle/></svg>");
+        assert_eq!(max_depth.get(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_with_renames_end_tags() -> io::Result<()> {
+        let max_depth = Rc::new(Cell::new(0));
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(GroupsToLinksPlugin);
+        processor.add_plugin(DepthPlugin { depth: 0, max_depth: max_depth.clone() });
+        processor.add_plugin(RemoveGroupsPlugin);
+
+        // Later plugins see the replacement, so no group is left to remove
+        let output = processor.optimize_str(r#"<svg><g id="a" fill="red"><g><rect/></g></g><g/></svg>"#)?;
+
+        assert_eq!(output, r#"<svg><a id="a"><a><rect/></a></a><a/></svg>"#);
+        assert_eq!(max_depth.get(), 4);
+        assert_eq!(processor.get_statistics().attributes_removed, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_element_and_attribute_counts() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(RemoveMetadataPlugin::new());
+        processor.add_tree_plugin(PruneGroupsPlugin { removed: 0 });
+
+        // The metadata is removed in the stream, the group it leaves empty
+        // by the tree plugin
+        let svg = r#"<svg id="s"><g id="g"><metadata id="m">x</metadata></g><g><rect x="1" y="1"/></g></svg>"#;
+        let output = processor.optimize_str(svg)?;
+
+        assert_eq!(output, r#"<svg id="s"><g><rect x="1" y="1"/></g></svg>"#);
+        let stats = processor.get_statistics();
+        assert_eq!((stats.elements_seen, stats.elements_removed), (5, 2));
+        assert_eq!((stats.attributes_seen, stats.attributes_removed), (5, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tree_plugins_run_after_streaming_plugins() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(RemoveMetadataPlugin::new());
+        processor.add_tree_plugin(PruneGroupsPlugin { removed: 0 });
+
+        // The groups only become empty once the metadata is gone
+        let svg = "<svg><g><g><metadata>x</metadata></g></g><g><rect/></g></svg>";
+        let output = processor.optimize_str(svg)?;
+
+        assert_eq!(output, "<svg><g><rect/></g></svg>");
+        let stats = processor.plugin_statistics();
+        assert_eq!(stats[1], ("PruneGroups".to_string(), vec![("Groups removed".to_string(), "2".to_string())]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_file_tree() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_tree_plugin(PruneGroupsPlugin { removed: 0 });
+        processor.set_engine(Engine::Streaming);
+
+        let temp_dir = tempfile::tempdir()?;
+        let input_path = temp_dir.path().join("input.svg");
+        let output_path = temp_dir.path().join("output.svg");
+        std::fs::write(&input_path, "<svg><g><g></g></g><rect></rect></svg>")?;
+
+        processor.process_file_tree(&input_path, &output_path)?;
+
+        assert_eq!(std::fs::read_to_string(&output_path)?, "<svg><rect/></svg>");
+        assert_eq!(processor.plugin_statistics()[0].1[0].1, "2");
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_optimization() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(PathOptimizerPlugin::new(2));
+
+        // Create test input file
+        let test_svg = r#"<?xml version="1.0"?>
+            <svg>
+                <path d="M 100.000 200.000 L 300.000 400.000"/>
+            </svg>"#;
+
+        let temp_dir = tempfile::tempdir()?;
+        let input_path = temp_dir.path().join("input.svg");
+        let output_path = temp_dir.path().join("output.svg");
+
+        std::fs::write(&input_path, test_svg)?;
+
+        // Process the file
+        processor.process_file(input_path, output_path.clone())?;
+
+        // Read and verify output
+        let output_content = std::fs::read_to_string(output_path)?;
+        assert!(output_content.contains(r#"d="M100 200 300 400""#));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimize_str() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(PathOptimizerPlugin::new(1));
+
+        let output = processor.optimize_str(r#"<svg><path d="M 1.25 2.00"/></svg>"#)?;
+
+        assert_eq!(output, r#"<svg><path d="M1.2 2"/></svg>"#);
+        Ok(())
+    }
+
+    /// Fails on every element.
+    struct FailingPlugin;
+
+    impl SVGPlugin for FailingPlugin {
+        fn process_element(&mut self, _element: &mut BytesStart) -> io::Result<ElementAction> {
+            Err(io::Error::other("cannot handle elements"))
+        }
+
+        fn name(&self) -> &str {
+            "Failing"
+        }
+    }
+
+    impl PluginStatistics for FailingPlugin {
+        fn get_statistics(&self) -> Vec<(&str, String)> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_errors_are_typed() {
+        let mut processor = SVGProcessor::new(1024);
+
+        let err = processor.optimize_str("<svg><g></svg>").unwrap_err();
+        assert!(matches!(err, SVGError::Parse { position: 8, .. }), "{:?}", err);
+        assert!(err.to_string().starts_with("invalid XML at byte 8: "));
+
+        assert!(matches!(processor.optimize_str("").unwrap_err(), SVGError::EmptyInput));
+
+        processor.add_plugin(FailingPlugin);
+        match processor.optimize_str("<svg/>").unwrap_err() {
+            SVGError::Plugin { plugin, source } => {
+                assert_eq!(plugin, "Failing");
+                assert_eq!(source.to_string(), "cannot handle elements");
+            }
+            err => panic!("expected a plugin error, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn test_builder() -> io::Result<()> {
+        let max_depth = Rc::new(Cell::new(0));
+        let mut processor = SVGProcessor::builder()
+            .buffer_size(16)
+            .plugin(DepthPlugin { depth: 0, max_depth: max_depth.clone() })
+            .plugin(PathOptimizerPlugin::new(1))
+            .build();
+        assert_eq!(processor.chunk_size, 16);
+
+        let output = processor.optimize_str(r#"<svg><g><path d="M 1.25 2.00"/></g></svg>"#)?;
+        assert_eq!(output, r#"<svg><g><path d="M1.2 2"/></g></svg>"#);
+        assert_eq!(max_depth.get(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_processor_builder_with_methods() -> io::Result<()> {
+        let mut processor = ProcessorBuilder::new()
+            .with_buffer_size(32)
+            .with_plugin(RemoveCommentsPlugin::new())
+            .with_plugin(PathOptimizerPlugin::new(0))
+            .build();
+        assert_eq!(processor.chunk_size, 32);
+
+        let output = processor.optimize_str(r#"<svg><!-- x --><path d="M 1.4 2"/></svg>"#)?;
+        assert_eq!(output, r#"<svg><path d="M1 2"/></svg>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pretty_output() -> io::Result<()> {
+        let svg = "<svg>\n<g><!-- a --><path d=\"M 1 2\"/>  <g><rect/></g></g><text>a <tspan>b</tspan></text></svg>";
+        let expected = "<svg>\n    <g>\n        <!-- a -->\n        <path d=\"M1 2\"/>\n        <g>\n            <rect/>\n        </g>\n    </g>\n    <text>a <tspan>b</tspan></text>\n</svg>";
+
+        let mut streaming = SVGProcessor::builder()
+            .plugin(PathOptimizerPlugin::new(0))
+            .indent(Indent::spaces(4))
+            .build();
+        assert_eq!(streaming.optimize_str(svg)?, expected);
+
+        // The tree engine indents only the final output
+        let mut tree = SVGProcessor::builder()
+            .plugin(PathOptimizerPlugin::new(0))
+            .engine(Engine::Tree)
+            .indent(Indent::spaces(4))
+            .build();
+        assert_eq!(tree.optimize_str(svg)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_from_config() -> io::Result<()> {
+        let config = PluginConfig {
+            data_attr_remover: true,
+            group_collapser: true,
+            ..PluginConfig::default()
+        };
+        let mut processor = SVGProcessor::builder()
+            .from_config(&config)
+            .plugin(PathOptimizerPlugin::new(0))
+            .build();
+        assert_eq!(processor.engine, Engine::Tree);
+
+        let names: Vec<String> = processor.plugin_statistics().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names.last().map(String::as_str), Some("CollapseGroups"));
+
+        let output = processor.optimize_str(r#"<svg data-x="1"><g><path d="M 1.4 2"/></g></svg>"#)?;
+        assert_eq!(output, r#"<svg><path d="M1 2"/></svg>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_reader_writer() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(PathOptimizerPlugin::new(2));
+
+        let input = io::Cursor::new(r#"<svg><path d="M 10.000 20.000"/></svg>"#);
+        let mut output = Vec::new();
+        processor.process(input, &mut output)?;
+
+        assert_eq!(output, br#"<svg><path d="M10 20"/></svg>"#);
+        Ok(())
+    }
+
+    #[test]
+    fn test_throughput_statistics() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        assert!(processor.get_statistics().throughput_mb_per_sec.is_none());
+
+        let input = format!("<svg>{}</svg>", r#"<path d="M 1 2 L 3 4"/>"#.repeat(1000));
+        processor.optimize_str(&input)?;
+
+        assert_eq!(processor.bytes_processed, input.len() as u64);
+        if let Some(throughput) = processor.get_statistics().throughput_mb_per_sec {
+            assert!(throughput > 0.0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_size_summary() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(PathOptimizerPlugin::new(0));
+        let input = format!("<svg>{}</svg>", r#"<path d="M 1.000 2.000"/>"#.repeat(1000));
+        processor.optimize_str(&input)?;
+
+        let stats = processor.get_statistics();
+        assert_eq!((stats.original_bytes, stats.optimized_bytes), (25011, 16011));
+        assert_eq!(stats.size_summary(), "24.4 KB → 15.6 KB (-36.0%)");
+
+        let grown = ProcessingStatistics {
+            original_bytes: 200,
+            optimized_bytes: 210,
+            ..stats.clone()
+        };
+        assert_eq!(grown.size_summary(), "200 B → 210 B (+5.0%)");
+        let same = ProcessingStatistics { original_bytes: 0, optimized_bytes: 0, ..stats };
+        assert_eq!(same.size_summary(), "0 B → 0 B (+0.0%)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_bytes_in_and_out() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(RemoveCommentsPlugin::new());
+        let stats = processor.get_statistics();
+        assert_eq!((stats.bytes_in(), stats.bytes_out(), stats.reduction_pct()), (None, None, None));
+
+        processor.optimize_str("<svg><!-- 12345678 --></svg>")?;
+        let stats = processor.get_statistics();
+        assert_eq!((stats.bytes_in(), stats.bytes_out()), (Some(28), Some(11)));
+        assert_eq!(stats.reduction_pct(), Some(60.71));
+        Ok(())
+    }
+
+    #[test]
+    fn test_svgz_round_trip() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input_path = temp_dir.path().join("input.svg");
+        let compressed_path = temp_dir.path().join("output.svgz");
+        let restored_path = temp_dir.path().join("restored.svg");
+        std::fs::write(&input_path, r#"<svg><path d="M 1.000 2.000"/></svg>"#)?;
+
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(PathOptimizerPlugin::new(2));
+        processor.process_file(&input_path, &compressed_path)?;
+
+        let compressed = std::fs::read(&compressed_path)?;
+        assert_eq!(&compressed[..2], &[0x1f, 0x8b]);
+        assert_eq!(
+            processor.get_statistics().compressed_size,
+            Some(compressed.len())
+        );
+        assert_eq!(processor.get_statistics().optimized_bytes, compressed.len() as u64);
+        assert_eq!(processor.get_statistics().uncompressed_bytes, 27);
+
+        let mut processor = SVGProcessor::new(1024);
+        processor.process_file(&compressed_path, &restored_path)?;
+
+        assert_eq!(
+            std::fs::read_to_string(&restored_path)?,
+            r#"<svg><path d="M1 2"/></svg>"#
+        );
+        assert!(processor.get_statistics().compressed_size.is_none());
+        assert_eq!(processor.get_statistics().original_bytes, compressed.len() as u64);
+        Ok(())
+    }
+
+    #[test]
+    fn test_gzip_input_is_detected_by_magic_bytes() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let misnamed = temp_dir.path().join("icon.svg");
+        let output = temp_dir.path().join("out.svg");
+
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(PathOptimizerPlugin::new(2));
+        let mut compressed = Vec::new();
+        processor.process_with_formats(
+            r#"<svg><path d="M 1.000 2.000"/></svg>"#.as_bytes(),
+            SvgFormat::Svg,
+            &mut compressed,
+            SvgFormat::Svgz,
+        )?;
+        fs::write(&misnamed, &compressed)?;
+
+        processor.process_file(&misnamed, &output)?;
+        assert_eq!(fs::read_to_string(&output)?, r#"<svg><path d="M1 2"/></svg>"#);
+
+        let stats = processor.get_statistics();
+        assert_eq!(stats.original_bytes, compressed.len() as u64);
+        assert_eq!((stats.optimized_bytes, stats.uncompressed_bytes), (27, 27));
+        assert!(stats.compressed_size.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_with_formats_in_memory() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        let mut compressed = Vec::new();
+        processor.process_with_formats(
+            "<svg/>".as_bytes(),
+            SvgFormat::Svg,
+            &mut compressed,
+            SvgFormat::Svgz,
+        )?;
+        assert_eq!(processor.get_statistics().compressed_size, Some(compressed.len()));
+
+        let mut restored = Vec::new();
+        processor.process_with_formats(
+            compressed.as_slice(),
+            SvgFormat::Svgz,
+            &mut restored,
+            SvgFormat::Svg,
+        )?;
+        assert_eq!(restored, b"<svg/>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_forced_output_format() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input_path = temp_dir.path().join("input.svg");
+        let output_path = temp_dir.path().join("output.svg");
+        std::fs::write(&input_path, "<svg/>")?;
+
+        let mut processor = SVGProcessor::new(1024);
+        processor.set_output_format(Some(SvgFormat::Svgz));
+        processor.process_file(&input_path, &output_path)?;
+
+        assert_eq!(&std::fs::read(&output_path)?[..2], &[0x1f, 0x8b]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_many_directory() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input_dir = temp_dir.path().join("icons");
+        let output_dir = temp_dir.path().join("out");
+        fs::create_dir_all(input_dir.join("nested"))?;
+        fs::write(input_dir.join("a.svg"), "<svg/>")?;
+        fs::write(input_dir.join("broken.svg"), "")?;
+        fs::write(input_dir.join("notes.txt"), "not an svg")?;
+        fs::write(input_dir.join("nested/b.svg"), "<svg/>")?;
+
+        let mut cli = SVGProcessorCLI::new(8, false);
+        let flat = cli.process_many(&input_dir, Some(&output_dir), false);
+        assert!(flat.is_err(), "the broken file should be reported");
+        assert!(output_dir.join("a.svg").exists());
+        assert!(!output_dir.join("notes.txt").exists());
+        assert!(!output_dir.join("nested/b.svg").exists());
+
+        fs::remove_file(input_dir.join("broken.svg"))?;
+        cli.process_many(&input_dir, Some(&output_dir), true)?;
+        assert!(output_dir.join("nested/b.svg").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_many_parallel() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input_dir = temp_dir.path().join("icons");
+        let output_dir = temp_dir.path().join("out");
+        fs::create_dir_all(&input_dir)?;
+        for i in 0..8 {
+            fs::write(
+                input_dir.join(format!("{}.svg", i)),
+                r#"<svg><path d="M 1.000 2.000"/></svg>"#,
+            )?;
+        }
+
+        let mut cli = SVGProcessorCLI::new(8, false);
+        cli.configure(PluginConfig {
+            path_optimizer: Some(crate::cli::PathOptimizerConfig {
+                decimal_places: 2,
+                use_relative: false,
+                use_shortest: false,
+                collapse_repeated: false,
+            }),
+            ..PluginConfig::default()
+        })
+        .jobs(4)
+        .process_many(&input_dir, Some(&output_dir), false)?;
+
+        for i in 0..8 {
+            let output = fs::read_to_string(output_dir.join(format!("{}.svg", i)))?;
+            assert_eq!(output, r#"<svg><path d="M1 2"/></svg>"#);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_stats_json() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input_dir = temp_dir.path().join("icons");
+        let stats = temp_dir.path().join("stats.json");
+        fs::create_dir_all(&input_dir)?;
+        fs::write(input_dir.join("a.svg"), "<svg><!-- a --><rect/></svg>")?;
+        fs::write(input_dir.join("b.svg"), r##"<svg fill="#12"/>"##)?;
+
+        let mut cli = SVGProcessorCLI::new(8, false);
+        cli.configure(PluginConfig {
+            enable: vec!["removeComments".to_string(), "convertColors".to_string()],
+            ..PluginConfig::default()
+        })
+        .stats_json(Some(stats.clone()));
+
+        cli.process(Some(input_dir.join("a.svg")), Some(temp_dir.path().join("a.svg")))?;
+        let report: serde_json::Value = serde_json::from_str(&fs::read_to_string(&stats)?)?;
+        assert_eq!(report["originalSize"], 28);
+        assert_eq!(report["optimizedSize"], 18);
+        assert_eq!(report["percentSaved"], 35.71);
+        assert_eq!(report["plugins"]["RemoveComments"]["Comments removed"], 1);
+        assert!(report.get("diagnostics").is_none());
+
+        cli.process_many(&input_dir, Some(&temp_dir.path().join("out")), false)?;
+        let reports: serde_json::Value = serde_json::from_str(&fs::read_to_string(&stats)?)?;
+        let reports = reports.as_array().unwrap();
+        assert_eq!(reports.len(), 2);
+        assert!(reports[1]["input"].as_str().unwrap().ends_with("b.svg"));
+        assert_eq!(reports[1]["percentSaved"], 0.0);
+        assert_eq!(
+            reports[1]["diagnostics"],
+            serde_json::json!([{
+                "plugin": "ConvertColors",
+                "level": "warning",
+                "message": "malformed color '#12' in fill",
+                "element": "svg",
+            }])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run_writes_nothing() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("icon.svg");
+        fs::write(&path, "<svg><!-- a --><rect/></svg>")?;
+
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(RemoveCommentsPlugin::new());
+        processor.set_dry_run(true);
+        processor.process_file(&path, &temp_dir.path().join("out.svg"))?;
+        processor.process_file_in_place(&path)?;
+
+        assert_eq!(fs::read_to_string(&path)?, "<svg><!-- a --><rect/></svg>");
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 1);
+        assert_eq!(processor.get_statistics().optimized_bytes, 18);
+        Ok(())
+    }
+
+    #[test]
+    fn test_analysis_report() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("icon.svg");
+        fs::write(&path, "<svg><!-- a --><rect/></svg>")?;
+
+        let mut cli = SVGProcessorCLI::new(8, false);
+        cli.configure(PluginConfig {
+            enable: vec!["removeComments".to_string(), "removeMetadata".to_string()],
+            ..PluginConfig::default()
+        });
+        let report = cli.analysis_report(&path)?;
+
+        assert_eq!(
+            report,
+            format!(
+                "Analysis of {}\n--------------------\nOriginal size: 28 B\nOptimized size: 18 B\n\
+                 Potential savings: 10 B (35.7%)\n--------------------\n\n\
+                 RemoveComments:\n  Comments removed: 1\n\nNothing found by: RemoveMetadata\n",
+                path.display()
+            )
+        );
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_file_in_place() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("icon.svg");
+        fs::write(&path, r#"<svg><path d="M 1.000 2.000"/></svg>"#)?;
+
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(PathOptimizerPlugin::new(2));
+        processor.process_file_in_place(&path)?;
+
+        assert_eq!(fs::read_to_string(&path)?, r#"<svg><path d="M1 2"/></svg>"#);
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_failed_in_place_run_keeps_original() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let path = temp_dir.path().join("broken.svg");
+        fs::write(&path, "<svg><g></svg>")?;
+
+        let mut processor = SVGProcessor::new(1024);
+        assert!(processor.process_file_in_place(&path).is_err());
+
+        assert_eq!(fs::read_to_string(&path)?, "<svg><g></svg>");
+        assert_eq!(fs::read_dir(temp_dir.path())?.count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_many_in_place() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let nested = temp_dir.path().join("nested");
+        fs::create_dir_all(&nested)?;
+        fs::write(temp_dir.path().join("a.svg"), "<svg><!-- a --><rect/></svg>")?;
+        fs::write(nested.join("b.svg"), "<svg><!-- b --><rect/></svg>")?;
+
+        let mut cli = SVGProcessorCLI::new(8, false);
+        cli.configure(PluginConfig {
+            comment_remover: crate::cli::CommentRemoverConfig {
+                enabled: true,
+                preserve: Vec::new(),
+            },
+            ..PluginConfig::default()
+        })
+        .process_many(temp_dir.path(), None, true)?;
+
+        assert_eq!(fs::read_to_string(temp_dir.path().join("a.svg"))?, "<svg><rect/></svg>");
+        assert_eq!(fs::read_to_string(nested.join("b.svg"))?, "<svg><rect/></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_in_place_refuses_stdin() {
+        let mut cli = SVGProcessorCLI::new(8, false);
+        let err = cli.process_in_place("-").unwrap_err();
+        assert!(matches!(err, SVGError::InvalidPath(_)));
+    }
+
+    #[test]
+    fn test_batch_files_glob() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input_dir = temp_dir.path().join("icons");
+        fs::create_dir_all(input_dir.join("nested"))?;
+        fs::write(input_dir.join("a.svg"), "<svg/>")?;
+        fs::write(input_dir.join("nested/b.svg"), "<svg/>")?;
+
+        let pattern = input_dir.join("**").join("*.svg");
+        let output_dir = temp_dir.path().join("out");
+        let files = batch_files(&pattern, Some(&output_dir), false)?;
+
+        let targets: Vec<_> = files.into_iter().map(|(_, target)| target).collect();
+        assert_eq!(
+            targets,
+            vec![output_dir.join("a.svg"), output_dir.join("nested/b.svg")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dir_files_pattern() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input_dir = temp_dir.path().join("icons");
+        fs::create_dir_all(input_dir.join("nested"))?;
+        fs::write(input_dir.join("a.svg"), "<svg/>")?;
+        fs::write(input_dir.join("b.svgz"), "")?;
+        fs::write(input_dir.join("nested/c.svgz"), "")?;
+        fs::write(input_dir.join("nested/d.svg"), "<svg/>")?;
+        let output_dir = temp_dir.path().join("out");
+
+        let targets = |pattern, recursive| -> io::Result<Vec<PathBuf>> {
+            let files = dir_files(&input_dir, &output_dir, pattern, recursive)?;
+            Ok(files.into_iter().map(|(_, target)| target).collect())
+        };
+
+        assert_eq!(targets(None, false)?, [output_dir.join("a.svg"), output_dir.join("b.svgz")]);
+        assert_eq!(
+            targets(Some("*.svgz"), true)?,
+            [output_dir.join("b.svgz"), output_dir.join("nested/c.svgz")]
+        );
+        assert_eq!(targets(Some("nested/*"), true)?.len(), 2);
+        assert!(targets(Some("[a"), true).is_err());
+        assert!(dir_files(&input_dir.join("a.svg"), &output_dir, None, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_process_dir() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input_dir = temp_dir.path().join("icons");
+        let output_dir = temp_dir.path().join("out");
+        fs::create_dir_all(input_dir.join("nested"))?;
+        fs::write(input_dir.join("a.svg"), "<svg><!-- a --></svg>")?;
+        fs::write(input_dir.join("nested/b.svg"), "<svg><!-- b --></svg>")?;
+
+        let mut cli = SVGProcessorCLI::new(8, false);
+        cli.configure(PluginConfig::from_preset(crate::cli::Preset::Safe))
+            .process_dir(&input_dir, &output_dir, None, true)?;
+
+        assert_eq!(fs::read_to_string(output_dir.join("a.svg"))?, "<svg></svg>");
+        assert_eq!(fs::read_to_string(output_dir.join("nested/b.svg"))?, "<svg></svg>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_dry_run_and_min_savings() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input = temp_dir.path().join("icon.svg");
+        let output = temp_dir.path().join("out.svg");
+        fs::write(&input, "<svg><!-- a long comment --><rect/></svg>")?;
+
+        let mut cli = SVGProcessorCLI::new(8, false);
+        cli.configure(PluginConfig::from_preset(crate::cli::Preset::Safe)).dry_run(true);
+        cli.process(Some(input.clone()), Some(output.clone()))?;
+        assert!(!output.exists());
+
+        cli.min_savings_pct(Some(10.0)).process(Some(input.clone()), Some(output.clone()))?;
+        assert!(cli.min_savings_pct(Some(90.0)).process(Some(input), Some(output.clone())).is_err());
+        assert!(!output.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimize_str_reuses_the_processor() -> io::Result<()> {
+        let mut processor = SVGProcessor::new(1024);
+        processor.add_plugin(RemoveCommentsPlugin::new());
+        processor.add_plugin(PathOptimizerPlugin::new(2));
+        processor.add_tree_plugin(CollapseGroupsPlugin::new());
+
+        let svg = r#"<svg><!-- icon --><g><path d="M 10.000 20.000 L 30 40"/></g></svg>"#;
+        for _ in 0..2 {
+            assert_eq!(processor.optimize_str(svg)?, r#"<svg><path d="M10 20 30 40"/></svg>"#);
+            assert_eq!(processor.plugin_statistics()[1].1[0].1, "1");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimize_str_rejects_empty_input() {
+        let mut processor = SVGProcessor::new(1024);
+        assert!(processor.optimize_str("").is_err());
     }
 }
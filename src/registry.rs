@@ -0,0 +1,793 @@
+//! Plugins by name.
+//!
+//! The registry knows every plugin under the name its config file key uses
+//! (`"pathOptimizer"`, `"removeIds"`, ...) along with how to build it from
+//! a [`PluginConfig`]. The pipeline is assembled from it in registration
+//! order, which is the order the plugins run in unless the config's
+//! `order` moves some of them to the front. Streaming plugins always run
+//! before tree plugins, whatever the order.
+
+use crate::cli::{Engine, PluginConfig};
+use crate::error::SVGError;
+use crate::plugins::{
+    CleanNamespacesPlugin,
+    CleanupIdsPlugin,
+    CleanupNumericValuesPlugin,
+    CollapseGroupsPlugin,
+    CollapseWhitespacePlugin,
+    ConvertColorsPlugin,
+    DeduplicateGradientsPlugin,
+    DeduplicatePathsPlugin,
+    InlineStylesPlugin,
+    InlineStylesheetPlugin,
+    MergePathsPlugin,
+    MinifyCSSPlugin,
+    NormalizeTextWhitespacePlugin,
+    NormalizeColorsPlugin,
+    OptimizeTransformsPlugin,
+    PathOptimizerPlugin,
+    PrefixIdsPlugin,
+    PresentationToStylePlugin,
+    RemoveAttrsPlugin,
+    RemoveCommentsPlugin,
+    RemoveDataAttributesPlugin,
+    RemoveDefaultAttributesPlugin,
+    RemoveDimensionsPlugin,
+    RemoveDoctypePlugin,
+    RemoveEditorNamespacesPlugin,
+    RemoveEmptyContainersPlugin,
+    RemoveExternalRefsPlugin,
+    RemoveHiddenElemsPlugin,
+    RemoveEmptyAttrsPlugin,
+    RemoveIDPlugin,
+    RemoveInvisiblePlugin,
+    RemoveMetadataPlugin,
+    RemoveScriptsPlugin,
+    RemoveTitleDescPlugin,
+    RemoveUnusedCSSPlugin,
+    RemoveUnusedDefsPlugin,
+    RemoveUselessPaintPlugin,
+    RemoveXmlProcInstPlugin,
+    SVGPlugin,
+    ShapeToPathPlugin,
+    SortAttrsPlugin,
+    TreePlugin,
+};
+use crate::processor::SVGProcessor;
+
+/// A plugin built by the registry, for either engine stage.
+pub enum Plugin {
+    Streaming(Box<dyn SVGPlugin>),
+    Tree(Box<dyn TreePlugin>),
+}
+
+impl Plugin {
+    pub fn streaming<P: SVGPlugin + 'static>(plugin: P) -> Self {
+        Plugin::Streaming(Box::new(plugin))
+    }
+
+    pub fn tree<P: TreePlugin + 'static>(plugin: P) -> Self {
+        Plugin::Tree(Box::new(plugin))
+    }
+}
+
+type Constructor = Box<dyn Fn(&PluginConfig) -> Plugin + Send + Sync>;
+type EnabledCheck = Box<dyn Fn(&PluginConfig) -> bool + Send + Sync>;
+
+/// A plugin known to the registry.
+pub struct RegisteredPlugin {
+    name: String,
+    title: String,
+    description: String,
+    flags: Vec<String>,
+    enabled: EnabledCheck,
+    constructor: Constructor,
+}
+
+impl RegisteredPlugin {
+    /// The name used by `--enable`, `--disable` and config files.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Command-line flags that enable or configure the plugin.
+    pub fn flags(&self) -> &[String] {
+        &self.flags
+    }
+
+    pub fn with_description(&mut self, description: &str) -> &mut Self {
+        self.description = description.to_string();
+        self
+    }
+
+    pub fn with_flags(&mut self, flags: &[&str]) -> &mut Self {
+        self.flags = flags.iter().map(|flag| flag.to_string()).collect();
+        self
+    }
+
+    /// Decide from the config's own settings whether the plugin is on.
+    /// Without this it only runs when named by `enable`.
+    pub fn enabled_by<F>(&mut self, enabled: F) -> &mut Self
+    where
+        F: Fn(&PluginConfig) -> bool + Send + Sync + 'static,
+    {
+        self.enabled = Box::new(enabled);
+        self
+    }
+
+    /// Whether the plugin runs under `config`: `disable` beats `enable`,
+    /// which beats the plugin's own settings.
+    pub fn is_enabled(&self, config: &PluginConfig) -> bool {
+        if config.disable.contains(&self.name) {
+            return false;
+        }
+        config.enable.contains(&self.name) || (self.enabled)(config)
+    }
+
+    /// Build the plugin with the options in `config`.
+    pub fn build(&self, config: &PluginConfig) -> Plugin {
+        (self.constructor)(config)
+    }
+}
+
+/// Maps plugin names to constructors.
+pub struct PluginRegistry {
+    plugins: Vec<RegisteredPlugin>,
+}
+
+impl PluginRegistry {
+    /// A registry without any plugins.
+    pub fn new() -> Self {
+        Self { plugins: Vec::new() }
+    }
+
+    /// Register a plugin under `name`, after the ones registered so far.
+    /// A plugin already registered under that name is replaced in place.
+    pub fn register<F>(&mut self, name: &str, title: &str, constructor: F) -> &mut RegisteredPlugin
+    where
+        F: Fn(&PluginConfig) -> Plugin + Send + Sync + 'static,
+    {
+        let plugin = RegisteredPlugin {
+            name: name.to_string(),
+            title: title.to_string(),
+            description: String::new(),
+            flags: Vec::new(),
+            enabled: Box::new(|_| false),
+            constructor: Box::new(constructor),
+        };
+        let index = match self.plugins.iter().position(|existing| existing.name == name) {
+            Some(index) => {
+                self.plugins[index] = plugin;
+                index
+            }
+            None => {
+                self.plugins.push(plugin);
+                self.plugins.len() - 1
+            }
+        };
+        &mut self.plugins[index]
+    }
+
+    pub fn get(&self, name: &str) -> Option<&RegisteredPlugin> {
+        self.plugins.iter().find(|plugin| plugin.name == name)
+    }
+
+    /// All plugins, in the order they run.
+    pub fn iter(&self) -> impl Iterator<Item = &RegisteredPlugin> {
+        self.plugins.iter()
+    }
+
+    /// Build the plugin registered as `name`.
+    pub fn create(&self, name: &str, config: &PluginConfig) -> Result<Plugin, SVGError> {
+        self.get(name)
+            .map(|plugin| plugin.build(config))
+            .ok_or_else(|| SVGError::UnknownPlugin(name.to_string()))
+    }
+
+    /// Fail on the first of `names` that isn't registered.
+    pub fn check<'a>(&self, names: impl IntoIterator<Item = &'a String>) -> Result<(), SVGError> {
+        match names.into_iter().find(|name| self.get(name).is_none()) {
+            Some(name) => Err(SVGError::UnknownPlugin(name.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// The plugins enabled in `config`, in the order they run: those named
+    /// in `config.order` first, in that order, then the others in
+    /// registration order.
+    pub fn ordered<'a>(&'a self, config: &PluginConfig) -> Vec<&'a RegisteredPlugin> {
+        let mut plugins: Vec<_> = self.plugins.iter().filter(|plugin| plugin.is_enabled(config)).collect();
+        plugins.sort_by_key(|plugin| {
+            config
+                .order
+                .iter()
+                .position(|name| *name == plugin.name)
+                .unwrap_or(usize::MAX)
+        });
+        plugins
+    }
+
+    /// Build every plugin enabled in `config`, in pipeline order.
+    pub fn pipeline(&self, config: &PluginConfig) -> Vec<(&RegisteredPlugin, Plugin)> {
+        self.ordered(config)
+            .into_iter()
+            .map(|plugin| (plugin, plugin.build(config)))
+            .collect()
+    }
+
+    /// The engine the plugins enabled in `config` need.
+    pub fn engine(&self, config: &PluginConfig) -> Engine {
+        let tree = self
+            .pipeline(config)
+            .into_iter()
+            .any(|(_, plugin)| matches!(plugin, Plugin::Tree(_)));
+        if tree {
+            Engine::Tree
+        } else {
+            Engine::Streaming
+        }
+    }
+
+    /// Add every plugin enabled in `config` to `processor` and select the
+    /// engine they need.
+    pub fn configure(&self, processor: &mut SVGProcessor, config: &PluginConfig, verbose: bool) {
+        for (registered, plugin) in self.pipeline(config) {
+            if verbose {
+                eprintln!("Enabling {}", registered.title);
+            }
+            match plugin {
+                Plugin::Streaming(plugin) => processor.add_boxed_plugin(plugin),
+                Plugin::Tree(plugin) => processor.add_boxed_tree_plugin(plugin),
+            }
+        }
+        if verbose && processor.engine() == Engine::Tree {
+            eprintln!("Using the tree engine");
+        }
+    }
+}
+
+impl Default for PluginRegistry {
+    /// The registry of all built-in plugins.
+    fn default() -> Self {
+        let mut registry = Self::new();
+
+        // Runs first so the path optimizer also sees the converted shapes
+        registry
+            .register("shapesToPaths", "Shape to Path Converter", |config| {
+                let decimal_places = config.path_optimizer.as_ref().map(|path| path.decimal_places);
+                Plugin::streaming(
+                    ShapeToPathPlugin::new(config.shape_converter.shapes.clone())
+                        .with_decimal_places(decimal_places),
+                )
+            })
+            .with_flags(&["--shapes-to-paths", "--shapes <SHAPES>"])
+            .with_description("Converts rect, circle, ellipse, line, polyline and polygon to paths")
+            .enabled_by(|config| config.shape_converter.enabled);
+
+        registry
+            .register("pathOptimizer", "Path Optimizer", |config| {
+                let path_config = config.path_optimizer.clone().unwrap_or_default();
+                Plugin::streaming(
+                    PathOptimizerPlugin::new(path_config.decimal_places)
+                        .with_relative(path_config.use_relative)
+                        .with_shortest(path_config.use_shortest)
+                        .with_collapse_repeated(path_config.collapse_repeated),
+                )
+            })
+            .with_flags(&[
+                "--optimize-paths",
+                "--path-decimals <VALUE>",
+                "--path-relative",
+                "--path-shortest",
+                "--path-collapse-commands",
+            ])
+            .with_description("Optimizes path data by reducing decimal places and removing unnecessary spaces")
+            .enabled_by(|config| config.path_optimizer.is_some());
+
+        registry
+            .register("dedupeGradients", "Gradient Deduplicator", |_| {
+                Plugin::streaming(DeduplicateGradientsPlugin::new())
+            })
+            .with_flags(&["--dedupe-gradients"])
+            .with_description("Merges identical gradients and rewrites references to the kept one")
+            .enabled_by(|config| config.gradient_deduplicator);
+
+        registry
+            .register("removeIds", "ID Remover", |config| {
+                Plugin::streaming(RemoveIDPlugin::new(config.id_remover.preserve.clone()))
+            })
+            .with_flags(&["--remove-ids", "--preserve-ids <IDS>"])
+            .with_description("Removes ids that are not referenced anywhere in the document")
+            .enabled_by(|config| config.id_remover.enabled);
+
+        registry
+            .register("removeDataAttrs", "Data Attribute Remover", |_| {
+                Plugin::streaming(RemoveDataAttributesPlugin::new())
+            })
+            .with_flags(&["--remove-data-attrs"])
+            .with_description("Removes data-* attributes from all elements")
+            .enabled_by(|config| config.data_attr_remover);
+
+        registry
+            .register("removeAttrs", "Attribute Remover", |config| {
+                Plugin::streaming(RemoveAttrsPlugin::new(config.attrs_remover.clone()))
+            })
+            .with_flags(&["--remove-attrs <PATTERN>"])
+            .with_description("Removes attributes matching element:attribute:value regex patterns")
+            .enabled_by(|config| !config.attrs_remover.is_empty());
+
+        registry
+            .register("removeComments", "Comment Remover", |config| {
+                let preserve = &config.comment_remover.preserve;
+                Plugin::streaming(if preserve.is_empty() {
+                    RemoveCommentsPlugin::new()
+                } else {
+                    RemoveCommentsPlugin::with_preserve_patterns(preserve.clone())
+                })
+            })
+            .with_flags(&["--remove-comments", "--preserve-comments <REGEX>"])
+            .with_description("Removes comments, keeping legal comments that start with '!'")
+            .enabled_by(|config| config.comment_remover.enabled);
+
+        registry
+            .register("collapseWhitespace", "Whitespace Collapser", |_| {
+                Plugin::streaming(CollapseWhitespacePlugin::new())
+            })
+            .with_flags(&["--collapse-whitespace"])
+            .with_description("Removes whitespace-only text between elements")
+            .enabled_by(|config| config.whitespace_collapser);
+
+        // Tree plugins run in the order they are added: propagated transforms
+        // leave bare groups behind for the group collapser
+        registry
+            .register("optimizeTransforms", "Transform Optimizer", |config| {
+                Plugin::tree(
+                    OptimizeTransformsPlugin::new().with_propagate(config.transform_optimizer.propagate),
+                )
+            })
+            .with_flags(&["--optimize-transforms", "--propagate-transforms"])
+            .with_description("Removes identity transforms and collapses transform chains")
+            .enabled_by(|config| config.transform_optimizer.enabled);
+
+        registry
+            .register("removeHiddenElems", "Hidden Element Remover", |config| {
+                let hidden = config.hidden_elems_remover.clone().unwrap_or_default();
+                Plugin::tree(
+                    RemoveHiddenElemsPlugin::new()
+                        .with_display_none(hidden.display_none)
+                        .with_visibility_hidden(hidden.visibility_hidden)
+                        .with_opacity_zero(hidden.opacity_zero)
+                        .with_zero_size(hidden.zero_size),
+                )
+            })
+            .with_flags(&["--remove-hidden-elems"])
+            .with_description("Removes hidden, fully transparent and zero-size elements with their content")
+            .enabled_by(|config| config.hidden_elems_remover.is_some());
+
+        registry
+            .register("removeEmptyContainers", "Empty Container Remover", |_| {
+                Plugin::tree(RemoveEmptyContainersPlugin::new())
+            })
+            .with_flags(&["--remove-empty-containers"])
+            .with_description("Removes <g>, <defs>, <symbol> and other containers without content")
+            .enabled_by(|config| config.empty_container_remover);
+
+        registry
+            .register("inlineStylesheet", "Stylesheet Inliner", |_| {
+                Plugin::tree(InlineStylesheetPlugin::new())
+            })
+            .with_flags(&["--inline-stylesheet"])
+            .with_description("Moves simple <style> rules into presentation attributes")
+            .enabled_by(|config| config.stylesheet_inliner);
+
+        registry
+            .register("collapseGroups", "Group Collapser", |_| Plugin::tree(CollapseGroupsPlugin::new()))
+            .with_flags(&["--collapse-groups"])
+            .with_description("Removes empty groups and merges groups into their only child")
+            .enabled_by(|config| config.group_collapser);
+
+        registry
+            .register("dedupePaths", "Path Deduplicator", |config| {
+                Plugin::tree(DeduplicatePathsPlugin::new().with_remove(config.path_deduplicator.remove))
+            })
+            .with_flags(&["--dedupe-paths", "--remove-duplicate-paths"])
+            .with_description("Reports paths with the same shape and removes exact repeats")
+            .enabled_by(|config| config.path_deduplicator.enabled);
+
+        registry
+            .register("mergePaths", "Path Merger", |_| Plugin::tree(MergePathsPlugin::new()))
+            .with_flags(&["--merge-paths"])
+            .with_description("Merges neighbouring paths with the same attributes that don't overlap")
+            .enabled_by(|config| config.path_merger);
+
+        registry
+            .register("cleanupIds", "Id Cleaner", |config| {
+                let cleaner = config.id_cleaner.clone().unwrap_or_default();
+                Plugin::tree(
+                    CleanupIdsPlugin::new()
+                        .with_remove(cleaner.remove)
+                        .with_minify(cleaner.minify)
+                        .with_preserve_prefixes(cleaner.preserve_prefixes),
+                )
+            })
+            .with_flags(&["--cleanup-ids", "--preserve-id-prefixes <PREFIXES>"])
+            .with_description("Removes unused ids and shortens the others, rewriting references")
+            .enabled_by(|config| config.id_cleaner.is_some());
+
+        registry
+            .register("prefixIds", "Id Prefixer", |config| {
+                let prefixer = config.id_prefixer.clone().unwrap_or_default();
+                let plugin = PrefixIdsPlugin::new().with_delimiter(prefixer.delimiter);
+                Plugin::tree(match prefixer.prefix {
+                    Some(prefix) => plugin.with_prefix(prefix),
+                    None => plugin,
+                })
+            })
+            .with_flags(&["--prefix-ids [PREFIX]", "--prefix-delimiter <DELIM>"])
+            .with_description("Prefixes ids and class names, by default with the file name, rewriting references")
+            .enabled_by(|config| config.id_prefixer.is_some());
+
+        registry
+            .register("normalizeTextWhitespace", "Text Whitespace Normalizer", |_| {
+                Plugin::tree(NormalizeTextWhitespacePlugin::new())
+            })
+            .with_flags(&["--normalize-text-whitespace"])
+            .with_description("Collapses and trims whitespace in <text> content, respecting xml:space")
+            .enabled_by(|config| config.text_whitespace_normalizer);
+
+        registry
+            .register("removeInvisible", "Invisible Element Remover", |_| {
+                Plugin::streaming(RemoveInvisiblePlugin::new())
+            })
+            .with_flags(&["--remove-invisible"])
+            .with_description("Removes elements hidden with display:none or visibility:hidden")
+            .enabled_by(|config| config.invisible_remover);
+
+        registry
+            .register("removeDefaultAttrs", "Default Attribute Remover", |_| {
+                Plugin::streaming(RemoveDefaultAttributesPlugin::new())
+            })
+            .with_flags(&["--remove-default-attrs"])
+            .with_description("Removes attributes whose value equals the SVG default")
+            .enabled_by(|config| config.default_attr_remover);
+
+        registry
+            .register("removeMetadata", "Metadata Remover", |_| {
+                Plugin::streaming(RemoveMetadataPlugin::new())
+            })
+            .with_flags(&["--remove-metadata"])
+            .with_description("Removes <metadata> elements and their contents")
+            .enabled_by(|config| config.metadata_remover);
+
+        registry
+            .register("removeScripts", "Script Remover", |_| Plugin::streaming(RemoveScriptsPlugin::new()))
+            .with_flags(&["--remove-scripts"])
+            .with_description("Removes <script> elements, on* event handler attributes and javascript: links")
+            .enabled_by(|config| config.script_remover);
+
+        registry
+            .register("removeExternalRefs", "External Reference Remover", |_| {
+                Plugin::streaming(RemoveExternalRefsPlugin::new())
+            })
+            .with_flags(&["--remove-external-refs"])
+            .with_description("Removes <foreignObject>, external <use> and links to remote URLs")
+            .enabled_by(|config| config.external_refs_remover);
+
+        registry
+            .register("removeDoctype", "DOCTYPE Remover", |_| Plugin::streaming(RemoveDoctypePlugin::new()))
+            .with_flags(&["--remove-doctype"])
+            .with_description("Removes the <!DOCTYPE> declaration")
+            .enabled_by(|config| config.doctype_remover);
+
+        registry
+            .register("removeUnusedDefs", "Unused Definitions Remover", |_| {
+                Plugin::streaming(RemoveUnusedDefsPlugin::new())
+            })
+            .with_flags(&["--remove-unused-defs"])
+            .with_description("Removes <defs> children that are never referenced")
+            .enabled_by(|config| config.unused_defs_remover);
+
+        registry
+            .register("removeXmlProcInst", "Processing Instruction Remover", |_| {
+                Plugin::streaming(RemoveXmlProcInstPlugin::new())
+            })
+            .with_flags(&["--remove-xml-proc-inst"])
+            .with_description("Removes processing instructions, keeping the XML declaration")
+            .enabled_by(|config| config.proc_inst_remover);
+
+        registry
+            .register("removeEditorNamespaces", "Editor Namespace Remover", |config| {
+                Plugin::streaming(RemoveEditorNamespacesPlugin::new(config.editor_ns_remover.preserve.clone()))
+            })
+            .with_flags(&["--remove-editor-ns", "--preserve-ns <PREFIXES>"])
+            .with_description("Removes Inkscape, Sodipodi, Illustrator and Sketch namespaces and attributes")
+            .enabled_by(|config| config.editor_ns_remover.enabled);
+
+        registry
+            .register("convertColors", "Color Converter", |config| {
+                let color_config = config.color_converter.clone().unwrap_or_default();
+                Plugin::streaming(
+                    ConvertColorsPlugin::new()
+                        .with_shorthand(color_config.shorthand)
+                        .with_lowercase(color_config.lowercase),
+                )
+            })
+            .with_flags(&["--convert-colors", "--color-longhand", "--color-keep-case"])
+            .with_description("Rewrites fill, stroke, stop-color and color values to their shortest form")
+            .enabled_by(|config| config.color_converter.is_some());
+
+        registry
+            .register("removeTitleDesc", "Title and Description Remover", |config| {
+                Plugin::streaming(
+                    RemoveTitleDescPlugin::new()
+                        .with_preserve_title(config.title_desc_remover.preserve_title)
+                        .with_preserve_desc(config.title_desc_remover.preserve_desc),
+                )
+            })
+            .with_flags(&["--remove-title-desc", "--preserve-title", "--preserve-desc"])
+            .with_description("Removes <title> and <desc> elements")
+            .enabled_by(|config| config.title_desc_remover.enabled);
+
+        registry
+            .register("removeEmptyAttrs", "Empty Attribute Remover", |config| {
+                Plugin::streaming(
+                    RemoveEmptyAttrsPlugin::new()
+                        .with_allow(config.empty_attr_remover.allow.clone())
+                        .with_deny(config.empty_attr_remover.deny.clone())
+                        .with_geometry(config.empty_attr_remover.geometry),
+                )
+            })
+            .with_flags(&[
+                "--remove-empty-attrs",
+                "--empty-attrs-allow <NAMES>",
+                "--empty-attrs-deny <NAMES>",
+                "--empty-attrs-geometry",
+            ])
+            .with_description("Removes presentation attributes, style, class and id when their value is empty")
+            .enabled_by(|config| config.empty_attr_remover.enabled);
+
+        registry
+            .register("normalizeColors", "Color Normalizer", |_| {
+                Plugin::streaming(NormalizeColorsPlugin::new())
+            })
+            .with_flags(&["--normalize-colors"])
+            .with_description("Rewrites fill, stroke and stop-color values, also in style, to lowercase shortest form")
+            .enabled_by(|config| config.color_normalizer);
+
+        registry
+            .register("cleanNamespaces", "Namespace Cleaner", |config| {
+                Plugin::streaming(
+                    CleanNamespacesPlugin::new().with_migrate_xlink(config.namespace_cleaner.xlink_to_href),
+                )
+            })
+            .with_flags(&["--clean-namespaces", "--xlink-to-href"])
+            .with_description("Removes redundant svg: prefixes from attributes and their unused declarations")
+            .enabled_by(|config| config.namespace_cleaner.enabled);
+
+        registry
+            .register("sortAttrs", "Attribute Sorter", |config| {
+                let mut plugin = SortAttrsPlugin::new();
+                if !config.attr_sorter.order.is_empty() {
+                    plugin = plugin.with_order(config.attr_sorter.order.clone());
+                }
+                Plugin::streaming(plugin)
+            })
+            .with_flags(&["--sort-attrs", "--attrs-order <NAMES>"])
+            .with_description("Sorts attributes into a consistent order for better gzip compression")
+            .enabled_by(|config| config.attr_sorter.enabled);
+
+        registry
+            .register("cleanupNumericValues", "Numeric Value Cleanup", |config| {
+                let numeric_config = config.numeric_cleaner.clone().unwrap_or_default();
+                Plugin::streaming(
+                    CleanupNumericValuesPlugin::new(numeric_config.decimal_places)
+                        .with_lists(numeric_config.lists),
+                )
+            })
+            .with_flags(&["--cleanup-numeric-values", "--numeric-decimals <N>", "--numeric-lists"])
+            .with_description("Rounds numbers in attributes like width, x and opacity and drops px units")
+            .enabled_by(|config| config.numeric_cleaner.is_some());
+
+        registry
+            .register("removeUselessPaint", "Useless Paint Remover", |_| {
+                Plugin::streaming(RemoveUselessPaintPlugin::new())
+            })
+            .with_flags(&["--remove-useless-paint"])
+            .with_description("Removes stroke and fill properties that can't show and paint of hidden elements")
+            .enabled_by(|config| config.useless_paint_remover);
+
+        registry
+            .register("removeDimensions", "Dimensions Remover", |_| {
+                Plugin::streaming(RemoveDimensionsPlugin::new())
+            })
+            .with_flags(&["--remove-dimensions"])
+            .with_description("Removes width and height from the root <svg>, keeping or adding a viewBox")
+            .enabled_by(|config| config.dimensions_remover);
+
+        registry
+            .register("inlineStyles", "Style Inliner", |_| Plugin::streaming(InlineStylesPlugin::new()))
+            .with_flags(&["--inline-styles"])
+            .with_description("Moves style declarations into presentation attributes")
+            .enabled_by(|config| config.style_inliner);
+
+        registry
+            .register("presentationToStyle", "Presentation to Style", |_| {
+                Plugin::streaming(PresentationToStylePlugin::new())
+            })
+            .with_flags(&["--presentation-to-style"])
+            .with_description("Moves presentation attributes into the style attribute")
+            .enabled_by(|config| config.style_converter);
+
+        // Before the minifier, which then drops what the removed rules leave behind
+        registry
+            .register("removeUnusedCss", "Unused CSS Remover", |_| {
+                Plugin::streaming(RemoveUnusedCSSPlugin::new())
+            })
+            .with_flags(&["--remove-unused-css"])
+            .with_description("Removes <style> rules for classes no element uses")
+            .enabled_by(|config| config.unused_css_remover);
+
+        registry
+            .register("minifyCss", "CSS Minifier", |_| Plugin::streaming(MinifyCSSPlugin::new()))
+            .with_flags(&["--minify-css"])
+            .with_description("Strips comments, whitespace and empty rules from <style> elements and style attributes")
+            .enabled_by(|config| config.css_minifier);
+
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plugins::{ElementAction, PluginStatistics};
+    use quick_xml::events::BytesStart;
+    use std::io;
+
+    fn names(registry: &PluginRegistry, config: &PluginConfig) -> Vec<String> {
+        registry
+            .pipeline(config)
+            .into_iter()
+            .map(|(plugin, _)| plugin.name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_enable_and_disable_by_name() {
+        let registry = PluginRegistry::default();
+        let config = PluginConfig {
+            gradient_deduplicator: true,
+            data_attr_remover: true,
+            enable: vec!["minifyCss".to_string(), "pathOptimizer".to_string()],
+            disable: vec!["removeDataAttrs".to_string()],
+            ..PluginConfig::default()
+        };
+
+        assert_eq!(names(&registry, &config), ["pathOptimizer", "dedupeGradients", "minifyCss"]);
+        assert_eq!(registry.engine(&config), Engine::Streaming);
+
+        let config = PluginConfig {
+            enable: vec!["collapseGroups".to_string()],
+            ..PluginConfig::default()
+        };
+        assert_eq!(registry.engine(&config), Engine::Tree);
+    }
+
+    #[test]
+    fn test_default_order() {
+        let registry = PluginRegistry::default();
+        let names: Vec<&str> = registry.iter().map(RegisteredPlugin::name).collect();
+
+        assert_eq!(
+            names,
+            [
+                "shapesToPaths",
+                "pathOptimizer",
+                "dedupeGradients",
+                "removeIds",
+                "removeDataAttrs",
+                "removeAttrs",
+                "removeComments",
+                "collapseWhitespace",
+                "optimizeTransforms",
+                "removeHiddenElems",
+                "removeEmptyContainers",
+                "inlineStylesheet",
+                "collapseGroups",
+                "dedupePaths",
+                "mergePaths",
+                "cleanupIds",
+                "prefixIds",
+                "normalizeTextWhitespace",
+                "removeInvisible",
+                "removeDefaultAttrs",
+                "removeMetadata",
+                "removeScripts",
+                "removeExternalRefs",
+                "removeDoctype",
+                "removeUnusedDefs",
+                "removeXmlProcInst",
+                "removeEditorNamespaces",
+                "convertColors",
+                "removeTitleDesc",
+                "removeEmptyAttrs",
+                "normalizeColors",
+                "cleanNamespaces",
+                "sortAttrs",
+                "cleanupNumericValues",
+                "removeUselessPaint",
+                "removeDimensions",
+                "inlineStyles",
+                "presentationToStyle",
+                "removeUnusedCss",
+                "minifyCss",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_configured_order_comes_first() {
+        let registry = PluginRegistry::default();
+        let config = PluginConfig {
+            enable: ["dedupeGradients", "removeIds", "convertColors", "minifyCss"].map(String::from).to_vec(),
+            order: ["minifyCss", "convertColors", "removeDoctype"].map(String::from).to_vec(),
+            ..PluginConfig::default()
+        };
+
+        assert_eq!(names(&registry, &config), ["minifyCss", "convertColors", "dedupeGradients", "removeIds"]);
+    }
+
+    #[test]
+    fn test_unknown_names_are_rejected() {
+        let registry = PluginRegistry::default();
+        let names = ["removeComments".to_string(), "removeEverything".to_string()];
+
+        let err = registry.check(&names).unwrap_err();
+        assert!(matches!(&err, SVGError::UnknownPlugin(name) if name == "removeEverything"));
+        assert!(registry.create("removeEverything", &PluginConfig::default()).is_err());
+        assert!(registry.check(&names[..1]).is_ok());
+    }
+
+    struct UppercasePlugin;
+
+    impl SVGPlugin for UppercasePlugin {
+        fn process_element(&mut self, element: &mut BytesStart) -> io::Result<ElementAction> {
+            let name = String::from_utf8_lossy(element.name().as_ref()).to_uppercase();
+            element.set_name(name.as_bytes());
+            Ok(ElementAction::Keep)
+        }
+
+        fn name(&self) -> &str {
+            "Uppercase"
+        }
+    }
+
+    impl PluginStatistics for UppercasePlugin {
+        fn get_statistics(&self) -> Vec<(&str, String)> {
+            Vec::new()
+        }
+    }
+
+    #[test]
+    fn test_custom_plugins() -> io::Result<()> {
+        let mut registry = PluginRegistry::new();
+        registry.register("uppercase", "Uppercase", |_| Plugin::streaming(UppercasePlugin));
+        let config = PluginConfig {
+            enable: vec!["uppercase".to_string()],
+            ..PluginConfig::default()
+        };
+
+        let mut processor = SVGProcessor::new(1024);
+        registry.configure(&mut processor, &config, false);
+        assert_eq!(processor.optimize_str("<svg/>")?, "<SVG/>");
+        Ok(())
+    }
+}
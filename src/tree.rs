@@ -0,0 +1,212 @@
+//! In-memory document tree for plugins that need structural context.
+//!
+//! The streaming engine shows plugins one event at a time. Optimizations
+//! that depend on parents, children or siblings (collapsing groups, pruning
+//! hidden subtrees) instead run on a `Document` built from the event stream
+//! and serialized back out afterwards.
+
+use quick_xml::events::{BytesCData, BytesDecl, BytesEnd, BytesPI, BytesStart, BytesText, Event};
+use quick_xml::reader::Reader;
+use quick_xml::errors::IllFormedError;
+use std::io::Write;
+
+use crate::error::SVGError;
+use crate::pretty::{Indent, OutputWriter};
+use crate::plugins::util::owned_attributes;
+
+/// A node of the document tree.
+///
+/// Text-like content is kept escaped, exactly as it appeared in the input,
+/// so a parsed document serializes back without re-escaping.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+    /// Content of a `<![CDATA[...]]>` section, without the markers
+    CData(String),
+    Comment(String),
+    ProcessingInstruction(String),
+    /// The XML declaration, such as `xml version="1.0"`
+    Declaration(String),
+    DocType(String),
+}
+
+/// An element with its unescaped attributes and child nodes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Element {
+    pub name: String,
+    pub attrs: Vec<(String, String)>,
+    pub children: Vec<Node>,
+}
+
+/// A parsed document: the root element and everything around it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Document {
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    /// The value of an attribute, if present.
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set an attribute, replacing its value in place or appending it.
+    pub fn set_attr(&mut self, key: &str, value: String) {
+        match self.attrs.iter_mut().find(|(k, _)| k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.attrs.push((key.to_string(), value)),
+        }
+    }
+
+    fn from_start(start: &BytesStart) -> Self {
+        Self {
+            name: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+            attrs: owned_attributes(start),
+            children: Vec::new(),
+        }
+    }
+
+    fn start(&self) -> BytesStart<'_> {
+        let mut start = BytesStart::new(self.name.as_str());
+        for (key, value) in &self.attrs {
+            start.push_attribute((key.as_str(), value.as_str()));
+        }
+        start
+    }
+}
+
+impl Document {
+    /// Parse a whole document into a tree.
+    pub fn parse(input: &[u8]) -> Result<Self, SVGError> {
+        let mut reader = Reader::from_reader(input);
+        let mut document = Document::default();
+        // Elements whose end tag hasn't been read yet
+        let mut open: Vec<Element> = Vec::new();
+        let mut buf = Vec::new();
+
+        loop {
+            let node = match reader.read_event_into(&mut buf) {
+                Ok(Event::Eof) => break,
+                Ok(Event::Start(start)) => {
+                    open.push(Element::from_start(&start));
+                    None
+                }
+                Ok(Event::End(_)) => open.pop().map(Node::Element),
+                Ok(Event::Empty(start)) => Some(Node::Element(Element::from_start(&start))),
+                Ok(Event::Text(text)) => Some(Node::Text(lossy(&text))),
+                Ok(Event::CData(cdata)) => Some(Node::CData(lossy(&cdata))),
+                Ok(Event::Comment(comment)) => Some(Node::Comment(lossy(&comment))),
+                Ok(Event::PI(pi)) => Some(Node::ProcessingInstruction(lossy(&pi))),
+                Ok(Event::Decl(decl)) => Some(Node::Declaration(lossy(&decl))),
+                Ok(Event::DocType(doctype)) => Some(Node::DocType(lossy(&doctype))),
+                Err(source) => {
+                    return Err(SVGError::Parse {
+                        position: reader.error_position(),
+                        source,
+                    })
+                }
+            };
+            buf.clear();
+
+            if let Some(node) = node {
+                match open.last_mut() {
+                    Some(parent) => parent.children.push(node),
+                    None => document.children.push(node),
+                }
+            }
+        }
+
+        if let Some(element) = open.last() {
+            return Err(SVGError::Parse {
+                position: reader.buffer_position(),
+                source: IllFormedError::MissingEndTag(element.name.clone()).into(),
+            });
+        }
+        Ok(document)
+    }
+
+    /// Serialize the tree. Elements without children are written
+    /// self-closing.
+    pub fn write<W: Write>(&self, output: W) -> Result<(), SVGError> {
+        self.write_indented(output, None)
+    }
+
+    /// Serialize the tree, indented when `indent` is given.
+    pub fn write_indented<W: Write>(&self, output: W, indent: Option<Indent>) -> Result<(), SVGError> {
+        let mut writer = OutputWriter::new(output, indent);
+        for node in &self.children {
+            write_node(&mut writer, node)?;
+        }
+        Ok(writer.into_inner().flush()?)
+    }
+}
+
+fn lossy(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn write_node<W: Write>(writer: &mut OutputWriter<W>, node: &Node) -> Result<(), SVGError> {
+    let event = match node {
+        Node::Element(element) if element.children.is_empty() => Event::Empty(element.start()),
+        Node::Element(element) => {
+            writer.write_event(Event::Start(element.start()))?;
+            for child in &element.children {
+                write_node(writer, child)?;
+            }
+            Event::End(BytesEnd::new(element.name.as_str()))
+        }
+        Node::Text(text) => Event::Text(BytesText::from_escaped(text.as_str())),
+        Node::CData(cdata) => Event::CData(BytesCData::new(cdata.as_str())),
+        Node::Comment(comment) => Event::Comment(BytesText::from_escaped(comment.as_str())),
+        Node::ProcessingInstruction(pi) => Event::PI(BytesPI::new(pi.as_str())),
+        Node::Declaration(decl) => {
+            Event::Decl(BytesDecl::from_start(BytesStart::from_content(decl.as_str(), 3)))
+        }
+        Node::DocType(doctype) => Event::DocType(BytesText::from_escaped(doctype.as_str())),
+    };
+    writer.write_event(event)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    fn round_trip(svg: &str) -> io::Result<String> {
+        let mut output = Vec::new();
+        Document::parse(svg.as_bytes())?.write(&mut output)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_document_round_trips() -> io::Result<()> {
+        let svg = "<?xml version=\"1.0\"?>\n<!DOCTYPE svg>\n<svg a=\"&amp;\"><!-- note --><g><text>1 &lt; 2</text><![CDATA[x]]></g><?pi data?><rect/></svg>";
+
+        assert_eq!(round_trip(svg)?, svg);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tree_structure() -> io::Result<()> {
+        let document = Document::parse(br#"<svg><g id="a"><rect/></g></svg>"#)?;
+
+        let [Node::Element(svg)] = document.children.as_slice() else { panic!("expected root") };
+        let [Node::Element(group)] = svg.children.as_slice() else { panic!("expected group") };
+        assert_eq!(group.name, "g");
+        assert_eq!(group.attrs, vec![("id".to_string(), "a".to_string())]);
+        assert_eq!(group.children.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_elements_are_self_closing_and_errors_reported() -> io::Result<()> {
+        assert_eq!(round_trip("<svg><g></g></svg>")?, "<svg><g/></svg>");
+        assert!(Document::parse(b"<svg><g>").is_err());
+        Ok(())
+    }
+}
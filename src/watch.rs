@@ -0,0 +1,242 @@
+//! Detecting changed input files for `--watch`.
+//!
+//! Changes are reported by the operating system through `notify`, whose
+//! `Create` and `Modify` events mark the files to look at again. Where no
+//! native watcher can be set up, such as on some network drives, the files
+//! are polled for their modification time instead.
+
+use crate::processor::{batch_files, is_glob_pattern};
+use notify::{Config, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How often the watch loop looks for new events, and how often the polling
+/// fallback checks the files.
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The SVG files matched by an input path, with the modification time each
+/// had when last looked at.
+pub(crate) struct FileWatcher {
+    input: PathBuf,
+    output: Option<PathBuf>,
+    recursive: bool,
+    seen: HashMap<PathBuf, SystemTime>,
+    events: Receiver<notify::Result<notify::Event>>,
+    /// Kept alive for as long as events are wanted.
+    _watcher: Box<dyn Watcher>,
+}
+
+impl FileWatcher {
+    /// Start watching `input`, taking the files' current state as unchanged.
+    ///
+    /// `input` is a file, directory or glob pattern, and `output` the file
+    /// or directory results go to, or `None` to optimize in place.
+    pub(crate) fn new(input: &Path, output: Option<&Path>, recursive: bool) -> io::Result<Self> {
+        let (root, mode) = watch_root(input, recursive);
+        let (sender, events) = mpsc::channel();
+        let native = RecommendedWatcher::new(sender.clone(), Config::default())
+            .and_then(|mut watcher| watcher.watch(&root, mode).map(|()| watcher));
+        let watcher: Box<dyn Watcher> = match native {
+            Ok(watcher) => Box::new(watcher),
+            Err(_) => {
+                let config = Config::default().with_poll_interval(POLL_INTERVAL);
+                let mut watcher = PollWatcher::new(sender, config).map_err(io::Error::other)?;
+                watcher.watch(&root, mode).map_err(io::Error::other)?;
+                Box::new(watcher)
+            }
+        };
+
+        let mut watcher = Self {
+            input: input.to_path_buf(),
+            output: output.map(Path::to_path_buf),
+            recursive,
+            seen: HashMap::new(),
+            events,
+            _watcher: watcher,
+        };
+        for (source, _) in watcher.files()? {
+            watcher.record(&source);
+        }
+        Ok(watcher)
+    }
+
+    /// The `(source, target)` pairs of files added or modified since the
+    /// last call.
+    ///
+    /// Files that can't be read right now are skipped, as editors saving
+    /// through a rename briefly leave no file behind; they are reported
+    /// once they're back with a new modification time.
+    pub(crate) fn changes(&mut self) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+        let mut touched = HashSet::new();
+        for event in self.events.try_iter() {
+            let event = event.map_err(io::Error::other)?;
+            if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                touched.extend(event.paths.iter().map(|path| canonical(path)));
+            }
+        }
+        if touched.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut changed = Vec::new();
+        for (source, target) in self.files()? {
+            if !touched.contains(&canonical(&source)) {
+                continue;
+            }
+            let Ok(modified) = fs::metadata(&source).and_then(|meta| meta.modified()) else {
+                continue;
+            };
+            if self.seen.insert(source.clone(), modified) != Some(modified) {
+                changed.push((source, target));
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Take the current state of `path` as unchanged, so writing an output
+    /// file that is also watched doesn't count as a change.
+    pub(crate) fn record(&mut self, path: &Path) {
+        if let Ok(modified) = fs::metadata(path).and_then(|meta| meta.modified()) {
+            self.seen.insert(path.to_path_buf(), modified);
+        }
+    }
+
+    fn files(&self) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+        if self.input.is_dir() || is_glob_pattern(&self.input) {
+            return batch_files(&self.input, self.output.as_deref(), self.recursive);
+        }
+        let target = self.output.clone().unwrap_or_else(|| self.input.clone());
+        Ok(vec![(self.input.clone(), target)])
+    }
+}
+
+/// The directory to watch for `input`, and whether to include its
+/// subdirectories.
+///
+/// A single file is watched through its directory, since editors often
+/// save by replacing the file. A glob pattern is watched from its literal
+/// part down.
+fn watch_root(input: &Path, recursive: bool) -> (PathBuf, RecursiveMode) {
+    let (root, mode) = if is_glob_pattern(input) {
+        let base: PathBuf = input
+            .components()
+            .take_while(|c| !is_glob_pattern(Path::new(c.as_os_str())))
+            .filter(|c| !matches!(c, Component::CurDir))
+            .collect();
+        (base, RecursiveMode::Recursive)
+    } else if input.is_dir() {
+        let mode = if recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+        (input.to_path_buf(), mode)
+    } else {
+        let parent = input.parent().map(Path::to_path_buf).unwrap_or_default();
+        (parent, RecursiveMode::NonRecursive)
+    };
+    if root.as_os_str().is_empty() {
+        return (PathBuf::from("."), mode);
+    }
+    (root, mode)
+}
+
+/// A path in the form events report it in, falling back to the path itself
+/// for files that are gone.
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// The time of day as `HH:MM:SS`, in UTC.
+pub(crate) fn clock_time(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() % 86_400;
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::thread;
+
+    fn touch(path: &Path, seconds: u64) -> io::Result<()> {
+        File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    /// The changes once the events of what was just done have come in.
+    fn settled_changes(watcher: &mut FileWatcher) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+        thread::sleep(POLL_INTERVAL * 5);
+        watcher.changes()
+    }
+
+    #[test]
+    fn test_changes_in_a_directory() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input = temp_dir.path().join("icons");
+        let output = temp_dir.path().join("out");
+        fs::create_dir(&input)?;
+        fs::write(input.join("a.svg"), "<svg/>")?;
+        fs::write(input.join("b.svg"), "<svg/>")?;
+
+        let mut watcher = FileWatcher::new(&input, Some(&output), false)?;
+        assert!(settled_changes(&mut watcher)?.is_empty());
+
+        touch(&input.join("b.svg"), 1_000)?;
+        fs::write(input.join("c.svg"), "<svg/>")?;
+        fs::write(input.join("notes.txt"), "")?;
+        let mut changes = settled_changes(&mut watcher)?;
+        changes.sort();
+        assert_eq!(
+            changes,
+            [
+                (input.join("b.svg"), output.join("b.svg")),
+                (input.join("c.svg"), output.join("c.svg")),
+            ]
+        );
+        assert!(settled_changes(&mut watcher)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_single_file_in_place() -> io::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let input = temp_dir.path().join("icon.svg");
+        fs::write(&input, "<svg/>")?;
+
+        let mut watcher = FileWatcher::new(&input, None, false)?;
+        fs::remove_file(&input)?;
+        fs::write(temp_dir.path().join("other.svg"), "<svg/>")?;
+        assert!(settled_changes(&mut watcher)?.is_empty());
+
+        fs::write(&input, "<svg></svg>")?;
+        touch(&input, 2_000)?;
+        assert_eq!(settled_changes(&mut watcher)?, [(input.clone(), input.clone())]);
+
+        // Writing the result doesn't count as a change
+        fs::write(&input, "<svg/>")?;
+        watcher.record(&input);
+        assert!(settled_changes(&mut watcher)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_watch_root() {
+        assert_eq!(
+            watch_root(Path::new("icons/**/*.svg"), false),
+            (PathBuf::from("icons"), RecursiveMode::Recursive)
+        );
+        assert_eq!(watch_root(Path::new("*.svg"), false), (PathBuf::from("."), RecursiveMode::Recursive));
+        assert_eq!(
+            watch_root(Path::new("icon.svg"), true),
+            (PathBuf::from("."), RecursiveMode::NonRecursive)
+        );
+    }
+
+    #[test]
+    fn test_clock_time() {
+        assert_eq!(clock_time(UNIX_EPOCH + Duration::from_secs(86_400 * 3 + 3_723)), "01:02:03");
+    }
+}